@@ -0,0 +1,22 @@
+use std::env;
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=BUILD_LABEL");
+    println!("cargo:rerun-if-env-changed=SHOW_VERSION_WATERMARK");
+
+    // Release builds only carry a watermark when explicitly asked for.
+    let release = env::var("PROFILE").is_ok_and(|profile| profile == "release");
+    if release && env::var("SHOW_VERSION_WATERMARK").as_deref() != Ok("1") {
+        return;
+    }
+
+    let label = env::var("BUILD_LABEL").unwrap_or_else(|_| {
+        let version = env::var("CARGO_PKG_VERSION").unwrap();
+        if release {
+            format!("v{}", version)
+        } else {
+            format!("v{}-dev", version)
+        }
+    });
+    println!("cargo:rustc-env=WATERMARK={}", label);
+}
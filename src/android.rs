@@ -0,0 +1,38 @@
+use crate::events::UserEvent;
+
+use winit::event::Event;
+use winit::event_loop::{ControlFlow, EventLoop};
+use winit::platform::android::activity::AndroidApp;
+use winit::platform::run_return::EventLoopExtRunReturn;
+
+/// Runs the event loop until the activity is resumed, as there is no native window to
+/// make a surface for before that.
+pub(crate) fn wait_until_resumed(mut event_loop: EventLoop<UserEvent>) -> EventLoop<UserEvent> {
+    event_loop.run_return(|event, _, control_flow| {
+        *control_flow = match event {
+            Event::Resumed => ControlFlow::Exit,
+            _ => ControlFlow::Wait,
+        };
+    });
+    event_loop
+}
+
+/// Shows the soft keyboard while egui wants text input, and hides it again after.
+#[derive(Default)]
+pub(crate) struct SoftKeyboard {
+    shown: bool,
+}
+
+impl SoftKeyboard {
+    pub fn update(&mut self, app: &AndroidApp, wants_input: bool) {
+        if wants_input == self.shown {
+            return;
+        }
+        if wants_input {
+            app.show_soft_input(true);
+        } else {
+            app.hide_soft_input(false);
+        }
+        self.shown = wants_input;
+    }
+}
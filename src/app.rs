@@ -0,0 +1,1150 @@
+use crate::assets::{AssetData, Assets};
+use crate::bezier::BezierEditor;
+use crate::big_table::BigTable;
+use crate::block_diagram::BlockDiagram;
+use crate::builder::DEFAULT_TITLE;
+use crate::camera::Camera;
+use crate::capabilities::Capabilities;
+use crate::clipboard::ClipboardHistory;
+use crate::clipboard_image::{self, PastedImages, RegionSelect};
+use crate::color_space::{ColorSpace, GamutTestPattern};
+use crate::commands::{Command, CommandPalette, ShortcutEditor, ShortcutRegistry};
+use crate::config::{self, AppConfig};
+use crate::crash::CrashReport;
+use crate::data_table::DataTable;
+use crate::deeplink::{self, DeepLink, Panel};
+use crate::dock::{self, Dock, DockTab};
+use crate::drawing::DrawingCanvas;
+use crate::dropped_files::DroppedFiles;
+use crate::ecs_inspector::EcsInspector;
+use crate::events::UserEvent;
+use crate::file_dialog::{FileDialogs, FileKind};
+use crate::fonts::FontsWindow;
+use crate::fps_overlay::FpsOverlay;
+use crate::frame_dump::{FrameDump, FrameDumper, FrameSummary};
+use crate::fullscreen::FullscreenMode;
+use crate::gamepad::{self, Gamepads};
+use crate::gpu_cache::GpuCache;
+use crate::gpu_errors::GpuErrorConsole;
+use crate::gpu_memory::GpuMemoryWindow;
+use crate::gpu_profiler::GpuTimingsOverlay;
+use crate::gui;
+use crate::inspector::{self, Inspector};
+use crate::jobs::Jobs;
+use crate::material::MaterialEditor;
+use crate::measure::MeasureTool;
+use crate::model::ModelViewer;
+use crate::offscreen::OffscreenView;
+use crate::palette::{Palette, PaletteWindow};
+use crate::particles::ParticleSystem;
+use crate::perf_plots::PerfPlots;
+use crate::pointer_lock::PointerLock;
+use crate::post::PostSettings;
+use crate::scene::{CubeScene, ScenePanel, SceneResources};
+use crate::shader_reload::ShaderReload;
+use crate::split_editor::SplitEditor;
+use crate::state_machine::{StateMachineView, TrafficLight};
+use crate::stats::{self, FrameRecord, FrameStats, StageBreakdown};
+use crate::texture_gen::TextureGenerator;
+use crate::textures::{TextureHandle, TexturesWindow};
+use crate::theme::Theme;
+use crate::title_bar::WindowAction;
+use crate::toasts::Toasts;
+use crate::touch::TouchCanvas;
+use crate::tray::{Tray, TrayEvent};
+use crate::update::UpdateInfo;
+use crate::viewport::Viewport3d;
+use crate::widgets::WidgetGallery;
+use crate::{
+    bezier, crash, material, ui, update, Application, FrameReport, PostPass, RenderFrame, Setup,
+    WindowSpec, DEPTH_FORMAT,
+};
+
+use egui_dock::DockState;
+use instant::SystemTime;
+use serde::{Deserialize, Serialize};
+use winit::event::{Event, StartCause, WindowEvent};
+use winit::window::{Window, WindowLevel};
+
+use std::path::{Path, PathBuf};
+
+const STATE_FILE: &str = "state.json";
+/// How much one step of zooming in or out scales the UI by.
+pub const ZOOM_STEP: f32 = 1.1;
+const ZOOM_RANGE: std::ops::RangeInclusive<f32> = 0.5..=3.0;
+
+/// The state of the example's own UI, kept between frames.
+pub struct AppState {
+    pub config: AppConfig,
+    /// Watches `config.toml` for edits while it's kept.
+    #[cfg(not(target_arch = "wasm32"))]
+    config_watcher: Option<notify::RecommendedWatcher>,
+    /// The theme picked in the settings, written to the config on the next frame.
+    pub requested_theme: Option<Theme>,
+    /// The OS's theme, for `Theme::System`. `None` where it doesn't tell.
+    system_theme: Option<winit::window::Theme>,
+    /// The dark or light theme last handed to egui.
+    applied_theme: Option<Theme>,
+    /// A theme to apply at the start of the next frame.
+    pending_theme: Option<Theme>,
+    /// Whether the desktop shows through what the frame leaves transparent.
+    pub transparent: bool,
+    /// The color space the surface actually ended up in.
+    pub color_space: ColorSpace,
+    pub show_gamut_test: bool,
+    /// Draws the cube scene beneath the UI.
+    pub show_scene: bool,
+    /// Off by default, so that a still cube lets the app go idle.
+    pub spin_cube: bool,
+    /// Locked by clicking the scene, to turn the camera with the mouse.
+    pub pointer_lock: PointerLock,
+    pub camera: Camera,
+    pub show_camera: bool,
+    /// Drawn in place of the cube once one is opened.
+    pub model: ModelViewer,
+    pub textures: TexturesWindow,
+    /// Loads what's opened as images, shaders or models on worker threads.
+    pub assets: Assets,
+    pub jobs: Jobs,
+    pub dock: Dock,
+    pub shaders: ShaderReload,
+    /// Simulates and draws the compute shader particles beneath the UI.
+    pub show_particles: bool,
+    pub show_demo: bool,
+    /// A file proposed by a link, waiting for the user to confirm loading it.
+    pub pending_file: Option<PathBuf>,
+    pub loaded_file: Option<LoadedFile>,
+    pub file_dialogs: FileDialogs,
+    pub fonts: FontsWindow,
+    /// The color the palette windows pick into.
+    pub active_color: egui::Color32,
+    pub palettes: Vec<PaletteWindow>,
+    next_palette_id: usize,
+    pub toasts: Toasts,
+    pub update: Option<UpdateInfo>,
+    pub measure: MeasureTool,
+    /// The scale factor of the window, as reported by the OS.
+    pub native_pixels_per_point: f32,
+    /// The window's mode as of the last frame.
+    pub fullscreen: FullscreenMode,
+    /// The mode picked in the settings or with a shortcut, switched to on the next frame.
+    pub requested_fullscreen: Option<FullscreenMode>,
+    /// Whether the window has the OS's title bar, else it gets the app's, and its title
+    /// and maximized state as of the last frame.
+    pub decorated: bool,
+    pub title: String,
+    pub maximized: bool,
+    /// What the title bar asked of the window, done on the next frame.
+    pub window_action: Option<WindowAction>,
+    close_requested: bool,
+    /// Closing the window hides it here once it's created.
+    tray: Tray,
+    /// Whether the window was last put above the others, `None` until it was set.
+    window_on_top: Option<bool>,
+    /// A crash report from an earlier run, offered to the user at startup.
+    pub crash_report: Option<CrashReport>,
+    pub stats: FrameStats,
+    pub clipboard: ClipboardHistory,
+    pub split_editor: SplitEditor,
+    pub show_files: bool,
+    /// Listed when the files window is first opened.
+    pub files: Option<DataTable>,
+    pub show_block_diagram: bool,
+    pub block_diagram: BlockDiagram,
+    pub ecs_inspector: EcsInspector,
+    pub frame_dump: FrameDumper,
+    pub texture_gen: TextureGenerator,
+    pub dropped_files: DroppedFiles,
+    /// The connected gamepads, moving egui's focus through the scaffold.
+    pub gamepads: Gamepads,
+    pub touch_canvas: TouchCanvas,
+    pub bezier: BezierEditor,
+    pub material_editor: MaterialEditor,
+    pub viewport: Viewport3d,
+    pub scene_panel: ScenePanel,
+    pub offscreen: OffscreenView,
+    pub traffic_light: StateMachineView<TrafficLight, &'static str>,
+    pub settings: Settings,
+    pub show_settings: bool,
+    pub command_palette: CommandPalette,
+    pub shortcuts: ShortcutRegistry,
+    pub shortcut_editor: ShortcutEditor,
+    /// Set when a shortcut was rebound, to write them to the config on the next frame.
+    pub save_shortcuts: bool,
+    /// The index and timestamp of the frame being drawn.
+    frame: (u64, f64),
+    /// How far the cube has spun, in seconds of spinning.
+    spin_time: f32,
+    /// A frame dump started in `end_frame`, finished once the frame is presented.
+    dump: Option<FrameDump>,
+    passes: Option<ScenePasses>,
+    particles: Option<ParticleSystem>,
+    pub gpu_timings: GpuTimingsOverlay,
+    pub fps_overlay: FpsOverlay,
+    /// Saves the next frame to a PNG once it's presented.
+    pub screenshot_requested: bool,
+    pub region_select: RegionSelect,
+    /// The part of the screenshot asked for to copy instead of saving it, in pixels.
+    pub copy_region: Option<egui::Rect>,
+    /// The part of the screenshot asked for to save on its own, in pixels.
+    pub save_region: Option<egui::Rect>,
+    pub pasted_images: PastedImages,
+    /// Where the window is being recorded to.
+    pub recording: Option<PathBuf>,
+    pub stage_breakdown: StageBreakdown,
+    pub gpu_errors: GpuErrorConsole,
+    /// The device's cache of bind groups and samplers, for its counts.
+    pub gpu_cache: GpuCache,
+    pub gpu_memory: GpuMemoryWindow,
+    pub perf_plots: PerfPlots,
+    pub big_table: BigTable,
+    pub widget_gallery: WidgetGallery,
+    pub drawing: DrawingCanvas,
+    pub inspector: Inspector,
+    /// The adapters that can present to the window, and the one in use.
+    pub adapters: Vec<wgpu::AdapterInfo>,
+    pub current_adapter: Option<wgpu::AdapterInfo>,
+    /// What the current device can do beyond the defaults.
+    pub capabilities: Option<Capabilities>,
+    /// The adapter picked in the settings, switched to on the next frame.
+    pub adapter: Option<wgpu::AdapterInfo>,
+    /// The present modes the surface supports, and the one of the last frame.
+    pub present_modes: Vec<wgpu::PresentMode>,
+    pub active_present_mode: Option<wgpu::PresentMode>,
+    /// The demo application that ships with egui.
+    #[cfg(feature = "demo")]
+    demo: egui_demo_lib::DemoWindows,
+}
+
+/// The pipelines drawing beneath the UI, rebuilt when the format or sample count
+/// changes.
+struct ScenePasses {
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+    cube: CubeScene,
+    /// The texture `cube` was last given.
+    cube_texture: Option<TextureHandle>,
+    gamut_test: GamutTestPattern,
+}
+
+/// The options of the settings window.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Waits for the display's refresh when presenting.
+    pub vsync: bool,
+    /// How frames are presented while VSync is off.
+    pub unsynced: UnsyncedMode,
+    /// Samples per pixel of the scene beneath the UI.
+    pub msaa: u32,
+    /// The most frames a second, `None` for no cap.
+    pub fps_limit: Option<u32>,
+    /// Redraws every frame instead of only when something changed.
+    pub continuous: bool,
+    /// Keeps the window above the others, for overlays and monitoring tools.
+    pub always_on_top: bool,
+    /// The passes run over the scene, which is drawn in HDR while any are enabled.
+    pub post: PostSettings,
+    /// Scales the whole UI on top of the OS's scale factor.
+    pub zoom: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            vsync: true,
+            unsynced: UnsyncedMode::Auto,
+            msaa: 1,
+            fps_limit: None,
+            continuous: false,
+            always_on_top: false,
+            post: PostSettings::default(),
+            zoom: 1.0,
+        }
+    }
+}
+
+impl Settings {
+    /// Scales the UI zoom by `factor`, within `ZOOM_RANGE`.
+    pub fn zoom_by(&mut self, factor: f32) {
+        self.zoom = (self.zoom * factor).clamp(*ZOOM_RANGE.start(), *ZOOM_RANGE.end());
+    }
+}
+
+/// The present mode used with VSync off.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnsyncedMode {
+    /// Immediate where supported, else Mailbox.
+    Auto,
+    /// Shows the latest finished frame at the next refresh, without tearing.
+    Mailbox,
+    /// Shows frames as soon as they're done, tearing.
+    Immediate,
+}
+
+impl UnsyncedMode {
+    pub fn present_mode(self) -> wgpu::PresentMode {
+        match self {
+            Self::Auto => wgpu::PresentMode::AutoNoVsync,
+            Self::Mailbox => wgpu::PresentMode::Mailbox,
+            Self::Immediate => wgpu::PresentMode::Immediate,
+        }
+    }
+}
+
+/// The parts of `AppState` that are saved when the app exits and restored on start.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+struct SavedState {
+    clipboard: ClipboardHistory,
+    split_ratio: f32,
+    settings: Settings,
+    dock: DockState<DockTab>,
+}
+
+impl Default for SavedState {
+    fn default() -> Self {
+        Self {
+            clipboard: ClipboardHistory::default(),
+            split_ratio: 0.5,
+            settings: Settings::default(),
+            dock: dock::default_layout(),
+        }
+    }
+}
+
+pub struct LoadedFile {
+    pub path: PathBuf,
+    pub bytes: Vec<u8>,
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        Self {
+            config: AppConfig::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            config_watcher: None,
+            requested_theme: None,
+            system_theme: None,
+            applied_theme: None,
+            pending_theme: None,
+            transparent: false,
+            color_space: ColorSpace::Srgb,
+            show_gamut_test: false,
+            show_scene: true,
+            spin_cube: false,
+            pointer_lock: PointerLock::default(),
+            camera: Camera::default(),
+            show_camera: false,
+            model: ModelViewer::default(),
+            textures: TexturesWindow::default(),
+            assets: Assets::default(),
+            jobs: Jobs::default(),
+            dock: Dock::default(),
+            shaders: ShaderReload::default(),
+            show_particles: false,
+            show_demo: true,
+            pending_file: None,
+            loaded_file: None,
+            file_dialogs: FileDialogs::default(),
+            fonts: FontsWindow::default(),
+            active_color: egui::Color32::WHITE,
+            palettes: Vec::new(),
+            next_palette_id: 0,
+            toasts: Toasts::default(),
+            update: None,
+            measure: MeasureTool::default(),
+            native_pixels_per_point: 1.0,
+            fullscreen: FullscreenMode::Windowed,
+            requested_fullscreen: None,
+            decorated: true,
+            title: String::new(),
+            maximized: false,
+            window_action: None,
+            close_requested: false,
+            tray: Tray::default(),
+            window_on_top: None,
+            crash_report: None,
+            stats: FrameStats::default(),
+            clipboard: ClipboardHistory::default(),
+            split_editor: SplitEditor::default(),
+            show_files: false,
+            show_block_diagram: false,
+            block_diagram: crate::block_diagram::demo(),
+            ecs_inspector: EcsInspector::new(std::sync::Arc::new(std::sync::RwLock::new(
+                crate::ecs::demo_world(),
+            ))),
+            files: None,
+            frame_dump: FrameDumper::default(),
+            texture_gen: TextureGenerator::default(),
+            dropped_files: DroppedFiles::default(),
+            gamepads: Gamepads::default(),
+            touch_canvas: TouchCanvas::default(),
+            bezier: BezierEditor::default(),
+            material_editor: MaterialEditor::default(),
+            viewport: Viewport3d::default(),
+            scene_panel: ScenePanel::default(),
+            offscreen: OffscreenView::default(),
+            traffic_light: crate::state_machine::traffic_light(),
+            settings: Settings::default(),
+            show_settings: false,
+            command_palette: CommandPalette::default(),
+            shortcuts: ShortcutRegistry::default(),
+            shortcut_editor: ShortcutEditor::default(),
+            save_shortcuts: false,
+            frame: (0, 0.0),
+            spin_time: 0.0,
+            dump: None,
+            passes: None,
+            particles: None,
+            gpu_timings: GpuTimingsOverlay::default(),
+            fps_overlay: FpsOverlay::default(),
+            screenshot_requested: false,
+            region_select: RegionSelect::default(),
+            copy_region: None,
+            save_region: None,
+            pasted_images: PastedImages::default(),
+            recording: None,
+            stage_breakdown: StageBreakdown::default(),
+            gpu_errors: GpuErrorConsole::default(),
+            gpu_cache: GpuCache::default(),
+            gpu_memory: GpuMemoryWindow::default(),
+            perf_plots: PerfPlots::default(),
+            big_table: BigTable::default(),
+            widget_gallery: WidgetGallery::default(),
+            drawing: DrawingCanvas::default(),
+            inspector: Inspector::default(),
+            adapters: Vec::new(),
+            current_adapter: None,
+            capabilities: None,
+            adapter: None,
+            present_modes: Vec::new(),
+            active_present_mode: None,
+            #[cfg(feature = "demo")]
+            demo: egui_demo_lib::DemoWindows::default(),
+        }
+    }
+}
+
+impl AppState {
+    /// Restores what was saved by the previous run, if anything.
+    pub fn load_saved(&mut self) {
+        let Some(path) = config::config_dir().map(|dir| dir.join(STATE_FILE)) else {
+            return;
+        };
+        let Ok(text) = std::fs::read_to_string(&path) else {
+            return;
+        };
+        match serde_json::from_str::<SavedState>(&text) {
+            Ok(saved) => {
+                self.clipboard = saved.clipboard;
+                self.split_editor.ratio = saved.split_ratio.clamp(0.0, 1.0);
+                self.settings = saved.settings;
+                self.dock.state = saved.dock;
+            }
+            Err(e) => tracing::warn!("Ignoring invalid saved state {}: {}", path.display(), e),
+        }
+    }
+
+    pub fn save(&mut self) {
+        let Some(dir) = config::config_dir() else {
+            return;
+        };
+        let saved = SavedState {
+            clipboard: std::mem::take(&mut self.clipboard),
+            split_ratio: self.split_editor.ratio,
+            settings: self.settings.clone(),
+            dock: self.dock.state.clone(),
+        };
+        let result = serde_json::to_string_pretty(&saved)
+            .map_err(std::io::Error::from)
+            .and_then(|text| {
+                std::fs::create_dir_all(&dir)?;
+                std::fs::write(dir.join(STATE_FILE), text)
+            });
+        self.clipboard = saved.clipboard;
+        if let Err(e) = result {
+            tracing::warn!("Failed to save the app state: {}", e);
+        }
+    }
+
+    /// Lets the config override the saved settings.
+    pub fn apply_config_overrides(&mut self) {
+        if let Some(vsync) = self.config.vsync {
+            self.settings.vsync = vsync;
+        }
+        if let Some(msaa) = self.config.msaa {
+            self.settings.msaa = msaa.max(1);
+        }
+        self.shortcuts = ShortcutRegistry::default();
+        self.shortcuts.apply(&self.config.shortcuts);
+    }
+
+    /// Takes over a config that was edited while running. What's only read at startup
+    /// keeps its old value until the next run.
+    fn reload_config(&mut self, config: AppConfig, window: &Window) {
+        let config = AppConfig {
+            color_space: self.config.color_space,
+            letterbox: self.config.letterbox.clone(),
+            ..config
+        };
+        // Saving the theme writes the file too, which changes nothing.
+        if config == self.config {
+            return;
+        }
+        if config.window.title != self.config.window.title {
+            window.set_title(config.window.title.as_deref().unwrap_or(DEFAULT_TITLE));
+        }
+        self.config = config;
+        self.apply_config_overrides();
+        self.toasts.push("Reloaded config.toml");
+    }
+
+    /// Looks at what the frame is about to hand to the platform, before it's applied.
+    pub fn on_platform_output(&mut self, output: &egui::PlatformOutput) {
+        self.clipboard.record(&output.copied_text);
+    }
+
+    /// Performs the action of a `myexample://` link, ignoring invalid ones.
+    pub fn handle_link(&mut self, link: &str) {
+        match deeplink::parse(link) {
+            Ok(DeepLink::OpenPanel(Panel::Demo)) => self.show_demo = true,
+            Ok(DeepLink::OpenPanel(Panel::Settings)) => self.show_settings = true,
+            Ok(DeepLink::LoadFile(path)) => self.pending_file = Some(path),
+            Err(e) => tracing::warn!("Ignoring link {}: {}", link, e),
+        }
+    }
+
+    pub fn open_palette(&mut self, palette: Palette) {
+        self.palettes.push(PaletteWindow::new(
+            ("palette", self.next_palette_id),
+            palette,
+        ));
+        self.next_palette_id += 1;
+    }
+
+    /// Opens an empty palette with a numbered name.
+    pub fn new_palette(&mut self) {
+        let name = format!("Palette {}", self.next_palette_id + 1);
+        self.open_palette(Palette::new(name));
+    }
+
+    /// The samples per pixel the scene is actually drawn with, once it was.
+    pub fn scene_sample_count(&self) -> Option<u32> {
+        self.passes.as_ref().map(|passes| passes.sample_count)
+    }
+
+    /// A loaded model is drawn instead of the cube, and it stands still.
+    fn is_spinning(&self) -> bool {
+        self.show_scene && self.spin_cube && self.model.data.is_none()
+    }
+
+    /// Whether the particles can be simulated, assumed until the device says otherwise.
+    pub fn can_compute(&self) -> bool {
+        self.capabilities
+            .as_ref()
+            .is_none_or(Capabilities::compute_shaders)
+    }
+
+    /// True while the split editor's source has the keyboard focus.
+    pub fn document_focused(&self, ctx: &egui::Context) -> bool {
+        self.split_editor.open && ctx.memory(|m| m.has_focus(SplitEditor::source_id()))
+    }
+
+    pub fn run(&mut self, command: Command) {
+        match command {
+            Command::NewPalette => self.new_palette(),
+            Command::ToggleSplitEditor => self.split_editor.open = !self.split_editor.open,
+            Command::ToggleFiles => self.show_files = !self.show_files,
+            Command::ToggleTextureGenerator => self.texture_gen.open = !self.texture_gen.open,
+            Command::ToggleFrameStats => self.stats.open = !self.stats.open,
+            Command::ToggleFrameData => self.frame_dump.open = !self.frame_dump.open,
+            Command::ToggleFpsOverlay => self.fps_overlay.open = !self.fps_overlay.open,
+            Command::ToggleDemo => self.show_demo = !self.show_demo,
+            Command::ToggleFullscreen => {
+                self.requested_fullscreen =
+                    Some(self.fullscreen.toggled(FullscreenMode::Borderless))
+            }
+            Command::ToggleExclusiveFullscreen => {
+                self.requested_fullscreen = Some(self.fullscreen.toggled(FullscreenMode::Exclusive))
+            }
+            Command::ToggleAlwaysOnTop => {
+                self.settings.always_on_top = !self.settings.always_on_top
+            }
+            Command::DumpFrame => self.frame_dump.request(),
+            Command::Screenshot => self.screenshot_requested = true,
+            Command::ZoomIn => self.settings.zoom_by(ZOOM_STEP),
+            Command::ZoomOut => self.settings.zoom_by(1.0 / ZOOM_STEP),
+            Command::ResetZoom => self.settings.zoom = 1.0,
+            Command::CopyRegion => self.region_select.active = true,
+            Command::ToggleRecording => {
+                self.recording = match self.recording {
+                    Some(_) => None,
+                    None => Some(recording_path()),
+                }
+            }
+            Command::OpenSettings => self.show_settings = true,
+            Command::OpenCommandPalette => self.command_palette.show(),
+            Command::Undo => self.split_editor.undo(),
+            Command::Quit => self.close_requested = true,
+        }
+    }
+
+    fn set_adapters(&mut self, setup: &mut Setup<'_>) {
+        let current = setup.adapter.get_info();
+        self.adapters = std::mem::take(&mut setup.adapters);
+        self.adapter.get_or_insert_with(|| current.clone());
+        self.current_adapter = Some(current);
+        self.present_modes = std::mem::take(&mut setup.present_modes);
+    }
+
+    /// Hides the window in the tray if there's one, or closes it.
+    fn close(&mut self, window: &Window) {
+        if self.tray.created {
+            window.set_visible(false);
+        } else {
+            self.close_requested = true;
+        }
+    }
+
+    pub fn set_update_available(&mut self, update: UpdateInfo) {
+        self.toasts.push(format!(
+            "Version {} is available, see the Help menu",
+            update.version
+        ));
+        self.update = Some(update);
+    }
+}
+
+impl Application for AppState {
+    fn setup(&mut self, mut setup: Setup<'_>) {
+        crash::set_adapter_info(&setup.adapter.get_info());
+        self.gpu_errors.set_errors(setup.errors.clone());
+        self.gpu_memory.set_memory(setup.memory.clone());
+        self.perf_plots.set_memory(setup.memory.clone());
+        self.gpu_cache = setup.cache.clone();
+        self.capabilities = Some(setup.capabilities.clone());
+        insert_callback_resources(&mut setup);
+        self.color_space = setup.color_space;
+        self.transparent = setup.alpha_mode != wgpu::CompositeAlphaMode::Opaque;
+        self.set_adapters(&mut setup);
+
+        // Headless there's nothing to deliver links, updates or tray clicks to.
+        let Some(proxy) = setup.proxy else {
+            return;
+        };
+        self.tray.listen(proxy.clone());
+        self.file_dialogs.set_proxy(proxy.clone());
+        self.jobs.set_proxy(proxy.clone());
+        gamepad::spawn(proxy.clone());
+        // A page has no other instances to get links from, and its proxy can't be
+        // handed to the logger, which may be called from any thread.
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let config_proxy = proxy.clone();
+            match config::watch(move |config| {
+                let _ = config_proxy.send_event(UserEvent::ConfigChanged(config));
+            }) {
+                Ok(watcher) => self.config_watcher = Some(watcher),
+                Err(e) => tracing::warn!("Edits to config.toml will not be applied: {}", e),
+            }
+            self.shaders.watch(proxy.clone());
+            let log_proxy = std::sync::Mutex::new(proxy.clone());
+            crate::logging::set_listener(move || {
+                if let Ok(proxy) = log_proxy.lock() {
+                    let _ = proxy.send_event(UserEvent::Logged);
+                }
+            });
+
+            let link_proxy = proxy.clone();
+            if let Err(e) = crate::platform::listen_for_links(move |link| {
+                let _ = link_proxy.send_event(UserEvent::DeepLink(link));
+            }) {
+                tracing::warn!("Links from other instances will not be received: {}", e);
+            }
+        }
+        update::spawn_check(&self.config.update_check, proxy);
+    }
+
+    fn device_recreated(&mut self, mut setup: Setup<'_>) {
+        insert_callback_resources(&mut setup);
+        self.color_space = setup.color_space;
+        self.transparent = setup.alpha_mode != wgpu::CompositeAlphaMode::Opaque;
+        self.set_adapters(&mut setup);
+        self.passes = None;
+        self.particles = None;
+        self.offscreen.forget_textures();
+        self.model.forget_gpu();
+        self.textures.textures.forget_gpu();
+        // egui starts over with a new context, without them.
+        self.fonts.fonts.reapply();
+    }
+
+    fn update(&mut self, window: &Window) {
+        let last = self.frame.1;
+        self.frame = self.stats.next_frame();
+        if self.is_spinning() {
+            // Capped, or the first frame after an idle spell would jump.
+            self.spin_time += ((self.frame.1 - last) as f32).min(0.1);
+        }
+        self.native_pixels_per_point = window.scale_factor() as f32;
+        if let Some(mode) = self.requested_fullscreen.take() {
+            mode.apply(window);
+        }
+        self.fullscreen = FullscreenMode::of(window);
+        match self.window_action.take() {
+            Some(WindowAction::Close) => self.close(window),
+            Some(action) => action.apply(window),
+            None => {}
+        }
+        self.decorated = window.is_decorated();
+        self.title = window.title();
+        self.maximized = window.is_maximized();
+        if self.window_on_top != Some(self.settings.always_on_top) {
+            self.window_on_top = Some(self.settings.always_on_top);
+            window.set_window_level(if self.settings.always_on_top {
+                WindowLevel::AlwaysOnTop
+            } else {
+                WindowLevel::Normal
+            });
+        }
+        if let Some(theme) = self.requested_theme.take() {
+            self.config.theme = Some(theme);
+            if let Err(e) = config::save_key("theme", &theme) {
+                self.toasts.push(format!("Failed to save the theme: {}", e));
+            }
+        }
+        if std::mem::take(&mut self.save_shortcuts) {
+            self.config.shortcuts = self.shortcuts.overrides();
+            if let Err(e) = config::save_key("shortcuts", &self.config.shortcuts) {
+                self.toasts
+                    .push(format!("Failed to save the shortcuts: {}", e));
+            }
+        }
+        self.pointer_lock.update(window);
+        self.camera.look(self.pointer_lock.take_delta());
+        // Until the OS reports a change, it's asked.
+        if self.system_theme.is_none() {
+            self.system_theme = window.theme();
+        }
+        let theme = self
+            .config
+            .theme
+            .unwrap_or_default()
+            .resolve(self.system_theme);
+        if self.applied_theme != Some(theme) {
+            self.applied_theme = Some(theme);
+            self.pending_theme = Some(theme);
+        }
+    }
+
+    fn ui(&mut self, ctx: &egui::Context) {
+        if let Some(theme) = self.pending_theme.take() {
+            ctx.set_visuals(theme.visuals());
+        }
+        // The themes are opaque, so their fills are made translucent after every change.
+        if self.transparent && ctx.style().visuals.window_fill.a() == u8::MAX {
+            ctx.style_mut(|style| gui::make_translucent(&mut style.visuals));
+        }
+        ui::show(ctx, self);
+        // The cube and the particles move on their own, beneath egui.
+        if self.settings.continuous
+            || self.is_spinning()
+            || (self.show_particles && self.can_compute())
+        {
+            ctx.request_repaint();
+        }
+
+        #[cfg(feature = "demo")]
+        if self.show_demo {
+            self.demo.ui(ctx);
+        }
+    }
+
+    fn end_frame(&mut self, output: &egui::FullOutput) {
+        if self.frame_dump.take_request() {
+            self.dump = Some(FrameDump::begin(
+                self.frame.0,
+                &output.platform_output,
+                &output.shapes,
+            ));
+        }
+        self.on_platform_output(&output.platform_output);
+    }
+
+    fn render(&mut self, mut frame: RenderFrame<'_>) {
+        self.offscreen.render(&mut frame, self.frame.1 as f32);
+        if self.passes.as_ref().is_none_or(|passes| {
+            passes.format != frame.format || passes.sample_count != frame.sample_count
+        }) {
+            self.passes = Some(
+                frame
+                    .errors
+                    .scope(frame.device, "scene passes", || ScenePasses {
+                        format: frame.format,
+                        sample_count: frame.sample_count,
+                        cube: CubeScene::new(
+                            frame.device,
+                            frame.queue,
+                            frame.format,
+                            frame.sample_count,
+                            Some(DEPTH_FORMAT),
+                        ),
+                        cube_texture: None,
+                        gamut_test: GamutTestPattern::new(
+                            frame.device,
+                            frame.format,
+                            frame.sample_count,
+                        ),
+                    }),
+            );
+            // Made with the shader that was built in, which may have been edited since.
+            self.shaders.mark_changed("scene.wgsl");
+        }
+        self.textures.textures.upload(&mut frame);
+        let can_compute = self.can_compute();
+        let Some(passes) = &mut self.passes else {
+            return;
+        };
+        for name in self.shaders.take_changed() {
+            let Some(wgsl) = self.shaders.source(&name) else {
+                continue;
+            };
+            match name.as_str() {
+                "scene.wgsl" => frame.errors.scope(frame.device, "scene shader", || {
+                    passes.cube.set_shader(frame.device, wgsl);
+                    passes.cube_texture = None;
+                    let resources = &mut frame.renderer.callback_resources;
+                    if let Some(SceneResources(scene)) = resources.get_mut::<SceneResources>() {
+                        scene.set_shader(frame.device, wgsl);
+                    }
+                }),
+                "model.wgsl" => self.model.set_shader(wgsl.to_owned()),
+                _ => {}
+            }
+        }
+        // Until it's uploaded, the checkerboard.
+        let on_cube = self
+            .textures
+            .on_cube
+            .filter(|&handle| self.textures.textures.view(handle).is_some());
+        if passes.cube_texture != on_cube {
+            let texture = on_cube.and_then(|handle| self.textures.textures.view(handle));
+            passes.cube.set_texture(frame.device, texture);
+            passes.cube_texture = on_cube;
+        }
+        let aspect = frame.size[0] as f32 / frame.size[1].max(1) as f32;
+        if self.show_scene && !self.model.render(&mut frame, self.camera.view_proj(aspect)) {
+            let (view, depth) = (frame.view, frame.depth);
+            passes.cube.render(
+                &mut frame,
+                view,
+                Some(depth),
+                self.camera.view_proj(aspect),
+                CubeScene::spin(self.spin_time),
+            );
+        }
+        if self.show_particles && can_compute {
+            self.particles
+                .get_or_insert_with(|| {
+                    frame.errors.scope(frame.device, "particles", || {
+                        ParticleSystem::new(frame.device, frame.memory)
+                    })
+                })
+                .render(&mut frame, self.frame.1 as f32);
+        } else if let Some(particles) = &mut self.particles {
+            particles.pause();
+        }
+        if !self.show_gamut_test {
+            return;
+        }
+        let mut pass = frame
+            .encoder
+            .begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("gamut test"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: frame.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+        passes.gamut_test.draw(&mut pass);
+    }
+
+    fn frame_done(&mut self, report: &FrameReport<'_>) {
+        self.frame_dump
+            .set_summary(FrameSummary::new(report.paint_jobs));
+        if let Some(mut dump) = self.dump.take() {
+            dump.finish(
+                report.paint_jobs,
+                report.textures_delta,
+                report.screen_descriptor,
+                self.frame_dump.include_vertices(),
+            );
+            self.frame_dump.set_status(match dump.write() {
+                Ok(path) => format!("Dumped to {}", path.display()),
+                Err(e) => format!("Dump failed: {}", e),
+            });
+        }
+        let regions = (self.copy_region.take(), self.save_region.take());
+        match (report.screenshot, regions) {
+            (Some(Ok(screenshot)), (_, Some(region))) => {
+                self.toasts.push(match screenshot.crop(region).save_png() {
+                    Ok(path) => format!("Saved the drawing to {}", path.display()),
+                    Err(e) => format!("Saving the drawing failed: {}", e),
+                });
+            }
+            (Some(Ok(screenshot)), (Some(region), None)) => {
+                let image = screenshot.crop(region);
+                self.toasts.push(match clipboard_image::copy(&image) {
+                    Ok(()) => format!("Copied a {}×{} image", image.width, image.height),
+                    Err(e) => format!("Copying the image failed: {}", e),
+                });
+            }
+            (Some(Ok(screenshot)), (None, None)) => self.toasts.push(match screenshot.save_png() {
+                Ok(path) => format!("Saved {}", path.display()),
+                Err(e) => format!("Saving the screenshot failed: {}", e),
+            }),
+            (Some(Err(e)), _) => self.toasts.push(format!("No screenshot: {}", e)),
+            (None, _) => {}
+        }
+        self.gpu_timings.set_times(report.gpu_times);
+        self.active_present_mode = Some(report.present_mode);
+        let (frame, timestamp) = self.frame;
+        self.fps_overlay.record(timestamp);
+        self.stage_breakdown.record(report.stage_ms);
+        let record = FrameRecord {
+            frame,
+            timestamp,
+            stage_ms: report.stage_ms,
+            gpu_ms: report
+                .gpu_times
+                .filter(|times| !times.is_empty())
+                .map(|times| times.iter().map(|time| time.ms).sum()),
+            vertices: stats::vertex_count(report.paint_jobs),
+            texture_upload_bytes: stats::texture_upload_bytes(report.textures_delta),
+            present_mode: report.present_mode,
+        };
+        self.perf_plots.record(&record);
+        self.stats.record(record);
+    }
+
+    fn on_event(&mut self, event: &Event<'_, UserEvent>, window: &Window) -> bool {
+        if self.pointer_lock.on_event(event) {
+            return true;
+        }
+        match event {
+            Event::UserEvent(UserEvent::DeepLink(link)) => {
+                self.handle_link(link);
+                window.focus_window();
+            }
+            Event::UserEvent(UserEvent::UpdateAvailable(update)) => {
+                self.set_update_available(update.clone());
+            }
+            Event::UserEvent(UserEvent::RecordingSaved(result)) => {
+                // A failed recording stops on its own.
+                self.recording = None;
+                self.toasts.push(match result {
+                    Ok(path) => format!("Saved {}", path.display()),
+                    Err(e) => format!("Recording failed: {}", e),
+                });
+            }
+            Event::UserEvent(UserEvent::FileOpened(kind, result)) => match (kind, result) {
+                (FileKind::Any, Ok((path, bytes))) => {
+                    self.loaded_file = Some(LoadedFile {
+                        path: path.clone(),
+                        bytes: bytes.clone(),
+                    })
+                }
+                (FileKind::Font, Ok((path, bytes))) => self.fonts.load(path, bytes.clone()),
+                (FileKind::Image | FileKind::Model, Ok((path, bytes))) => {
+                    if let Err(e) =
+                        self.assets
+                            .load_bytes(&mut self.jobs, path.clone(), bytes.clone())
+                    {
+                        self.toasts.push(e);
+                    }
+                }
+                (_, Err(e)) => self.toasts.push(e.clone()),
+            },
+            Event::UserEvent(UserEvent::FilesPicked(paths)) => {
+                self.assets.open = true;
+                for path in paths {
+                    if let Err(e) = self.assets.load(&mut self.jobs, path.clone()) {
+                        self.toasts.push(e);
+                    }
+                }
+            }
+            Event::UserEvent(UserEvent::AssetLoaded(id, result)) => {
+                if let Err(e) = result {
+                    self.toasts.push(e.clone());
+                }
+                match self.assets.finish(*id, result.clone()) {
+                    Some(AssetData::Image(image)) => {
+                        let name = self
+                            .assets
+                            .path(*id)
+                            .and_then(|path| path.file_name())
+                            .map(|name| name.to_string_lossy().into_owned())
+                            .unwrap_or_else(|| "Image".to_owned());
+                        self.textures.textures.insert(name, image);
+                        self.textures.open = true;
+                    }
+                    Some(AssetData::Model(model)) => self.model.set(model),
+                    Some(AssetData::Shader(_)) | None => {}
+                }
+            }
+            Event::UserEvent(UserEvent::JobFinished(id, output)) => {
+                // Nothing else spawns jobs yet.
+                if !self.jobs.finish(*id, output.as_ref()) {
+                    tracing::warn!("Nothing took what job {:?} returned", id);
+                }
+            }
+            Event::UserEvent(UserEvent::FileSaved(result)) => self.toasts.push(match result {
+                Ok(path) => format!("Saved {}", path.display()),
+                Err(e) => e.clone(),
+            }),
+            Event::UserEvent(UserEvent::ConfigChanged(config)) => {
+                self.reload_config(config.clone(), window);
+            }
+            Event::UserEvent(UserEvent::ShaderChanged(name, source)) => {
+                self.shaders.on_change(name.clone(), source.clone());
+            }
+            // Only worth a redraw when the new line may be shown.
+            Event::UserEvent(UserEvent::Logged) => return self.inspector.open,
+            Event::UserEvent(UserEvent::Gamepad(event)) => {
+                self.gamepads.on_event(event);
+                return self.gamepads.open;
+            }
+            Event::NewEvents(StartCause::Init) => {
+                self.tray.create();
+                return false;
+            }
+            Event::UserEvent(UserEvent::Tray(event)) => match event {
+                TrayEvent::Created => self.tray.created = true,
+                TrayEvent::Show => {
+                    window.set_visible(true);
+                    window.set_minimized(false);
+                    window.focus_window();
+                }
+                TrayEvent::Hide => window.set_visible(false),
+                TrayEvent::Quit => self.close_requested = true,
+            },
+            Event::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                ..
+            } if self.tray.created => self.close(window),
+            Event::WindowEvent {
+                event: WindowEvent::ThemeChanged(theme),
+                ..
+            } => self.system_theme = Some(*theme),
+            _ => return false,
+        }
+        true
+    }
+
+    fn present_mode(&self) -> Option<wgpu::PresentMode> {
+        Some(if self.settings.vsync {
+            wgpu::PresentMode::Fifo
+        } else {
+            self.settings.unsynced.present_mode()
+        })
+    }
+
+    fn sample_count(&self) -> Option<u32> {
+        Some(self.settings.msaa)
+    }
+
+    fn adapter(&self) -> Option<&wgpu::AdapterInfo> {
+        self.adapter.as_ref()
+    }
+
+    fn screenshot_requested(&mut self) -> bool {
+        std::mem::take(&mut self.screenshot_requested)
+    }
+
+    fn recording(&self) -> Option<&Path> {
+        self.recording.as_deref()
+    }
+
+    fn clear_color(&self) -> wgpu::Color {
+        match self.applied_theme {
+            Some(theme) if !self.transparent => theme.clear_color(),
+            _ => wgpu::Color::TRANSPARENT,
+        }
+    }
+
+    fn ui_zoom(&self) -> f32 {
+        self.settings.zoom
+    }
+
+    fn fps_limit(&self) -> Option<u32> {
+        self.settings.fps_limit
+    }
+
+    fn post_passes(&self) -> Vec<PostPass> {
+        self.settings.post.passes()
+    }
+
+    fn windows(&self) -> Vec<WindowSpec> {
+        self.inspector.window_spec().into_iter().collect()
+    }
+
+    fn window_ui(&mut self, id: &str, ctx: &egui::Context) {
+        if id == inspector::WINDOW_ID {
+            inspector::show_detached(ctx, self);
+        }
+    }
+
+    fn window_closed(&mut self, id: &str) {
+        if id == inspector::WINDOW_ID {
+            self.inspector.open = false;
+        }
+    }
+
+    fn suspended(&mut self) {
+        self.save();
+    }
+
+    fn close_requested(&mut self) -> bool {
+        std::mem::take(&mut self.close_requested)
+    }
+
+    fn exit(&mut self) {
+        self.save();
+    }
+}
+
+/// A GIF named after the time the recording starts at, in the current directory.
+fn recording_path() -> PathBuf {
+    let millis = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    PathBuf::from(format!("recording-{}.gif", millis))
+}
+
+/// Puts what the paint callbacks draw with into egui's renderer.
+fn insert_callback_resources(setup: &mut Setup<'_>) {
+    let resources = &mut setup.renderer.callback_resources;
+    setup.errors.scope(setup.device, "paint callbacks", || {
+        resources.insert(bezier::BezierResources::new(setup.device, setup.ui_format));
+        resources.insert(material::MaterialResources::new(
+            setup.device,
+            setup.queue,
+            setup.ui_format,
+        ));
+        resources.insert(SceneResources(CubeScene::new(
+            setup.device,
+            setup.queue,
+            setup.ui_format,
+            1,
+            None,
+        )));
+    });
+}
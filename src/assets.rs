@@ -0,0 +1,327 @@
+use crate::events::UserEvent;
+use crate::file_dialog::FileDialogs;
+use crate::jobs::Jobs;
+use crate::model::ModelData;
+use crate::textures::{self, DecodedImage};
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// How much of a file is read at a time, between updates of its progress.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// What a file is loaded as, going by its extension.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AssetKind {
+    /// A PNG or JPEG, loaded into the textures.
+    Image,
+    /// WGSL source.
+    Shader,
+    /// A glTF or GLB model, opened in the model viewer.
+    Model,
+}
+
+impl AssetKind {
+    pub const EXTENSIONS: &'static [&'static str] = &["png", "jpg", "jpeg", "wgsl", "gltf", "glb"];
+
+    pub fn of(path: &Path) -> Option<AssetKind> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        Some(match extension.as_str() {
+            "png" | "jpg" | "jpeg" => AssetKind::Image,
+            "wgsl" => AssetKind::Shader,
+            "gltf" | "glb" => AssetKind::Model,
+            _ => return None,
+        })
+    }
+}
+
+/// An asset in `Assets`, from when it's asked for on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AssetId(u64);
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LoadState {
+    Loading,
+    Loaded,
+    Failed(String),
+}
+
+/// What a job made of a file, sent to the event loop as `UserEvent::AssetLoaded`.
+#[derive(Clone, Debug)]
+pub enum AssetData {
+    Image(DecodedImage),
+    Shader(String),
+    Model(ModelData),
+}
+
+/// How much of a file a job read, shared with it.
+#[derive(Default)]
+struct Progress {
+    read: AtomicU64,
+    /// 0 until the file's size is known.
+    total: AtomicU64,
+}
+
+struct Asset {
+    path: PathBuf,
+    kind: AssetKind,
+    state: LoadState,
+    progress: Arc<Progress>,
+    /// A shader's source, kept here as nothing else takes it yet.
+    source: Option<String>,
+}
+
+struct Job {
+    path: PathBuf,
+    kind: AssetKind,
+    /// Already read, only to be decoded.
+    bytes: Option<Vec<u8>>,
+    progress: Arc<Progress>,
+}
+
+/// Loads images, shaders and models as background jobs, handing out ids to follow them
+/// by. Each comes back to the event loop as `UserEvent::AssetLoaded`, for `finish`.
+#[derive(Default)]
+pub struct Assets {
+    pub open: bool,
+    next: u64,
+    assets: BTreeMap<AssetId, Asset>,
+}
+
+impl Assets {
+    /// Reads the file at `path` and decodes it as what its extension says.
+    pub fn load(&mut self, jobs: &mut Jobs, path: PathBuf) -> Result<AssetId, String> {
+        self.queue(jobs, path, None)
+    }
+
+    /// Decodes a file that was read already.
+    pub fn load_bytes(
+        &mut self,
+        jobs: &mut Jobs,
+        path: PathBuf,
+        bytes: Vec<u8>,
+    ) -> Result<AssetId, String> {
+        self.queue(jobs, path, Some(bytes))
+    }
+
+    fn queue(
+        &mut self,
+        jobs: &mut Jobs,
+        path: PathBuf,
+        bytes: Option<Vec<u8>>,
+    ) -> Result<AssetId, String> {
+        let kind = AssetKind::of(&path)
+            .ok_or_else(|| format!("{} isn't an image, shader or model", path.display()))?;
+        let id = AssetId(self.next);
+        self.next += 1;
+        let progress = Arc::new(Progress::default());
+        self.assets.insert(
+            id,
+            Asset {
+                path: path.clone(),
+                kind,
+                state: LoadState::Loading,
+                progress: Arc::clone(&progress),
+                source: None,
+            },
+        );
+        let name = format!("Loading {}", path.display());
+        let job = Job {
+            path,
+            kind,
+            bytes,
+            progress,
+        };
+        jobs.submit(name, move |_| UserEvent::AssetLoaded(id, job.load()));
+        Ok(id)
+    }
+
+    /// Takes in what a job loaded. Images and models are returned for the app to
+    /// use, shaders are kept here.
+    pub fn finish(&mut self, id: AssetId, result: Result<AssetData, String>) -> Option<AssetData> {
+        let asset = self.assets.get_mut(&id)?;
+        match result {
+            Ok(AssetData::Shader(source)) => {
+                asset.state = LoadState::Loaded;
+                asset.source = Some(source);
+                None
+            }
+            Ok(data) => {
+                asset.state = LoadState::Loaded;
+                Some(data)
+            }
+            Err(e) => {
+                asset.state = LoadState::Failed(e);
+                None
+            }
+        }
+    }
+
+    pub fn state(&self, id: AssetId) -> Option<&LoadState> {
+        Some(&self.assets.get(&id)?.state)
+    }
+
+    pub fn path(&self, id: AssetId) -> Option<&Path> {
+        Some(&self.assets.get(&id)?.path)
+    }
+
+    /// The source of a loaded shader.
+    pub fn source(&self, id: AssetId) -> Option<&str> {
+        self.assets.get(&id)?.source.as_deref()
+    }
+
+    /// How many assets are still loading.
+    pub fn loading(&self) -> usize {
+        self.assets
+            .values()
+            .filter(|asset| asset.state == LoadState::Loading)
+            .count()
+    }
+
+    /// A spinner and the bytes read so far while anything is loading, for the status
+    /// bar.
+    pub fn status_ui(&self, ui: &mut egui::Ui) {
+        let loading = self.loading();
+        if loading == 0 {
+            return;
+        }
+        ui.spinner();
+        let (read, total) = self
+            .assets
+            .values()
+            .filter(|asset| asset.state == LoadState::Loading)
+            .fold((0, 0), |(read, total), asset| {
+                (
+                    read + asset.progress.read.load(Ordering::Relaxed),
+                    total + asset.progress.total.load(Ordering::Relaxed),
+                )
+            });
+        let fraction = if total > 0 {
+            read as f32 / total as f32
+        } else {
+            0.0
+        };
+        ui.add(
+            egui::ProgressBar::new(fraction)
+                .desired_width(120.0)
+                .text(if loading == 1 {
+                    "Loading an asset".to_owned()
+                } else {
+                    format!("Loading {} assets", loading)
+                }),
+        );
+        // The jobs only wake the event loop once they're done.
+        ui.ctx().request_repaint();
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context, dialogs: &FileDialogs) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        let mut remove = None;
+        egui::Window::new("Assets")
+            .open(&mut open)
+            .default_width(400.0)
+            .show(ctx, |ui| {
+                let load = ui
+                    .add_enabled(dialogs.available(), egui::Button::new("Load files…"))
+                    .on_disabled_hover_text("There's no file dialog here");
+                if load.clicked() {
+                    dialogs.pick_assets();
+                }
+                ui.separator();
+                if self.assets.is_empty() {
+                    ui.weak("Load images, WGSL shaders or glTF models to follow them here");
+                }
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (&id, asset) in &self.assets {
+                        ui.horizontal(|ui| {
+                            let name = asset
+                                .path
+                                .file_name()
+                                .map(|name| name.to_string_lossy().into_owned())
+                                .unwrap_or_default();
+                            ui.strong(name)
+                                .on_hover_text(asset.path.display().to_string());
+                            ui.weak(format!("{:?}", asset.kind));
+                            match &asset.state {
+                                LoadState::Loading => {
+                                    ui.spinner();
+                                    let read = asset.progress.read.load(Ordering::Relaxed);
+                                    let total = asset.progress.total.load(Ordering::Relaxed);
+                                    if total > 0 {
+                                        ui.add(
+                                            egui::ProgressBar::new(read as f32 / total as f32)
+                                                .show_percentage(),
+                                        );
+                                    }
+                                }
+                                LoadState::Loaded => {
+                                    let text = match &asset.source {
+                                        Some(source) => {
+                                            format!("Loaded, {} lines", source.lines().count())
+                                        }
+                                        None => "Loaded".to_owned(),
+                                    };
+                                    ui.label(text);
+                                }
+                                LoadState::Failed(e) => {
+                                    ui.colored_label(ui.visuals().error_fg_color, e);
+                                }
+                            }
+                            if asset.state != LoadState::Loading
+                                && ui.small_button("Remove").clicked()
+                            {
+                                remove = Some(id);
+                            }
+                        });
+                    }
+                });
+            });
+        if let Some(id) = remove {
+            self.assets.remove(&id);
+        }
+        self.open = open;
+    }
+}
+
+impl Job {
+    fn load(self) -> Result<AssetData, String> {
+        let bytes = match self.bytes {
+            Some(bytes) => bytes,
+            None => read(&self.path, &self.progress)
+                .map_err(|e| format!("Failed to read {}: {}", self.path.display(), e))?,
+        };
+        let data = match self.kind {
+            AssetKind::Image => textures::decode(&bytes).map(AssetData::Image),
+            AssetKind::Shader => String::from_utf8(bytes)
+                .map(AssetData::Shader)
+                .map_err(|_| "It isn't UTF-8".to_owned()),
+            AssetKind::Model => ModelData::parse(&self.path, &bytes).map(AssetData::Model),
+        };
+        data.map_err(|e| format!("Failed to load {}: {}", self.path.display(), e))
+    }
+}
+
+/// Reads the file a chunk at a time, counting what was read into `progress`.
+fn read(path: &Path, progress: &Progress) -> std::io::Result<Vec<u8>> {
+    use std::io::Read;
+
+    let mut file = std::fs::File::open(path)?;
+    let total = file.metadata()?.len();
+    progress.total.store(total, Ordering::Relaxed);
+    let mut bytes = Vec::with_capacity(total as usize);
+    let mut chunk = vec![0; CHUNK_SIZE];
+    loop {
+        let n = file.read(&mut chunk)?;
+        if n == 0 {
+            return Ok(bytes);
+        }
+        bytes.extend_from_slice(&chunk[..n]);
+        progress.read.fetch_add(n as u64, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,559 @@
+use egui::{Key, Modifiers, Pos2, Rect, Sense, Vec2};
+
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// How close, in points, the pointer has to be to grab an anchor or a handle.
+const GRAB_RADIUS: f32 = 8.0;
+
+/// The line segments each cubic is flattened into before being drawn.
+const SEGMENT_STEPS: usize = 32;
+
+/// A point the path goes through, with the control points of the curves around it.
+#[derive(Clone, Copy, Debug)]
+pub struct Anchor {
+    pub pos: Pos2,
+    /// The control points, relative to `pos`, of the incoming and outgoing curve.
+    pub in_handle: Vec2,
+    pub out_handle: Vec2,
+    /// The handles of a corner move independently, or are zero for a sharp corner.
+    pub corner: bool,
+}
+
+/// A chain of cubic Bezier segments, one between each pair of consecutive anchors.
+#[derive(Clone, Debug, Default)]
+pub struct BezierPath {
+    pub anchors: Vec<Anchor>,
+}
+
+impl BezierPath {
+    /// Points the handles of a smooth anchor along the line between its neighbors.
+    fn smooth(&mut self, i: usize) {
+        if self.anchors[i].corner {
+            return;
+        }
+        let pos = self.anchors[i].pos;
+        let prev = i.checked_sub(1).map(|i| self.anchors[i].pos);
+        let next = self.anchors.get(i + 1).map(|a| a.pos);
+        let tangent = match (prev, next) {
+            (Some(prev), Some(next)) => (next - prev) / 6.0,
+            (Some(prev), None) => (pos - prev) / 3.0,
+            (None, Some(next)) => (next - pos) / 3.0,
+            (None, None) => Vec2::ZERO,
+        };
+        self.anchors[i].in_handle = -tangent;
+        self.anchors[i].out_handle = tangent;
+    }
+
+    fn smooth_around(&mut self, i: usize) {
+        for j in i.saturating_sub(1)..(i + 2).min(self.anchors.len()) {
+            self.smooth(j);
+        }
+    }
+
+    pub fn push(&mut self, pos: Pos2) {
+        self.anchors.push(Anchor {
+            pos,
+            in_handle: Vec2::ZERO,
+            out_handle: Vec2::ZERO,
+            corner: false,
+        });
+        self.smooth_around(self.anchors.len() - 1);
+    }
+
+    pub fn remove(&mut self, i: usize) {
+        self.anchors.remove(i);
+        if !self.anchors.is_empty() {
+            self.smooth_around(i.min(self.anchors.len() - 1));
+        }
+    }
+
+    /// The control points of every segment: start, two controls and end.
+    fn segments(&self) -> impl Iterator<Item = [Pos2; 4]> + '_ {
+        self.anchors.windows(2).map(|pair| {
+            let (a, b) = (pair[0], pair[1]);
+            [a.pos, a.pos + a.out_handle, b.pos + b.in_handle, b.pos]
+        })
+    }
+
+    /// The path flattened into a polyline.
+    fn flatten(&self) -> Vec<Pos2> {
+        let mut points: Vec<Pos2> = self.anchors.first().map(|a| a.pos).into_iter().collect();
+        for [p0, p1, p2, p3] in self.segments() {
+            for step in 1..=SEGMENT_STEPS {
+                let t = step as f32 / SEGMENT_STEPS as f32;
+                let s = 1.0 - t;
+                let point = p0.to_vec2() * (s * s * s)
+                    + p1.to_vec2() * (3.0 * s * s * t)
+                    + p2.to_vec2() * (3.0 * s * t * t)
+                    + p3.to_vec2() * (t * t * t);
+                if points
+                    .last()
+                    .is_none_or(|last| last.distance(point.to_pos2()) > 0.01)
+                {
+                    points.push(point.to_pos2());
+                }
+            }
+        }
+        points
+    }
+
+    /// The path in SVG's `<path d="…">` notation, relative to `origin`.
+    pub fn to_svg_path(&self, origin: Pos2) -> String {
+        let mut d = String::new();
+        let Some(first) = self.anchors.first() else {
+            return d;
+        };
+        let p = |pos: Pos2| pos - origin;
+        let start = p(first.pos);
+        let _ = write!(d, "M {:.2} {:.2}", start.x, start.y);
+        for [_, c1, c2, end] in self.segments() {
+            let (c1, c2, end) = (p(c1), p(c2), p(end));
+            let _ = write!(
+                d,
+                " C {:.2} {:.2}, {:.2} {:.2}, {:.2} {:.2}",
+                c1.x, c1.y, c2.x, c2.y, end.x, end.y
+            );
+        }
+        d
+    }
+}
+
+/// What is being dragged on the canvas.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Grab {
+    Anchor(usize),
+    InHandle(usize),
+    OutHandle(usize),
+}
+
+/// A canvas for drawing a Bezier path, rendered by its own wgpu pipeline.
+///
+/// Click to add anchors, drag them or their handles, Shift+drag a handle to break the
+/// tangent, Shift+click an anchor for a sharp corner and Delete to remove the selection.
+pub struct BezierEditor {
+    pub open: bool,
+    path: BezierPath,
+    selected: Option<usize>,
+    grab: Option<Grab>,
+    width: f32,
+    dashed: bool,
+    color: egui::Color32,
+    svg_path: String,
+    status: Option<String>,
+}
+
+impl Default for BezierEditor {
+    fn default() -> Self {
+        Self {
+            open: false,
+            path: BezierPath::default(),
+            selected: None,
+            grab: None,
+            width: 4.0,
+            dashed: false,
+            color: egui::Color32::from_rgb(80, 160, 255),
+            svg_path: "path.svg".to_owned(),
+            status: None,
+        }
+    }
+}
+
+impl BezierEditor {
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        let mut open = self.open;
+        egui::Window::new("Bezier editor")
+            .open(&mut open)
+            .default_size([600.0, 450.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.add(egui::Slider::new(&mut self.width, 1.0..=32.0).text("Width"));
+                    ui.color_edit_button_srgba(&mut self.color);
+                    ui.checkbox(&mut self.dashed, "Dashed");
+                    if ui.button("Clear").clicked() {
+                        self.path.anchors.clear();
+                        self.selected = None;
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.svg_path);
+                    if ui.button("Export SVG").clicked() {
+                        let path = Path::new(&self.svg_path);
+                        self.status = Some(match self.write_svg(path) {
+                            Ok(()) => format!("Saved {}", path.display()),
+                            Err(e) => format!("Export failed: {}", e),
+                        });
+                    }
+                });
+                if let Some(status) = &self.status {
+                    ui.weak(status);
+                }
+                self.canvas(ui);
+            });
+        self.open = open;
+    }
+
+    fn write_svg(&self, path: &Path) -> std::io::Result<()> {
+        let points: Vec<Pos2> = self.path.segments().flatten().collect();
+        let bounds = Rect::from_points(&points).expand(self.width);
+        let (origin, size) = if bounds.is_positive() {
+            (bounds.min, bounds.size())
+        } else {
+            (Pos2::ZERO, Vec2::splat(1.0))
+        };
+        let [r, g, b, _] = self.color.to_array();
+        let svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w:.0}\" height=\"{h:.0}\" viewBox=\"0 0 {w:.2} {h:.2}\">\n  \
+             <path d=\"{d}\" fill=\"none\" stroke=\"#{r:02x}{g:02x}{b:02x}\" stroke-width=\"{width}\"{dash}/>\n</svg>\n",
+            w = size.x,
+            h = size.y,
+            d = self.path.to_svg_path(origin),
+            width = self.width,
+            dash = if self.dashed {
+                format!(" stroke-dasharray=\"{0} {0}\"", self.dash_length())
+            } else {
+                String::new()
+            },
+        );
+        std::fs::write(path, svg)
+    }
+
+    fn dash_length(&self) -> f32 {
+        self.width * 3.0
+    }
+
+    fn hit(&self, pos: Pos2) -> Option<Grab> {
+        let near = |p: Pos2| p.distance(pos) <= GRAB_RADIUS;
+        if let Some(i) = self.selected {
+            let a = self.path.anchors[i];
+            if near(a.pos + a.out_handle) && a.out_handle != Vec2::ZERO {
+                return Some(Grab::OutHandle(i));
+            }
+            if near(a.pos + a.in_handle) && a.in_handle != Vec2::ZERO {
+                return Some(Grab::InHandle(i));
+            }
+        }
+        self.path
+            .anchors
+            .iter()
+            .rposition(|a| near(a.pos))
+            .map(Grab::Anchor)
+    }
+
+    fn canvas(&mut self, ui: &mut egui::Ui) {
+        let (response, painter) = ui.allocate_painter(ui.available_size(), Sense::click_and_drag());
+        let rect = response.rect;
+        painter.rect_filled(rect, 4.0, ui.visuals().extreme_bg_color);
+        let shift = ui.input(|i| i.modifiers.shift);
+
+        if response.drag_started() {
+            self.grab = response
+                .interact_pointer_pos()
+                .and_then(|pos| self.hit(pos));
+            if let Some(Grab::Anchor(i)) = self.grab {
+                self.selected = Some(i);
+            }
+        }
+        if response.dragged() {
+            let delta = response.drag_delta();
+            match self.grab {
+                Some(Grab::Anchor(i)) => self.path.anchors[i].pos += delta,
+                Some(Grab::InHandle(i)) | Some(Grab::OutHandle(i)) => {
+                    let anchor = &mut self.path.anchors[i];
+                    anchor.corner |= shift;
+                    let (handle, opposite) = match self.grab {
+                        Some(Grab::InHandle(_)) => (&mut anchor.in_handle, &mut anchor.out_handle),
+                        _ => (&mut anchor.out_handle, &mut anchor.in_handle),
+                    };
+                    *handle += delta;
+                    if !anchor.corner {
+                        *opposite = -*handle;
+                    }
+                }
+                None => {}
+            }
+        }
+        if response.drag_released() {
+            self.grab = None;
+        }
+
+        if response.clicked() {
+            if let Some(pos) = response.interact_pointer_pos() {
+                match self.hit(pos) {
+                    Some(Grab::Anchor(i)) => {
+                        self.selected = Some(i);
+                        if shift {
+                            let anchor = &mut self.path.anchors[i];
+                            anchor.corner = !anchor.corner;
+                            if anchor.corner {
+                                anchor.in_handle = Vec2::ZERO;
+                                anchor.out_handle = Vec2::ZERO;
+                            } else {
+                                self.path.smooth(i);
+                            }
+                        }
+                    }
+                    Some(_) => {}
+                    None => {
+                        self.path.push(pos);
+                        self.selected = Some(self.path.anchors.len() - 1);
+                    }
+                }
+            }
+        }
+
+        // Only while hovered, so the key still reaches text fields elsewhere.
+        if let (Some(i), true) = (self.selected, response.hovered()) {
+            if ui.input_mut(|i| i.consume_key(Modifiers::NONE, Key::Delete)) {
+                self.path.remove(i);
+                self.selected = None;
+            }
+        }
+
+        let points = self.path.flatten();
+        if points.len() >= 2 {
+            let rgba = egui::Rgba::from(self.color);
+            let callback = BezierCallback {
+                vertices: stroke_vertices(&points),
+                uniforms: Uniforms {
+                    screen_size: ui.ctx().screen_rect().size().into(),
+                    half_width: self.width / 2.0,
+                    pixels_per_point: ui.ctx().pixels_per_point(),
+                    color: [rgba.r(), rgba.g(), rgba.b(), rgba.a()],
+                    dash: if self.dashed { self.dash_length() } else { 0.0 },
+                    _pad: [0.0; 3],
+                },
+                gamma: self.color.to_normalized_gamma_f32(),
+            };
+            // egui sets the viewport to this rect, it can't be off the screen.
+            let viewport = rect.intersect(ui.ctx().screen_rect());
+            if viewport.is_positive() {
+                painter.add(egui_wgpu::Callback::new_paint_callback(viewport, callback));
+            }
+        }
+
+        let stroke = ui.visuals().widgets.active.fg_stroke;
+        for (i, anchor) in self.path.anchors.iter().enumerate() {
+            if Some(i) == self.selected {
+                for handle in [anchor.in_handle, anchor.out_handle] {
+                    if handle != Vec2::ZERO {
+                        painter.line_segment([anchor.pos, anchor.pos + handle], stroke);
+                        painter.circle_stroke(anchor.pos + handle, 3.0, stroke);
+                    }
+                }
+            }
+            let fill = if Some(i) == self.selected {
+                ui.visuals().selection.bg_fill
+            } else {
+                ui.visuals().widgets.inactive.bg_fill
+            };
+            if anchor.corner {
+                let square = Rect::from_center_size(anchor.pos, Vec2::splat(8.0));
+                painter.rect(square, 0.0, fill, stroke);
+            } else {
+                painter.circle(anchor.pos, 4.0, fill, stroke);
+            }
+        }
+        if self.path.anchors.is_empty() {
+            painter.text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                "Click to add points",
+                egui::TextStyle::Body.resolve(ui.style()),
+                ui.visuals().weak_text_color(),
+            );
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Vertex {
+    center: [f32; 2],
+    /// Scaled so that the offset reaches the edge at mitered joins.
+    normal: [f32; 2],
+    side: f32,
+    arc_length: f32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Uniforms {
+    screen_size: [f32; 2],
+    half_width: f32,
+    pixels_per_point: f32,
+    color: [f32; 4],
+    dash: f32,
+    _pad: [f32; 3],
+}
+
+/// A triangle strip along the polyline, two vertices per point.
+fn stroke_vertices(points: &[Pos2]) -> Vec<Vertex> {
+    let direction = |a: Pos2, b: Pos2| (b - a).normalized();
+    let mut arc_length = 0.0;
+    let mut vertices = Vec::with_capacity(points.len() * 2);
+    for (i, &point) in points.iter().enumerate() {
+        if i > 0 {
+            arc_length += points[i - 1].distance(point);
+        }
+        let incoming = (i > 0).then(|| direction(points[i - 1], point));
+        let outgoing = points.get(i + 1).map(|&next| direction(point, next));
+        let normal = match (incoming, outgoing) {
+            (Some(a), Some(b)) => {
+                let tangent = a + b;
+                if tangent.length() < 1e-3 {
+                    a.rot90()
+                } else {
+                    let miter = tangent.normalized().rot90();
+                    miter / miter.dot(a.rot90()).max(0.25)
+                }
+            }
+            (Some(d), None) | (None, Some(d)) => d.rot90(),
+            (None, None) => Vec2::ZERO,
+        };
+        for side in [-1.0, 1.0] {
+            vertices.push(Vertex {
+                center: [point.x, point.y],
+                normal: [normal.x, normal.y],
+                side,
+                arc_length,
+            });
+        }
+    }
+    vertices
+}
+
+/// The pipeline drawing the path, kept in the egui renderer's callback resources.
+pub struct BezierResources {
+    pipeline: wgpu::RenderPipeline,
+    uniforms: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    vertices: wgpu::Buffer,
+    vertex_count: u32,
+    /// False when the target isn't sRGB, so colors have to be passed gamma encoded.
+    srgb_target: bool,
+}
+
+impl BezierResources {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let shader = device.create_shader_module(wgpu::include_wgsl!("shaders/bezier.wgsl"));
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("bezier"),
+            layout: None,
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<Vertex>() as u64,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![
+                        0 => Float32x2,
+                        1 => Float32x2,
+                        2 => Float32,
+                        3 => Float32,
+                    ],
+                }],
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+        let uniforms = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("bezier uniforms"),
+            size: std::mem::size_of::<Uniforms>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("bezier"),
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniforms.as_entire_binding(),
+            }],
+        });
+        Self {
+            pipeline,
+            uniforms,
+            bind_group,
+            vertices: create_vertex_buffer(device, 1024),
+            vertex_count: 0,
+            srgb_target: format.is_srgb(),
+        }
+    }
+}
+
+fn create_vertex_buffer(device: &wgpu::Device, vertices: usize) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("bezier vertices"),
+        size: (vertices * std::mem::size_of::<Vertex>()) as u64,
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+struct BezierCallback {
+    vertices: Vec<Vertex>,
+    uniforms: Uniforms,
+    /// The color gamma encoded, for targets that aren't sRGB.
+    gamma: [f32; 4],
+}
+
+impl egui_wgpu::CallbackTrait for BezierCallback {
+    fn prepare(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        _egui_encoder: &mut wgpu::CommandEncoder,
+        callback_resources: &mut egui_wgpu::CallbackResources,
+    ) -> Vec<wgpu::CommandBuffer> {
+        let Some(resources) = callback_resources.get_mut::<BezierResources>() else {
+            return Vec::new();
+        };
+        let size = std::mem::size_of_val(self.vertices.as_slice()) as u64;
+        if resources.vertices.size() < size {
+            resources.vertices =
+                create_vertex_buffer(device, self.vertices.len().next_power_of_two());
+        }
+        queue.write_buffer(&resources.vertices, 0, bytemuck::cast_slice(&self.vertices));
+        resources.vertex_count = self.vertices.len() as u32;
+
+        let mut uniforms = self.uniforms;
+        if !resources.srgb_target {
+            uniforms.color = self.gamma;
+        }
+        queue.write_buffer(&resources.uniforms, 0, bytemuck::bytes_of(&uniforms));
+        Vec::new()
+    }
+
+    fn paint<'a>(
+        &'a self,
+        info: egui::PaintCallbackInfo,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        callback_resources: &'a egui_wgpu::CallbackResources,
+    ) {
+        let Some(resources) = callback_resources.get::<BezierResources>() else {
+            return;
+        };
+        // The vertices are in screen points, the clip rect still applies as scissor.
+        let [width, height] = info.screen_size_px;
+        render_pass.set_viewport(0.0, 0.0, width as f32, height as f32, 0.0, 1.0);
+        render_pass.set_pipeline(&resources.pipeline);
+        render_pass.set_bind_group(0, &resources.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, resources.vertices.slice(..));
+        render_pass.draw(0..resources.vertex_count, 0..1);
+    }
+}
@@ -0,0 +1,193 @@
+use egui_extras::{Column, TableBuilder};
+
+/// How many rows the table has, made up from their index when they're shown.
+const ROWS: usize = 1_000_000;
+/// Names are three of these. They're sorted and as long as each other, so sorting names
+/// is sorting the indices they're picked by.
+const SYLLABLES: [&str; 16] = [
+    "ba", "ce", "di", "fo", "ga", "he", "ki", "lo", "ma", "ne", "po", "ra", "si", "to", "vu", "ze",
+];
+/// Sorted too, for the same reason.
+const CATEGORIES: [&str; 6] = ["Audio", "Input", "Network", "Render", "Storage", "Ui"];
+
+/// What the rows can be sorted by, one of the table's columns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortBy {
+    Index,
+    Name,
+    Value,
+    Category,
+}
+
+impl SortBy {
+    const ALL: [SortBy; 4] = [SortBy::Index, SortBy::Name, SortBy::Value, SortBy::Category];
+
+    fn title(self) -> &'static str {
+        match self {
+            SortBy::Index => "#",
+            SortBy::Name => "Name",
+            SortBy::Value => "Value",
+            SortBy::Category => "Category",
+        }
+    }
+
+    /// What a row is ordered by in this column.
+    fn key(self, row: u32) -> u64 {
+        let hash = hash(row);
+        match self {
+            SortBy::Index => row.into(),
+            SortBy::Name => hash >> 52,
+            // A positive float's bits are in the same order as it.
+            SortBy::Value => value(hash).to_bits().into(),
+            SortBy::Category => category(hash) as u64,
+        }
+    }
+}
+
+/// The "Large table" window: a million made up rows in an `egui_extras` table, laying
+/// out only the rows in view, with sortable and resizable columns.
+pub struct BigTable {
+    pub open: bool,
+    /// The rows in the order shown, made when the window is first shown.
+    order: Vec<u32>,
+    sort_by: SortBy,
+    ascending: bool,
+    /// How long the last sort took, in milliseconds.
+    sort_ms: Option<f32>,
+}
+
+impl Default for BigTable {
+    fn default() -> Self {
+        Self {
+            open: false,
+            order: Vec::new(),
+            sort_by: SortBy::Index,
+            ascending: true,
+            sort_ms: None,
+        }
+    }
+}
+
+impl BigTable {
+    /// Sorts by `sort_by`, or turns the order around when it's sorted by it already.
+    pub fn sort(&mut self, sort_by: SortBy) {
+        let start = instant::Instant::now();
+        if sort_by == self.sort_by {
+            self.ascending = !self.ascending;
+            self.order.reverse();
+        } else {
+            self.sort_by = sort_by;
+            self.ascending = true;
+            self.order.sort_by_key(|&row| sort_by.key(row));
+        }
+        self.sort_ms = Some(start.elapsed().as_secs_f32() * 1000.0);
+    }
+
+    /// `frame_ms` is the CPU time of the last frame, shown to see it stays put however
+    /// the table is scrolled.
+    pub fn ui(&mut self, ctx: &egui::Context, frame_ms: Option<f32>) {
+        if !self.open {
+            return;
+        }
+        if self.order.is_empty() {
+            self.order = (0..ROWS as u32).collect();
+            self.sort_by = SortBy::Index;
+            self.ascending = true;
+        }
+
+        let mut open = self.open;
+        let mut sort = None;
+        egui::Window::new("Large table")
+            .open(&mut open)
+            .default_size([480.0, 400.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} rows", ROWS));
+                    if let Some(ms) = frame_ms {
+                        ui.weak(format!("Frame: {:.2} ms of CPU", ms));
+                    }
+                    if let Some(ms) = self.sort_ms {
+                        ui.weak(format!("Sorted in {:.0} ms", ms));
+                    }
+                });
+                ui.separator();
+
+                let row_height = ui.text_style_height(&egui::TextStyle::Body);
+                TableBuilder::new(ui)
+                    .striped(true)
+                    .resizable(true)
+                    .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                    .column(Column::initial(70.0).at_least(40.0).resizable(true))
+                    .column(Column::initial(90.0).at_least(40.0).resizable(true))
+                    .column(Column::initial(90.0).at_least(40.0).resizable(true))
+                    .column(Column::remainder().at_least(40.0))
+                    .min_scrolled_height(0.0)
+                    .header(row_height + 4.0, |mut header| {
+                        for sort_by in SortBy::ALL {
+                            header.col(|ui| {
+                                let arrow = match (sort_by == self.sort_by, self.ascending) {
+                                    (false, _) => "",
+                                    (true, true) => " ⏶",
+                                    (true, false) => " ⏷",
+                                };
+                                let title = format!("{}{}", sort_by.title(), arrow);
+                                if ui
+                                    .add(
+                                        egui::Button::new(egui::RichText::new(title).strong())
+                                            .frame(false),
+                                    )
+                                    .on_hover_text("Sort by this column")
+                                    .clicked()
+                                {
+                                    sort = Some(sort_by);
+                                }
+                            });
+                        }
+                    })
+                    .body(|body| {
+                        body.rows(row_height, self.order.len(), |index, mut row| {
+                            let row_index = self.order[index];
+                            let hash = hash(row_index);
+                            row.col(|ui| {
+                                ui.monospace(row_index.to_string());
+                            });
+                            row.col(|ui| {
+                                ui.label(name(hash));
+                            });
+                            row.col(|ui| {
+                                ui.monospace(format!("{:.4}", value(hash)));
+                            });
+                            row.col(|ui| {
+                                ui.label(CATEGORIES[category(hash)]);
+                            });
+                        });
+                    });
+            });
+        if let Some(sort_by) = sort {
+            self.sort(sort_by);
+        }
+        self.open = open;
+    }
+}
+
+/// SplitMix64 of the row's index, which its made up cells are taken from.
+fn hash(row: u32) -> u64 {
+    let mut z = u64::from(row).wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+fn name(hash: u64) -> String {
+    let syllable = |shift: u32| SYLLABLES[(hash >> shift) as usize & 0xf];
+    format!("{}{}{}", syllable(60), syllable(56), syllable(52))
+}
+
+/// From 0 to 1000.
+fn value(hash: u64) -> f32 {
+    ((hash >> 20) & 0xffff_ffff) as f32 / u32::MAX as f32 * 1000.0
+}
+
+fn category(hash: u64) -> usize {
+    (hash % CATEGORIES.len() as u64) as usize
+}
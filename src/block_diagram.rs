@@ -0,0 +1,525 @@
+use egui::{Color32, Pos2, Rect, Sense, Stroke, Vec2};
+use serde::{Deserialize, Serialize};
+
+use std::path::Path;
+
+const BLOCK_WIDTH: f32 = 140.0;
+const HEADER_HEIGHT: f32 = 22.0;
+const PORT_SPACING: f32 = 18.0;
+const PORT_RADIUS: f32 = 5.0;
+/// How far wires go straight out of a port before turning.
+const STUB: f32 = 15.0;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Port {
+    pub name: String,
+    pub ty: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Block {
+    pub id: u32,
+    pub name: String,
+    /// The top left corner, relative to the canvas.
+    pub pos: [f32; 2],
+    pub inputs: Vec<Port>,
+    pub outputs: Vec<Port>,
+}
+
+impl Block {
+    fn rect(&self, origin: Pos2) -> Rect {
+        let ports = self.inputs.len().max(self.outputs.len()).max(1);
+        let size = egui::vec2(
+            BLOCK_WIDTH,
+            HEADER_HEIGHT + ports as f32 * PORT_SPACING + 6.0,
+        );
+        Rect::from_min_size(origin + Vec2::from(self.pos), size)
+    }
+
+    fn port_pos(rect: Rect, i: usize, output: bool) -> Pos2 {
+        let x = if output { rect.right() } else { rect.left() };
+        egui::pos2(
+            x,
+            rect.top() + HEADER_HEIGHT + (i as f32 + 0.5) * PORT_SPACING,
+        )
+    }
+}
+
+/// A port of a block, by the block's id and the port's index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortRef {
+    pub block: u32,
+    pub port: usize,
+}
+
+/// A wire from an output port to an input port.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Connection {
+    pub from: PortRef,
+    pub to: PortRef,
+}
+
+/// What the diagram saves to JSON.
+#[derive(Default, Serialize, Deserialize)]
+pub struct Graph {
+    pub blocks: Vec<Block>,
+    pub connections: Vec<Connection>,
+    next_id: u32,
+}
+
+impl Graph {
+    pub fn add_block(
+        &mut self,
+        name: impl Into<String>,
+        pos: [f32; 2],
+        inputs: Vec<Port>,
+        outputs: Vec<Port>,
+    ) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.blocks.push(Block {
+            id,
+            name: name.into(),
+            pos,
+            inputs,
+            outputs,
+        });
+        id
+    }
+
+    fn block(&self, id: u32) -> Option<&Block> {
+        self.blocks.iter().find(|b| b.id == id)
+    }
+
+    fn remove_block(&mut self, id: u32) {
+        self.blocks.retain(|b| b.id != id);
+        self.connections
+            .retain(|c| c.from.block != id && c.to.block != id);
+    }
+
+    /// Connects the ports, replacing the wire the input had.
+    pub fn connect(&mut self, from: PortRef, to: PortRef) {
+        self.connections.retain(|c| c.to != to);
+        self.connections.push(Connection { from, to });
+    }
+
+    /// Whether following the wires from `from` leads to `to`.
+    fn reaches(&self, from: u32, to: u32) -> bool {
+        let mut stack = vec![from];
+        let mut seen = vec![from];
+        while let Some(block) = stack.pop() {
+            if block == to {
+                return true;
+            }
+            for c in self.connections.iter().filter(|c| c.from.block == block) {
+                if !seen.contains(&c.to.block) {
+                    seen.push(c.to.block);
+                    stack.push(c.to.block);
+                }
+            }
+        }
+        false
+    }
+
+    /// Why the wire is invalid: its ports' types differ, or it closes a cycle.
+    fn problem(&self, c: &Connection) -> Option<String> {
+        let from = self.block(c.from.block)?.outputs.get(c.from.port)?;
+        let to = self.block(c.to.block)?.inputs.get(c.to.port)?;
+        if from.ty != to.ty {
+            Some(format!("{} doesn't match {}", from.ty, to.ty))
+        } else if self.reaches(c.to.block, c.from.block) {
+            Some("Closes a cycle".to_owned())
+        } else {
+            None
+        }
+    }
+}
+
+/// Ports typed as `name: type`, separated by commas. The type defaults to `f32`.
+fn parse_ports(text: &str) -> Vec<Port> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|port| !port.is_empty())
+        .map(|port| match port.split_once(':') {
+            Some((name, ty)) => Port {
+                name: name.trim().to_owned(),
+                ty: ty.trim().to_owned(),
+            },
+            None => Port {
+                name: port.to_owned(),
+                ty: "f32".to_owned(),
+            },
+        })
+        .collect()
+}
+
+/// The "Add Block" form, opened from the canvas context menu.
+struct NewBlock {
+    /// Where the canvas was right-clicked, relative to it.
+    pos: [f32; 2],
+    name: String,
+    inputs: String,
+    outputs: String,
+}
+
+#[derive(Clone, Copy)]
+enum Drag {
+    Block(u32),
+    /// A wire being pulled out of an output port.
+    Wire(PortRef),
+}
+
+/// A canvas of blocks with typed ports, wired from outputs to inputs.
+pub struct BlockDiagram {
+    pub graph: Graph,
+    drag: Option<Drag>,
+    /// Where the context menu was opened, relative to the canvas, and the block there.
+    menu: (Vec2, Option<u32>),
+    new_block: Option<NewBlock>,
+    path: String,
+    status: Option<String>,
+}
+
+impl BlockDiagram {
+    pub fn new(graph: Graph) -> Self {
+        Self {
+            graph,
+            drag: None,
+            menu: (Vec2::ZERO, None),
+            new_block: None,
+            path: "block_diagram.json".to_owned(),
+            status: None,
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.path);
+            if ui.button("Save").clicked() {
+                self.status = Some(match self.save(Path::new(&self.path)) {
+                    Ok(()) => format!("Saved {}", self.path),
+                    Err(e) => format!("Save failed: {}", e),
+                });
+            }
+            if ui.button("Load").clicked() {
+                self.status = Some(match load(Path::new(&self.path)) {
+                    Ok(graph) => {
+                        self.graph = graph;
+                        format!("Loaded {}", self.path)
+                    }
+                    Err(e) => format!("Load failed: {}", e),
+                });
+            }
+        });
+        if let Some(status) = &self.status {
+            ui.weak(status);
+        }
+        self.canvas(ui);
+        self.new_block_ui(ui.ctx());
+    }
+
+    fn save(&self, path: &Path) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(&self.graph)?;
+        std::fs::write(path, json)
+    }
+
+    fn hit_port(&self, origin: Pos2, pos: Pos2, output: bool) -> Option<PortRef> {
+        self.graph.blocks.iter().rev().find_map(|block| {
+            let rect = block.rect(origin);
+            let ports = if output {
+                &block.outputs
+            } else {
+                &block.inputs
+            };
+            (0..ports.len())
+                .find(|&i| Block::port_pos(rect, i, output).distance(pos) <= PORT_RADIUS * 2.0)
+                .map(|port| PortRef {
+                    block: block.id,
+                    port,
+                })
+        })
+    }
+
+    fn hit_block(&self, origin: Pos2, pos: Pos2) -> Option<u32> {
+        self.graph
+            .blocks
+            .iter()
+            .rev()
+            .find(|block| block.rect(origin).contains(pos))
+            .map(|block| block.id)
+    }
+
+    fn port_screen_pos(&self, origin: Pos2, port: PortRef, output: bool) -> Option<Pos2> {
+        let block = self.graph.block(port.block)?;
+        Some(Block::port_pos(block.rect(origin), port.port, output))
+    }
+
+    fn canvas(&mut self, ui: &mut egui::Ui) {
+        let size = ui.available_size().max(egui::vec2(400.0, 300.0));
+        let (response, painter) = ui.allocate_painter(size, Sense::click_and_drag());
+        let origin = response.rect.min;
+        let visuals = ui.visuals().clone();
+        painter.rect_filled(response.rect, 0.0, visuals.extreme_bg_color);
+
+        // The blocks move and the wires get pulled out all through the canvas' response,
+        // so the window doesn't move along with them.
+        if response.drag_started() {
+            if let Some(pos) = response.interact_pointer_pos() {
+                self.drag = match self.hit_port(origin, pos, true) {
+                    Some(port) => Some(Drag::Wire(port)),
+                    None => self.hit_block(origin, pos).map(Drag::Block),
+                };
+            }
+        }
+        if let Some(Drag::Block(id)) = self.drag {
+            let delta = response.drag_delta();
+            if let Some(block) = self.graph.blocks.iter_mut().find(|b| b.id == id) {
+                block.pos[0] += delta.x;
+                block.pos[1] += delta.y;
+            }
+        }
+        if response.drag_released() {
+            if let (Some(Drag::Wire(from)), Some(pos)) = (self.drag, ui.ctx().pointer_latest_pos())
+            {
+                if let Some(to) = self.hit_port(origin, pos, false) {
+                    self.graph.connect(from, to);
+                }
+            }
+            self.drag = None;
+        }
+        if response.secondary_clicked() {
+            if let Some(pos) = response.interact_pointer_pos() {
+                self.menu = (pos - origin, self.hit_block(origin, pos));
+            }
+        }
+        let response = response.context_menu(|ui| match self.menu.1 {
+            Some(id) => {
+                if ui.button("Delete Block").clicked() {
+                    self.graph.remove_block(id);
+                    ui.close_menu();
+                }
+            }
+            None => {
+                if ui.button("Add Block").clicked() {
+                    let pos = self.menu.0;
+                    self.new_block = Some(NewBlock {
+                        pos: [pos.x, pos.y],
+                        name: "Block".to_owned(),
+                        inputs: "in".to_owned(),
+                        outputs: "out".to_owned(),
+                    });
+                    ui.close_menu();
+                }
+            }
+        });
+
+        // Wires are routed from the block positions every frame, so moving a block
+        // re-routes everything attached to it.
+        let hover = response.hover_pos();
+        let mut tooltip = None;
+        for c in &self.graph.connections {
+            let (Some(from), Some(to)) = (
+                self.port_screen_pos(origin, c.from, true),
+                self.port_screen_pos(origin, c.to, false),
+            ) else {
+                continue;
+            };
+            let rects = (
+                self.graph.block(c.from.block).map(|b| b.rect(origin)),
+                self.graph.block(c.to.block).map(|b| b.rect(origin)),
+            );
+            let points = route(from, to, rects.0.zip(rects.1));
+            let problem = self.graph.problem(c);
+            let color = if problem.is_some() {
+                Color32::RED
+            } else {
+                visuals.widgets.inactive.fg_stroke.color
+            };
+            if let (Some(problem), Some(hover)) = (&problem, hover) {
+                if points
+                    .windows(2)
+                    .any(|w| Rect::from_two_pos(w[0], w[1]).expand(4.0).contains(hover))
+                {
+                    tooltip = Some(problem.clone());
+                }
+            }
+            painter.add(egui::Shape::line(points, Stroke::new(2.0, color)));
+        }
+        if let (Some(Drag::Wire(from)), Some(pos)) = (self.drag, hover) {
+            if let Some(start) = self.port_screen_pos(origin, from, true) {
+                let points = route(start, pos, None);
+                painter.add(egui::Shape::line(points, visuals.selection.stroke));
+            }
+        }
+
+        let body = egui::TextStyle::Body.resolve(ui.style());
+        let small = egui::TextStyle::Small.resolve(ui.style());
+        for block in &self.graph.blocks {
+            let rect = block.rect(origin);
+            let header = Rect::from_min_size(rect.min, egui::vec2(rect.width(), HEADER_HEIGHT));
+            let stroke = if matches!(self.drag, Some(Drag::Block(id)) if id == block.id) {
+                visuals.selection.stroke
+            } else {
+                visuals.widgets.inactive.bg_stroke
+            };
+            painter.rect(rect, 4.0, visuals.widgets.inactive.bg_fill, stroke);
+            painter.text(
+                header.center(),
+                egui::Align2::CENTER_CENTER,
+                &block.name,
+                body.clone(),
+                visuals.strong_text_color(),
+            );
+            for (ports, output) in [(&block.inputs, false), (&block.outputs, true)] {
+                for (i, port) in ports.iter().enumerate() {
+                    let pos = Block::port_pos(rect, i, output);
+                    painter.circle(
+                        pos,
+                        PORT_RADIUS,
+                        visuals.widgets.active.bg_fill,
+                        visuals.widgets.inactive.fg_stroke,
+                    );
+                    let (anchor, offset) = if output {
+                        (egui::Align2::RIGHT_CENTER, -2.0 * PORT_RADIUS)
+                    } else {
+                        (egui::Align2::LEFT_CENTER, 2.0 * PORT_RADIUS)
+                    };
+                    painter.text(
+                        pos + egui::vec2(offset, 0.0),
+                        anchor,
+                        format!("{}: {}", port.name, port.ty),
+                        small.clone(),
+                        visuals.text_color(),
+                    );
+                }
+            }
+        }
+
+        if let Some(tooltip) = tooltip {
+            response.on_hover_text_at_pointer(tooltip);
+        }
+    }
+
+    fn new_block_ui(&mut self, ctx: &egui::Context) {
+        let Some(new_block) = &mut self.new_block else {
+            return;
+        };
+        let mut done = false;
+        let mut add = false;
+        egui::Window::new("Add Block")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("add_block_grid").show(ui, |ui| {
+                    ui.label("Name");
+                    ui.text_edit_singleline(&mut new_block.name);
+                    ui.end_row();
+                    ui.label("Inputs");
+                    ui.text_edit_singleline(&mut new_block.inputs);
+                    ui.end_row();
+                    ui.label("Outputs");
+                    ui.text_edit_singleline(&mut new_block.outputs);
+                    ui.end_row();
+                });
+                ui.weak("Ports are comma separated, as name: type");
+                ui.horizontal(|ui| {
+                    add = ui.button("Add").clicked();
+                    done = add || ui.button("Cancel").clicked();
+                });
+            });
+        if add {
+            self.graph.add_block(
+                new_block.name.clone(),
+                new_block.pos,
+                parse_ports(&new_block.inputs),
+                parse_ports(&new_block.outputs),
+            );
+        }
+        if done {
+            self.new_block = None;
+        }
+    }
+}
+
+fn load(path: &Path) -> std::io::Result<Graph> {
+    let json = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+/// An orthogonal route from an output port to an input port.
+///
+/// When the input is behind the output the wire goes around, between the two blocks or
+/// below both of them if they overlap vertically.
+fn route(from: Pos2, to: Pos2, blocks: Option<(Rect, Rect)>) -> Vec<Pos2> {
+    if to.x >= from.x + 2.0 * STUB {
+        let mid = (from.x + to.x) / 2.0;
+        return vec![from, egui::pos2(mid, from.y), egui::pos2(mid, to.y), to];
+    }
+    let y = match blocks {
+        Some((a, b)) if a.bottom() + STUB < b.top() => (a.bottom() + b.top()) / 2.0,
+        Some((a, b)) if b.bottom() + STUB < a.top() => (b.bottom() + a.top()) / 2.0,
+        Some((a, b)) => a.bottom().max(b.bottom()) + STUB,
+        None => (from.y + to.y) / 2.0,
+    };
+    let (x1, x2) = (from.x + STUB, to.x - STUB);
+    vec![
+        from,
+        egui::pos2(x1, from.y),
+        egui::pos2(x1, y),
+        egui::pos2(x2, y),
+        egui::pos2(x2, to.y),
+        to,
+    ]
+}
+
+/// The demo diagram: a feedback loop around a plant, and a comparator to wire wrongly.
+pub fn demo() -> BlockDiagram {
+    let port = |name: &str, ty: &str| Port {
+        name: name.to_owned(),
+        ty: ty.to_owned(),
+    };
+    let mut graph = Graph::default();
+    let setpoint = graph.add_block("Setpoint", [20.0, 40.0], vec![], vec![port("out", "f32")]);
+    let sum = graph.add_block(
+        "Sum",
+        [200.0, 30.0],
+        vec![port("a", "f32"), port("b", "f32")],
+        vec![port("out", "f32")],
+    );
+    let gain = graph.add_block(
+        "Gain",
+        [380.0, 40.0],
+        vec![port("in", "f32")],
+        vec![port("out", "f32")],
+    );
+    let scope = graph.add_block("Scope", [560.0, 40.0], vec![port("in", "f32")], vec![]);
+    let sensor = graph.add_block(
+        "Sensor",
+        [380.0, 160.0],
+        vec![port("in", "f32")],
+        vec![port("out", "f32")],
+    );
+    graph.add_block(
+        "Comparator",
+        [20.0, 160.0],
+        vec![port("a", "f32"), port("b", "f32")],
+        vec![port("above", "bool")],
+    );
+    let wire = |graph: &mut Graph, from: u32, out: usize, to: u32, input: usize| {
+        graph.connect(
+            PortRef {
+                block: from,
+                port: out,
+            },
+            PortRef {
+                block: to,
+                port: input,
+            },
+        )
+    };
+    wire(&mut graph, setpoint, 0, sum, 0);
+    wire(&mut graph, sum, 0, gain, 0);
+    wire(&mut graph, gain, 0, scope, 0);
+    wire(&mut graph, gain, 0, sensor, 0);
+    BlockDiagram::new(graph)
+}
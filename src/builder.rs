@@ -0,0 +1,340 @@
+use crate::color_space::ColorSpace;
+use crate::error::AppError;
+use crate::events::{self, UserEvent};
+use crate::gfx::GraphicsContext;
+use crate::gui::Gui;
+use crate::headless;
+use crate::icon;
+use crate::letterbox::LetterboxConfig;
+use crate::theme::Theme;
+use crate::window_geometry::{WindowGeometry, DEFAULT_SIZE};
+use crate::Application;
+
+use winit::event_loop::{EventLoop, EventLoopBuilder, EventLoopWindowTarget};
+#[cfg(target_os = "android")]
+use winit::platform::android::{activity::AndroidApp, EventLoopBuilderExtAndroid};
+use winit::window::Window;
+
+use std::borrow::Cow;
+use std::path::PathBuf;
+
+/// The window's title unless `AppBuilder::title` says otherwise.
+pub(crate) const DEFAULT_TITLE: &str = "winit-wgpu-egui example";
+
+/// Configures the window and the wgpu and egui setup, then runs an `Application` in it.
+#[derive(Clone, Debug)]
+pub struct AppBuilder {
+    title: String,
+    size: Option<[u32; 2]>,
+    vsync: bool,
+    backends: wgpu::Backends,
+    msaa: u32,
+    theme: Theme,
+    color_space: ColorSpace,
+    letterbox: LetterboxConfig,
+    persist_ui: bool,
+    persist_window: bool,
+    fullscreen: Option<bool>,
+    transparent: bool,
+    compat: bool,
+    /// Where headless runs write their frame timings.
+    bench_json: Option<PathBuf>,
+    decorations: bool,
+    /// A PNG, decoded when the window is created.
+    icon: Option<Cow<'static, [u8]>>,
+    #[cfg(target_os = "android")]
+    android_app: Option<AndroidApp>,
+}
+
+impl Default for AppBuilder {
+    fn default() -> Self {
+        Self {
+            title: DEFAULT_TITLE.to_owned(),
+            size: None,
+            vsync: true,
+            backends: wgpu::Backends::PRIMARY,
+            msaa: 1,
+            theme: Theme::default(),
+            color_space: ColorSpace::default(),
+            letterbox: LetterboxConfig::default(),
+            persist_ui: true,
+            persist_window: true,
+            fullscreen: None,
+            transparent: false,
+            compat: false,
+            bench_json: None,
+            decorations: true,
+            icon: None,
+            #[cfg(target_os = "android")]
+            android_app: None,
+        }
+    }
+}
+
+impl AppBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// The initial inner size of the window, in physical pixels, instead of the size
+    /// it had when it was last closed.
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.size = Some([width, height]);
+        self
+    }
+
+    /// Whether presenting waits for the display's refresh, until the app's own
+    /// `Application::present_mode` says otherwise.
+    pub fn vsync(mut self, vsync: bool) -> Self {
+        self.vsync = vsync;
+        self
+    }
+
+    /// The backends wgpu may pick an adapter from.
+    pub fn backend(mut self, backends: wgpu::Backends) -> Self {
+        self.backends = backends;
+        self
+    }
+
+    /// Samples per pixel of what the app renders beneath the UI, lowered to what the
+    /// adapter supports.
+    pub fn msaa(mut self, samples: u32) -> Self {
+        self.msaa = samples.max(1);
+        self
+    }
+
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// The requested output color space, sRGB is used if the display can't do it.
+    pub fn color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
+    pub fn letterbox(mut self, letterbox: LetterboxConfig) -> Self {
+        self.letterbox = letterbox;
+        self
+    }
+
+    /// Whether egui's memory, with where the windows are, is saved in the config
+    /// directory when the app exits or is suspended, and restored at startup. On by
+    /// default.
+    pub fn persist_ui(mut self, persist: bool) -> Self {
+        self.persist_ui = persist;
+        self
+    }
+
+    /// Opens the window fullscreen or not, instead of the way it was last closed.
+    pub fn fullscreen(mut self, fullscreen: bool) -> Self {
+        self.fullscreen = Some(fullscreen);
+        self
+    }
+
+    /// Lets the desktop show through wherever the frame is left transparent, where the
+    /// platform composites windows and the surface can blend. `Setup::alpha_mode` tells
+    /// whether it does. Post-processing and the letterbox bars are always opaque.
+    pub fn transparent(mut self, transparent: bool) -> Self {
+        self.transparent = transparent;
+        self
+    }
+
+    /// Asks for no more of the GPU than WebGL 2 has, like the browser does, to check the
+    /// app keeps working on weak GPUs. `Setup::capabilities` tells what's left.
+    pub fn compat(mut self, compat: bool) -> Self {
+        self.compat = compat;
+        self
+    }
+
+    /// Has `run_headless` write how long each frame took to `path`, as JSON Lines with
+    /// the same fields as the frame statistics export.
+    pub fn bench_json(mut self, path: impl Into<PathBuf>) -> Self {
+        self.bench_json = Some(path.into());
+        self
+    }
+
+    /// Whether the window has the OS's title bar and borders, on by default. Without them
+    /// the app draws its own title bar, with `WindowAction`s for the buttons.
+    pub fn decorations(mut self, decorations: bool) -> Self {
+        self.decorations = decorations;
+        self
+    }
+
+    /// The window's icon as a PNG, usually `include_bytes!`d, also shown in the taskbar
+    /// on Windows. macOS and Wayland take the icon from the app bundle or desktop file
+    /// instead.
+    pub fn icon(mut self, png: impl Into<Cow<'static, [u8]>>) -> Self {
+        self.icon = Some(png.into());
+        self
+    }
+
+    /// Whether the window's size, position and maximized and fullscreen states are
+    /// saved in the config directory when it's closed, and restored at startup. On by
+    /// default.
+    pub fn persist_window(mut self, persist: bool) -> Self {
+        self.persist_window = persist;
+        self
+    }
+
+    /// The activity to run in, required on Android.
+    #[cfg(target_os = "android")]
+    pub fn android_app(mut self, app: AndroidApp) -> Self {
+        self.android_app = Some(app);
+        self
+    }
+
+    pub(crate) fn letterbox_config(&self) -> &LetterboxConfig {
+        &self.letterbox
+    }
+
+    pub(crate) fn persists_ui(&self) -> bool {
+        self.persist_ui
+    }
+
+    pub(crate) fn persists_window(&self) -> bool {
+        self.persist_window
+    }
+
+    #[cfg(target_os = "android")]
+    pub(crate) fn android_app_config(&self) -> Option<&AndroidApp> {
+        self.android_app.as_ref()
+    }
+
+    pub(crate) fn build_event_loop(&self) -> EventLoop<UserEvent> {
+        let mut builder = EventLoopBuilder::<UserEvent>::with_user_event();
+        #[cfg(target_os = "android")]
+        if let Some(app) = &self.android_app {
+            builder.with_android_app(app.clone());
+        }
+        builder.build()
+    }
+
+    /// Creates the window the way it was last closed, in the browser as a canvas
+    /// appended to the page.
+    pub(crate) fn build_window(
+        &self,
+        event_loop: &EventLoopWindowTarget<UserEvent>,
+    ) -> Result<Window, AppError> {
+        let saved = self
+            .persist_window
+            .then(WindowGeometry::load)
+            .flatten()
+            .unwrap_or_default();
+        let geometry = WindowGeometry {
+            size: self.size.unwrap_or(saved.size),
+            fullscreen: self.fullscreen.unwrap_or(saved.fullscreen),
+            ..saved
+        };
+        let icon = self.icon.as_deref().and_then(|png| {
+            icon::decode(png)
+                .map_err(|e| tracing::warn!("Ignoring the window icon: {}", e))
+                .ok()
+        });
+        let builder = winit::window::WindowBuilder::new()
+            .with_title(&self.title)
+            .with_transparent(self.transparent)
+            .with_decorations(self.decorations)
+            .with_window_icon(icon.clone());
+        #[cfg(windows)]
+        let builder = {
+            use winit::platform::windows::WindowBuilderExtWindows;
+            builder.with_taskbar_icon(icon)
+        };
+        let builder = geometry.apply(builder, event_loop);
+        // Shown once AccessKit is set up, which has to come first.
+        #[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
+        let builder = builder.with_visible(false);
+        // In the browser the canvas fills the page instead.
+        #[cfg(target_arch = "wasm32")]
+        let builder = builder.with_inner_size(page_size());
+        let window = builder.build(event_loop)?;
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            use winit::platform::web::WindowExtWebSys;
+            web_sys::window()
+                .and_then(|page| page.document())
+                .and_then(|document| document.body())
+                .and_then(|body| body.append_child(&window.canvas()).ok())
+                .expect("the page has a body to put the canvas in");
+        }
+        Ok(window)
+    }
+
+    /// Creates the wgpu context and the egui state for `window`. Getting the adapter
+    /// and the device is asynchronous in the browser, natively it's blocked on.
+    pub(crate) async fn build_graphics(
+        &self,
+        window: &Window,
+    ) -> Result<(GraphicsContext, Gui), AppError> {
+        let present_mode = if self.vsync {
+            wgpu::PresentMode::Fifo
+        } else {
+            wgpu::PresentMode::AutoNoVsync
+        };
+        let gfx = GraphicsContext::new(
+            window,
+            self.backends,
+            self.color_space,
+            present_mode,
+            self.msaa,
+            self.transparent,
+            self.compat,
+        )
+        .await?;
+        let gui = Gui::new(window, &gfx.device, gfx.ui_format(), self.theme);
+        if self.persist_ui {
+            gui.load_memory();
+        }
+        Ok((gfx, gui))
+    }
+
+    /// Runs `app` in the configured window. Never returns, when the window or the GPU
+    /// can't be set up the user is told why before exiting.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn run(self, app: impl Application) -> ! {
+        events::run(app, self)
+    }
+
+    /// Runs `app` in a canvas on the page. Returns right away, the app keeps running
+    /// once the browser's event loop takes over. Setup errors go to the console.
+    #[cfg(target_arch = "wasm32")]
+    pub fn run(self, app: impl Application) {
+        events::spawn(app, self)
+    }
+
+    /// Renders `frames` frames of `app` to PNGs in the current directory instead, with
+    /// no window, returning their paths. Only the size, backends, compat mode, theme and
+    /// bench file apply.
+    pub fn run_headless(self, app: impl Application, frames: u32) -> Result<Vec<PathBuf>, String> {
+        let size = self.size.unwrap_or(DEFAULT_SIZE);
+        let bench = self.bench_json.as_deref();
+        headless::run(
+            app,
+            size,
+            self.backends,
+            self.compat,
+            self.theme,
+            frames,
+            bench,
+        )
+    }
+}
+
+/// The size of the browser window's viewport.
+#[cfg(target_arch = "wasm32")]
+fn page_size() -> winit::dpi::LogicalSize<f64> {
+    let page = web_sys::window().expect("running in a browser window");
+    let length = |length: Result<wasm_bindgen::JsValue, _>| length.ok()?.as_f64();
+    winit::dpi::LogicalSize::new(
+        length(page.inner_width()).unwrap_or(1280.0),
+        length(page.inner_height()).unwrap_or(720.0),
+    )
+}
@@ -0,0 +1,172 @@
+use egui::{Key, Vec2};
+use glam::{EulerRot, Mat4, Quat, Vec3};
+
+/// Radians per unit of the mouse's raw motion the camera turns by.
+const LOOK_SPEED: f32 = 0.005;
+/// Units per second the fly camera moves at.
+const FLY_SPEED: f32 = 3.0;
+/// How much a point of scrolling brings the orbit camera closer, as a factor's logarithm.
+const ZOOM_SPEED: f32 = 0.005;
+const DISTANCE_RANGE: std::ops::RangeInclusive<f32> = 1.0..=50.0;
+/// Just short of straight up or down, where the yaw would stop meaning anything.
+const PITCH_LIMIT: f32 = 1.5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CameraMode {
+    /// Turns about `target`, scrolling changing the distance to it.
+    Orbit,
+    /// Moves freely with WASD, Q and E.
+    Fly,
+}
+
+/// The camera the 3D scene beneath the UI is drawn with. Both modes look the same way
+/// for the same yaw and pitch, so switching keeps the view.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Camera {
+    pub mode: CameraMode,
+    pub target: Vec3,
+    pub distance: f32,
+    /// Where the fly camera is, the orbit camera's being `eye`.
+    pub position: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    /// The vertical field of view, in degrees.
+    pub fov_y: f32,
+    pub near: f32,
+    pub far: f32,
+}
+
+impl Default for Camera {
+    /// Looking at the origin from 4 in front and 1.5 above.
+    fn default() -> Self {
+        let offset = Vec3::new(0.0, 1.5, 4.0);
+        Self {
+            mode: CameraMode::Orbit,
+            target: Vec3::ZERO,
+            distance: offset.length(),
+            position: offset,
+            yaw: 0.0,
+            pitch: -(offset.y / offset.z).atan(),
+            fov_y: 45.0,
+            near: 0.1,
+            far: 100.0,
+        }
+    }
+}
+
+impl Camera {
+    pub fn rotation(&self) -> Quat {
+        Quat::from_euler(EulerRot::YXZ, self.yaw, self.pitch, 0.0)
+    }
+
+    /// Where the camera is, in either mode.
+    pub fn eye(&self) -> Vec3 {
+        match self.mode {
+            CameraMode::Orbit => self.target + self.rotation() * Vec3::Z * self.distance,
+            CameraMode::Fly => self.position,
+        }
+    }
+
+    pub fn view(&self) -> Mat4 {
+        Mat4::from_rotation_translation(self.rotation(), self.eye()).inverse()
+    }
+
+    pub fn projection(&self, aspect: f32) -> Mat4 {
+        // A far plane at or before the near one would make the projection degenerate.
+        let far = self.far.max(self.near * 2.0);
+        Mat4::perspective_rh(self.fov_y.to_radians(), aspect, self.near, far)
+    }
+
+    /// For a target of `aspect` width over height.
+    pub fn view_proj(&self, aspect: f32) -> Mat4 {
+        self.projection(aspect) * self.view()
+    }
+
+    /// Switches to `mode` keeping the view, the orbit camera circling what was
+    /// `distance` in front of the fly camera.
+    pub fn set_mode(&mut self, mode: CameraMode) {
+        match (self.mode, mode) {
+            (CameraMode::Orbit, CameraMode::Fly) => self.position = self.eye(),
+            (CameraMode::Fly, CameraMode::Orbit) => {
+                self.target = self.position + self.rotation() * Vec3::NEG_Z * self.distance;
+            }
+            _ => {}
+        }
+        self.mode = mode;
+    }
+
+    /// Turns by the mouse's raw motion, right and down being positive.
+    pub fn look(&mut self, delta: Vec2) {
+        self.yaw -= delta.x * LOOK_SPEED;
+        self.pitch = (self.pitch - delta.y * LOOK_SPEED).clamp(-PITCH_LIMIT, PITCH_LIMIT);
+    }
+
+    /// Brings the orbit camera closer for scrolling up, by `scroll` points.
+    pub fn zoom(&mut self, scroll: f32) {
+        if self.mode == CameraMode::Orbit {
+            self.distance = (self.distance * (-scroll * ZOOM_SPEED).exp())
+                .clamp(*DISTANCE_RANGE.start(), *DISTANCE_RANGE.end());
+        }
+    }
+
+    /// Moves the fly camera by the WASD, Q and E keys held down. Returns whether it
+    /// moved, to draw the next frame.
+    pub fn fly(&mut self, input: &egui::InputState) -> bool {
+        if self.mode != CameraMode::Fly {
+            return false;
+        }
+        let rotation = self.rotation();
+        let mut direction = Vec3::ZERO;
+        for (key, dir) in [
+            (Key::W, Vec3::NEG_Z),
+            (Key::S, Vec3::Z),
+            (Key::A, Vec3::NEG_X),
+            (Key::D, Vec3::X),
+        ] {
+            if input.key_down(key) {
+                direction += rotation * dir;
+            }
+        }
+        if input.key_down(Key::E) {
+            direction += Vec3::Y;
+        }
+        if input.key_down(Key::Q) {
+            direction -= Vec3::Y;
+        }
+        if direction == Vec3::ZERO {
+            return false;
+        }
+        self.position += direction.normalize() * FLY_SPEED * input.stable_dt;
+        true
+    }
+
+    /// The mode and the projection's settings.
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        let mut mode = self.mode;
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut mode, CameraMode::Orbit, "Orbit");
+            ui.radio_value(&mut mode, CameraMode::Fly, "Fly");
+        });
+        if mode != self.mode {
+            self.set_mode(mode);
+        }
+        ui.add(egui::Slider::new(&mut self.fov_y, 20.0..=120.0).text("Field of view (°)"));
+        ui.add(
+            egui::Slider::new(&mut self.near, 0.01..=10.0)
+                .logarithmic(true)
+                .text("Near plane"),
+        );
+        ui.add(
+            egui::Slider::new(&mut self.far, 10.0..=1000.0)
+                .logarithmic(true)
+                .text("Far plane"),
+        );
+        if ui.button("Reset").clicked() {
+            *self = Self::default();
+        }
+        ui.weak(match self.mode {
+            CameraMode::Orbit => "Click the scene to turn about the cube, scroll to zoom",
+            CameraMode::Fly => "Click the scene to look around, and fly with WASD, Q and E",
+        });
+    }
+}
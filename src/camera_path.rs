@@ -0,0 +1,187 @@
+use glam::{Quat, Vec3};
+use serde::{Deserialize, Serialize};
+
+use std::path::Path;
+
+/// How often, in seconds, a keyframe is taken while recording.
+const RECORD_INTERVAL: f64 = 0.25;
+/// The points the spline is drawn with between two keyframes.
+const SPLINE_STEPS: usize = 16;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Keyframe {
+    pub position: Vec3,
+    pub rotation: Quat,
+    /// Seconds since the recording started.
+    pub timestamp: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum State {
+    Idle,
+    /// Started at the given input time.
+    Recording(f64),
+    /// At the given time along the path.
+    Playing(f64),
+}
+
+/// Keyframes recorded from the debug camera, played back along a Catmull-Rom spline.
+pub struct CameraPath {
+    keyframes: Vec<Keyframe>,
+    state: State,
+    pub speed: f64,
+}
+
+impl Default for CameraPath {
+    fn default() -> Self {
+        Self {
+            keyframes: Vec::new(),
+            state: State::Idle,
+            speed: 1.0,
+        }
+    }
+}
+
+impl CameraPath {
+    pub fn keyframes(&self) -> &[Keyframe] {
+        &self.keyframes
+    }
+
+    pub fn is_recording(&self) -> bool {
+        matches!(self.state, State::Recording(_))
+    }
+
+    pub fn is_playing(&self) -> bool {
+        matches!(self.state, State::Playing(_))
+    }
+
+    pub fn record(&mut self, now: f64) {
+        self.keyframes.clear();
+        self.state = State::Recording(now);
+    }
+
+    pub fn play(&mut self) {
+        if self.keyframes.len() >= 2 {
+            self.state = State::Playing(0.0);
+        }
+    }
+
+    pub fn stop(&mut self) {
+        self.state = State::Idle;
+    }
+
+    fn duration(&self) -> f64 {
+        self.keyframes.last().map_or(0.0, |k| k.timestamp)
+    }
+
+    /// Takes a keyframe of the camera if one is due.
+    pub fn capture(&mut self, now: f64, position: Vec3, rotation: Quat) {
+        let State::Recording(start) = self.state else {
+            return;
+        };
+        let timestamp = now - start;
+        if self
+            .keyframes
+            .last()
+            .is_none_or(|k| timestamp - k.timestamp >= RECORD_INTERVAL)
+        {
+            self.keyframes.push(Keyframe {
+                position,
+                rotation,
+                timestamp,
+            });
+        }
+    }
+
+    /// Moves playback along by `dt` seconds, returning where the camera is. Playback
+    /// stops at the end of the path.
+    pub fn advance(&mut self, dt: f64) -> Option<(Vec3, Quat)> {
+        let State::Playing(t) = self.state else {
+            return None;
+        };
+        let t = t + dt * self.speed;
+        if t >= self.duration() {
+            self.state = State::Idle;
+            return self.keyframes.last().map(|k| (k.position, k.rotation));
+        }
+        self.state = State::Playing(t);
+        Some(self.sample(t))
+    }
+
+    /// The interpolated position and rotation at `t` seconds along the path.
+    fn sample(&self, t: f64) -> (Vec3, Quat) {
+        let keys = &self.keyframes;
+        let i = keys
+            .windows(2)
+            .position(|w| t < w[1].timestamp)
+            .unwrap_or(keys.len() - 2);
+        let span = keys[i + 1].timestamp - keys[i].timestamp;
+        let u = if span > 0.0 {
+            ((t - keys[i].timestamp) / span) as f32
+        } else {
+            0.0
+        };
+        self.segment(i, u)
+    }
+
+    /// The point `u` of the way through the segment from keyframe `i` to the next.
+    fn segment(&self, i: usize, u: f32) -> (Vec3, Quat) {
+        let keys = &self.keyframes;
+        let at = |j: isize| keys[j.clamp(0, keys.len() as isize - 1) as usize];
+        let i = i as isize;
+        let [k0, k1, k2, k3] = [at(i - 1), at(i), at(i + 1), at(i + 2)];
+        let position = catmull_rom(k0.position, k1.position, k2.position, k3.position, u);
+
+        // Quaternions are interpolated as 4D points, each flipped to the same hemisphere
+        // as the previous so the rotation takes the short way around.
+        let align = |q: Quat, to: Quat| if q.dot(to) < 0.0 { -q } else { q };
+        let q1 = k1.rotation;
+        let q0 = align(k0.rotation, q1);
+        let q2 = align(k2.rotation, q1);
+        let q3 = align(k3.rotation, q2);
+        let rotation = catmull_rom(q0.into(), q1.into(), q2.into(), q3.into(), u);
+        (position, Quat::from_vec4(rotation).normalize())
+    }
+
+    /// The path sampled along the spline, for drawing.
+    pub fn spline(&self) -> Vec<Vec3> {
+        if self.keyframes.len() < 2 {
+            return self.keyframes.iter().map(|k| k.position).collect();
+        }
+        let mut points = Vec::new();
+        for i in 0..self.keyframes.len() - 1 {
+            for step in 0..SPLINE_STEPS {
+                points.push(self.segment(i, step as f32 / SPLINE_STEPS as f32).0);
+            }
+        }
+        points.extend(self.keyframes.last().map(|k| k.position));
+        points
+    }
+
+    pub fn write_json(&self, path: &Path) -> std::io::Result<()> {
+        #[derive(Serialize)]
+        struct Export<'a> {
+            keyframes: &'a [Keyframe],
+        }
+        let json = serde_json::to_string_pretty(&Export {
+            keyframes: &self.keyframes,
+        })?;
+        std::fs::write(path, json)
+    }
+}
+
+fn catmull_rom<T>(p0: T, p1: T, p2: T, p3: T, t: f32) -> T
+where
+    T: Copy
+        + std::ops::Add<Output = T>
+        + std::ops::Sub<Output = T>
+        + std::ops::Mul<f32, Output = T>,
+{
+    let t2 = t * t;
+    let t3 = t2 * t;
+    (p1 * 2.0
+        + (p2 - p0) * t
+        + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2
+        + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3)
+        * 0.5
+}
@@ -0,0 +1,78 @@
+/// The features asked for where the adapter has them, as the app gets by without
+/// each: timestamps for the GPU profiler, line polygons for wireframes and the
+/// compressed texture formats.
+pub const OPTIONAL_FEATURES: wgpu::Features = wgpu::Features::TIMESTAMP_QUERY
+    .union(wgpu::Features::POLYGON_MODE_LINE)
+    .union(wgpu::Features::TEXTURE_COMPRESSION_BC)
+    .union(wgpu::Features::TEXTURE_COMPRESSION_ETC2)
+    .union(wgpu::Features::TEXTURE_COMPRESSION_ASTC);
+
+/// The optional features by the name they're shown with.
+const NAMED: [(&str, wgpu::Features); 5] = [
+    ("Timestamp queries", wgpu::Features::TIMESTAMP_QUERY),
+    ("Line polygons", wgpu::Features::POLYGON_MODE_LINE),
+    ("BC textures", wgpu::Features::TEXTURE_COMPRESSION_BC),
+    ("ETC2 textures", wgpu::Features::TEXTURE_COMPRESSION_ETC2),
+    ("ASTC textures", wgpu::Features::TEXTURE_COMPRESSION_ASTC),
+];
+
+/// The features the adapter has out of `OPTIONAL_FEATURES`, to create the device with.
+pub fn negotiate(adapter: &wgpu::Adapter) -> wgpu::Features {
+    let features = adapter.features() & OPTIONAL_FEATURES;
+    let missing = OPTIONAL_FEATURES - features;
+    if !missing.is_empty() {
+        tracing::info!("Going without {:?}, which the adapter lacks", missing);
+    }
+    features
+}
+
+/// What the device ended up able to do, to branch on instead of asking for more than
+/// it has.
+#[derive(Clone, Debug)]
+pub struct Capabilities {
+    /// What the device was created with, out of `OPTIONAL_FEATURES`.
+    pub features: wgpu::Features,
+    pub limits: wgpu::Limits,
+    pub downlevel: wgpu::DownlevelCapabilities,
+}
+
+impl Capabilities {
+    pub fn of(adapter: &wgpu::Adapter, device: &wgpu::Device) -> Self {
+        Self {
+            features: device.features() & OPTIONAL_FEATURES,
+            limits: device.limits(),
+            downlevel: adapter.get_downlevel_capabilities(),
+        }
+    }
+
+    pub fn timestamps(&self) -> bool {
+        self.features.contains(wgpu::Features::TIMESTAMP_QUERY)
+    }
+
+    /// Whether pipelines can draw `PolygonMode::Line`.
+    pub fn wireframe(&self) -> bool {
+        self.features.contains(wgpu::Features::POLYGON_MODE_LINE)
+    }
+
+    /// Whether compute pipelines can run, with storage buffers to write to. Not on
+    /// WebGL 2, or in compat mode.
+    pub fn compute_shaders(&self) -> bool {
+        self.downlevel
+            .flags
+            .contains(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+            && self.limits.max_storage_buffers_per_shader_stage > 0
+            && self.limits.max_compute_workgroups_per_dimension > 0
+    }
+
+    /// Whether textures can be made in `format`, compressed ones needing their feature.
+    pub fn supports_format(&self, format: wgpu::TextureFormat) -> bool {
+        self.features.contains(format.required_features())
+    }
+
+    /// Each optional feature by name, and whether the device has it.
+    pub fn report(&self) -> impl Iterator<Item = (&'static str, bool)> + '_ {
+        NAMED
+            .iter()
+            .map(|&(name, feature)| (name, self.features.contains(feature)))
+    }
+}
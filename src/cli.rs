@@ -0,0 +1,65 @@
+use crate::gfx;
+use crate::window_geometry::DEFAULT_SIZE;
+
+use clap::Parser;
+
+use std::path::PathBuf;
+
+/// The example's command line. Whatever is given here wins over `config.toml` and
+/// over what was saved by the last run.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// The window's inner width in physical pixels.
+    #[arg(long)]
+    pub width: Option<u32>,
+    /// The window's inner height in physical pixels.
+    #[arg(long)]
+    pub height: Option<u32>,
+    /// The window's title.
+    #[arg(long)]
+    pub title: Option<String>,
+    /// Opens the window fullscreen, or not with `--fullscreen=false`.
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    pub fullscreen: Option<bool>,
+    /// Waits for the display's refresh when presenting, or not with `--vsync=false`.
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    pub vsync: Option<bool>,
+    /// Shows egui's demo windows, or not with `--demo=false`.
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    pub demo: Option<bool>,
+    /// The backends wgpu picks an adapter from: vulkan, dx12, metal, gl or auto.
+    #[arg(long, value_parser = parse_backend)]
+    pub backend: Option<wgpu::Backends>,
+    /// Asks for WebGL 2's limits, to try the app the way weak GPUs and browsers run it.
+    #[arg(long)]
+    pub compat: bool,
+    /// Renders this many frames to PNGs instead, without opening a window.
+    #[arg(long, value_name = "FRAMES")]
+    pub headless: Option<u32>,
+    /// Writes how long each headless frame took to this file, as JSON Lines.
+    #[arg(long, value_name = "FILE", requires = "headless")]
+    pub bench_json: Option<PathBuf>,
+    /// Registers the example as the handler of its links, then exits.
+    #[arg(long)]
+    pub register_url_scheme: bool,
+    /// Links the OS hands over when one is clicked, anything else is ignored.
+    #[arg(value_name = "LINK")]
+    pub links: Vec<String>,
+}
+
+impl Cli {
+    /// The window size asked for, the side left out taking its default.
+    pub fn size(&self) -> Option<[u32; 2]> {
+        (self.width.is_some() || self.height.is_some()).then(|| {
+            [
+                self.width.unwrap_or(DEFAULT_SIZE[0]),
+                self.height.unwrap_or(DEFAULT_SIZE[1]),
+            ]
+        })
+    }
+}
+
+fn parse_backend(name: &str) -> Result<wgpu::Backends, String> {
+    gfx::parse_backends(name).ok_or_else(|| "expected vulkan, dx12, metal, gl or auto".to_owned())
+}
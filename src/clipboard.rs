@@ -0,0 +1,156 @@
+use egui::{Key, KeyboardShortcut, Modifiers};
+use serde::{Deserialize, Serialize};
+
+use std::collections::VecDeque;
+
+/// How many unpinned items the history keeps.
+pub const HISTORY_LEN: usize = 20;
+
+const TOGGLE: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::V);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ClipboardItem {
+    pub text: String,
+    /// Pinned items are never evicted.
+    pub pinned: bool,
+}
+
+/// The texts copied through egui, newest first, shown in a panel with Ctrl+Shift+V.
+#[derive(Default, Serialize, Deserialize)]
+pub struct ClipboardHistory {
+    items: VecDeque<ClipboardItem>,
+    #[serde(skip)]
+    pub open: bool,
+    #[serde(skip)]
+    search: String,
+    #[serde(skip)]
+    focus_search: bool,
+}
+
+impl ClipboardHistory {
+    /// Remembers a text that was just copied. One that's in the history already is
+    /// moved to the front, pinned or not, like when it's copied again from the panel.
+    pub fn record(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        let item = match self.items.iter().position(|item| item.text == text) {
+            Some(index) => self.items.remove(index).unwrap(),
+            None => ClipboardItem {
+                text: text.to_owned(),
+                pinned: false,
+            },
+        };
+        self.items.push_front(item);
+
+        if self.items.iter().filter(|item| !item.pinned).count() > HISTORY_LEN {
+            if let Some(oldest) = self.items.iter().rposition(|item| !item.pinned) {
+                self.items.remove(oldest);
+            }
+        }
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        if ctx.input_mut(|i| i.consume_shortcut(&TOGGLE)) {
+            self.open = !self.open;
+            self.search.clear();
+            self.focus_search = true;
+        }
+        if !self.open {
+            return;
+        }
+
+        let mut open = true;
+        let mut copy = None;
+        let mut delete = None;
+        egui::Window::new("Clipboard history")
+            .open(&mut open)
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                let search = ui.add(
+                    egui::TextEdit::singleline(&mut self.search)
+                        .hint_text("Search")
+                        .desired_width(f32::INFINITY),
+                );
+                if std::mem::take(&mut self.focus_search) {
+                    search.request_focus();
+                }
+                ui.separator();
+
+                if self.items.is_empty() {
+                    ui.weak("Nothing copied yet");
+                }
+                egui::ScrollArea::vertical()
+                    .max_height(400.0)
+                    .show(ui, |ui| {
+                        for (i, item) in self.items.iter_mut().enumerate() {
+                            if !fuzzy_match(&self.search, &item.text) {
+                                continue;
+                            }
+                            let mut label = item.text.lines().next().unwrap_or_default().to_owned();
+                            if item.pinned {
+                                label.insert_str(0, "📌 ");
+                            }
+                            let response =
+                                ui.selectable_label(false, label).on_hover_text(&item.text);
+                            if response.clicked() {
+                                copy = Some(i);
+                            }
+                            response.context_menu(|ui| {
+                                if ui.button("Delete from history").clicked() {
+                                    delete = Some(i);
+                                    ui.close_menu();
+                                }
+                                let pin = if item.pinned { "Unpin" } else { "Pin" };
+                                if ui.button(pin).clicked() {
+                                    item.pinned = !item.pinned;
+                                    ui.close_menu();
+                                }
+                            });
+                        }
+                    });
+            });
+
+        if let Some(i) = delete {
+            self.items.remove(i);
+        }
+        if let Some(item) = copy.and_then(|i| self.items.get(i)) {
+            ctx.output_mut(|o| o.copied_text = item.text.clone());
+            open = false;
+        }
+        self.open = open;
+    }
+}
+
+/// True if all characters of the query appear in order in the text, ignoring case.
+pub fn fuzzy_match(query: &str, text: &str) -> bool {
+    let mut text = text.chars().flat_map(char::to_lowercase);
+    query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|q| text.any(|c| c == q))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copying_an_older_item_again_moves_it_to_the_front() {
+        let mut history = ClipboardHistory::default();
+        for text in ["one", "two", "three"] {
+            history.record(text);
+        }
+        history.items[2].pinned = true;
+        history.record("one");
+        history.record("one");
+        let texts: Vec<_> = history
+            .items
+            .iter()
+            .map(|item| item.text.as_str())
+            .collect();
+        assert_eq!(texts, ["one", "three", "two"]);
+        assert!(history.items[0].pinned);
+    }
+}
@@ -0,0 +1,169 @@
+use crate::screenshot::Screenshot;
+
+use egui::{Color32, Key, Modifiers, Order, Pos2, Rect, Sense, Stroke};
+
+/// Puts `image` on the OS clipboard. egui only copies text, through egui-winit.
+#[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+pub fn copy(image: &Screenshot) -> Result<(), String> {
+    let image = arboard::ImageData {
+        width: image.width as usize,
+        height: image.height as usize,
+        bytes: image.rgba.as_slice().into(),
+    };
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_image(image))
+        .map_err(|e| e.to_string())
+}
+
+/// The image on the OS clipboard, if there's one.
+#[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+pub fn paste() -> Result<egui::ColorImage, String> {
+    let image = arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_image())
+        .map_err(|e| e.to_string())?;
+    Ok(egui::ColorImage::from_rgba_unmultiplied(
+        [image.width, image.height],
+        &image.bytes,
+    ))
+}
+
+/// The browser's clipboard and Android's aren't reachable through arboard.
+#[cfg(any(target_arch = "wasm32", target_os = "android"))]
+pub fn copy(_image: &Screenshot) -> Result<(), String> {
+    Err("Images can't be copied here".to_owned())
+}
+
+#[cfg(any(target_arch = "wasm32", target_os = "android"))]
+pub fn paste() -> Result<egui::ColorImage, String> {
+    Err("Images can't be pasted here".to_owned())
+}
+
+/// Dragging out a part of the screen to copy, Escape cancels.
+#[derive(Default)]
+pub struct RegionSelect {
+    pub active: bool,
+    start: Option<Pos2>,
+}
+
+impl RegionSelect {
+    /// The rectangle dragged out, in points, once the drag ends. Nothing is drawn in
+    /// that frame, so a screenshot of it doesn't show the selection.
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<Rect> {
+        if !self.active {
+            return None;
+        }
+        if ctx.input_mut(|i| i.consume_key(Modifiers::NONE, Key::Escape)) {
+            self.active = false;
+            self.start = None;
+            return None;
+        }
+
+        let screen_rect = ctx.screen_rect();
+        let mut selected = None;
+        egui::Area::new("region_select")
+            .order(Order::Foreground)
+            .fixed_pos(screen_rect.min)
+            .show(ctx, |ui| {
+                // Cover the whole screen so drags select instead of reaching the widgets.
+                let response = ui.allocate_rect(screen_rect, Sense::drag());
+                if response.drag_started() {
+                    self.start = response.interact_pointer_pos();
+                }
+                let pos = ui.input(|i| i.pointer.interact_pos());
+                let rect = self.start.zip(pos).map(|(a, b)| Rect::from_two_pos(a, b));
+                if response.drag_released() {
+                    selected = rect.filter(|rect| rect.area() > 0.0);
+                    self.active = false;
+                    self.start = None;
+                    return;
+                }
+
+                let painter = ui.painter();
+                painter.rect_filled(screen_rect, 0.0, Color32::from_black_alpha(96));
+                if let Some(rect) = rect {
+                    painter.rect_stroke(rect, 0.0, Stroke::new(1.5, Color32::YELLOW));
+                }
+                painter.text(
+                    screen_rect.left_top() + egui::vec2(8.0, 8.0),
+                    egui::Align2::LEFT_TOP,
+                    "Drag to copy a part of the screen, Esc to cancel",
+                    egui::FontId::proportional(14.0),
+                    Color32::YELLOW,
+                );
+            });
+        selected
+    }
+}
+
+/// The images pasted with Ctrl+V while no text field has the focus.
+#[derive(Default)]
+pub struct PastedImages {
+    pub open: bool,
+    images: Vec<egui::TextureHandle>,
+    error: Option<String>,
+}
+
+impl PastedImages {
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        // egui-winit only turns text on the clipboard into a paste, it's left to the app
+        // to look for an image when the keys were pressed.
+        let paste_keys = ctx.input(|i| {
+            i.modifiers.command
+                && i.key_pressed(Key::V)
+                && !i.events.iter().any(|e| matches!(e, egui::Event::Paste(_)))
+        });
+        if paste_keys && !ctx.wants_keyboard_input() {
+            self.paste(ctx);
+        }
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        egui::Window::new("Pasted images")
+            .open(&mut open)
+            .default_size([320.0, 400.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("Paste").clicked() {
+                        self.paste(ui.ctx());
+                    }
+                    if ui.button("Clear").clicked() {
+                        self.images.clear();
+                        self.error = None;
+                    }
+                });
+                if let Some(e) = &self.error {
+                    ui.colored_label(ui.visuals().error_fg_color, e);
+                }
+                if self.images.is_empty() {
+                    ui.weak("Copy an image elsewhere and press Ctrl+V");
+                }
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for texture in self.images.iter().rev() {
+                        ui.separator();
+                        let [width, height] = texture.size();
+                        ui.label(format!("{}×{}", width, height));
+                        let size = texture.size_vec2();
+                        let scale = (ui.available_width() / size.x).min(1.0);
+                        ui.image((texture.id(), size * scale));
+                    }
+                });
+            });
+        self.open = open;
+    }
+
+    /// Reads the clipboard into a new texture, opening the window to show it.
+    fn paste(&mut self, ctx: &egui::Context) {
+        self.open = true;
+        match paste() {
+            Ok(image) => {
+                let name = format!("pasted {}", self.images.len());
+                self.images
+                    .push(ctx.load_texture(name, image, Default::default()));
+                self.error = None;
+            }
+            Err(e) => self.error = Some(format!("No image to paste: {}", e)),
+        }
+    }
+}
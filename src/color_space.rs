@@ -0,0 +1,287 @@
+use crate::gpu_memory::{Allocation, GpuMemory};
+
+use serde::{Deserialize, Serialize};
+
+/// The color space the surface is presented in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorSpace {
+    /// Plain sRGB, what every display and backend supports.
+    #[default]
+    Srgb,
+    /// Linear extended-range sRGB on a float surface. Values outside of 0..1 reach the
+    /// colors of wide-gamut displays, like macOS EDR and `EXTENDED_SRGB_LINEAR` on Vulkan.
+    ExtendedSrgb,
+}
+
+impl ColorSpace {
+    /// Picks the surface format for the requested color space, falling back to sRGB
+    /// when the surface has no float format, and to whatever it has without an sRGB one.
+    /// `None` when it has no format at all.
+    pub fn select(
+        requested: ColorSpace,
+        formats: &[wgpu::TextureFormat],
+    ) -> Option<(Self, wgpu::TextureFormat)> {
+        let srgb = match formats.iter().find(|f| f.is_srgb()) {
+            Some(&format) => format,
+            None => {
+                let &format = formats.first()?;
+                tracing::warn!(
+                    "The surface has no sRGB format, colors may be off in {:?}",
+                    format
+                );
+                format
+            }
+        };
+        Some(match requested {
+            ColorSpace::ExtendedSrgb if formats.contains(&wgpu::TextureFormat::Rgba16Float) => {
+                (ColorSpace::ExtendedSrgb, wgpu::TextureFormat::Rgba16Float)
+            }
+            ColorSpace::ExtendedSrgb => {
+                tracing::info!("The surface has no wide-gamut format, using sRGB");
+                (ColorSpace::Srgb, srgb)
+            }
+            ColorSpace::Srgb => (ColorSpace::Srgb, srgb),
+        })
+    }
+
+    /// The matrix, as columns, taking linear sRGB to this color space.
+    ///
+    /// Extended sRGB shares the sRGB primaries, so egui's colors pass through unchanged
+    /// while anything drawn directly to the surface can go beyond them.
+    fn srgb_to_output(self) -> [[f32; 4]; 3] {
+        match self {
+            ColorSpace::Srgb | ColorSpace::ExtendedSrgb => [
+                [1.0, 0.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+            ],
+        }
+    }
+}
+
+/// The format egui renders to before being composited onto a wide-gamut surface.
+pub const UI_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+/// Renders the UI offscreen and blends it over a wide-gamut surface.
+///
+/// egui assumes an sRGB target, so on a float surface it draws into an sRGB texture
+/// first, which the composite pass converts to the output color space.
+pub struct WideGamutCompositor {
+    pipeline: wgpu::RenderPipeline,
+    layout: wgpu::BindGroupLayout,
+    params: wgpu::Buffer,
+    ui_view: wgpu::TextureView,
+    memory: GpuMemory,
+    _ui_allocation: Allocation,
+    bind_group: wgpu::BindGroup,
+}
+
+impl WideGamutCompositor {
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        memory: &GpuMemory,
+        color_space: ColorSpace,
+        surface_format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::include_wgsl!("shaders/composite.wgsl"));
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("composite"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("composite"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("composite"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_format,
+                    blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("composite params"),
+            size: std::mem::size_of::<[[f32; 4]; 3]>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        queue.write_buffer(
+            &params,
+            0,
+            bytemuck::cast_slice(&color_space.srgb_to_output()),
+        );
+
+        let (ui_view, ui_allocation) = create_ui_view(device, memory, width, height);
+        let bind_group = create_bind_group(device, &layout, &ui_view, &params);
+        Self {
+            pipeline,
+            layout,
+            params,
+            ui_view,
+            memory: memory.clone(),
+            _ui_allocation: ui_allocation,
+            bind_group,
+        }
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        (self.ui_view, self._ui_allocation) = create_ui_view(device, &self.memory, width, height);
+        self.bind_group = create_bind_group(device, &self.layout, &self.ui_view, &self.params);
+    }
+
+    /// The texture egui has to render into.
+    pub fn ui_view(&self) -> &wgpu::TextureView {
+        &self.ui_view
+    }
+
+    /// Blends the UI texture over what's already on the surface.
+    pub fn composite(&self, encoder: &mut wgpu::CommandEncoder, surface_view: &wgpu::TextureView) {
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("composite"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: surface_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
+
+fn create_ui_view(
+    device: &wgpu::Device,
+    memory: &GpuMemory,
+    width: u32,
+    height: u32,
+) -> (wgpu::TextureView, Allocation) {
+    let desc = wgpu::TextureDescriptor {
+        label: Some("ui"),
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: UI_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    };
+    let view = device
+        .create_texture(&desc)
+        .create_view(&wgpu::TextureViewDescriptor::default());
+    (view, memory.track_texture(&desc))
+}
+
+fn create_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    ui_view: &wgpu::TextureView,
+    params: &wgpu::Buffer,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("composite"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(ui_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: params.as_entire_binding(),
+            },
+        ],
+    })
+}
+
+/// Patches of the sRGB and Display P3 primaries, to see whether the surface shows
+/// colors outside of sRGB.
+pub struct GamutTestPattern {
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl GamutTestPattern {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, sample_count: u32) -> Self {
+        let shader = device.create_shader_module(wgpu::include_wgsl!("shaders/gamut_test.wgsl"));
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("gamut test"),
+            layout: None,
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                ..Default::default()
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(format.into())],
+            }),
+            multiview: None,
+        });
+        Self { pipeline }
+    }
+
+    pub fn draw<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>) {
+        pass.set_pipeline(&self.pipeline);
+        pass.draw(0..4, 0..6);
+    }
+}
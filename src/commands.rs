@@ -0,0 +1,417 @@
+use egui::{Key, KeyboardShortcut, Modifiers};
+use serde::de::IntoDeserializer;
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+
+/// The actions that can be run from the command palette or a shortcut.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Command {
+    NewPalette,
+    ToggleSplitEditor,
+    ToggleFiles,
+    ToggleTextureGenerator,
+    ToggleFrameStats,
+    ToggleFrameData,
+    ToggleFpsOverlay,
+    ToggleDemo,
+    ToggleFullscreen,
+    ToggleExclusiveFullscreen,
+    ToggleAlwaysOnTop,
+    ZoomIn,
+    ZoomOut,
+    ResetZoom,
+    DumpFrame,
+    Screenshot,
+    CopyRegion,
+    ToggleRecording,
+    OpenSettings,
+    OpenCommandPalette,
+    Undo,
+    Quit,
+}
+
+impl Command {
+    pub const ALL: [Command; 22] = [
+        Command::NewPalette,
+        Command::ToggleSplitEditor,
+        Command::ToggleFiles,
+        Command::ToggleTextureGenerator,
+        Command::ToggleFrameStats,
+        Command::ToggleFrameData,
+        Command::ToggleFpsOverlay,
+        Command::ToggleDemo,
+        Command::ToggleFullscreen,
+        Command::ToggleExclusiveFullscreen,
+        Command::ToggleAlwaysOnTop,
+        Command::ZoomIn,
+        Command::ZoomOut,
+        Command::ResetZoom,
+        Command::DumpFrame,
+        Command::Screenshot,
+        Command::CopyRegion,
+        Command::ToggleRecording,
+        Command::OpenSettings,
+        Command::OpenCommandPalette,
+        Command::Undo,
+        Command::Quit,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Command::NewPalette => "New palette",
+            Command::ToggleSplitEditor => "Toggle split editor",
+            Command::ToggleFiles => "Toggle files",
+            Command::ToggleTextureGenerator => "Toggle texture generator",
+            Command::ToggleFrameStats => "Toggle frame statistics",
+            Command::ToggleFrameData => "Toggle frame data",
+            Command::ToggleFpsOverlay => "Toggle FPS overlay",
+            Command::ToggleDemo => "Toggle egui demo",
+            Command::ToggleFullscreen => "Toggle fullscreen",
+            Command::ToggleExclusiveFullscreen => "Toggle exclusive fullscreen",
+            Command::ToggleAlwaysOnTop => "Toggle always on top",
+            Command::ZoomIn => "Zoom in",
+            Command::ZoomOut => "Zoom out",
+            Command::ResetZoom => "Reset zoom",
+            Command::DumpFrame => "Dump frame data",
+            Command::Screenshot => "Save screenshot",
+            Command::CopyRegion => "Copy screen region",
+            Command::ToggleRecording => "Start or stop recording",
+            Command::OpenSettings => "Settings",
+            Command::OpenCommandPalette => "Command palette",
+            Command::Undo => "Undo",
+            Command::Quit => "Quit",
+        }
+    }
+}
+
+/// The commands whose name matches the query, in the order of `Command::ALL`.
+pub fn filter(query: &str) -> Vec<Command> {
+    Command::ALL
+        .into_iter()
+        .filter(|command| crate::clipboard::fuzzy_match(query, command.name()))
+        .collect()
+}
+
+/// Where a shortcut applies.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scope {
+    Global,
+    /// Only while the document, the split editor's source, has the keyboard focus.
+    Document,
+}
+
+pub struct Shortcut {
+    pub shortcut: KeyboardShortcut,
+    pub command: Command,
+    pub scope: Scope,
+}
+
+/// The keyboard shortcuts of the app's commands.
+pub struct ShortcutRegistry {
+    shortcuts: Vec<Shortcut>,
+}
+
+impl Default for ShortcutRegistry {
+    fn default() -> Self {
+        let mut registry = Self {
+            shortcuts: Vec::new(),
+        };
+        registry.register(
+            KeyboardShortcut::new(Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::P),
+            Command::OpenCommandPalette,
+            Scope::Global,
+        );
+        registry.register(
+            KeyboardShortcut::new(Modifiers::NONE, Key::F3),
+            Command::ToggleFpsOverlay,
+            Scope::Global,
+        );
+        registry.register(
+            KeyboardShortcut::new(Modifiers::NONE, Key::F11),
+            Command::ToggleFullscreen,
+            Scope::Global,
+        );
+        registry.register(
+            KeyboardShortcut::new(Modifiers::ALT, Key::Enter),
+            Command::ToggleExclusiveFullscreen,
+            Scope::Global,
+        );
+        registry.register(
+            KeyboardShortcut::new(Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::T),
+            Command::ToggleAlwaysOnTop,
+            Scope::Global,
+        );
+        registry.register(
+            KeyboardShortcut::new(Modifiers::COMMAND, Key::PlusEquals),
+            Command::ZoomIn,
+            Scope::Global,
+        );
+        // Where + is shifted, as Shift has to match too.
+        registry.register(
+            KeyboardShortcut::new(Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::PlusEquals),
+            Command::ZoomIn,
+            Scope::Global,
+        );
+        registry.register(
+            KeyboardShortcut::new(Modifiers::COMMAND, Key::Minus),
+            Command::ZoomOut,
+            Scope::Global,
+        );
+        registry.register(
+            KeyboardShortcut::new(Modifiers::COMMAND, Key::Num0),
+            Command::ResetZoom,
+            Scope::Global,
+        );
+        registry.register(
+            KeyboardShortcut::new(Modifiers::NONE, Key::F12),
+            Command::Screenshot,
+            Scope::Global,
+        );
+        registry.register(
+            KeyboardShortcut::new(Modifiers::COMMAND, Key::F12),
+            Command::CopyRegion,
+            Scope::Global,
+        );
+        registry.register(
+            KeyboardShortcut::new(Modifiers::SHIFT, Key::F12),
+            Command::ToggleRecording,
+            Scope::Global,
+        );
+        registry.register(
+            KeyboardShortcut::new(Modifiers::COMMAND, Key::Z),
+            Command::Undo,
+            Scope::Document,
+        );
+        registry.register(
+            KeyboardShortcut::new(Modifiers::COMMAND, Key::Q),
+            Command::Quit,
+            Scope::Global,
+        );
+        registry
+    }
+}
+
+impl ShortcutRegistry {
+    pub fn register(&mut self, shortcut: KeyboardShortcut, command: Command, scope: Scope) {
+        self.shortcuts.push(Shortcut {
+            shortcut,
+            command,
+            scope,
+        });
+    }
+
+    /// Consumes the pressed shortcuts that apply, returning their commands.
+    ///
+    /// Shortcuts out of their scope are left in the input for the widgets to see.
+    pub fn dispatch(&self, ctx: &egui::Context, document_focused: bool) -> Vec<Command> {
+        ctx.input_mut(|i| {
+            self.shortcuts
+                .iter()
+                .filter(|s| s.scope == Scope::Global || document_focused)
+                .filter(|s| i.consume_shortcut(&s.shortcut))
+                .map(|s| s.command)
+                .collect()
+        })
+    }
+
+    /// The text of the shortcut running `command`, if it has one.
+    pub fn shortcut_text(&self, ctx: &egui::Context, command: Command) -> Option<String> {
+        self.shortcuts
+            .iter()
+            .find(|s| s.command == command)
+            .map(|s| ctx.format_shortcut(&s.shortcut))
+    }
+
+    pub fn shortcuts_of(&self, command: Command) -> impl Iterator<Item = &KeyboardShortcut> {
+        self.shortcuts
+            .iter()
+            .filter(move |s| s.command == command)
+            .map(|s| &s.shortcut)
+    }
+
+    /// Makes `shortcuts` the only ones running `command`, taking them from the commands
+    /// they ran before. The command keeps its scope.
+    pub fn rebind(&mut self, command: Command, shortcuts: &[KeyboardShortcut]) {
+        let scope = self
+            .shortcuts
+            .iter()
+            .find(|s| s.command == command)
+            .map_or(Scope::Global, |s| s.scope);
+        self.shortcuts
+            .retain(|s| s.command != command && !shortcuts.contains(&s.shortcut));
+        for shortcut in shortcuts {
+            self.register(*shortcut, command, scope);
+        }
+    }
+
+    /// Rebinds the commands the config lists, skipping and logging shortcuts that don't
+    /// parse.
+    pub fn apply(&mut self, overrides: &BTreeMap<Command, Vec<String>>) {
+        for (command, texts) in overrides {
+            let shortcuts: Vec<_> = texts
+                .iter()
+                .filter_map(|text| {
+                    parse_shortcut(text)
+                        .map_err(|e| tracing::warn!("Ignoring the shortcut {:?}: {}", text, e))
+                        .ok()
+                })
+                .collect();
+            self.rebind(*command, &shortcuts);
+        }
+    }
+
+    /// The commands bound differently than by default, as the config lists them.
+    pub fn overrides(&self) -> BTreeMap<Command, Vec<String>> {
+        let defaults = Self::default();
+        Command::ALL
+            .into_iter()
+            .filter(|&command| {
+                !self
+                    .shortcuts_of(command)
+                    .eq(defaults.shortcuts_of(command))
+            })
+            .map(|command| {
+                let texts = self.shortcuts_of(command).map(shortcut_to_string).collect();
+                (command, texts)
+            })
+            .collect()
+    }
+}
+
+/// Writes `shortcut` as the config takes it, like "Ctrl+Shift+P" where Ctrl is Cmd on
+/// macOS, and the key as egui names its variant.
+pub fn shortcut_to_string(shortcut: &KeyboardShortcut) -> String {
+    let modifiers = shortcut.modifiers;
+    let mut text = String::new();
+    for (held, name) in [
+        (modifiers.command || modifiers.ctrl, "Ctrl"),
+        (modifiers.alt, "Alt"),
+        (modifiers.shift, "Shift"),
+    ] {
+        if held {
+            text += name;
+            text += "+";
+        }
+    }
+    text + &format!("{:?}", shortcut.key)
+}
+
+pub fn parse_shortcut(text: &str) -> Result<KeyboardShortcut, String> {
+    let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+    let key = parts.pop().unwrap_or_default();
+    let mut modifiers = Modifiers::NONE;
+    for part in parts {
+        modifiers = modifiers.plus(match part.to_ascii_lowercase().as_str() {
+            "ctrl" | "cmd" => Modifiers::COMMAND,
+            "alt" => Modifiers::ALT,
+            "shift" => Modifiers::SHIFT,
+            _ => return Err(format!("Unknown modifier {:?}", part)),
+        });
+    }
+    let key = Key::deserialize(key.into_deserializer())
+        .map_err(|_: serde::de::value::Error| format!("Unknown key {:?}", key))?;
+    Ok(KeyboardShortcut::new(modifiers, key))
+}
+
+/// Rebinds the commands' shortcuts, listing them all.
+#[derive(Default)]
+pub struct ShortcutEditor {
+    /// The command the next key press is bound to.
+    capturing: Option<Command>,
+}
+
+impl ShortcutEditor {
+    /// Whether a shortcut is being pressed, which shouldn't run anything meanwhile.
+    pub fn capturing(&self) -> bool {
+        self.capturing.is_some()
+    }
+
+    /// Returns true if a binding changed. Escape cancels a rebinding.
+    pub fn ui(&mut self, ui: &mut egui::Ui, registry: &mut ShortcutRegistry) -> bool {
+        let mut changed = false;
+        if let Some(command) = self.capturing {
+            if let Some(shortcut) = ui.input_mut(take_pressed_shortcut) {
+                self.capturing = None;
+                if shortcut != KeyboardShortcut::new(Modifiers::NONE, Key::Escape) {
+                    registry.rebind(command, &[shortcut]);
+                    changed = true;
+                }
+            }
+        }
+
+        egui::Grid::new("shortcuts").striped(true).show(ui, |ui| {
+            for command in Command::ALL {
+                ui.label(command.name());
+                let texts: Vec<_> = registry
+                    .shortcuts_of(command)
+                    .map(|shortcut| ui.ctx().format_shortcut(shortcut))
+                    .collect();
+                if self.capturing == Some(command) {
+                    ui.strong("Press a shortcut…");
+                } else if texts.is_empty() {
+                    ui.weak("None");
+                } else {
+                    ui.label(texts.join(", "));
+                }
+                ui.horizontal(|ui| {
+                    if ui.small_button("Rebind").clicked() {
+                        self.capturing = Some(command);
+                    }
+                    if ui.small_button("Clear").clicked() {
+                        registry.rebind(command, &[]);
+                        changed = true;
+                    }
+                });
+                ui.end_row();
+            }
+        });
+        if ui.button("Reset to defaults").clicked() {
+            *registry = ShortcutRegistry::default();
+            self.capturing = None;
+            changed = true;
+        }
+        changed
+    }
+}
+
+/// Consumes the first key pressed this frame, with the modifiers held.
+fn take_pressed_shortcut(input: &mut egui::InputState) -> Option<KeyboardShortcut> {
+    let shortcut = input.events.iter().find_map(|event| match event {
+        egui::Event::Key {
+            key,
+            pressed: true,
+            modifiers,
+            ..
+        } => {
+            let mut held = Modifiers::NONE;
+            held.alt = modifiers.alt;
+            held.shift = modifiers.shift;
+            held.command = modifiers.command;
+            Some(KeyboardShortcut::new(held, *key))
+        }
+        _ => None,
+    })?;
+    input.consume_shortcut(&shortcut);
+    Some(shortcut)
+}
+
+/// The state of the command palette, a searchable list of all the commands.
+#[derive(Default)]
+pub struct CommandPalette {
+    pub open: bool,
+    pub query: String,
+    /// The highlighted entry among the filtered commands.
+    pub selected: usize,
+    pub focus_query: bool,
+}
+
+impl CommandPalette {
+    pub fn show(&mut self) {
+        self.open = true;
+        self.query.clear();
+        self.selected = 0;
+        self.focus_query = true;
+    }
+}
@@ -0,0 +1,178 @@
+use crate::color_space::ColorSpace;
+use crate::commands::Command;
+use crate::letterbox::LetterboxConfig;
+use crate::theme::Theme;
+use crate::watermark::WatermarkConfig;
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+const CONFIG_FILE: &str = "config.toml";
+
+/// The user configuration, read from `config.toml` in the config directory.
+///
+/// Missing keys take their default value, so an empty file is a valid config. Edits
+/// to the file apply while the example runs, except for the color space, the
+/// letterbox and the window's size, transparency and title bar, which are only read at
+/// startup.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    pub update_check: UpdateCheckConfig,
+    pub watermark: WatermarkConfig,
+    /// The requested output color space, sRGB is used if the display can't do it.
+    pub color_space: ColorSpace,
+    pub letterbox: LetterboxConfig,
+    pub window: WindowConfig,
+    /// Overrides the VSync setting when set.
+    pub vsync: Option<bool>,
+    /// Overrides the MSAA setting when set.
+    pub msaa: Option<u32>,
+    /// The UI's theme, dark when not set. Picking one in the settings writes it here.
+    pub theme: Option<Theme>,
+    /// The commands bound to other shortcuts than by default, like
+    /// `toggle_fullscreen = ["F11", "Ctrl+F"]`, an empty list unbinding one. Rebinding
+    /// them in the settings writes them here.
+    pub shortcuts: BTreeMap<Command, Vec<String>>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowConfig {
+    pub title: Option<String>,
+    /// The initial inner size in physical pixels, instead of the size the window had
+    /// when it was last closed.
+    pub size: Option<[u32; 2]>,
+    /// Lets the desktop show through where nothing is drawn, see
+    /// `AppBuilder::transparent`.
+    pub transparent: bool,
+    /// Leaves out the OS's title bar and borders for the example's own title bar.
+    pub custom_title_bar: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UpdateCheckConfig {
+    /// Checking for updates is opt-in.
+    pub enabled: bool,
+    /// Where to fetch the JSON describing the latest release from.
+    pub url: String,
+    /// The minimum time between two checks.
+    pub interval_hours: u64,
+}
+
+impl Default for UpdateCheckConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: String::new(),
+            interval_hours: 24,
+        }
+    }
+}
+
+/// The per-user directory holding the config file and the app's saved state.
+pub fn config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(env!("CARGO_PKG_NAME")))
+}
+
+impl AppConfig {
+    /// Loads the config file, falling back to the defaults if it is missing or invalid.
+    pub fn load() -> Self {
+        let Some(path) = config_dir().map(|dir| dir.join(CONFIG_FILE)) else {
+            return Self::default();
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Self::parse(&text).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring invalid config file {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => {
+                tracing::warn!("Failed to read config file {}: {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(text)
+    }
+}
+
+/// Writes `value` into the config file as `key`, leaving the rest of it and its
+/// comments as they are.
+pub fn save_key(key: &str, value: &impl Serialize) -> Result<(), String> {
+    let dir = config_dir().ok_or("There is no config directory")?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(CONFIG_FILE);
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.to_string()),
+    };
+    let mut document = text
+        .parse::<toml_edit::Document>()
+        .map_err(|e| e.to_string())?;
+    let value = value
+        .serialize(toml_edit::ser::ValueSerializer::new())
+        .map_err(|e| e.to_string())?;
+    // Maps get a table of their own, rather than a line.
+    document[key] = match value {
+        toml_edit::Value::InlineTable(table) => toml_edit::Item::Table(table.into_table()),
+        value => toml_edit::Item::Value(value),
+    };
+    std::fs::write(&path, document.to_string()).map_err(|e| e.to_string())
+}
+
+/// Calls `on_change` on a watcher thread with the config file's new contents every
+/// time it's changed. Edits that don't parse are logged and skipped, so a file saved
+/// halfway through an edit doesn't reset everything. Keep the watcher to keep watching.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn watch(
+    on_change: impl Fn(AppConfig) + Send + 'static,
+) -> Result<notify::RecommendedWatcher, String> {
+    use notify::{EventKind, RecursiveMode, Watcher};
+
+    let dir = config_dir().ok_or("There is no config directory")?;
+    // The file may not exist yet, but the directory has to, to be watched.
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(CONFIG_FILE);
+    let mut last = std::fs::read_to_string(&path).ok();
+    let watched = path.clone();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let Ok(event) = event else {
+            return;
+        };
+        if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+            || !event
+                .paths
+                .iter()
+                .any(|changed| changed.ends_with(CONFIG_FILE))
+        {
+            return;
+        }
+        // Editors tend to write a file in several steps.
+        let Ok(text) = std::fs::read_to_string(&watched) else {
+            return;
+        };
+        if last.as_deref() == Some(text.as_str()) {
+            return;
+        }
+        match AppConfig::parse(&text) {
+            Ok(config) => {
+                tracing::info!("Reloaded {}", watched.display());
+                on_change(config);
+            }
+            Err(e) => tracing::warn!("Ignoring the edit to {}: {}", watched.display(), e),
+        }
+        last = Some(text);
+    })
+    .map_err(|e| e.to_string())?;
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .map_err(|e| e.to_string())?;
+    Ok(watcher)
+}
@@ -0,0 +1,186 @@
+use crate::config::{self, AppConfig};
+
+use std::any::Any;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How many crash reports are kept, older ones are deleted at startup.
+const MAX_REPORTS: usize = 5;
+
+/// Reports nobody looked at yet carry this suffix, it's dropped once the user saw them.
+const UNSEEN_SUFFIX: &str = ".new.txt";
+
+/// Everything about the session known before a panic, formatted ahead of time so the
+/// panic hook has as little work to do as possible.
+static CONTEXT: Mutex<String> = Mutex::new(String::new());
+static ADAPTER: Mutex<String> = Mutex::new(String::new());
+
+pub fn reports_dir() -> Option<PathBuf> {
+    config::config_dir().map(|dir| dir.join("crash_reports"))
+}
+
+/// Installs a panic hook writing a crash report and running the default hook, then
+/// telling the user in a native dialog when the panic takes the app down.
+pub fn install(config: &AppConfig) {
+    *CONTEXT.lock().unwrap() = format!(
+        "Version: {} {}\nOS: {} {}\n\nConfig:\n{}\n",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        toml::to_string(config).unwrap_or_default(),
+    );
+    prune_reports();
+
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let report = write_report(&info.to_string());
+        match &report {
+            Ok(path) => eprintln!("Crash report written to {}", path.display()),
+            Err(e) => eprintln!("Failed to write the crash report: {}", e),
+        }
+        default_hook(info);
+        // Panics on other threads only end those.
+        if std::thread::current().name() == Some("main") {
+            show_dialog(message(info.payload()), report.ok().as_deref());
+        }
+    }));
+}
+
+/// What the panic was given, if it was a string.
+fn message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown error")
+}
+
+/// Blocks until the user dismissed a message box about the panic.
+fn show_dialog(message: &str, report: Option<&Path>) {
+    let mut description = format!("{} crashed: {}", env!("CARGO_PKG_NAME"), message);
+    if let Some(path) = report {
+        description += &format!(
+            "\n\nA report with the details was written to {}, it's shown at the next start.",
+            path.display()
+        );
+    }
+    message_box(&description);
+}
+
+/// Shows `description` as an error in a native message box, blocking until it's
+/// dismissed.
+#[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+pub(crate) fn message_box(description: &str) {
+    rfd::MessageDialog::new()
+        .set_level(rfd::MessageLevel::Error)
+        .set_title(env!("CARGO_PKG_NAME"))
+        .set_description(description)
+        .set_buttons(rfd::MessageButtons::Ok)
+        .show();
+}
+
+/// The browser's console and Android's log are all there is.
+#[cfg(any(target_arch = "wasm32", target_os = "android"))]
+pub(crate) fn message_box(_description: &str) {}
+
+/// Records the adapter in use, called once it has been picked.
+pub fn set_adapter_info(info: &wgpu::AdapterInfo) {
+    *ADAPTER.lock().unwrap() = format!("{:#?}", info);
+}
+
+/// `panic` is the panic's message and where it happened.
+fn write_report(panic: &str) -> io::Result<PathBuf> {
+    let dir = reports_dir().ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))?;
+    std::fs::create_dir_all(&dir)?;
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let path = dir.join(format!("crash-{}{}", secs, UNSEEN_SUFFIX));
+
+    // Everything is written straight into the file instead of being collected first.
+    let mut file = io::BufWriter::new(File::create(&path)?);
+    writeln!(file, "Panic: {}\n", panic)?;
+    if let Ok(context) = CONTEXT.try_lock() {
+        file.write_all(context.as_bytes())?;
+    }
+    if let Ok(adapter) = ADAPTER.try_lock() {
+        writeln!(file, "Adapter:\n{}\n", adapter)?;
+    }
+    writeln!(file, "Backtrace:\n{:?}", backtrace::Backtrace::new())?;
+    writeln!(file, "Recent log:")?;
+    let mut result = Ok(());
+    crate::logging::for_each_recent(|_, line| {
+        if result.is_ok() {
+            result = writeln!(file, "{}", line);
+        }
+    });
+    result?;
+    file.flush()?;
+    Ok(path)
+}
+
+/// All reports, oldest first.
+fn reports() -> Vec<PathBuf> {
+    let Some(entries) = reports_dir().and_then(|dir| std::fs::read_dir(dir).ok()) else {
+        return Vec::new();
+    };
+    let mut reports: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("crash-"))
+        })
+        .collect();
+    // The names start with the timestamp, so this sorts by age.
+    reports.sort();
+    reports
+}
+
+fn prune_reports() {
+    let reports = reports();
+    for path in &reports[..reports.len().saturating_sub(MAX_REPORTS)] {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// A report from an earlier run the user has not seen yet.
+pub struct CrashReport {
+    pub path: PathBuf,
+    pub text: String,
+}
+
+impl CrashReport {
+    /// Finds the latest report written since the user last dismissed one.
+    pub fn find_unseen() -> Option<Self> {
+        let path = reports()
+            .into_iter()
+            .rev()
+            .find(|path| path.to_string_lossy().ends_with(UNSEEN_SUFFIX))?;
+        let text = std::fs::read_to_string(&path).ok()?;
+        Some(Self { path, text })
+    }
+
+    /// Marks this and any older unseen reports as seen.
+    pub fn mark_seen(&self) {
+        for path in reports() {
+            let name = path.to_string_lossy();
+            if let Some(stem) = name.strip_suffix(UNSEEN_SUFFIX) {
+                let _ = std::fs::rename(&path, format!("{}.txt", stem));
+            }
+            if path == self.path {
+                break;
+            }
+        }
+    }
+
+    pub fn folder(&self) -> &Path {
+        self.path.parent().unwrap_or(&self.path)
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+/// The index of a column of a `DataTable`.
+pub type ColumnId = usize;
+
+const ARROW_WIDTH: f32 = 20.0;
+
+/// A table of text cells, scrolled virtually so only the visible rows are laid out.
+///
+/// With `group_by` set the rows are sorted by that column and shown under collapsible
+/// headers, one per distinct value.
+pub struct DataTable {
+    id: egui::Id,
+    columns: Vec<String>,
+    rows: Vec<Vec<String>>,
+    pub group_by: Option<ColumnId>,
+    /// The values of the groups that are collapsed.
+    collapsed: HashSet<String>,
+    selected: HashSet<usize>,
+}
+
+/// One line of the scroller, either a group header or a row.
+enum Line {
+    Header { value: String, rows: Vec<usize> },
+    Row(usize),
+}
+
+impl DataTable {
+    pub fn new(id_source: impl std::hash::Hash, columns: Vec<String>) -> Self {
+        Self {
+            id: egui::Id::new(id_source),
+            columns,
+            rows: Vec::new(),
+            group_by: None,
+            collapsed: HashSet::new(),
+            selected: HashSet::new(),
+        }
+    }
+
+    /// Adds a row, missing cells are left empty.
+    pub fn push_row(&mut self, mut row: Vec<String>) {
+        row.resize(self.columns.len(), String::new());
+        self.rows.push(row);
+    }
+
+    fn lines(&self) -> Vec<Line> {
+        let Some(column) = self.group_by else {
+            return (0..self.rows.len()).map(Line::Row).collect();
+        };
+        let mut order: Vec<usize> = (0..self.rows.len()).collect();
+        order.sort_by(|&a, &b| self.rows[a][column].cmp(&self.rows[b][column]));
+
+        let mut lines = Vec::new();
+        for group in order.chunk_by(|&a, &b| self.rows[a][column] == self.rows[b][column]) {
+            let value = self.rows[group[0]][column].clone();
+            let collapsed = self.collapsed.contains(&value);
+            lines.push(Line::Header {
+                value,
+                rows: group.to_vec(),
+            });
+            if !collapsed {
+                lines.extend(group.iter().copied().map(Line::Row));
+            }
+        }
+        lines
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        let row_height = ui.spacing().interact_size.y;
+        let column_width = ui.available_width() / self.columns.len().max(1) as f32;
+        let font = egui::TextStyle::Body.resolve(ui.style());
+
+        let (header, response) = ui.allocate_exact_size(
+            egui::vec2(ui.available_width(), row_height),
+            egui::Sense::click(),
+        );
+        for (i, name) in self.columns.iter().enumerate() {
+            let cell = column_rect(header, i, column_width);
+            let text = match self.group_by {
+                Some(column) if column == i => format!("{} ▼", name),
+                _ => name.clone(),
+            };
+            ui.painter().with_clip_rect(cell).text(
+                cell.left_center(),
+                egui::Align2::LEFT_CENTER,
+                text,
+                font.clone(),
+                ui.visuals().strong_text_color(),
+            );
+        }
+        response.context_menu(|ui| {
+            ui.menu_button("Group By", |ui| {
+                let before = self.group_by;
+                ui.radio_value(&mut self.group_by, None, "None");
+                for (i, name) in self.columns.iter().enumerate() {
+                    ui.radio_value(&mut self.group_by, Some(i), name);
+                }
+                if self.group_by != before {
+                    self.collapsed.clear();
+                    ui.close_menu();
+                }
+            });
+        });
+        ui.separator();
+
+        let lines = self.lines();
+        egui::ScrollArea::vertical()
+            .id_source(self.id.with("rows"))
+            .auto_shrink([false, false])
+            .show_rows(ui, row_height, lines.len(), |ui, range| {
+                for line in &lines[range] {
+                    let (rect, response) = ui.allocate_exact_size(
+                        egui::vec2(ui.available_width(), row_height),
+                        egui::Sense::click(),
+                    );
+                    let additive = ui.input(|i| i.modifiers.command);
+                    match line {
+                        Line::Header { value, rows } => {
+                            let collapsed = self.collapsed.contains(value);
+                            if response.clicked() {
+                                let on_arrow = response
+                                    .interact_pointer_pos()
+                                    .is_some_and(|pos| pos.x < rect.left() + ARROW_WIDTH);
+                                if on_arrow {
+                                    if collapsed {
+                                        self.collapsed.remove(value);
+                                    } else {
+                                        self.collapsed.insert(value.clone());
+                                    }
+                                } else {
+                                    if !additive {
+                                        self.selected.clear();
+                                    }
+                                    self.selected.extend(rows.iter().copied());
+                                }
+                            }
+                            let selected = rows.iter().all(|row| self.selected.contains(row));
+                            let visuals = ui.style().interact_selectable(&response, selected);
+                            ui.painter().rect_filled(rect, 0.0, visuals.weak_bg_fill);
+                            let arrow = if collapsed { "⏵" } else { "⏷" };
+                            let value = if value.is_empty() {
+                                "(none)"
+                            } else {
+                                value.as_str()
+                            };
+                            ui.painter().text(
+                                rect.left_center(),
+                                egui::Align2::LEFT_CENTER,
+                                format!("{} {} ({})", arrow, value, rows.len()),
+                                font.clone(),
+                                visuals.text_color(),
+                            );
+                        }
+                        Line::Row(row) => {
+                            if response.clicked() {
+                                if !additive {
+                                    self.selected.clear();
+                                    self.selected.insert(*row);
+                                } else if !self.selected.remove(row) {
+                                    self.selected.insert(*row);
+                                }
+                            }
+                            let selected = self.selected.contains(row);
+                            let visuals = ui.style().interact_selectable(&response, selected);
+                            if selected || response.hovered() {
+                                ui.painter().rect_filled(rect, 0.0, visuals.bg_fill);
+                            }
+                            let indent = if self.group_by.is_some() {
+                                ARROW_WIDTH
+                            } else {
+                                0.0
+                            };
+                            let mut rect = rect;
+                            rect.min.x += indent;
+                            for (i, text) in self.rows[*row].iter().enumerate() {
+                                let cell = column_rect(rect, i, column_width);
+                                ui.painter().with_clip_rect(cell).text(
+                                    cell.left_center(),
+                                    egui::Align2::LEFT_CENTER,
+                                    text,
+                                    font.clone(),
+                                    visuals.text_color(),
+                                );
+                            }
+                        }
+                    }
+                }
+            });
+    }
+}
+
+fn column_rect(row: egui::Rect, column: ColumnId, width: f32) -> egui::Rect {
+    let left = row.left() + column as f32 * width;
+    egui::Rect::from_x_y_ranges(left..=left + width - 4.0, row.y_range())
+}
+
+/// The example's files, grouped by extension, to show off the grouping.
+pub fn files_demo(root: &Path) -> DataTable {
+    const MAX_FILES: usize = 5000;
+
+    let columns = ["Name", "Extension", "Size"];
+    let mut table = DataTable::new("files_table", columns.map(str::to_owned).to_vec());
+    table.group_by = Some(1);
+
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                let name = entry.file_name();
+                if name != "target" && name != ".git" {
+                    dirs.push(path);
+                }
+                continue;
+            }
+            let name = path.strip_prefix(root).unwrap_or(&path);
+            let extension = path.extension().unwrap_or_default();
+            table.push_row(vec![
+                name.display().to_string(),
+                extension.to_string_lossy().into_owned(),
+                format!("{} bytes", metadata.len()),
+            ]);
+            if table.rows.len() >= MAX_FILES {
+                return table;
+            }
+        }
+    }
+    table
+}
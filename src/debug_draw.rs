@@ -0,0 +1,113 @@
+use egui::{Color32, Pos2, Rect, Stroke};
+use glam::{Mat4, Vec3, Vec4};
+
+/// Lines in world space, collected over a frame and painted over a viewport.
+#[derive(Default)]
+pub struct DebugDraw {
+    lines: Vec<(Vec3, Vec3, Color32)>,
+}
+
+impl DebugDraw {
+    pub fn line(&mut self, a: Vec3, b: Vec3, color: Color32) {
+        self.lines.push((a, b, color));
+    }
+
+    /// Connected lines, colored from `start` at the first point to `end` at the last.
+    pub fn polyline(&mut self, points: &[Vec3], start: Color32, end: Color32) {
+        let (start, end) = (egui::Rgba::from(start), egui::Rgba::from(end));
+        let last = points.len().saturating_sub(1).max(1) as f32;
+        for (i, pair) in points.windows(2).enumerate() {
+            let color = egui::lerp(start..=end, (i as f32 + 0.5) / last);
+            self.line(pair[0], pair[1], color.into());
+        }
+    }
+
+    /// A grid on the y = 0 plane, `half_extent` lines either side of the origin.
+    pub fn grid(&mut self, half_extent: i32, color: Color32) {
+        let e = half_extent as f32;
+        for i in -half_extent..=half_extent {
+            let i = i as f32;
+            self.line(Vec3::new(i, 0.0, -e), Vec3::new(i, 0.0, e), color);
+            self.line(Vec3::new(-e, 0.0, i), Vec3::new(e, 0.0, i), color);
+        }
+    }
+
+    /// The edges of the unit cube centered on the origin, transformed.
+    pub fn cube(&mut self, transform: Mat4, color: Color32) {
+        let corner = |i: usize| {
+            let p = Vec3::new(
+                if i & 1 == 0 { -0.5 } else { 0.5 },
+                if i & 2 == 0 { -0.5 } else { 0.5 },
+                if i & 4 == 0 { -0.5 } else { 0.5 },
+            );
+            transform.transform_point3(p)
+        };
+        for i in 0..8 {
+            for bit in [1, 2, 4] {
+                if i & bit == 0 {
+                    self.line(corner(i), corner(i | bit), color);
+                }
+            }
+        }
+    }
+
+    /// A small three-axis cross, for marking points.
+    pub fn cross(&mut self, at: Vec3, size: f32, color: Color32) {
+        for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+            self.line(at - axis * size, at + axis * size, color);
+        }
+    }
+
+    /// Projects the lines into `rect` and paints them, dropping what's behind the camera.
+    pub fn paint(&mut self, painter: &egui::Painter, view_proj: Mat4, rect: Rect) {
+        for (a, b, color) in self.lines.drain(..) {
+            let (a, b) = (view_proj * a.extend(1.0), view_proj * b.extend(1.0));
+            let Some((a, b)) = clip_near(a, b) else {
+                continue;
+            };
+            painter.line_segment(
+                [to_screen(a, rect), to_screen(b, rect)],
+                Stroke::new(1.5, color),
+            );
+        }
+    }
+}
+
+/// Cuts the clip space segment where it crosses the near plane.
+fn clip_near(a: Vec4, b: Vec4) -> Option<(Vec4, Vec4)> {
+    // With wgpu's 0 to 1 depth range the near plane is z = 0.
+    match (a.z >= 0.0, b.z >= 0.0) {
+        (true, true) => Some((a, b)),
+        (false, false) => None,
+        (a_in, _) => {
+            let t = a.z / (a.z - b.z);
+            let cut = a.lerp(b, t);
+            Some(if a_in { (a, cut) } else { (cut, b) })
+        }
+    }
+}
+
+/// A clip space point in front of the camera, in screen points.
+fn to_screen(clip: Vec4, rect: Rect) -> Pos2 {
+    let ndc = clip.truncate() / clip.w.max(1e-6);
+    egui::pos2(
+        rect.left() + (ndc.x * 0.5 + 0.5) * rect.width(),
+        rect.top() + (0.5 - ndc.y * 0.5) * rect.height(),
+    )
+}
+
+/// Where a world space point lands in `rect`, if it's in front of the camera.
+pub fn project(view_proj: Mat4, rect: Rect, point: Vec3) -> Option<Pos2> {
+    let clip = view_proj * point.extend(1.0);
+    (clip.z >= 0.0 && clip.w > 0.0).then(|| to_screen(clip, rect))
+}
+
+/// The ray through a point of `rect`, as an origin on the near plane and a direction.
+pub fn unproject(view_proj: Mat4, rect: Rect, pos: Pos2) -> (Vec3, Vec3) {
+    let ndc_x = (pos.x - rect.left()) / rect.width() * 2.0 - 1.0;
+    let ndc_y = 1.0 - (pos.y - rect.top()) / rect.height() * 2.0;
+    let inverse = view_proj.inverse();
+    let near = inverse.project_point3(Vec3::new(ndc_x, ndc_y, 0.0));
+    let far = inverse.project_point3(Vec3::new(ndc_x, ndc_y, 1.0));
+    (near, (far - near).normalize())
+}
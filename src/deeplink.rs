@@ -0,0 +1,184 @@
+use std::fmt;
+use std::path::PathBuf;
+
+/// The URL scheme the example registers with the OS.
+pub const SCHEME: &str = "myexample";
+
+/// The panels a link is allowed to open.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Panel {
+    Demo,
+    Settings,
+}
+
+impl Panel {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "demo" => Some(Panel::Demo),
+            "settings" => Some(Panel::Settings),
+            _ => None,
+        }
+    }
+}
+
+/// A validated `myexample://` link.
+///
+/// Links can only open one of the whitelisted panels or propose a file to load,
+/// the latter always needs to be confirmed by the user before anything is read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DeepLink {
+    OpenPanel(Panel),
+    LoadFile(PathBuf),
+}
+
+#[derive(Debug)]
+pub enum DeepLinkError {
+    Malformed(url::ParseError),
+    WrongScheme(String),
+    UnknownAction(String),
+    UnknownPanel(String),
+    MissingParameter(&'static str),
+    UnexpectedParameter(String),
+    RelativePath(PathBuf),
+}
+
+impl fmt::Display for DeepLinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeepLinkError::Malformed(e) => write!(f, "malformed link: {}", e),
+            DeepLinkError::WrongScheme(s) => write!(f, "unsupported scheme '{}'", s),
+            DeepLinkError::UnknownAction(a) => write!(f, "unknown action '{}'", a),
+            DeepLinkError::UnknownPanel(p) => write!(f, "unknown panel '{}'", p),
+            DeepLinkError::MissingParameter(p) => write!(f, "missing parameter '{}'", p),
+            DeepLinkError::UnexpectedParameter(p) => write!(f, "unexpected parameter '{}'", p),
+            DeepLinkError::RelativePath(p) => {
+                write!(f, "file path must be absolute: {}", p.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for DeepLinkError {}
+
+/// Returns true if the command-line argument looks like a link for our scheme.
+pub fn is_link(arg: &str) -> bool {
+    arg.get(..SCHEME.len() + 3).is_some_and(|prefix| {
+        prefix[..SCHEME.len()].eq_ignore_ascii_case(SCHEME) && prefix.ends_with("://")
+    })
+}
+
+/// Parses and validates a link like `myexample://open?panel=demo`.
+pub fn parse(link: &str) -> Result<DeepLink, DeepLinkError> {
+    let url = url::Url::parse(link).map_err(DeepLinkError::Malformed)?;
+    if url.scheme() != SCHEME {
+        return Err(DeepLinkError::WrongScheme(url.scheme().to_owned()));
+    }
+
+    let action = url.host_str().unwrap_or_default();
+    let expected = match (action, url.path()) {
+        ("open", "" | "/") => "panel",
+        ("load", "" | "/") => "file",
+        (_, path) => return Err(DeepLinkError::UnknownAction(format!("{}{}", action, path))),
+    };
+
+    // Every action takes exactly one parameter, anything else is refused.
+    let mut value = None;
+    for (key, v) in url.query_pairs() {
+        if key != expected || value.is_some() {
+            return Err(DeepLinkError::UnexpectedParameter(key.into_owned()));
+        }
+        value = Some(v.into_owned());
+    }
+    let value = value.ok_or(DeepLinkError::MissingParameter(expected))?;
+
+    match action {
+        "open" => Panel::from_name(&value)
+            .map(DeepLink::OpenPanel)
+            .ok_or(DeepLinkError::UnknownPanel(value)),
+        _ => {
+            let path = PathBuf::from(value);
+            if !path.is_absolute() {
+                return Err(DeepLinkError::RelativePath(path));
+            }
+            Ok(DeepLink::LoadFile(path))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whitelisted_panels_are_opened() {
+        let open = |link| parse(link).unwrap();
+        assert_eq!(
+            open("myexample://open?panel=demo"),
+            DeepLink::OpenPanel(Panel::Demo)
+        );
+        assert_eq!(
+            open("myexample://open?panel=settings"),
+            DeepLink::OpenPanel(Panel::Settings)
+        );
+        // Percent-encoded values are decoded before they're checked.
+        assert_eq!(
+            open("MyExample://open/?panel=%73ettings"),
+            DeepLink::OpenPanel(Panel::Settings)
+        );
+        assert!(matches!(
+            parse("myexample://open?panel=%2e%2e"),
+            Err(DeepLinkError::UnknownPanel(panel)) if panel == ".."
+        ));
+    }
+
+    #[test]
+    fn links_outside_the_whitelist_are_refused() {
+        let error = |link| parse(link).unwrap_err();
+        assert!(matches!(
+            error("https://open?panel=demo"),
+            DeepLinkError::WrongScheme(_)
+        ));
+        assert!(matches!(error("not a link"), DeepLinkError::Malformed(_)));
+        assert!(matches!(
+            error("myexample://delete?panel=demo"),
+            DeepLinkError::UnknownAction(_)
+        ));
+        assert!(matches!(
+            error("myexample://open/../load?panel=demo"),
+            DeepLinkError::UnknownAction(_)
+        ));
+        assert!(matches!(
+            error("myexample://open?panel=shell"),
+            DeepLinkError::UnknownPanel(_)
+        ));
+        assert!(matches!(
+            error("myexample://load?file=notes.txt"),
+            DeepLinkError::RelativePath(_)
+        ));
+    }
+
+    #[test]
+    fn each_action_takes_exactly_its_one_parameter() {
+        let error = |link| parse(link).unwrap_err();
+        assert!(matches!(
+            error("myexample://open?"),
+            DeepLinkError::MissingParameter("panel")
+        ));
+        assert!(matches!(
+            error("myexample://load"),
+            DeepLinkError::MissingParameter("file")
+        ));
+        assert!(matches!(
+            error("myexample://open?panel=demo&panel=settings"),
+            DeepLinkError::UnexpectedParameter(key) if key == "panel"
+        ));
+        assert!(matches!(
+            error("myexample://open?panel=demo&then=quit"),
+            DeepLinkError::UnexpectedParameter(key) if key == "then"
+        ));
+        assert!(matches!(
+            error("myexample://open?file=demo"),
+            DeepLinkError::UnexpectedParameter(key) if key == "file"
+        ));
+    }
+}
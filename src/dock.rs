@@ -0,0 +1,138 @@
+use crate::app::AppState;
+use crate::inspector;
+use crate::logging::LogViewer;
+use crate::stats;
+
+use egui_dock::{DockArea, DockState, NodeIndex, Style, TabViewer};
+use serde::{Deserialize, Serialize};
+
+/// A panel in the dock, which is in it once and can't be closed, only moved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DockTab {
+    Scene,
+    Inspector,
+    Log,
+    Stats,
+}
+
+impl DockTab {
+    fn title(self) -> &'static str {
+        match self {
+            DockTab::Scene => "Scene",
+            DockTab::Inspector => "Inspector",
+            DockTab::Log => "Log",
+            DockTab::Stats => "Stats",
+        }
+    }
+}
+
+/// The "Docked panels" window: the scene, the GPU in use, the log and the latest frame's
+/// stats as tabs, which can be dragged into splits and tab groups of their own. How
+/// they're arranged is saved with the app's state.
+pub struct Dock {
+    pub open: bool,
+    pub state: DockState<DockTab>,
+    log: LogViewer,
+}
+
+impl Default for Dock {
+    fn default() -> Self {
+        Self {
+            open: false,
+            state: default_layout(),
+            log: LogViewer::default(),
+        }
+    }
+}
+
+/// The scene on the left, the inspector and stats to its right, above the log.
+pub fn default_layout() -> DockState<DockTab> {
+    let mut state = DockState::new(vec![DockTab::Scene]);
+    let surface = state.main_surface_mut();
+    let [_, right] = surface.split_right(
+        NodeIndex::root(),
+        0.6,
+        vec![DockTab::Inspector, DockTab::Stats],
+    );
+    surface.split_below(right, 0.55, vec![DockTab::Log]);
+    state
+}
+
+/// Shows the tabs through the rest of the app's state, which they're views of.
+struct Viewer<'a> {
+    app: &'a mut AppState,
+    log: &'a mut LogViewer,
+}
+
+impl TabViewer for Viewer<'_> {
+    type Tab = DockTab;
+
+    fn title(&mut self, tab: &mut DockTab) -> egui::WidgetText {
+        tab.title().into()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, tab: &mut DockTab) {
+        match tab {
+            DockTab::Scene => self.app.scene_panel.canvas(ui),
+            DockTab::Inspector => inspector::gpu_tab(ui, self.app),
+            DockTab::Log => self.log.ui(ui),
+            DockTab::Stats => match self.app.stats.last() {
+                Some(last) => stats::record_grid(ui, "dock stats", last),
+                None => {
+                    ui.weak("No frame was drawn yet");
+                }
+            },
+        }
+    }
+
+    fn closeable(&mut self, _tab: &mut DockTab) -> bool {
+        false
+    }
+
+    // The scene's drawn by a paint callback, which would be left behind in a window.
+    fn allowed_in_windows(&self, _tab: &mut DockTab) -> bool {
+        false
+    }
+
+    fn scroll_bars(&self, tab: &DockTab) -> [bool; 2] {
+        match tab {
+            DockTab::Scene => [false, false],
+            _ => [true, true],
+        }
+    }
+}
+
+pub fn show(ctx: &egui::Context, app: &mut AppState) {
+    if !app.dock.open {
+        return;
+    }
+    // Taken out while shown, as the tabs borrow the rest of the app.
+    let mut state = std::mem::replace(&mut app.dock.state, DockState::new(Vec::new()));
+    let mut log = std::mem::take(&mut app.dock.log);
+    let mut open = true;
+    egui::Window::new("Docked panels")
+        .open(&mut open)
+        .default_size([640.0, 420.0])
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.weak("Drag the tabs to rearrange them");
+                if ui.button("Reset layout").clicked() {
+                    state = default_layout();
+                }
+            });
+            DockArea::new(&mut state)
+                .id(egui::Id::new("dock area"))
+                .style(Style::from_egui(ui.style()))
+                .show_close_buttons(false)
+                .show_inside(
+                    ui,
+                    &mut Viewer {
+                        app: &mut *app,
+                        log: &mut log,
+                    },
+                );
+        });
+    app.dock.state = state;
+    app.dock.log = log;
+    app.dock.open = open;
+}
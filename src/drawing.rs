@@ -0,0 +1,185 @@
+use egui::{Color32, Key, KeyboardShortcut, Modifiers, Pos2, Rect, Sense, Stroke};
+
+const UNDO: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::Z);
+const REDO: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::Z);
+
+/// A line drawn in one go, from pressing to letting go.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DrawnStroke {
+    /// Where it went from the canvas's top left corner, in points, and how hard the pen
+    /// was pressed there, from 0 to 1.
+    pub points: Vec<(Pos2, f32)>,
+    pub color: Color32,
+    /// At full pressure.
+    pub width: f32,
+}
+
+impl DrawnStroke {
+    fn paint(&self, painter: &egui::Painter, origin: Pos2) {
+        if let [(pos, pressure)] = self.points[..] {
+            let radius = self.width * pressure / 2.0;
+            painter.circle_filled(origin + pos.to_vec2(), radius, self.color);
+        }
+        for pair in self.points.windows(2) {
+            let [(from, from_pressure), (to, to_pressure)] = [pair[0], pair[1]];
+            let width = self.width * (from_pressure + to_pressure) / 2.0;
+            painter.line_segment(
+                [origin + from.to_vec2(), origin + to.to_vec2()],
+                Stroke::new(width, self.color),
+            );
+            // Rounds the joints, which segments of their own leave gaps at.
+            painter.circle_filled(origin + to.to_vec2(), width / 2.0, self.color);
+        }
+    }
+}
+
+/// The "Drawing" window: a canvas to draw on with the mouse, a finger or a pen, thinner
+/// where a pen is pressed lightly. The drawing is exported through a screenshot, cropped
+/// to the canvas.
+pub struct DrawingCanvas {
+    pub open: bool,
+    pub color: Color32,
+    pub width: f32,
+    /// Follows a pen's pressure, where the platform tells it.
+    pub use_pressure: bool,
+    /// What's drawn, oldest first.
+    pub strokes: Vec<DrawnStroke>,
+    /// The stroke being drawn, until the button or pen is let go of.
+    current: Option<DrawnStroke>,
+    /// What was undone, for redo, until something new is drawn.
+    undone: Vec<DrawnStroke>,
+    /// The canvas's rect on screen, in points, as last shown.
+    rect: Rect,
+    export: bool,
+}
+
+impl Default for DrawingCanvas {
+    fn default() -> Self {
+        Self {
+            open: false,
+            color: Color32::from_rgb(40, 120, 220),
+            width: 4.0,
+            use_pressure: true,
+            strokes: Vec::new(),
+            current: None,
+            undone: Vec::new(),
+            rect: Rect::NOTHING,
+            export: false,
+        }
+    }
+}
+
+impl DrawingCanvas {
+    pub fn undo(&mut self) {
+        if let Some(stroke) = self.strokes.pop() {
+            self.undone.push(stroke);
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if let Some(stroke) = self.undone.pop() {
+            self.strokes.push(stroke);
+        }
+    }
+
+    /// The canvas to export, in points, once it was asked for.
+    pub fn take_export(&mut self) -> Option<Rect> {
+        std::mem::take(&mut self.export).then_some(self.rect)
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        egui::Window::new("Drawing")
+            .open(&mut open)
+            .default_size([420.0, 360.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.color_edit_button_srgba(&mut self.color);
+                    ui.add(egui::Slider::new(&mut self.width, 1.0..=24.0).text("Width"));
+                    ui.checkbox(&mut self.use_pressure, "Pressure");
+                });
+                ui.horizontal(|ui| {
+                    let shortcut = |shortcut| ctx.format_shortcut(shortcut);
+                    let undo = egui::Button::new("Undo").shortcut_text(shortcut(&UNDO));
+                    if ui.add_enabled(!self.strokes.is_empty(), undo).clicked() {
+                        self.undo();
+                    }
+                    let redo = egui::Button::new("Redo").shortcut_text(shortcut(&REDO));
+                    if ui.add_enabled(!self.undone.is_empty(), redo).clicked() {
+                        self.redo();
+                    }
+                    if ui
+                        .add_enabled(!self.strokes.is_empty(), egui::Button::new("Clear"))
+                        .clicked()
+                    {
+                        // Cleared strokes can be brought back one by one.
+                        self.undone.extend(self.strokes.drain(..).rev());
+                    }
+                    if ui.button("Export PNG").clicked() {
+                        self.export = true;
+                    }
+                });
+
+                let size = ui.available_size().max(egui::vec2(200.0, 160.0));
+                let (response, painter) = ui.allocate_painter(size, Sense::drag());
+                self.rect = response.rect;
+                let origin = response.rect.min;
+                if response.hovered() {
+                    // Redo first, as undo's shortcut is in it.
+                    if ui.input_mut(|i| i.consume_shortcut(&REDO)) {
+                        self.redo();
+                    } else if ui.input_mut(|i| i.consume_shortcut(&UNDO)) {
+                        self.undo();
+                    }
+                }
+
+                // Only pens tell how hard they're pressed, the rest are at full pressure.
+                let pressure = ui
+                    .input(|i| {
+                        i.events.iter().rev().find_map(|event| match event {
+                            egui::Event::Touch { force, .. } => *force,
+                            _ => None,
+                        })
+                    })
+                    .filter(|_| self.use_pressure)
+                    .unwrap_or(1.0);
+                if let Some(pos) = response.interact_pointer_pos() {
+                    let point = (pos - origin.to_vec2(), pressure);
+                    let current = self.current.get_or_insert_with(|| DrawnStroke {
+                        points: Vec::new(),
+                        color: self.color,
+                        width: self.width,
+                    });
+                    if current.points.last().map(|(last, _)| *last) != Some(point.0) {
+                        current.points.push(point);
+                    }
+                }
+                if !response.dragged() {
+                    if let Some(stroke) = self.current.take() {
+                        self.strokes.push(stroke);
+                        self.undone.clear();
+                    }
+                }
+
+                painter.rect_filled(response.rect, 0.0, Color32::WHITE);
+                for stroke in self.strokes.iter().chain(&self.current) {
+                    stroke.paint(&painter, origin);
+                }
+                if self.strokes.is_empty() && self.current.is_none() {
+                    painter.text(
+                        response.rect.center(),
+                        egui::Align2::CENTER_CENTER,
+                        "Draw here",
+                        egui::FontId::proportional(16.0),
+                        Color32::GRAY,
+                    );
+                }
+            });
+        self.open = open;
+    }
+}
@@ -0,0 +1,139 @@
+use std::path::PathBuf;
+
+/// How large a preview is drawn at most, in points.
+const PREVIEW_SIZE: f32 = 256.0;
+
+/// A file dropped on the window, with a preview if it's a PNG.
+struct DroppedFile {
+    name: String,
+    path: Option<PathBuf>,
+    /// `None` if it couldn't be read.
+    size: Option<usize>,
+    preview: Option<Result<egui::TextureHandle, String>>,
+}
+
+impl DroppedFile {
+    /// Natively only the path is dropped and the file is read here, in the browser its
+    /// contents come along.
+    fn new(ctx: &egui::Context, file: &egui::DroppedFile) -> Self {
+        let name = match &file.path {
+            Some(path) => path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| path.display().to_string()),
+            None => file.name.clone(),
+        };
+        let bytes = match (&file.bytes, &file.path) {
+            (Some(bytes), _) => Ok(bytes.to_vec()),
+            (None, Some(path)) => std::fs::read(path).map_err(|e| e.to_string()),
+            (None, None) => Err("Nothing was dropped".to_owned()),
+        };
+        if let (Err(e), Some(path)) = (&bytes, &file.path) {
+            tracing::warn!("Failed to read {}: {}", path.display(), e);
+        }
+        let is_png = name.to_ascii_lowercase().ends_with(".png");
+        let preview = bytes.as_ref().ok().filter(|_| is_png).map(|bytes| {
+            let (rgba, width, height) = crate::icon::decode_rgba(bytes)?;
+            let image =
+                egui::ColorImage::from_rgba_unmultiplied([width as usize, height as usize], &rgba);
+            Ok(ctx.load_texture(format!("dropped {}", name), image, Default::default()))
+        });
+        Self {
+            name,
+            path: file.path.clone(),
+            size: bytes.ok().map(|bytes| bytes.len()),
+            preview,
+        }
+    }
+}
+
+/// Lists the files dropped on the window, and shows the PNGs among them.
+#[derive(Default)]
+pub struct DroppedFiles {
+    pub open: bool,
+    files: Vec<DroppedFile>,
+}
+
+impl DroppedFiles {
+    /// Takes in what was dropped this frame, opening the window for it, and covers the
+    /// screen with a hint while files are dragged over it.
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        let (hovered, dropped) =
+            ctx.input(|i| (i.raw.hovered_files.len(), i.raw.dropped_files.clone()));
+        if hovered > 0 {
+            drop_hint(ctx, hovered);
+        }
+        if !dropped.is_empty() {
+            self.open = true;
+            self.files
+                .extend(dropped.iter().map(|file| DroppedFile::new(ctx, file)));
+        }
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        egui::Window::new("Dropped files")
+            .open(&mut open)
+            .default_size([320.0, 400.0])
+            .show(ctx, |ui| {
+                if self.files.is_empty() {
+                    ui.weak("Drop files on the window to list them here");
+                    return;
+                }
+                if ui.button("Clear").clicked() {
+                    self.files.clear();
+                }
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for file in &self.files {
+                        ui.separator();
+                        ui.horizontal(|ui| {
+                            ui.strong(&file.name);
+                            match file.size {
+                                Some(size) => ui.weak(format!("{} bytes", size)),
+                                None => ui.colored_label(ui.visuals().error_fg_color, "Unreadable"),
+                            };
+                        });
+                        if let Some(path) = &file.path {
+                            ui.weak(path.display().to_string());
+                        }
+                        match &file.preview {
+                            Some(Ok(texture)) => {
+                                let [width, height] = texture.size();
+                                ui.label(format!("{}×{}", width, height));
+                                let size = texture.size_vec2();
+                                let scale = (PREVIEW_SIZE / size.max_elem()).min(1.0);
+                                ui.image((texture.id(), size * scale));
+                            }
+                            Some(Err(e)) => {
+                                ui.colored_label(ui.visuals().error_fg_color, e);
+                            }
+                            None => {}
+                        }
+                    }
+                });
+            });
+        self.open = open;
+    }
+}
+
+/// Darkens the screen above everything else while `count` files are dragged over it.
+fn drop_hint(ctx: &egui::Context, count: usize) {
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Foreground,
+        egui::Id::new("drop_hint"),
+    ));
+    let screen = ctx.screen_rect();
+    painter.rect_filled(screen, 0.0, egui::Color32::from_black_alpha(192));
+    let text = match count {
+        1 => "Drop the file to list it".to_owned(),
+        count => format!("Drop the {} files to list them", count),
+    };
+    painter.text(
+        screen.center(),
+        egui::Align2::CENTER_CENTER,
+        text,
+        egui::TextStyle::Heading.resolve(&ctx.style()),
+        egui::Color32::WHITE,
+    );
+}
@@ -0,0 +1,232 @@
+use egui::Color32;
+
+use std::any::Any;
+
+/// A value that can be edited in the inspector.
+pub trait Inspect {
+    /// Shows the fields as editable widgets.
+    fn inspect(&mut self, ui: &mut egui::Ui);
+}
+
+pub trait Component: Inspect + Send + Sync + 'static {
+    fn name(&self) -> &'static str;
+    fn clone_box(&self) -> Box<dyn Component>;
+    fn as_any(&self) -> &dyn Any;
+}
+
+/// Implements `Component` for types that are `Clone` and `Inspect`.
+macro_rules! component {
+    ($($ty:ident),* $(,)?) => {
+        $(impl Component for $ty {
+            fn name(&self) -> &'static str {
+                stringify!($ty)
+            }
+
+            fn clone_box(&self) -> Box<dyn Component> {
+                Box::new(self.clone())
+            }
+
+            fn as_any(&self) -> &dyn Any {
+                self
+            }
+        })*
+    };
+}
+
+pub type EntityId = u32;
+
+pub struct Entity {
+    pub id: EntityId,
+    pub components: Vec<Box<dyn Component>>,
+}
+
+impl Entity {
+    /// The names of the entity's component types, sorted. Entities with the same
+    /// components share an archetype.
+    pub fn archetype(&self) -> Vec<&'static str> {
+        let mut names: Vec<_> = self.components.iter().map(|c| c.name()).collect();
+        names.sort_unstable();
+        names
+    }
+}
+
+/// A minimal entity-component store: entities are just lists of boxed components.
+#[derive(Default)]
+pub struct World {
+    pub entities: Vec<Entity>,
+    next_id: EntityId,
+}
+
+impl World {
+    pub fn spawn(&mut self, components: Vec<Box<dyn Component>>) -> EntityId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.entities.push(Entity { id, components });
+        id
+    }
+
+    pub fn despawn(&mut self, id: EntityId) {
+        self.entities.retain(|e| e.id != id);
+    }
+
+    pub fn entity_mut(&mut self, id: EntityId) -> Option<&mut Entity> {
+        self.entities.iter_mut().find(|e| e.id == id)
+    }
+
+    /// Spawns a copy of the entity's components, returning the new entity.
+    pub fn clone_entity(&mut self, id: EntityId) -> Option<EntityId> {
+        let entity = self.entities.iter().find(|e| e.id == id)?;
+        let components = entity.components.iter().map(|c| c.clone_box()).collect();
+        Some(self.spawn(components))
+    }
+}
+
+#[derive(Clone)]
+pub struct Name(pub String);
+
+#[derive(Clone)]
+pub struct Transform {
+    pub position: [f32; 2],
+    pub rotation: f32,
+    pub scale: f32,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            position: [0.0, 0.0],
+            rotation: 0.0,
+            scale: 1.0,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Velocity(pub [f32; 2]);
+
+#[derive(Clone)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+#[derive(Clone)]
+pub struct Sprite {
+    pub color: Color32,
+    pub layer: i32,
+}
+
+component!(Name, Transform, Velocity, Health, Sprite);
+
+impl Inspect for Name {
+    fn inspect(&mut self, ui: &mut egui::Ui) {
+        ui.text_edit_singleline(&mut self.0);
+    }
+}
+
+impl Inspect for Transform {
+    fn inspect(&mut self, ui: &mut egui::Ui) {
+        egui::Grid::new("transform").show(ui, |ui| {
+            ui.label("Position");
+            ui.horizontal(|ui| {
+                ui.add(egui::DragValue::new(&mut self.position[0]).prefix("x "));
+                ui.add(egui::DragValue::new(&mut self.position[1]).prefix("y "));
+            });
+            ui.end_row();
+            ui.label("Rotation");
+            ui.drag_angle(&mut self.rotation);
+            ui.end_row();
+            ui.label("Scale");
+            ui.add(
+                egui::DragValue::new(&mut self.scale)
+                    .speed(0.01)
+                    .clamp_range(0.0..=100.0),
+            );
+            ui.end_row();
+        });
+    }
+}
+
+impl Inspect for Velocity {
+    fn inspect(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.add(egui::DragValue::new(&mut self.0[0]).prefix("x "));
+            ui.add(egui::DragValue::new(&mut self.0[1]).prefix("y "));
+        });
+    }
+}
+
+impl Inspect for Health {
+    fn inspect(&mut self, ui: &mut egui::Ui) {
+        ui.add(egui::Slider::new(&mut self.current, 0.0..=self.max).text("Current"));
+        ui.add(
+            egui::DragValue::new(&mut self.max)
+                .prefix("Max ")
+                .clamp_range(1.0..=10_000.0),
+        );
+    }
+}
+
+impl Inspect for Sprite {
+    fn inspect(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Color");
+            ui.color_edit_button_srgba(&mut self.color);
+        });
+        ui.add(egui::DragValue::new(&mut self.layer).prefix("Layer "));
+    }
+}
+
+/// A few entities of different archetypes to inspect.
+pub fn demo_world() -> World {
+    let mut world = World::default();
+    world.spawn(vec![
+        Box::new(Name("Player".to_owned())),
+        Box::new(Transform::default()),
+        Box::new(Velocity([0.0, 0.0])),
+        Box::new(Health {
+            current: 100.0,
+            max: 100.0,
+        }),
+        Box::new(Sprite {
+            color: Color32::LIGHT_BLUE,
+            layer: 1,
+        }),
+    ]);
+    for i in 0..5 {
+        world.spawn(vec![
+            Box::new(Name(format!("Enemy {}", i + 1))),
+            Box::new(Transform {
+                position: [i as f32 * 40.0, 100.0],
+                ..Transform::default()
+            }),
+            Box::new(Velocity([-1.0, 0.0])),
+            Box::new(Health {
+                current: 30.0,
+                max: 30.0,
+            }),
+            Box::new(Sprite {
+                color: Color32::RED,
+                layer: 1,
+            }),
+        ]);
+    }
+    for i in 0..3 {
+        world.spawn(vec![
+            Box::new(Name(format!("Tree {}", i + 1))),
+            Box::new(Transform {
+                position: [i as f32 * 120.0, -80.0],
+                ..Transform::default()
+            }),
+            Box::new(Sprite {
+                color: Color32::DARK_GREEN,
+                layer: 0,
+            }),
+        ]);
+    }
+    world.spawn(vec![
+        Box::new(Name("Camera".to_owned())),
+        Box::new(Transform::default()),
+    ]);
+    world
+}
@@ -0,0 +1,217 @@
+use crate::ecs::{EntityId, Name, Transform, World};
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+/// A window listing a world's entities by archetype, with an editor for the selected
+/// entity's components.
+pub struct EcsInspector {
+    pub open: bool,
+    world: Arc<RwLock<World>>,
+    selected: Option<EntityId>,
+    /// Only entities, and components, whose component names match are shown.
+    filter: String,
+}
+
+impl EcsInspector {
+    pub fn new(world: Arc<RwLock<World>>) -> Self {
+        Self {
+            open: false,
+            world,
+            selected: None,
+            filter: String::new(),
+        }
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        let world = Arc::clone(&self.world);
+        // A poisoned lock only means a writer panicked, the world is still worth showing.
+        let mut world = world.write().unwrap_or_else(|e| e.into_inner());
+        let mut open = self.open;
+        egui::Window::new("ECS inspector")
+            .open(&mut open)
+            .default_size([640.0, 480.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("Spawn Entity").clicked() {
+                        let name = format!("Entity {}", world.entities.len() + 1);
+                        let id =
+                            world.spawn(vec![Box::new(Name(name)), Box::new(Transform::default())]);
+                        self.selected = Some(id);
+                    }
+                    ui.add_enabled_ui(self.selected.is_some(), |ui| {
+                        if let Some(id) = self.selected {
+                            if ui.button("Despawn Selected").clicked() {
+                                world.despawn(id);
+                                self.selected = None;
+                            }
+                            if ui.button("Clone Selected").clicked() {
+                                self.selected = world.clone_entity(id);
+                            }
+                        }
+                    });
+                    ui.separator();
+                    ui.label("Filter");
+                    ui.text_edit_singleline(&mut self.filter);
+                });
+                ui.separator();
+
+                egui::SidePanel::left("ecs_inspector_entities")
+                    .resizable(true)
+                    .default_width(220.0)
+                    .show_inside(ui, |ui| {
+                        egui::ScrollArea::vertical().show(ui, |ui| self.tree_ui(ui, &world));
+                    });
+                egui::CentralPanel::default().show_inside(ui, |ui| {
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        self.components_ui(ui, &mut world);
+                        ui.separator();
+                        stats_ui(ui, &world);
+                    });
+                });
+            });
+        self.open = open;
+    }
+
+    fn matches(&self, component: &str) -> bool {
+        crate::clipboard::fuzzy_match(&self.filter, component)
+    }
+
+    fn tree_ui(&mut self, ui: &mut egui::Ui, world: &World) {
+        for (archetype, entities) in archetypes(world) {
+            if !archetype.iter().any(|c| self.matches(c)) {
+                continue;
+            }
+            let title = format!("{} ({})", archetype.join(", "), entities.len());
+            egui::CollapsingHeader::new(title)
+                .id_source(&archetype)
+                .default_open(true)
+                .show(ui, |ui| {
+                    for id in entities {
+                        let entity = world.entities.iter().find(|e| e.id == id);
+                        let text = entity
+                            .and_then(|e| {
+                                e.components
+                                    .iter()
+                                    .find_map(|c| c.as_any().downcast_ref::<Name>())
+                                    .map(|name| name.0.clone())
+                            })
+                            .unwrap_or_else(|| format!("Entity {}", id));
+                        if ui
+                            .selectable_label(self.selected == Some(id), text)
+                            .clicked()
+                        {
+                            self.selected = Some(id);
+                        }
+                    }
+                });
+        }
+    }
+
+    fn components_ui(&self, ui: &mut egui::Ui, world: &mut World) {
+        let Some(entity) = self.selected.and_then(|id| world.entity_mut(id)) else {
+            ui.weak("Select an entity to edit its components");
+            return;
+        };
+        ui.heading(format!("Entity {}", entity.id));
+        for (i, component) in entity.components.iter_mut().enumerate() {
+            if !self.matches(component.name()) {
+                continue;
+            }
+            egui::CollapsingHeader::new(component.name())
+                .id_source((entity.id, i))
+                .default_open(true)
+                .show(ui, |ui| {
+                    ui.push_id(i, |ui| component.inspect(ui));
+                });
+        }
+    }
+}
+
+/// The entities of every archetype, by their sorted component names.
+fn archetypes(world: &World) -> BTreeMap<Vec<&'static str>, Vec<EntityId>> {
+    let mut archetypes: BTreeMap<_, Vec<_>> = BTreeMap::new();
+    for entity in &world.entities {
+        archetypes
+            .entry(entity.archetype())
+            .or_default()
+            .push(entity.id);
+    }
+    archetypes
+}
+
+fn stats_ui(ui: &mut egui::Ui, world: &World) {
+    ui.strong("Entities per archetype");
+    let archetypes = archetypes(world);
+    let total: usize = archetypes.values().map(Vec::len).sum();
+    ui.horizontal(|ui| {
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(120.0, 120.0), egui::Sense::hover());
+        let painter = ui.painter_at(rect);
+        let mut start = 0.0;
+        ui.vertical(|ui| {
+            for (i, (archetype, entities)) in archetypes.iter().enumerate() {
+                let color = egui::ecolor::Hsva::new(i as f32 * 0.618 % 1.0, 0.6, 0.9, 1.0).into();
+                let sweep = entities.len() as f32 / total.max(1) as f32 * std::f32::consts::TAU;
+                pie_slice(
+                    &painter,
+                    rect.center(),
+                    rect.width() / 2.0,
+                    start,
+                    sweep,
+                    color,
+                );
+                start += sweep;
+                ui.horizontal(|ui| {
+                    let (swatch, _) =
+                        ui.allocate_exact_size(egui::vec2(10.0, 10.0), egui::Sense::hover());
+                    ui.painter().rect_filled(swatch, 2.0, color);
+                    ui.label(format!("{}: {}", archetype.join(", "), entities.len()));
+                });
+            }
+        });
+    });
+
+    ui.strong("Memory per component type");
+    let mut usage: BTreeMap<&'static str, (usize, usize)> = BTreeMap::new();
+    for component in world.entities.iter().flat_map(|e| &e.components) {
+        let entry = usage.entry(component.name()).or_default();
+        entry.0 += 1;
+        entry.1 += std::mem::size_of_val(&**component);
+    }
+    egui::Grid::new("ecs_memory").striped(true).show(ui, |ui| {
+        ui.label("Component");
+        ui.label("Count");
+        ui.label("Bytes");
+        ui.end_row();
+        for (name, (count, bytes)) in usage {
+            ui.label(name);
+            ui.label(count.to_string());
+            ui.label(bytes.to_string());
+            ui.end_row();
+        }
+    });
+}
+
+fn pie_slice(
+    painter: &egui::Painter,
+    center: egui::Pos2,
+    radius: f32,
+    start: f32,
+    sweep: f32,
+    color: egui::Color32,
+) {
+    let steps = ((sweep / std::f32::consts::TAU * 64.0).ceil() as usize).max(1);
+    let mut points = vec![center];
+    points.extend((0..=steps).map(|i| {
+        let angle = start + sweep * i as f32 / steps as f32;
+        center + radius * egui::vec2(angle.cos(), angle.sin())
+    }));
+    // Slices over half the pie aren't convex, so draw them as a fan of triangles.
+    for pair in points[1..].windows(2) {
+        painter.add(egui::Shape::convex_polygon(
+            vec![center, pair[0], pair[1]],
+            color,
+            egui::Stroke::NONE,
+        ));
+    }
+}
@@ -0,0 +1,87 @@
+use std::fmt;
+
+/// Why the app couldn't start, or go on drawing. Each says what the user can try.
+#[derive(Debug)]
+pub enum AppError {
+    Window(winit::error::OsError),
+    Surface(wgpu::CreateSurfaceError),
+    /// Not even a software adapter of these backends can be used.
+    NoAdapter(wgpu::Backends),
+    Device(wgpu::RequestDeviceError),
+    /// The surface lists no format at all to present in.
+    NoSurfaceFormat,
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::Window(e) => write!(
+                f,
+                "The window couldn't be created ({}). Check that a display is available, \
+                 or use --headless to render without one.",
+                e
+            ),
+            AppError::Surface(e) => write!(
+                f,
+                "The window can't be drawn to ({}). Updating the graphics driver may help.",
+                e
+            ),
+            AppError::NoAdapter(backends) if *backends == wgpu::Backends::PRIMARY => write!(
+                f,
+                "No usable GPU was found. Updating the graphics driver may help, or \
+                 --backend gl for older GPUs."
+            ),
+            AppError::NoAdapter(backends) => write!(
+                f,
+                "No usable GPU of the {:?} backends was found. Try --backend auto.",
+                backends
+            ),
+            AppError::Device(e) => write!(
+                f,
+                "The GPU couldn't be set up ({}). Updating the graphics driver may help.",
+                e
+            ),
+            AppError::NoSurfaceFormat => write!(
+                f,
+                "The window offers no format to draw in. Try another --backend."
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AppError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AppError::Window(e) => Some(e),
+            AppError::Surface(e) => Some(e),
+            AppError::Device(e) => Some(e),
+            AppError::NoAdapter(_) | AppError::NoSurfaceFormat => None,
+        }
+    }
+}
+
+impl From<winit::error::OsError> for AppError {
+    fn from(e: winit::error::OsError) -> Self {
+        AppError::Window(e)
+    }
+}
+
+impl From<wgpu::CreateSurfaceError> for AppError {
+    fn from(e: wgpu::CreateSurfaceError) -> Self {
+        AppError::Surface(e)
+    }
+}
+
+impl From<wgpu::RequestDeviceError> for AppError {
+    fn from(e: wgpu::RequestDeviceError) -> Self {
+        AppError::Device(e)
+    }
+}
+
+/// Tells the user why the app can't go on, in a message box where there are those,
+/// and exits.
+pub(crate) fn exit(error: &AppError) -> ! {
+    tracing::error!("{}", error);
+    crate::crash::message_box(&error.to_string());
+    std::process::exit(1)
+}
@@ -0,0 +1,753 @@
+use crate::assets::{AssetData, AssetId};
+use crate::builder::AppBuilder;
+use crate::capabilities::Capabilities;
+use crate::color_space::{ColorSpace, WideGamutCompositor};
+use crate::config::AppConfig;
+use crate::error;
+use crate::file_dialog::FileKind;
+use crate::frame_limiter::FrameLimiter;
+use crate::gamepad::GamepadEvent;
+use crate::gfx::{GraphicsContext, RenderTarget, BELT_CHUNK_SIZE, DEPTH_FORMAT};
+use crate::gpu_profiler::GpuProfiler;
+use crate::gui::Gui;
+use crate::hdr::HDR_FORMAT;
+use crate::jobs::JobId;
+use crate::letterbox::Letterbox;
+use crate::post::PostChain;
+use crate::recorder::Recorder;
+use crate::screenshot::Capture;
+use crate::stats::{Stage, StageTimer};
+use crate::tray::TrayEvent;
+use crate::update::UpdateInfo;
+use crate::window_geometry::WindowGeometry;
+use crate::windows::Windows;
+use crate::{Application, FrameReport, RenderFrame, Setup};
+
+use instant::Instant;
+use winit::{
+    dpi::PhysicalSize,
+    event::Event::*,
+    event_loop::{ControlFlow, EventLoop, EventLoopProxy},
+    window::Window,
+};
+
+use std::iter;
+use std::path::{Path, PathBuf};
+
+/// Events sent to the winit event loop from other threads.
+#[derive(Debug)]
+pub enum UserEvent {
+    /// A `myexample://` link forwarded by another instance of the example.
+    DeepLink(String),
+    /// The update check found a newer release.
+    UpdateAvailable(UpdateInfo),
+    /// The recording was written to the path, or failed.
+    RecordingSaved(Result<PathBuf, String>),
+    /// A line was logged, which the inspector may be showing.
+    Logged,
+    /// `config.toml` was edited.
+    ConfigChanged(AppConfig),
+    /// A shader was saved, with its file name and new contents.
+    ShaderChanged(String, String),
+    /// A file was picked in the open dialog and read, or failed to be.
+    FileOpened(FileKind, Result<(PathBuf, Vec<u8>), String>),
+    /// A file was picked in the save dialog and written to, or failed to be.
+    FileSaved(Result<PathBuf, String>),
+    /// Assets were picked in the open dialog, to be loaded.
+    FilesPicked(Vec<PathBuf>),
+    /// A job loaded an asset, or failed to.
+    AssetLoaded(AssetId, Result<AssetData, String>),
+    /// A job given to `Jobs::spawn` returned, with what it returned.
+    JobFinished(JobId, Box<dyn std::any::Any + Send>),
+    /// The tray icon is ready, or its menu was clicked.
+    Tray(TrayEvent),
+    /// A gamepad connected, disconnected or was used.
+    Gamepad(GamepadEvent),
+    /// A screen reader asked for the UI's accessibility tree, which egui only builds
+    /// from then on.
+    #[cfg(feature = "accesskit")]
+    AccessKitRequested,
+    /// A screen reader asked for an action, like clicking a button.
+    #[cfg(feature = "accesskit")]
+    AccessKit(egui_winit::accesskit_winit::ActionRequestEvent),
+}
+
+#[cfg(feature = "accesskit")]
+impl From<egui_winit::accesskit_winit::ActionRequestEvent> for UserEvent {
+    fn from(event: egui_winit::accesskit_winit::ActionRequestEvent) -> Self {
+        UserEvent::AccessKit(event)
+    }
+}
+
+/// Everything drawn with the device besides egui, rebuilt along with it.
+struct Targets {
+    /// With a virtual resolution everything is drawn offscreen at that size, and only
+    /// scaled to the window at the end.
+    letterbox: Option<Letterbox>,
+    /// On a wide-gamut surface egui draws offscreen, to be blended in by this.
+    compositor: Option<WideGamutCompositor>,
+    /// With post-processing the scene is drawn into a float target, and the chain's
+    /// last pass draws it into the frame.
+    post: PostChain,
+    /// With multisampling the scene is drawn into this, and resolved before the UI.
+    scene_msaa: RenderTarget,
+    depth: RenderTarget,
+    profiler: Option<GpuProfiler>,
+    /// What `RenderFrame::write_buffer` stages its writes in.
+    belt: wgpu::util::StagingBelt,
+}
+
+impl Targets {
+    fn new(gfx: &GraphicsContext, builder: &AppBuilder, size: PhysicalSize<u32>) -> Self {
+        let surface_format = gfx.format();
+        let letterbox_config = builder.letterbox_config();
+        let letterbox = letterbox_config.enabled.then(|| {
+            Letterbox::new(
+                &gfx.device,
+                &gfx.memory,
+                letterbox_config,
+                surface_format,
+                [size.width, size.height],
+            )
+        });
+        let [frame_width, frame_height] = match &letterbox {
+            Some(letterbox) => letterbox.size(),
+            None => [size.width, size.height],
+        };
+        let compositor = (gfx.color_space != ColorSpace::Srgb).then(|| {
+            WideGamutCompositor::new(
+                &gfx.device,
+                &gfx.queue,
+                &gfx.memory,
+                gfx.color_space,
+                surface_format,
+                frame_width,
+                frame_height,
+            )
+        });
+        Self {
+            letterbox,
+            compositor,
+            post: gfx.errors.scope(&gfx.device, "post chain", || {
+                PostChain::new(
+                    &gfx.device,
+                    &gfx.memory,
+                    &gfx.cache,
+                    gfx.color_space,
+                    surface_format,
+                )
+            }),
+            scene_msaa: RenderTarget::new("msaa target", surface_format, gfx.sample_count())
+                .tracked(&gfx.memory),
+            depth: RenderTarget::new("depth", DEPTH_FORMAT, gfx.sample_count())
+                .tracked(&gfx.memory),
+            profiler: GpuProfiler::new(&gfx.device, &gfx.queue),
+            belt: wgpu::util::StagingBelt::new(BELT_CHUNK_SIZE),
+        }
+    }
+}
+
+fn setup<'a>(
+    window: &'a Window,
+    gfx: &'a GraphicsContext,
+    gui: &'a mut Gui,
+    proxy: EventLoopProxy<UserEvent>,
+) -> Setup<'a> {
+    Setup {
+        window: Some(window),
+        adapter: &gfx.adapter,
+        adapters: gfx.adapters(),
+        present_modes: gfx.present_modes(),
+        device: &gfx.device,
+        queue: &gfx.queue,
+        capabilities: Capabilities::of(&gfx.adapter, &gfx.device),
+        errors: &gfx.errors,
+        memory: &gfx.memory,
+        cache: &gfx.cache,
+        renderer: &mut gui.renderer,
+        surface_format: gfx.format(),
+        ui_format: gfx.ui_format(),
+        color_space: gfx.color_space,
+        alpha_mode: gfx.alpha_mode(),
+        sample_count: gfx.sample_count(),
+        proxy: Some(proxy),
+    }
+}
+
+/// Makes the device and egui's renderer again, on `adapter` if given, and lets the
+/// app make its resources again. The `Targets` have to be rebuilt as well.
+fn recreate(
+    app: &mut impl Application,
+    window: &Window,
+    gfx: &mut GraphicsContext,
+    gui: &mut Gui,
+    windows: &mut Windows,
+    proxy: &EventLoopProxy<UserEvent>,
+    adapter: Option<&wgpu::AdapterInfo>,
+) {
+    if let Err(e) = gfx.recreate(window, adapter) {
+        error::exit(&e);
+    }
+    gui.recreate(&gfx.device, gfx.ui_format());
+    windows.device_recreated(gfx);
+    gfx.memory.forget_egui_textures();
+    app.device_recreated(setup(window, gfx, gui, proxy.clone()));
+    window.request_redraw();
+}
+
+/// Saves what's kept of the window and egui for the next run, and lets `app` know it
+/// exits.
+fn close(
+    app: &mut impl Application,
+    builder: &AppBuilder,
+    window: &Window,
+    gui: &Gui,
+    geometry: &mut Option<WindowGeometry>,
+) {
+    if builder.persists_ui() {
+        gui.save_memory();
+    }
+    if let Some(geometry) = geometry {
+        geometry.track(window);
+        geometry.save();
+    }
+    app.exit();
+}
+
+/// Sets up `app` and runs the event loop, blocking on getting the device.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn run(app: impl Application, builder: AppBuilder) -> ! {
+    let event_loop = builder.build_event_loop();
+    let window = builder
+        .build_window(&event_loop)
+        .unwrap_or_else(|e| error::exit(&e));
+    #[cfg(target_os = "android")]
+    let event_loop = crate::android::wait_until_resumed(event_loop);
+    #[allow(unused_mut)]
+    let (gfx, mut gui) = crate::runtime::block_on(builder.build_graphics(&window))
+        .unwrap_or_else(|e| error::exit(&e));
+    // The window is built hidden for this, AccessKit has to be set up before it's shown.
+    #[cfg(feature = "accesskit")]
+    {
+        gui.init_accesskit(&window, event_loop.create_proxy());
+        window.set_visible(true);
+    }
+    run_loop(app, builder, event_loop, window, gfx, gui)
+}
+
+/// Sets up `app` once the browser hands over the device, then runs the event loop.
+#[cfg(target_arch = "wasm32")]
+pub fn spawn(app: impl Application, builder: AppBuilder) {
+    let event_loop = builder.build_event_loop();
+    let window = match builder.build_window(&event_loop) {
+        Ok(window) => window,
+        Err(e) => {
+            tracing::error!("{}", e);
+            return;
+        }
+    };
+    wasm_bindgen_futures::spawn_local(async move {
+        match builder.build_graphics(&window).await {
+            Ok((gfx, gui)) => run_loop(app, builder, event_loop, window, gfx, gui),
+            Err(e) => tracing::error!("{}", e),
+        }
+    });
+}
+
+/// Runs the winit event loop, drawing a frame of `app` whenever the window asks for one.
+fn run_loop(
+    mut app: impl Application,
+    builder: AppBuilder,
+    event_loop: EventLoop<UserEvent>,
+    window: Window,
+    mut gfx: GraphicsContext,
+    mut gui: Gui,
+) -> ! {
+    let proxy = event_loop.create_proxy();
+    let mut targets = Targets::new(&gfx, &builder, window.inner_size());
+    let mut requested_samples = None;
+    let mut requested_present_mode = None;
+    let mut requested_adapter = None;
+    let mut requested_recording: Option<PathBuf> = None;
+    let mut recorder: Option<Recorder> = None;
+    let mut limiter = FrameLimiter::default();
+    // When egui asked to be drawn again, `None` until there is input.
+    let mut repaint_at = Some(Instant::now());
+    // Nothing is drawn while the window can't be seen, not even egui's frame.
+    let mut minimized = false;
+    let mut occluded = false;
+    // Between `Suspended` and `Resumed` there's no surface to draw to at all.
+    let mut suspended = false;
+    let mut windows = Windows::default();
+    // What's saved of the window when it's closed, the restored size kept while it's
+    // maximized.
+    let mut geometry = builder.persists_window().then(|| {
+        let mut geometry = WindowGeometry::load().unwrap_or_default();
+        geometry.track(&window);
+        geometry
+    });
+    #[cfg(target_os = "android")]
+    let mut keyboard = crate::android::SoftKeyboard::default();
+
+    app.setup(setup(&window, &gfx, &mut gui, proxy.clone()));
+
+    event_loop.run(move |event, target, control_flow| {
+        // The other windows' events only go to them, or nowhere once they're closed.
+        match &event {
+            WindowEvent { window_id, event } if *window_id != window.id() => {
+                windows.on_event(&mut app, &gfx, *window_id, event);
+                return;
+            }
+            RedrawRequested(window_id) if *window_id != window.id() => {
+                windows.redraw(&mut app, &gfx, *window_id);
+                return;
+            }
+            _ => {}
+        }
+
+        // Pass the winit events to the platform integration.
+        if let WindowEvent { event, .. } = &event {
+            let response = gui.on_event(event);
+            if response.repaint {
+                window.request_redraw();
+            }
+            if response.consumed {
+                return;
+            }
+        }
+        // The D-pad moves egui's focus, and the app still sees the event.
+        if let UserEvent(self::UserEvent::Gamepad(gamepad)) = &event {
+            if gui.on_gamepad(gamepad) {
+                window.request_redraw();
+            }
+        }
+        if app.on_event(&event, &window) {
+            // Whatever the app did with it likely shows in the UI, in any of its windows.
+            window.request_redraw();
+            windows.request_redraw_all();
+            return;
+        }
+
+        match event {
+            #[cfg(feature = "accesskit")]
+            UserEvent(self::UserEvent::AccessKitRequested) => {
+                gui.enable_accesskit();
+                window.request_redraw();
+            }
+            #[cfg(feature = "accesskit")]
+            UserEvent(self::UserEvent::AccessKit(event)) if event.window_id == window.id() => {
+                gui.on_accesskit_action_request(event.request);
+                window.request_redraw();
+            }
+            RedrawRequested(..) if minimized || occluded || suspended => {}
+            RedrawRequested(..) => {
+                let _frame = tracing::debug_span!("frame").entered();
+                repaint_at = None;
+                if let Some(adapter) = app
+                    .adapter()
+                    .filter(|&adapter| Some(adapter) != requested_adapter.as_ref())
+                    .cloned()
+                {
+                    requested_adapter = Some(adapter.clone());
+                    if adapter != gfx.adapter.get_info() {
+                        tracing::info!("Switching to {}", adapter.name);
+                        recreate(
+                            &mut app,
+                            &window,
+                            &mut gfx,
+                            &mut gui,
+                            &mut windows,
+                            &proxy,
+                            Some(&adapter),
+                        );
+                        targets = Targets::new(&gfx, &builder, window.inner_size());
+                        requested_samples = None;
+                        requested_present_mode = None;
+                        if let Some(recorder) = &mut recorder {
+                            recorder.device_recreated();
+                        }
+                    }
+                }
+                // Both only on changes, they may be lowered to what's supported.
+                if let Some(present_mode) = app
+                    .present_mode()
+                    .filter(|&mode| Some(mode) != requested_present_mode)
+                {
+                    requested_present_mode = Some(present_mode);
+                    gfx.set_present_mode(present_mode);
+                }
+                if let Some(sample_count) = app
+                    .sample_count()
+                    .filter(|&count| Some(count) != requested_samples)
+                {
+                    requested_samples = Some(sample_count);
+                    gfx.set_sample_count(sample_count);
+                    if gfx.sample_count() != targets.depth.sample_count() {
+                        targets.depth =
+                            RenderTarget::new("depth", DEPTH_FORMAT, gfx.sample_count())
+                                .tracked(&gfx.memory);
+                    }
+                }
+                if app.recording() != requested_recording.as_deref() {
+                    requested_recording = app.recording().map(Path::to_path_buf);
+                    // Dropping the last one finishes its file.
+                    recorder = requested_recording.clone().and_then(|path| {
+                        let started = if gfx.can_capture() {
+                            Recorder::start(path, &gfx.memory, proxy.clone())
+                        } else {
+                            Err("The surface can't be copied from")
+                        };
+                        started
+                            .map_err(|e| {
+                                let _ =
+                                    proxy.send_event(UserEvent::RecordingSaved(Err(e.to_owned())));
+                            })
+                            .ok()
+                    });
+                }
+                let post_passes = app.post_passes();
+                let surface_format = gfx.format();
+                let scene_format = if post_passes.is_empty() {
+                    surface_format
+                } else {
+                    HDR_FORMAT
+                };
+                if gfx.sample_count() != targets.scene_msaa.sample_count()
+                    || scene_format != targets.scene_msaa.format()
+                {
+                    targets.scene_msaa =
+                        RenderTarget::new("msaa target", scene_format, gfx.sample_count())
+                            .tracked(&gfx.memory);
+                }
+                let mut timer = StageTimer::start();
+                let Some(output_frame) = gfx.acquire_frame() else {
+                    // Try again once the surface is usable, as nothing else might ask.
+                    repaint_at = Some(Instant::now());
+                    if gfx.is_lost() {
+                        // Everything made with the old device is unusable, so all of it
+                        // is made again, down to egui's renderer.
+                        tracing::warn!("The GPU device was lost, recreating it");
+                        let adapter = gfx.adapter.get_info();
+                        recreate(
+                            &mut app,
+                            &window,
+                            &mut gfx,
+                            &mut gui,
+                            &mut windows,
+                            &proxy,
+                            Some(&adapter),
+                        );
+                        targets = Targets::new(&gfx, &builder, window.inner_size());
+                        requested_samples = None;
+                        requested_present_mode = None;
+                        if let Some(recorder) = &mut recorder {
+                            recorder.device_recreated();
+                        }
+                    }
+                    return;
+                };
+                timer.lap(Stage::Acquire);
+                let output_view = &output_frame.view;
+                let Targets {
+                    letterbox,
+                    compositor,
+                    post,
+                    scene_msaa,
+                    depth,
+                    profiler,
+                    belt,
+                } = &mut targets;
+
+                app.update(&window);
+
+                let frame_view = match &letterbox {
+                    Some(letterbox) => letterbox.target_view(),
+                    None => output_view,
+                };
+                let size_in_pixels = match &letterbox {
+                    Some(letterbox) => letterbox.size(),
+                    None => gfx.size(),
+                };
+                let frame = gui.run(
+                    &window,
+                    &mut app,
+                    letterbox.as_ref(),
+                    size_in_pixels,
+                    &mut timer,
+                );
+                #[cfg(target_os = "android")]
+                if let Some(android) = builder.android_app_config() {
+                    keyboard.update(android, gui.context.wants_keyboard_input());
+                }
+
+                let mut encoder =
+                    gfx.device
+                        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                            label: Some("encoder"),
+                        });
+
+                // Upload all resources for the GPU.
+                let callback_commands = gui.upload(&gfx.device, &gfx.queue, &mut encoder, &frame);
+                gfx.memory.egui_textures(&frame.textures_delta);
+                timer.lap(Stage::Upload);
+                if let Some(profiler) = profiler {
+                    profiler.begin(&mut encoder, "scene");
+                }
+
+                // With multisampling the scene is drawn into its own target, and resolved
+                // into the frame, or the post chain's input, before the UI goes over it.
+                let post_view =
+                    (!post_passes.is_empty()).then(|| post.input_view(&gfx.device, size_in_pixels));
+                let resolved_view = post_view.unwrap_or(frame_view);
+                let scene_msaa_view = (scene_msaa.sample_count() > 1)
+                    .then(|| scene_msaa.view(&gfx.device, size_in_pixels));
+                let scene_view = scene_msaa_view.unwrap_or(resolved_view);
+                let depth_view = depth.view(&gfx.device, size_in_pixels);
+                let clear_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("clear"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: scene_view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(app.clear_color()),
+                            store: true,
+                        },
+                    })],
+                    depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                        view: depth_view,
+                        depth_ops: Some(wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(1.0),
+                            store: true,
+                        }),
+                        stencil_ops: None,
+                    }),
+                });
+                drop(clear_pass);
+
+                app.render(RenderFrame {
+                    device: &gfx.device,
+                    queue: &gfx.queue,
+                    encoder: &mut encoder,
+                    errors: &gfx.errors,
+                    memory: &gfx.memory,
+                    cache: &gfx.cache,
+                    renderer: &mut gui.renderer,
+                    view: scene_view,
+                    depth: depth_view,
+                    format: scene_format,
+                    sample_count: gfx.sample_count(),
+                    size: size_in_pixels,
+                    belt,
+                });
+
+                if scene_msaa_view.is_some() {
+                    let resolve_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                        label: Some("resolve"),
+                        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                            view: scene_view,
+                            resolve_target: Some(resolved_view),
+                            ops: wgpu::Operations {
+                                load: wgpu::LoadOp::Load,
+                                store: true,
+                            },
+                        })],
+                        depth_stencil_attachment: None,
+                    });
+                    drop(resolve_pass);
+                }
+                if let Some(profiler) = profiler.as_mut().filter(|_| !post_passes.is_empty()) {
+                    profiler.begin(&mut encoder, "post");
+                }
+                post.run(
+                    &gfx.device,
+                    &gfx.queue,
+                    &mut encoder,
+                    &post_passes,
+                    frame_view,
+                    size_in_pixels,
+                );
+
+                // egui draws straight over what the app rendered, or offscreen to be
+                // composited on a wide-gamut surface.
+                let (ui_view, ui_load) = match &compositor {
+                    Some(compositor) => (
+                        compositor.ui_view(),
+                        wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    ),
+                    None => (frame_view, wgpu::LoadOp::Load),
+                };
+                if let Some(profiler) = profiler {
+                    profiler.begin(&mut encoder, "ui");
+                }
+                let mut ui_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("ui"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: ui_view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: ui_load,
+                            store: true,
+                        },
+                    })],
+                    depth_stencil_attachment: None,
+                });
+                gui.paint(&mut ui_pass, &frame);
+                drop(ui_pass);
+
+                if let Some(compositor) = &compositor {
+                    compositor.composite(&mut encoder, frame_view);
+                }
+                if let Some(letterbox) = &letterbox {
+                    letterbox.present(&mut encoder, output_view);
+                }
+                let capture = app.screenshot_requested().then(|| {
+                    if !gfx.can_capture() {
+                        return Err("The surface can't be copied from");
+                    }
+                    Capture::copy(&gfx.device, &mut encoder, output_frame.texture())
+                });
+                if let Some(recorder) = &mut recorder {
+                    recorder.copy(&gfx.device, &mut encoder, output_frame.texture());
+                }
+
+                // Submit the commands.
+                if let Some(profiler) = profiler {
+                    profiler.end(&mut encoder);
+                }
+                belt.finish();
+                gfx.queue.submit(
+                    callback_commands
+                        .into_iter()
+                        .chain(iter::once(encoder.finish())),
+                );
+                belt.recall();
+                gfx.cache.end_frame();
+                if let Some(profiler) = profiler {
+                    profiler.submitted();
+                }
+                if let Some(recorder) = &mut recorder {
+                    recorder.submitted();
+                }
+                timer.lap(Stage::Encode);
+
+                // Redraw egui
+                gfx.present(output_frame);
+                timer.lap(Stage::Present);
+                let screenshot =
+                    capture.map(|capture| capture.and_then(|capture| capture.read(&gfx.device)));
+                if let Some(recorder) = &mut recorder {
+                    recorder.collect(&gfx.device);
+                }
+
+                app.frame_done(&FrameReport {
+                    paint_jobs: &frame.paint_jobs,
+                    textures_delta: &frame.textures_delta,
+                    screen_descriptor: &frame.screen_descriptor,
+                    stage_ms: timer.stage_ms(),
+                    present_mode: gfx.present_mode(),
+                    gpu_times: profiler
+                        .as_mut()
+                        .map(|profiler| profiler.collect(&gfx.device)),
+                    screenshot: screenshot
+                        .as_ref()
+                        .map(|result| result.as_ref().map_err(|e| *e)),
+                });
+                gui.free(&frame);
+                repaint_at = Instant::now().checked_add(frame.repaint_after);
+            }
+            // Only draw when egui asks for it, input asks egui through `on_event` above.
+            MainEventsCleared => {
+                // Checked here rather than after a frame, as none is drawn while the
+                // window is hidden in the tray.
+                if app.close_requested() {
+                    close(&mut app, &builder, &window, &gui, &mut geometry);
+                    *control_flow = ControlFlow::Exit;
+                    return;
+                }
+                windows.sync(app.windows(), target, &gfx, &gui.context.style());
+                windows.request_redraws();
+                if !minimized
+                    && !occluded
+                    && !suspended
+                    && repaint_at.is_some_and(|at| at <= Instant::now())
+                {
+                    limiter.wait(app.fps_limit());
+                    window.request_redraw();
+                }
+            }
+            RedrawEventsCleared if *control_flow != ControlFlow::Exit => {
+                let main = repaint_at.filter(|_| !minimized && !occluded && !suspended);
+                *control_flow = match main.into_iter().chain(windows.repaint_at()).min() {
+                    Some(at) => ControlFlow::WaitUntil(at),
+                    None => ControlFlow::Wait,
+                };
+            }
+            // The surface is only valid while the app is resumed: on Android the native
+            // window goes away in between. Desktops never suspend, and resume once at
+            // startup while the surface made with the window is still there.
+            Suspended if !suspended => {
+                suspended = true;
+                gfx.suspend();
+                windows.suspend();
+                if builder.persists_ui() {
+                    gui.save_memory();
+                }
+                app.suspended();
+            }
+            Resumed if suspended || gfx.is_suspended() => {
+                suspended = false;
+                if let Err(e) = gfx.resume(&window) {
+                    error::exit(&e);
+                }
+                windows.resume(&gfx);
+                repaint_at = Some(Instant::now());
+            }
+            WindowEvent { event, .. } => match event {
+                // `resume` picks the size up.
+                winit::event::WindowEvent::Resized(_)
+                | winit::event::WindowEvent::ScaleFactorChanged { .. }
+                    if suspended => {}
+                // Moving to a monitor with another scale factor resizes the window, and
+                // not every platform sends a `Resized` for it as well. egui and the
+                // screen descriptor take the new scale factor up with the next frame.
+                winit::event::WindowEvent::Resized(size)
+                | winit::event::WindowEvent::ScaleFactorChanged {
+                    new_inner_size: &mut size,
+                    ..
+                } => {
+                    if let Some(geometry) = &mut geometry {
+                        geometry.track(&window);
+                    }
+                    minimized = !gfx.resize(size.width, size.height);
+                    if minimized {
+                        return;
+                    }
+                    // The frame is drawn as soon as it's restored, whatever egui asked.
+                    repaint_at = Some(Instant::now());
+                    if let Some(letterbox) = &mut targets.letterbox {
+                        letterbox.resize(size.width, size.height);
+                    } else if let Some(compositor) = &mut targets.compositor {
+                        compositor.resize(&gfx.device, size.width, size.height);
+                    }
+                }
+                winit::event::WindowEvent::Occluded(hidden) => {
+                    occluded = hidden;
+                    if !occluded {
+                        repaint_at = Some(Instant::now());
+                    }
+                }
+                winit::event::WindowEvent::Moved(_) => {
+                    if let Some(geometry) = &mut geometry {
+                        geometry.track(&window);
+                    }
+                }
+                winit::event::WindowEvent::CloseRequested => {
+                    close(&mut app, &builder, &window, &gui, &mut geometry);
+                    *control_flow = ControlFlow::Exit;
+                }
+                _ => {}
+            },
+            _ => (),
+        }
+    })
+}
@@ -0,0 +1,138 @@
+use crate::events::UserEvent;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use winit::event_loop::EventLoopProxy;
+
+/// What a file is opened for, which the open dialog filters by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileKind {
+    Any,
+    /// A TrueType or OpenType font.
+    Font,
+    /// A glTF or GLB model.
+    Model,
+    /// A PNG or JPEG image.
+    Image,
+}
+
+/// Open and save dialogs, shown in the background so frames keep being drawn
+/// while one is up. What they come back with is delivered as `UserEvent::FileOpened`,
+/// `UserEvent::FileSaved` or `UserEvent::FilesPicked`, and nothing is when they're
+/// cancelled.
+#[derive(Default)]
+pub struct FileDialogs {
+    proxy: Option<EventLoopProxy<UserEvent>>,
+    /// Set while a dialog is up, as only one is shown at a time.
+    busy: Arc<AtomicBool>,
+}
+
+impl FileDialogs {
+    pub fn set_proxy(&mut self, proxy: EventLoopProxy<UserEvent>) {
+        self.proxy = Some(proxy);
+    }
+
+    /// Whether a dialog can be shown now. There's none headless, in the browser or on
+    /// Android.
+    pub fn available(&self) -> bool {
+        cfg!(not(any(target_arch = "wasm32", target_os = "android")))
+            && self.proxy.is_some()
+            && !self.busy.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+impl FileDialogs {
+    /// Asks for a file of `kind` and reads it.
+    pub fn open(&self, kind: FileKind) {
+        self.spawn(async move {
+            let dialog = match kind {
+                FileKind::Any => rfd::AsyncFileDialog::new(),
+                FileKind::Font => rfd::AsyncFileDialog::new().add_filter("Fonts", &["ttf", "otf"]),
+                FileKind::Image => {
+                    rfd::AsyncFileDialog::new().add_filter("Images", &["png", "jpg", "jpeg"])
+                }
+                FileKind::Model => {
+                    rfd::AsyncFileDialog::new().add_filter("glTF models", &["gltf", "glb"])
+                }
+            };
+            let file = dialog.pick_file().await?;
+            let path = file.path().to_owned();
+            let result = match crate::runtime::read(path.clone()).await {
+                Ok(bytes) => Ok((path, bytes)),
+                Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+            };
+            Some(UserEvent::FileOpened(kind, result))
+        });
+    }
+
+    /// Asks for any number of images, shaders and models, for the assets to read.
+    pub fn pick_assets(&self) {
+        self.spawn(async {
+            let dialog =
+                rfd::AsyncFileDialog::new().add_filter("Assets", crate::AssetKind::EXTENSIONS);
+            let files = dialog.pick_files().await?;
+            let paths = files.iter().map(|file| file.path().to_owned()).collect();
+            Some(UserEvent::FilesPicked(paths))
+        });
+    }
+
+    /// Asks where to write `bytes`, suggesting `name`.
+    pub fn save(&self, name: String, bytes: Vec<u8>) {
+        self.save_with(rfd::AsyncFileDialog::new().set_file_name(name), bytes);
+    }
+
+    /// Like `save`, only offering files with one of `extensions`, which are described
+    /// as `filter`.
+    pub fn save_as(&self, name: String, filter: &str, extensions: &[&str], bytes: Vec<u8>) {
+        let dialog = rfd::AsyncFileDialog::new()
+            .set_file_name(name)
+            .add_filter(filter, extensions);
+        self.save_with(dialog, bytes);
+    }
+
+    fn save_with(&self, dialog: rfd::AsyncFileDialog, bytes: Vec<u8>) {
+        self.spawn(async move {
+            let file = dialog.save_file().await?;
+            let path = file.path().to_owned();
+            Some(UserEvent::FileSaved(
+                match crate::runtime::write(path.clone(), bytes).await {
+                    Ok(()) => Ok(path),
+                    Err(e) => Err(format!("Failed to write {}: {}", path.display(), e)),
+                },
+            ))
+        });
+    }
+
+    /// Runs `dialog` in the background unless one is up already, sending what it
+    /// returns to the event loop.
+    fn spawn(&self, dialog: impl std::future::Future<Output = Option<UserEvent>> + Send + 'static) {
+        let Some(proxy) = self.proxy.clone() else {
+            return;
+        };
+        if self.busy.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        let busy = Arc::clone(&self.busy);
+        let spawned = crate::runtime::spawn("file dialog", proxy, async move {
+            let event = dialog.await;
+            busy.store(false, Ordering::Relaxed);
+            event
+        });
+        if let Err(e) = spawned {
+            self.busy.store(false, Ordering::Relaxed);
+            tracing::warn!("Failed to show a file dialog: {}", e);
+        }
+    }
+}
+
+#[cfg(any(target_arch = "wasm32", target_os = "android"))]
+impl FileDialogs {
+    pub fn open(&self, _kind: FileKind) {}
+
+    pub fn pick_assets(&self) {}
+
+    pub fn save(&self, _name: String, _bytes: Vec<u8>) {}
+
+    pub fn save_as(&self, _name: String, _filter: &str, _extensions: &[&str], _bytes: Vec<u8>) {}
+}
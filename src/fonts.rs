@@ -0,0 +1,176 @@
+use crate::file_dialog::{FileDialogs, FileKind};
+
+use ab_glyph::Font;
+
+/// The text the fonts window shows each font with.
+const SAMPLE: &str = "The quick brown fox jumps over the lazy dog 0123456789";
+
+/// Fonts loaded at runtime on top of egui's own, each as a family named after it that
+/// falls back to egui's fonts for what it has no glyphs for.
+///
+/// Changes reach egui through `update`, which rebuilds the font atlas for the next
+/// frame, so a family can only be drawn with from then on.
+#[derive(Default)]
+pub struct CustomFonts {
+    fonts: Vec<(String, Vec<u8>)>,
+    /// The font proportional text is drawn with first, egui's own when `None`.
+    ui_font: Option<String>,
+    changed: bool,
+}
+
+impl CustomFonts {
+    /// Adds the TTF or OTF `bytes` as the family `name`, replacing a font loaded under
+    /// that name before. egui panics on fonts it can't parse, so they're checked first.
+    pub fn insert(&mut self, name: impl Into<String>, bytes: Vec<u8>) -> Result<(), String> {
+        let font = ab_glyph::FontRef::try_from_slice(&bytes).map_err(|e| e.to_string())?;
+        if !font
+            .units_per_em()
+            .is_some_and(|units| (16.0..=16384.0).contains(&units))
+        {
+            return Err("Its glyphs have an unusable size".to_owned());
+        }
+        let name = name.into();
+        self.fonts.retain(|(loaded, _)| *loaded != name);
+        self.fonts.push((name, bytes));
+        self.changed = true;
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.fonts.retain(|(loaded, _)| loaded != name);
+        if self.ui_font.as_deref() == Some(name) {
+            self.ui_font = None;
+        }
+        self.changed = true;
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.fonts.iter().map(|(name, _)| name.as_str())
+    }
+
+    pub fn ui_font(&self) -> Option<&str> {
+        self.ui_font.as_deref()
+    }
+
+    /// Draws the UI's proportional text with the font loaded as `name`, or egui's own.
+    pub fn set_ui_font(&mut self, name: Option<&str>) {
+        self.ui_font = name
+            .filter(|name| self.names().any(|loaded| loaded == *name))
+            .map(str::to_owned);
+        self.changed = true;
+    }
+
+    /// egui's fonts with these added.
+    pub fn definitions(&self) -> egui::FontDefinitions {
+        let mut definitions = egui::FontDefinitions::default();
+        let fallbacks = definitions.families[&egui::FontFamily::Proportional].clone();
+        for (name, bytes) in &self.fonts {
+            definitions
+                .font_data
+                .insert(name.clone(), egui::FontData::from_owned(bytes.clone()));
+            let family = std::iter::once(name.clone())
+                .chain(fallbacks.iter().cloned())
+                .collect();
+            definitions
+                .families
+                .insert(egui::FontFamily::Name(name.as_str().into()), family);
+        }
+        if let Some(name) = &self.ui_font {
+            if let Some(proportional) = definitions
+                .families
+                .get_mut(&egui::FontFamily::Proportional)
+            {
+                proportional.insert(0, name.clone());
+            }
+        }
+        definitions
+    }
+
+    /// Hands the fonts to egui if they changed, to be used from the next frame on.
+    pub fn update(&mut self, ctx: &egui::Context) {
+        if std::mem::take(&mut self.changed) {
+            ctx.set_fonts(self.definitions());
+        }
+    }
+
+    /// Has `update` hand them over again, to a new context.
+    pub fn reapply(&mut self) {
+        self.changed = true;
+    }
+}
+
+/// Lists the loaded fonts with a sample of each, and loads more.
+#[derive(Default)]
+pub struct FontsWindow {
+    pub open: bool,
+    pub fonts: CustomFonts,
+    pub error: Option<String>,
+}
+
+impl FontsWindow {
+    /// Adds a font read from `path`, named after the file.
+    pub fn load(&mut self, path: &std::path::Path, bytes: Vec<u8>) {
+        let name = path
+            .file_stem()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "Font".to_owned());
+        self.open = true;
+        self.error = self
+            .fonts
+            .insert(name, bytes)
+            .err()
+            .map(|e| format!("Failed to load {}: {}", path.display(), e));
+    }
+
+    /// Fonts picked in `dialogs` come back as `UserEvent::FileOpened`, for `load`.
+    pub fn ui(&mut self, ctx: &egui::Context, dialogs: &FileDialogs) {
+        self.fonts.update(ctx);
+        if !self.open {
+            return;
+        }
+
+        let families = ctx.fonts(|fonts| fonts.families());
+        let mut open = self.open;
+        let mut remove = None;
+        let mut ui_font = self.fonts.ui_font().map(str::to_owned);
+        egui::Window::new("Fonts")
+            .open(&mut open)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                let load = ui
+                    .add_enabled(dialogs.available(), egui::Button::new("Load font…"))
+                    .on_disabled_hover_text("There's no file dialog here");
+                if load.clicked() {
+                    dialogs.open(FileKind::Font);
+                }
+                if let Some(e) = &self.error {
+                    ui.colored_label(ui.visuals().error_fg_color, e);
+                }
+                ui.separator();
+                ui.radio_value(&mut ui_font, None, "egui's font for the UI");
+                for name in self.fonts.names() {
+                    ui.horizontal(|ui| {
+                        ui.radio_value(&mut ui_font, Some(name.to_owned()), name);
+                        if ui.small_button("Remove").clicked() {
+                            remove = Some(name.to_owned());
+                        }
+                    });
+                    let family = egui::FontFamily::Name(name.into());
+                    // Only drawn with once egui has built the atlas with it.
+                    if families.contains(&family) {
+                        ui.label(egui::RichText::new(SAMPLE).family(family).size(18.0));
+                    }
+                }
+                if self.fonts.names().next().is_none() {
+                    ui.weak("Load a TTF or OTF file to add it as a family");
+                }
+            });
+        if ui_font.as_deref() != self.fonts.ui_font() {
+            self.fonts.set_ui_font(ui_font.as_deref());
+        }
+        if let Some(name) = remove {
+            self.fonts.remove(&name);
+        }
+        self.open = open;
+    }
+}
@@ -0,0 +1,80 @@
+use std::collections::VecDeque;
+
+/// How many frame times the averages and the sparkline cover.
+const HISTORY: usize = 240;
+
+/// A corner overlay with the frame rate, the average and 99th percentile frame time,
+/// and a sparkline of the frames before.
+#[derive(Default)]
+pub struct FpsOverlay {
+    pub open: bool,
+    /// Milliseconds between frames, oldest first.
+    frame_ms: VecDeque<f32>,
+    /// When the last frame started, in seconds.
+    last: Option<f64>,
+}
+
+impl FpsOverlay {
+    /// Adds the frame that started `timestamp` seconds in.
+    pub fn record(&mut self, timestamp: f64) {
+        if let Some(last) = self.last {
+            if self.frame_ms.len() == HISTORY {
+                self.frame_ms.pop_front();
+            }
+            self.frame_ms
+                .push_back(((timestamp - last) * 1000.0) as f32);
+        }
+        self.last = Some(timestamp);
+    }
+
+    pub fn ui(&self, ctx: &egui::Context) {
+        egui::Area::new("fps overlay")
+            .anchor(egui::Align2::LEFT_TOP, [8.0, 32.0])
+            .order(egui::Order::Foreground)
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    let Some(&latest) = self.frame_ms.back() else {
+                        ui.weak("Waiting for frames");
+                        return;
+                    };
+                    let average = self.frame_ms.iter().sum::<f32>() / self.frame_ms.len() as f32;
+                    ui.strong(format!("{:.0} fps", 1000.0 / latest));
+                    ui.monospace(format!("avg {:.2} ms", average));
+                    ui.monospace(format!("99% {:.2} ms", self.percentile(0.99)));
+                    self.sparkline(ui);
+                });
+            });
+    }
+
+    /// The frame time that `fraction` of the history is at or below.
+    fn percentile(&self, fraction: f32) -> f32 {
+        let mut sorted: Vec<f32> = self.frame_ms.iter().copied().collect();
+        sorted.sort_by(f32::total_cmp);
+        let index = ((sorted.len() - 1) as f32 * fraction).round() as usize;
+        sorted[index]
+    }
+
+    fn sparkline(&self, ui: &mut egui::Ui) {
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(160.0, 32.0), egui::Sense::hover());
+        let max = self.frame_ms.iter().copied().fold(1.0, f32::max);
+        let step = rect.width() / (HISTORY - 1) as f32;
+        let points = self
+            .frame_ms
+            .iter()
+            .enumerate()
+            .map(|(i, &ms)| {
+                egui::pos2(
+                    rect.left() + i as f32 * step,
+                    rect.bottom() - ms / max * rect.height(),
+                )
+            })
+            .collect();
+        let painter = ui.painter();
+        painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+        painter.add(egui::Shape::line(
+            points,
+            egui::Stroke::new(1.0, ui.visuals().text_color()),
+        ));
+    }
+}
@@ -0,0 +1,310 @@
+use serde::Serialize;
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+/// What egui produced for one frame, as plain serializable data.
+///
+/// Most of egui's output types don't implement `Serialize`, so this mirrors the parts
+/// that are useful when a frame renders wrong.
+#[derive(Serialize)]
+pub struct FrameDump {
+    frame: u64,
+    screen: ScreenDump,
+    platform_output: PlatformOutputDump,
+    shapes: ShapesDump,
+    summary: FrameSummary,
+    primitives: Vec<PrimitiveDump>,
+    textures: TexturesDump,
+}
+
+#[derive(Serialize)]
+struct ScreenDump {
+    size_in_pixels: [u32; 2],
+    pixels_per_point: f32,
+}
+
+#[derive(Serialize)]
+struct PlatformOutputDump {
+    cursor_icon: String,
+    open_url: Option<String>,
+    copied_text: String,
+    events: Vec<String>,
+    mutable_text_under_cursor: bool,
+    text_cursor_pos: Option<[f32; 2]>,
+}
+
+#[derive(Serialize)]
+struct ShapesDump {
+    count: usize,
+    /// The union of the visual bounds of all shapes, in points.
+    bounding_rect: Option<RectDump>,
+    clip_rects: usize,
+}
+
+#[derive(Clone, Copy, Serialize)]
+struct RectDump {
+    min: [f32; 2],
+    max: [f32; 2],
+}
+
+impl From<egui::Rect> for RectDump {
+    fn from(rect: egui::Rect) -> Self {
+        Self {
+            min: [rect.min.x, rect.min.y],
+            max: [rect.max.x, rect.max.y],
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct PrimitiveDump {
+    clip_rect: RectDump,
+    kind: &'static str,
+    texture_id: Option<String>,
+    vertices: usize,
+    indices: usize,
+    /// Only filled in when asked for, it makes the file huge.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vertex_data: Option<Vec<VertexDump>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    index_data: Option<Vec<u32>>,
+}
+
+#[derive(Serialize)]
+struct VertexDump {
+    pos: [f32; 2],
+    uv: [f32; 2],
+    color: [u8; 4],
+}
+
+#[derive(Serialize)]
+struct TexturesDump {
+    set: Vec<TextureSetDump>,
+    free: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct TextureSetDump {
+    id: String,
+    size: [usize; 2],
+    /// Where the patch goes, `None` when the whole texture is replaced.
+    pos: Option<[usize; 2]>,
+    options: String,
+}
+
+/// The totals shown in the "Frame data" window.
+#[derive(Clone, Copy, Default, Serialize)]
+pub struct FrameSummary {
+    pub meshes: usize,
+    pub draw_calls: usize,
+    pub clip_rects: usize,
+    pub vertices: usize,
+}
+
+impl FrameSummary {
+    pub fn new(paint_jobs: &[egui::ClippedPrimitive]) -> Self {
+        let mut summary = Self {
+            draw_calls: paint_jobs.len(),
+            clip_rects: distinct_rects(paint_jobs.iter().map(|job| job.clip_rect)),
+            ..Self::default()
+        };
+        for job in paint_jobs {
+            if let egui::epaint::Primitive::Mesh(mesh) = &job.primitive {
+                summary.meshes += 1;
+                summary.vertices += mesh.vertices.len();
+            }
+        }
+        summary
+    }
+}
+
+fn distinct_rects(rects: impl Iterator<Item = egui::Rect>) -> usize {
+    rects
+        .map(|rect| [rect.min.x, rect.min.y, rect.max.x, rect.max.y].map(f32::to_bits))
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+impl FrameDump {
+    /// Starts a dump with what has to be looked at before egui's output is consumed.
+    pub fn begin(
+        frame: u64,
+        platform_output: &egui::PlatformOutput,
+        shapes: &[egui::epaint::ClippedShape],
+    ) -> Self {
+        let bounding_rect = shapes
+            .iter()
+            .map(|shape| shape.shape.visual_bounding_rect())
+            .filter(|rect| rect.is_positive())
+            .reduce(|a, b| a.union(b));
+        Self {
+            frame,
+            screen: ScreenDump {
+                size_in_pixels: [0, 0],
+                pixels_per_point: 1.0,
+            },
+            platform_output: PlatformOutputDump {
+                cursor_icon: format!("{:?}", platform_output.cursor_icon),
+                open_url: platform_output.open_url.as_ref().map(|url| url.url.clone()),
+                copied_text: platform_output.copied_text.clone(),
+                events: platform_output
+                    .events
+                    .iter()
+                    .map(|event| format!("{:?}", event))
+                    .collect(),
+                mutable_text_under_cursor: platform_output.mutable_text_under_cursor,
+                text_cursor_pos: platform_output.text_cursor_pos.map(|pos| [pos.x, pos.y]),
+            },
+            shapes: ShapesDump {
+                count: shapes.len(),
+                bounding_rect: bounding_rect.map(RectDump::from),
+                clip_rects: distinct_rects(shapes.iter().map(|shape| shape.clip_rect)),
+            },
+            summary: FrameSummary::default(),
+            primitives: Vec::new(),
+            textures: TexturesDump {
+                set: Vec::new(),
+                free: Vec::new(),
+            },
+        }
+    }
+
+    /// Adds the tessellated frame and what is about to be uploaded for it.
+    pub fn finish(
+        &mut self,
+        paint_jobs: &[egui::ClippedPrimitive],
+        textures_delta: &egui::TexturesDelta,
+        screen: &egui_wgpu::renderer::ScreenDescriptor,
+        include_vertices: bool,
+    ) {
+        self.screen = ScreenDump {
+            size_in_pixels: screen.size_in_pixels,
+            pixels_per_point: screen.pixels_per_point,
+        };
+        self.summary = FrameSummary::new(paint_jobs);
+        self.primitives = paint_jobs
+            .iter()
+            .map(|job| match &job.primitive {
+                egui::epaint::Primitive::Mesh(mesh) => PrimitiveDump {
+                    clip_rect: job.clip_rect.into(),
+                    kind: "mesh",
+                    texture_id: Some(format!("{:?}", mesh.texture_id)),
+                    vertices: mesh.vertices.len(),
+                    indices: mesh.indices.len(),
+                    vertex_data: include_vertices.then(|| {
+                        mesh.vertices
+                            .iter()
+                            .map(|v| VertexDump {
+                                pos: [v.pos.x, v.pos.y],
+                                uv: [v.uv.x, v.uv.y],
+                                color: v.color.to_array(),
+                            })
+                            .collect()
+                    }),
+                    index_data: include_vertices.then(|| mesh.indices.clone()),
+                },
+                egui::epaint::Primitive::Callback(_) => PrimitiveDump {
+                    clip_rect: job.clip_rect.into(),
+                    kind: "callback",
+                    texture_id: None,
+                    vertices: 0,
+                    indices: 0,
+                    vertex_data: None,
+                    index_data: None,
+                },
+            })
+            .collect();
+        self.textures = TexturesDump {
+            set: textures_delta
+                .set
+                .iter()
+                .map(|(id, delta)| TextureSetDump {
+                    id: format!("{:?}", id),
+                    size: delta.image.size(),
+                    pos: delta.pos,
+                    options: format!("{:?}", delta.options),
+                })
+                .collect(),
+            free: textures_delta
+                .free
+                .iter()
+                .map(|id| format!("{:?}", id))
+                .collect(),
+        };
+    }
+
+    /// Writes the dump as pretty-printed JSON next to the working directory.
+    pub fn write(&self) -> std::io::Result<PathBuf> {
+        let path = PathBuf::from(format!("frame_dump_{}.json", self.frame));
+        let file = std::io::BufWriter::new(std::fs::File::create(&path)?);
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(path)
+    }
+}
+
+/// The "Frame data" window, with the totals of the last frame and the dump action.
+#[derive(Default)]
+pub struct FrameDumper {
+    pub open: bool,
+    include_vertices: bool,
+    requested: bool,
+    summary: FrameSummary,
+    status: Option<String>,
+}
+
+impl FrameDumper {
+    /// Dumps the frame being built once it has been tessellated.
+    pub fn request(&mut self) {
+        self.requested = true;
+    }
+
+    /// True once per `request`, for the frame it was made in.
+    pub fn take_request(&mut self) -> bool {
+        std::mem::take(&mut self.requested)
+    }
+
+    pub fn include_vertices(&self) -> bool {
+        self.include_vertices
+    }
+
+    pub fn set_summary(&mut self, summary: FrameSummary) {
+        self.summary = summary;
+    }
+
+    pub fn set_status(&mut self, status: String) {
+        self.status = Some(status);
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        let mut open = self.open;
+        egui::Window::new("Frame data")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("frame_data_grid").show(ui, |ui| {
+                    ui.label("Meshes");
+                    ui.label(self.summary.meshes.to_string());
+                    ui.end_row();
+                    ui.label("Draw calls");
+                    ui.label(self.summary.draw_calls.to_string());
+                    ui.end_row();
+                    ui.label("Clip rects");
+                    ui.label(self.summary.clip_rects.to_string());
+                    ui.end_row();
+                    ui.label("Vertices");
+                    ui.label(self.summary.vertices.to_string());
+                    ui.end_row();
+                });
+                ui.separator();
+                ui.checkbox(&mut self.include_vertices, "Include vertex data");
+                if ui.button("Dump frame data").clicked() {
+                    self.request();
+                }
+                if let Some(status) = &self.status {
+                    ui.weak(status);
+                }
+            });
+        self.open = open;
+    }
+}
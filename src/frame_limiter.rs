@@ -0,0 +1,47 @@
+use instant::Instant;
+
+use std::time::Duration;
+
+/// How long before the deadline sleeping stops and spinning takes over, covering how
+/// late the OS may wake the thread.
+const SPIN_MARGIN: Duration = Duration::from_millis(2);
+
+/// Keeps frames at least `1 / fps` seconds apart, so an uncapped frame rate doesn't
+/// keep a core busy redrawing.
+///
+/// It sleeps for most of the wait and spins for the rest, as sleeping alone overshoots
+/// by up to the timer resolution and spinning alone burns as much as not waiting.
+#[derive(Default)]
+pub struct FrameLimiter {
+    /// When the last frame was let through.
+    last: Option<Instant>,
+}
+
+impl FrameLimiter {
+    /// Blocks until the next frame is due at the rate of `fps`, returning right away
+    /// without a cap.
+    pub fn wait(&mut self, fps: Option<u32>) {
+        // The browser paces the frames itself, and its thread can't sleep.
+        let Some(fps) = fps.filter(|&fps| fps > 0 && !cfg!(target_arch = "wasm32")) else {
+            self.last = None;
+            return;
+        };
+        let now = Instant::now();
+        let Some(deadline) = self.last.map(|last| last + Duration::from_secs(1) / fps) else {
+            self.last = Some(now);
+            return;
+        };
+        if now >= deadline {
+            // Running behind, so the next frame is paced from now rather than catching up.
+            self.last = Some(now);
+            return;
+        }
+        if let Some(sleep) = (deadline - now).checked_sub(SPIN_MARGIN) {
+            std::thread::sleep(sleep);
+        }
+        while Instant::now() < deadline {
+            std::hint::spin_loop();
+        }
+        self.last = Some(deadline);
+    }
+}
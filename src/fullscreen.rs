@@ -0,0 +1,77 @@
+use winit::window::{Fullscreen, Window};
+
+/// How the main window fills its monitor.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FullscreenMode {
+    #[default]
+    Windowed,
+    /// A window without decorations covering the monitor, keeping its video mode.
+    Borderless,
+    /// The monitor's video mode taken over by the window, which can mean faster
+    /// presenting, but switching to and from it blanks the display for a moment.
+    Exclusive,
+}
+
+impl FullscreenMode {
+    pub const ALL: [FullscreenMode; 3] = [
+        FullscreenMode::Windowed,
+        FullscreenMode::Borderless,
+        FullscreenMode::Exclusive,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FullscreenMode::Windowed => "Windowed",
+            FullscreenMode::Borderless => "Borderless",
+            FullscreenMode::Exclusive => "Exclusive",
+        }
+    }
+
+    /// The mode `window` is in now, which the OS may have changed on its own.
+    pub fn of(window: &Window) -> Self {
+        match window.fullscreen() {
+            None => FullscreenMode::Windowed,
+            Some(Fullscreen::Borderless(_)) => FullscreenMode::Borderless,
+            Some(Fullscreen::Exclusive(_)) => FullscreenMode::Exclusive,
+        }
+    }
+
+    /// `mode`, or back to windowed if it's `mode` already.
+    pub fn toggled(self, mode: FullscreenMode) -> Self {
+        if self == mode {
+            FullscreenMode::Windowed
+        } else {
+            mode
+        }
+    }
+
+    /// Puts `window` in this mode on the monitor it's on. The surface follows with the
+    /// `Resized` that comes after.
+    ///
+    /// Exclusive fullscreen keeps the monitor's resolution at its fastest refresh rate,
+    /// and is borderless where there's no video mode to take, as in the browser.
+    pub fn apply(self, window: &Window) {
+        let monitor = window.current_monitor();
+        let fullscreen = match self {
+            FullscreenMode::Windowed => None,
+            FullscreenMode::Borderless => Some(Fullscreen::Borderless(monitor)),
+            FullscreenMode::Exclusive => {
+                let video_mode = monitor.as_ref().and_then(|monitor| {
+                    let size = monitor.size();
+                    monitor
+                        .video_modes()
+                        .filter(|mode| mode.size() == size)
+                        .max_by_key(|mode| (mode.bit_depth(), mode.refresh_rate_millihertz()))
+                });
+                match video_mode {
+                    Some(video_mode) => Some(Fullscreen::Exclusive(video_mode)),
+                    None => {
+                        tracing::info!("No video mode to take, going borderless instead");
+                        Some(Fullscreen::Borderless(monitor))
+                    }
+                }
+            }
+        };
+        window.set_fullscreen(fullscreen);
+    }
+}
@@ -0,0 +1,322 @@
+use crate::events::UserEvent;
+
+use egui::{Color32, Key, Modifiers, Stroke, Vec2};
+use winit::event_loop::EventLoopProxy;
+
+use std::collections::BTreeSet;
+
+/// Whether gamepads are read at all, which takes the `gamepad` feature and a desktop.
+pub const AVAILABLE: bool = cfg!(all(
+    feature = "gamepad",
+    not(any(target_arch = "wasm32", target_os = "android"))
+));
+
+/// The buttons of a gamepad laid out like an Xbox controller, South being A.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftTrigger,
+    LeftTrigger2,
+    RightTrigger,
+    RightTrigger2,
+    Select,
+    Start,
+    Mode,
+    LeftThumb,
+    RightThumb,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+}
+
+/// What a gamepad did, sent to the event loop as `UserEvent::Gamepad`. The ids stay the
+/// same while the example runs, also for a gamepad that's plugged in again.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GamepadEvent {
+    Connected {
+        id: usize,
+        name: String,
+    },
+    Disconnected(usize),
+    Button {
+        id: usize,
+        button: GamepadButton,
+        pressed: bool,
+    },
+    /// A stick moved, `value` between -1.0 and 1.0 with up positive.
+    Axis {
+        id: usize,
+        axis: GamepadAxis,
+        value: f32,
+    },
+}
+
+impl GamepadEvent {
+    /// The key press moving egui's focus the D-pad stands for, South clicking what has
+    /// the focus and East being Escape.
+    pub(crate) fn to_egui(&self) -> Option<egui::Event> {
+        let GamepadEvent::Button {
+            button, pressed, ..
+        } = *self
+        else {
+            return None;
+        };
+        let (key, modifiers) = match button {
+            GamepadButton::DPadDown | GamepadButton::DPadRight => (Key::Tab, Modifiers::NONE),
+            GamepadButton::DPadUp | GamepadButton::DPadLeft => (Key::Tab, Modifiers::SHIFT),
+            GamepadButton::South => (Key::Enter, Modifiers::NONE),
+            GamepadButton::East => (Key::Escape, Modifiers::NONE),
+            _ => return None,
+        };
+        Some(egui::Event::Key {
+            key,
+            pressed,
+            repeat: false,
+            modifiers,
+        })
+    }
+}
+
+/// Reads the gamepads on a thread of their own, sending what they do as
+/// `UserEvent::Gamepad`.
+#[cfg(all(
+    feature = "gamepad",
+    not(any(target_arch = "wasm32", target_os = "android"))
+))]
+pub fn spawn(proxy: EventLoopProxy<UserEvent>) {
+    let spawned = std::thread::Builder::new()
+        .name("gamepad".to_owned())
+        .spawn(move || {
+            // Gilrs can't be sent between threads, so it's made on this one.
+            let mut gilrs = match gilrs::Gilrs::new() {
+                Ok(gilrs) => gilrs,
+                Err(e) => {
+                    tracing::warn!("Gamepads can't be read: {}", e);
+                    return;
+                }
+            };
+            // Those plugged in already aren't reported as connecting.
+            for (id, gamepad) in gilrs.gamepads() {
+                let event = GamepadEvent::Connected {
+                    id: id.into(),
+                    name: gamepad.name().to_owned(),
+                };
+                if proxy.send_event(UserEvent::Gamepad(event)).is_err() {
+                    return;
+                }
+            }
+            while let Some(event) = gilrs.next_event_blocking(None) {
+                let Some(event) = translate(&gilrs, event) else {
+                    continue;
+                };
+                // The event loop is gone.
+                if proxy.send_event(UserEvent::Gamepad(event)).is_err() {
+                    return;
+                }
+            }
+        });
+    if let Err(e) = spawned {
+        tracing::warn!("Failed to start reading gamepads: {}", e);
+    }
+}
+
+#[cfg(not(all(
+    feature = "gamepad",
+    not(any(target_arch = "wasm32", target_os = "android"))
+)))]
+pub fn spawn(_proxy: EventLoopProxy<UserEvent>) {}
+
+#[cfg(all(
+    feature = "gamepad",
+    not(any(target_arch = "wasm32", target_os = "android"))
+))]
+fn translate(gilrs: &gilrs::Gilrs, event: gilrs::Event) -> Option<GamepadEvent> {
+    use gilrs::EventType;
+
+    let id = event.id.into();
+    Some(match event.event {
+        EventType::Connected => GamepadEvent::Connected {
+            id,
+            name: gilrs.gamepad(event.id).name().to_owned(),
+        },
+        EventType::Disconnected => GamepadEvent::Disconnected(id),
+        EventType::ButtonPressed(button, _) => GamepadEvent::Button {
+            id,
+            button: button_of(button)?,
+            pressed: true,
+        },
+        EventType::ButtonReleased(button, _) => GamepadEvent::Button {
+            id,
+            button: button_of(button)?,
+            pressed: false,
+        },
+        EventType::AxisChanged(axis, value, _) => GamepadEvent::Axis {
+            id,
+            axis: match axis {
+                gilrs::Axis::LeftStickX => GamepadAxis::LeftStickX,
+                gilrs::Axis::LeftStickY => GamepadAxis::LeftStickY,
+                gilrs::Axis::RightStickX => GamepadAxis::RightStickX,
+                gilrs::Axis::RightStickY => GamepadAxis::RightStickY,
+                _ => return None,
+            },
+            value,
+        },
+        _ => return None,
+    })
+}
+
+#[cfg(all(
+    feature = "gamepad",
+    not(any(target_arch = "wasm32", target_os = "android"))
+))]
+fn button_of(button: gilrs::Button) -> Option<GamepadButton> {
+    use gilrs::Button;
+
+    Some(match button {
+        Button::South => GamepadButton::South,
+        Button::East => GamepadButton::East,
+        Button::North => GamepadButton::North,
+        Button::West => GamepadButton::West,
+        Button::LeftTrigger => GamepadButton::LeftTrigger,
+        Button::LeftTrigger2 => GamepadButton::LeftTrigger2,
+        Button::RightTrigger => GamepadButton::RightTrigger,
+        Button::RightTrigger2 => GamepadButton::RightTrigger2,
+        Button::Select => GamepadButton::Select,
+        Button::Start => GamepadButton::Start,
+        Button::Mode => GamepadButton::Mode,
+        Button::LeftThumb => GamepadButton::LeftThumb,
+        Button::RightThumb => GamepadButton::RightThumb,
+        Button::DPadUp => GamepadButton::DPadUp,
+        Button::DPadDown => GamepadButton::DPadDown,
+        Button::DPadLeft => GamepadButton::DPadLeft,
+        Button::DPadRight => GamepadButton::DPadRight,
+        Button::C | Button::Z | Button::Unknown => return None,
+    })
+}
+
+/// A connected gamepad as of its last event.
+#[derive(Clone, Debug)]
+pub struct Gamepad {
+    pub id: usize,
+    pub name: String,
+    pub pressed: BTreeSet<GamepadButton>,
+    pub left_stick: Vec2,
+    pub right_stick: Vec2,
+}
+
+/// The connected gamepads, for the app to read its input from, and a window showing
+/// them.
+#[derive(Default)]
+pub struct Gamepads {
+    pub open: bool,
+    pads: Vec<Gamepad>,
+}
+
+impl Gamepads {
+    pub fn pads(&self) -> &[Gamepad] {
+        &self.pads
+    }
+
+    pub fn on_event(&mut self, event: &GamepadEvent) {
+        match event {
+            GamepadEvent::Connected { id, name } => {
+                self.pads.retain(|pad| pad.id != *id);
+                self.pads.push(Gamepad {
+                    id: *id,
+                    name: name.clone(),
+                    pressed: BTreeSet::new(),
+                    left_stick: Vec2::ZERO,
+                    right_stick: Vec2::ZERO,
+                });
+            }
+            GamepadEvent::Disconnected(id) => self.pads.retain(|pad| pad.id != *id),
+            GamepadEvent::Button {
+                id,
+                button,
+                pressed,
+            } => {
+                if let Some(pad) = self.pads.iter_mut().find(|pad| pad.id == *id) {
+                    if *pressed {
+                        pad.pressed.insert(*button);
+                    } else {
+                        pad.pressed.remove(button);
+                    }
+                }
+            }
+            GamepadEvent::Axis { id, axis, value } => {
+                if let Some(pad) = self.pads.iter_mut().find(|pad| pad.id == *id) {
+                    match axis {
+                        GamepadAxis::LeftStickX => pad.left_stick.x = *value,
+                        GamepadAxis::LeftStickY => pad.left_stick.y = *value,
+                        GamepadAxis::RightStickX => pad.right_stick.x = *value,
+                        GamepadAxis::RightStickY => pad.right_stick.y = *value,
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        egui::Window::new("Gamepads")
+            .open(&mut open)
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                if !AVAILABLE {
+                    ui.weak("Built without the gamepad feature");
+                    return;
+                }
+                if self.pads.is_empty() {
+                    ui.weak("No gamepads are connected");
+                }
+                for pad in &self.pads {
+                    ui.separator();
+                    ui.strong(&pad.name);
+                    ui.horizontal(|ui| {
+                        stick(ui, pad.left_stick);
+                        stick(ui, pad.right_stick);
+                        ui.vertical(|ui| {
+                            let pressed: Vec<_> =
+                                pad.pressed.iter().map(|b| format!("{:?}", b)).collect();
+                            if pressed.is_empty() {
+                                ui.weak("No buttons pressed");
+                            } else {
+                                ui.label(pressed.join(", "));
+                            }
+                        });
+                    });
+                }
+                ui.separator();
+                ui.weak("The D-pad moves the focus, South clicks and East is Escape");
+            });
+        self.open = open;
+    }
+}
+
+/// Where a stick is, as a dot in a circle.
+fn stick(ui: &mut egui::Ui, position: Vec2) {
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(48.0, 48.0), egui::Sense::hover());
+    let radius = rect.width() / 2.0 - 4.0;
+    let painter = ui.painter();
+    let stroke = ui.visuals().widgets.noninteractive.fg_stroke;
+    painter.circle_stroke(rect.center(), radius, stroke);
+    // Up is positive on the gamepad, and down on the screen.
+    let dot = rect.center() + egui::vec2(position.x, -position.y) * radius;
+    painter.circle(dot, 4.0, Color32::YELLOW, Stroke::NONE);
+}
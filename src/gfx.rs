@@ -0,0 +1,575 @@
+use crate::capabilities;
+use crate::color_space::{self, ColorSpace};
+use crate::error::AppError;
+use crate::gpu_cache::GpuCache;
+use crate::gpu_errors::GpuErrors;
+use crate::gpu_memory::{Allocation, GpuMemory};
+use crate::runtime;
+
+use wgpu::InstanceDescriptor;
+use winit::window::Window;
+
+/// The format of the depth buffer `Application::render` draws with.
+pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+/// The size of the chunks `RenderFrame::write_buffer` stages in. A frame writes a few
+/// hundred bytes, a write bigger than this gets a chunk of its own.
+pub const BELT_CHUNK_SIZE: wgpu::BufferAddress = 16 * 1024;
+
+/// The backends a `--backend` name stands for, `auto` being wgpu's primary ones.
+pub fn parse_backends(name: &str) -> Option<wgpu::Backends> {
+    Some(match name.to_ascii_lowercase().as_str() {
+        "vulkan" => wgpu::Backends::VULKAN,
+        "dx12" => wgpu::Backends::DX12,
+        "metal" => wgpu::Backends::METAL,
+        "gl" => wgpu::Backends::GL,
+        "auto" => wgpu::Backends::PRIMARY,
+        _ => return None,
+    })
+}
+
+/// The GPU side of the window: the device and the surface it presents to. All surface
+/// configuration goes through here, so it's reconfigured the same way everywhere.
+pub struct GraphicsContext {
+    pub instance: wgpu::Instance,
+    pub adapter: wgpu::Adapter,
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+    /// `None` while suspended, and while `recreate` makes a new one.
+    surface: Option<wgpu::Surface>,
+    surface_config: wgpu::SurfaceConfiguration,
+    /// The color space the surface actually ended up in.
+    pub color_space: ColorSpace,
+    /// Every error of `device` no error scope caught.
+    pub errors: GpuErrors,
+    /// The targets made by the scaffold and egui's textures, and what the app tracks.
+    pub memory: GpuMemory,
+    pub cache: GpuCache,
+    backends: wgpu::Backends,
+    /// Whether the surface was asked to blend with what's behind the window.
+    transparent: bool,
+    /// Whether the device only gets WebGL 2's limits.
+    compat: bool,
+    sample_count: u32,
+    /// Set when the surface kept getting lost, or ran out of memory.
+    lost: bool,
+    /// Set when the surface was reconfigured after getting lost, so that happening
+    /// again right away means the device is gone too.
+    reconfigured: bool,
+}
+
+/// What `GraphicsContext::new` and `recreate` both make.
+struct Parts {
+    instance: wgpu::Instance,
+    surface: wgpu::Surface,
+    adapter: wgpu::Adapter,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    surface_config: wgpu::SurfaceConfiguration,
+    color_space: ColorSpace,
+}
+
+impl Parts {
+    #[allow(clippy::too_many_arguments)]
+    async fn new(
+        window: &Window,
+        backends: wgpu::Backends,
+        adapter: Option<&wgpu::AdapterInfo>,
+        color_space: ColorSpace,
+        present_mode: wgpu::PresentMode,
+        transparent: bool,
+        compat: bool,
+        errors: &GpuErrors,
+    ) -> Result<Self, AppError> {
+        let instance_descriptor = InstanceDescriptor {
+            backends,
+            ..InstanceDescriptor::default()
+        };
+        let instance = wgpu::Instance::new(instance_descriptor);
+        let surface = unsafe { instance.create_surface(window) }?;
+
+        let wanted = adapter.and_then(|wanted| {
+            let found = instance.enumerate_adapters(backends).find(|adapter| {
+                adapter.get_info() == *wanted && adapter.is_surface_supported(&surface)
+            });
+            if found.is_none() {
+                tracing::warn!("{} is gone, letting wgpu pick an adapter", wanted.name);
+            }
+            found
+        });
+        let adapter = match wanted {
+            Some(adapter) => adapter,
+            None => request_adapter(&instance, Some(&surface))
+                .await
+                .ok_or(AppError::NoAdapter(backends))?,
+        };
+
+        let (device, queue) = request_device(&adapter, compat, errors).await?;
+
+        let capabilities = surface.get_capabilities(&adapter);
+        let (color_space, surface_format) = ColorSpace::select(color_space, &capabilities.formats)
+            .ok_or(AppError::NoSurfaceFormat)?;
+
+        let size = window.inner_size();
+        // Copying out of the surface is only for screenshots, which need it supported.
+        let usage = wgpu::TextureUsages::RENDER_ATTACHMENT
+            | (capabilities.usages & wgpu::TextureUsages::COPY_SRC);
+        let surface_config = wgpu::SurfaceConfiguration {
+            usage,
+            format: surface_format,
+            width: size.width,
+            height: size.height,
+            present_mode: supported_present_mode(present_mode, &capabilities.present_modes),
+            alpha_mode: alpha_mode(transparent, &capabilities.alpha_modes),
+            view_formats: vec![],
+        };
+        surface.configure(&device, &surface_config);
+
+        Ok(Self {
+            instance,
+            surface,
+            adapter,
+            device,
+            queue,
+            surface_config,
+            color_space,
+        })
+    }
+}
+
+/// When `transparent`, the first mode the surface supports that blends with what's
+/// behind the window, premultiplied first as egui's colors are. Otherwise, or when it
+/// supports none, its preferred mode.
+fn alpha_mode(
+    transparent: bool,
+    supported: &[wgpu::CompositeAlphaMode],
+) -> wgpu::CompositeAlphaMode {
+    use wgpu::CompositeAlphaMode::{Inherit, PostMultiplied, PreMultiplied};
+    if !transparent {
+        return supported[0];
+    }
+    match [PreMultiplied, PostMultiplied, Inherit]
+        .into_iter()
+        .find(|mode| supported.contains(mode))
+    {
+        Some(mode) => mode,
+        None => {
+            tracing::warn!(
+                "The surface can't be transparent, it only supports {:?}",
+                supported
+            );
+            supported[0]
+        }
+    }
+}
+
+/// The fastest adapter there is, or a software one when no GPU will do.
+pub(crate) async fn request_adapter(
+    instance: &wgpu::Instance,
+    surface: Option<&wgpu::Surface>,
+) -> Option<wgpu::Adapter> {
+    let options = |force_fallback_adapter| wgpu::RequestAdapterOptions {
+        power_preference: wgpu::PowerPreference::HighPerformance,
+        compatible_surface: surface,
+        force_fallback_adapter,
+    };
+    if let Some(adapter) = instance.request_adapter(&options(false)).await {
+        return Some(adapter);
+    }
+    let adapter = instance.request_adapter(&options(true)).await?;
+    tracing::warn!("No GPU was found, using {}", adapter.get_info().name);
+    Some(adapter)
+}
+
+/// The device the scaffold draws with, its uncaught errors going to `errors`. Adapters
+/// that can't meet the default limits get the downlevel ones. With `compat` it gets
+/// WebGL 2's, like in the browser, whatever the adapter can do.
+pub(crate) async fn request_device(
+    adapter: &wgpu::Adapter,
+    compat: bool,
+    errors: &GpuErrors,
+) -> Result<(wgpu::Device, wgpu::Queue), wgpu::RequestDeviceError> {
+    let descriptor = |limits| wgpu::DeviceDescriptor {
+        features: capabilities::negotiate(adapter),
+        limits,
+        label: None,
+    };
+    // WebGL 2 can't do everything the default limits ask for.
+    let webgl2 = compat || cfg!(target_arch = "wasm32");
+    if compat {
+        tracing::info!("Compat mode: asking for WebGL 2's limits");
+    }
+    let limits = if webgl2 {
+        wgpu::Limits::downlevel_webgl2_defaults().using_resolution(adapter.limits())
+    } else {
+        wgpu::Limits::default()
+    };
+    let (device, queue) = match adapter.request_device(&descriptor(limits), None).await {
+        Ok(device) => device,
+        Err(e) if !webgl2 => {
+            tracing::warn!("Falling back to downlevel limits: {}", e);
+            let limits = wgpu::Limits::downlevel_defaults().using_resolution(adapter.limits());
+            adapter.request_device(&descriptor(limits), None).await?
+        }
+        Err(e) => return Err(e),
+    };
+    errors.attach(&device);
+    Ok((device, queue))
+}
+
+impl GraphicsContext {
+    /// Sets up a surface for `window`, in `color_space` if the display can do it, with
+    /// up to `msaa` samples per pixel, and blending with the desktop if `transparent`.
+    pub async fn new(
+        window: &Window,
+        backends: wgpu::Backends,
+        color_space: ColorSpace,
+        present_mode: wgpu::PresentMode,
+        msaa: u32,
+        transparent: bool,
+        compat: bool,
+    ) -> Result<Self, AppError> {
+        let errors = GpuErrors::default();
+        let parts = Parts::new(
+            window,
+            backends,
+            None,
+            color_space,
+            present_mode,
+            transparent,
+            compat,
+            &errors,
+        )
+        .await?;
+        let mut gfx = Self {
+            instance: parts.instance,
+            surface: Some(parts.surface),
+            adapter: parts.adapter,
+            device: parts.device,
+            queue: parts.queue,
+            surface_config: parts.surface_config,
+            color_space: parts.color_space,
+            errors,
+            memory: GpuMemory::default(),
+            cache: GpuCache::default(),
+            backends,
+            transparent,
+            compat,
+            sample_count: 1,
+            lost: false,
+            reconfigured: false,
+        };
+        gfx.set_sample_count(msaa);
+        Ok(gfx)
+    }
+
+    /// Makes everything again from a new instance, on `adapter` if it's still there,
+    /// keeping the present mode and sample count. The old surface is dropped first, as
+    /// a window can only have one.
+    ///
+    /// This blocks on getting the device, which works in the browser too as WebGL goes
+    /// through wgpu-core, whose futures are ready right away. On an error nothing can be
+    /// drawn anymore.
+    pub fn recreate(
+        &mut self,
+        window: &Window,
+        adapter: Option<&wgpu::AdapterInfo>,
+    ) -> Result<(), AppError> {
+        self.surface = None;
+        let parts = runtime::block_on(Parts::new(
+            window,
+            self.backends,
+            adapter,
+            self.color_space,
+            self.surface_config.present_mode,
+            self.transparent,
+            self.compat,
+            &self.errors,
+        ))?;
+        self.instance = parts.instance;
+        self.surface = Some(parts.surface);
+        self.adapter = parts.adapter;
+        self.device = parts.device;
+        self.queue = parts.queue;
+        self.cache.clear();
+        self.surface_config = parts.surface_config;
+        self.color_space = parts.color_space;
+        self.lost = false;
+        self.reconfigured = false;
+        self.set_sample_count(self.sample_count);
+        Ok(())
+    }
+
+    /// The adapters of the enabled backends that can present to the window.
+    pub fn adapters(&self) -> Vec<wgpu::AdapterInfo> {
+        self.instance
+            .enumerate_adapters(self.backends)
+            .filter(|adapter| adapter.is_surface_supported(self.surface()))
+            .map(|adapter| adapter.get_info())
+            .collect()
+    }
+
+    /// Whether the device is gone and has to be `recreate`d.
+    pub fn is_lost(&self) -> bool {
+        self.lost || self.errors.device_lost()
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.surface_config.format
+    }
+
+    pub fn alpha_mode(&self) -> wgpu::CompositeAlphaMode {
+        self.surface_config.alpha_mode
+    }
+
+    /// The format egui, and so its paint callbacks, draw in.
+    pub fn ui_format(&self) -> wgpu::TextureFormat {
+        ui_format(self.color_space, self.format())
+    }
+
+    /// Samples per pixel of the scene drawn beneath the UI, before it's resolved.
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    /// Switches to `requested` samples per pixel, or the most below it the adapter
+    /// supports for the surface's format.
+    pub fn set_sample_count(&mut self, requested: u32) {
+        let features = self.adapter.get_texture_format_features(self.format());
+        let supported = [requested, 8, 4, 2, 1]
+            .into_iter()
+            .filter(|&count| count <= requested)
+            .find(|&count| features.flags.sample_count_supported(count))
+            .unwrap_or(1);
+        if supported != requested {
+            tracing::warn!("{}x MSAA is not supported, using {}x", requested, supported);
+        }
+        self.sample_count = supported;
+    }
+
+    pub fn size(&self) -> [u32; 2] {
+        [self.surface_config.width, self.surface_config.height]
+    }
+
+    pub fn present_mode(&self) -> wgpu::PresentMode {
+        self.surface_config.present_mode
+    }
+
+    /// The present modes the surface supports on this adapter.
+    pub fn present_modes(&self) -> Vec<wgpu::PresentMode> {
+        self.surface().get_capabilities(&self.adapter).present_modes
+    }
+
+    /// Reconfigures the surface with `present_mode`, or with `Fifo` when it isn't one of
+    /// `present_modes`.
+    pub fn set_present_mode(&mut self, present_mode: wgpu::PresentMode) {
+        let present_mode = supported_present_mode(present_mode, &self.present_modes());
+        if self.surface_config.present_mode != present_mode {
+            self.surface_config.present_mode = present_mode;
+            self.configure();
+        }
+    }
+
+    /// Returns false for the empty size winit uses to signal a minimized window on
+    /// Windows, which is ignored instead of making the surface panic, and while
+    /// suspended, when there's nothing to draw to either.
+    /// See: https://github.com/rust-windowing/winit/issues/208
+    pub fn resize(&mut self, width: u32, height: u32) -> bool {
+        if width == 0 || height == 0 {
+            return false;
+        }
+        self.surface_config.width = width;
+        self.surface_config.height = height;
+        self.configure();
+        !self.is_suspended()
+    }
+
+    /// The next texture to draw to, or `None` if this frame has to be skipped.
+    pub fn acquire_frame(&mut self) -> Option<SurfaceFrame> {
+        let texture = match self.surface().get_current_texture() {
+            Ok(texture) => {
+                self.reconfigured = false;
+                texture
+            }
+            Err(wgpu::SurfaceError::Outdated) => {
+                // This error occurs when the app is minimized on Windows.
+                // Silently return here to prevent spamming the console with:
+                // "The underlying surface has changed, and therefore the swap chain must be updated"
+                return None;
+            }
+            Err(wgpu::SurfaceError::Lost) if !self.reconfigured => {
+                self.configure();
+                self.reconfigured = true;
+                return None;
+            }
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::OutOfMemory) => {
+                self.lost = true;
+                return None;
+            }
+            Err(wgpu::SurfaceError::Timeout) => {
+                tracing::warn!("Timed out waiting for the next frame, skipping it");
+                return None;
+            }
+        };
+        let view = texture
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        Some(SurfaceFrame { texture, view })
+    }
+
+    /// Whether frames can be copied out of, for `Application::screenshot_requested`.
+    pub fn can_capture(&self) -> bool {
+        self.surface_config
+            .usage
+            .contains(wgpu::TextureUsages::COPY_SRC)
+    }
+
+    pub fn present(&self, frame: SurfaceFrame) {
+        frame.texture.present();
+    }
+
+    /// Drops the surface while the window can't be drawn to, until `resume`. Nothing
+    /// may be drawn in between, resizes are only kept track of.
+    pub fn suspend(&mut self) {
+        self.surface = None;
+    }
+
+    pub fn is_suspended(&self) -> bool {
+        self.surface.is_none()
+    }
+
+    /// Makes the surface again after `suspend`, with the same device presenting to it.
+    pub fn resume(&mut self, window: &Window) -> Result<(), AppError> {
+        self.surface = Some(unsafe { self.instance.create_surface(window) }?);
+        let size = window.inner_size();
+        if size.width > 0 && size.height > 0 {
+            self.surface_config.width = size.width;
+            self.surface_config.height = size.height;
+        }
+        self.configure();
+        Ok(())
+    }
+
+    fn configure(&self) {
+        if let Some(surface) = &self.surface {
+            surface.configure(&self.device, &self.surface_config);
+        }
+    }
+
+    fn surface(&self) -> &wgpu::Surface {
+        self.surface.as_ref().unwrap()
+    }
+}
+
+/// A surface texture being drawn, until it's handed to `GraphicsContext::present`.
+pub struct SurfaceFrame {
+    texture: wgpu::SurfaceTexture,
+    pub view: wgpu::TextureView,
+}
+
+impl SurfaceFrame {
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture.texture
+    }
+}
+
+/// `requested`, or `Fifo`, which every surface supports, when it isn't in `supported`.
+/// The automatic modes always fall back to something supported by themselves.
+fn supported_present_mode(
+    requested: wgpu::PresentMode,
+    supported: &[wgpu::PresentMode],
+) -> wgpu::PresentMode {
+    let automatic = matches!(
+        requested,
+        wgpu::PresentMode::AutoVsync | wgpu::PresentMode::AutoNoVsync
+    );
+    if automatic || supported.contains(&requested) {
+        requested
+    } else {
+        tracing::warn!("{:?} is not supported, presenting with Fifo", requested);
+        wgpu::PresentMode::Fifo
+    }
+}
+
+/// On a wide-gamut surface egui draws offscreen, to be blended in by the compositor.
+fn ui_format(color_space: ColorSpace, surface_format: wgpu::TextureFormat) -> wgpu::TextureFormat {
+    match color_space {
+        ColorSpace::Srgb => surface_format,
+        _ => color_space::UI_FORMAT,
+    }
+}
+
+/// A texture drawn to alongside the frame, like a multisampled color target or a depth
+/// buffer. It's reallocated whenever the frame changes size.
+pub struct RenderTarget {
+    label: &'static str,
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+    usage: wgpu::TextureUsages,
+    texture: Option<(wgpu::Texture, wgpu::TextureView)>,
+    memory: Option<(GpuMemory, Option<Allocation>)>,
+}
+
+impl RenderTarget {
+    pub fn new(label: &'static str, format: wgpu::TextureFormat, sample_count: u32) -> Self {
+        Self {
+            label,
+            format,
+            sample_count,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            texture: None,
+            memory: None,
+        }
+    }
+
+    /// Counts the target in `memory` for as long as it's allocated.
+    pub fn tracked(mut self, memory: &GpuMemory) -> Self {
+        self.memory = Some((memory.clone(), None));
+        self
+    }
+
+    /// Lets shaders read the target as well.
+    pub fn sampled(mut self) -> Self {
+        self.usage |= wgpu::TextureUsages::TEXTURE_BINDING;
+        self
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    /// The view of the target, reallocated first if it isn't `size` large.
+    pub fn view(&mut self, device: &wgpu::Device, size: [u32; 2]) -> &wgpu::TextureView {
+        let stale = self
+            .texture
+            .as_ref()
+            .is_none_or(|(texture, _)| [texture.width(), texture.height()] != size);
+        if stale {
+            let desc = wgpu::TextureDescriptor {
+                label: Some(self.label),
+                size: wgpu::Extent3d {
+                    width: size[0],
+                    height: size[1],
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: self.sample_count,
+                dimension: wgpu::TextureDimension::D2,
+                format: self.format,
+                usage: self.usage,
+                view_formats: &[],
+            };
+            let texture = device.create_texture(&desc);
+            if let Some((memory, allocation)) = &mut self.memory {
+                *allocation = Some(memory.track_texture(&desc));
+            }
+            let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+            self.texture = Some((texture, view));
+        }
+        &self.texture.as_ref().unwrap().1
+    }
+}
@@ -0,0 +1,296 @@
+use crate::debug_draw::project;
+use crate::viewport::SceneObject;
+
+use egui::{Color32, Key, Modifiers, Pos2, Rect, Stroke};
+use glam::{Mat4, Quat, Vec3};
+
+/// How close, in points, the pointer has to be to grab a handle.
+const GRAB_DISTANCE: f32 = 6.0;
+/// The length of the handles as a fraction of the distance to the camera, so they keep
+/// their size on screen.
+const HANDLE_SCALE: f32 = 0.2;
+const RING_STEPS: usize = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GizmoMode {
+    Translate,
+    Rotate,
+    Scale,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+impl Axis {
+    const ALL: [Axis; 3] = [Axis::X, Axis::Y, Axis::Z];
+
+    fn dir(self) -> Vec3 {
+        match self {
+            Axis::X => Vec3::X,
+            Axis::Y => Vec3::Y,
+            Axis::Z => Vec3::Z,
+        }
+    }
+
+    fn color(self) -> Color32 {
+        match self {
+            Axis::X => Color32::from_rgb(230, 60, 60),
+            Axis::Y => Color32::from_rgb(60, 200, 60),
+            Axis::Z => Color32::from_rgb(70, 110, 240),
+        }
+    }
+}
+
+/// The object's transform and the pointer when the drag started.
+struct Drag {
+    axis: Axis,
+    start_pointer: Pos2,
+    translation: Vec3,
+    rotation: Quat,
+    scale: Vec3,
+}
+
+/// Translate, rotate and scale handles drawn over the selected object.
+///
+/// `G`, `R` and `S` pick the mode, `1`, `2` and `3` constrain it to the X, Y or Z axis,
+/// after which dragging anywhere in the viewport moves along that axis. `0` lifts the
+/// constraint.
+pub struct Gizmo {
+    pub mode: GizmoMode,
+    constraint: Option<Axis>,
+    drag: Option<Drag>,
+}
+
+impl Default for Gizmo {
+    fn default() -> Self {
+        Self {
+            mode: GizmoMode::Translate,
+            constraint: None,
+            drag: None,
+        }
+    }
+}
+
+/// Where the gizmo is on screen this frame.
+struct Frame {
+    view_proj: Mat4,
+    camera: Vec3,
+    rect: Rect,
+    origin: Vec3,
+    screen_origin: Pos2,
+    length: f32,
+}
+
+impl Frame {
+    fn project(&self, point: Vec3) -> Option<Pos2> {
+        project(self.view_proj, self.rect, point)
+    }
+
+    /// The screen points of the handle of `axis` in `mode`.
+    fn handle(&self, mode: GizmoMode, axis: Axis) -> Vec<Pos2> {
+        match mode {
+            GizmoMode::Translate | GizmoMode::Scale => {
+                let end = self.origin + axis.dir() * self.length;
+                self.project(end)
+                    .map(|end| vec![self.screen_origin, end])
+                    .unwrap_or_default()
+            }
+            GizmoMode::Rotate => {
+                // Two axes perpendicular to this one span the ring's plane.
+                let (u, v) = axis.dir().any_orthonormal_pair();
+                (0..=RING_STEPS)
+                    .filter_map(|i| {
+                        let angle = i as f32 / RING_STEPS as f32 * std::f32::consts::TAU;
+                        let offset = (u * angle.cos() + v * angle.sin()) * self.length * 0.8;
+                        self.project(self.origin + offset)
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+impl Gizmo {
+    /// Handles the mode and axis keys, while the viewport is hovered.
+    pub fn keys(&mut self, ctx: &egui::Context) {
+        ctx.input_mut(|i| {
+            for (key, mode) in [
+                (Key::G, GizmoMode::Translate),
+                (Key::R, GizmoMode::Rotate),
+                (Key::S, GizmoMode::Scale),
+            ] {
+                if i.consume_key(Modifiers::NONE, key) {
+                    self.mode = mode;
+                }
+            }
+            for (key, axis) in [
+                (Key::Num0, None),
+                (Key::Num1, Some(Axis::X)),
+                (Key::Num2, Some(Axis::Y)),
+                (Key::Num3, Some(Axis::Z)),
+            ] {
+                if i.consume_key(Modifiers::NONE, key) {
+                    self.constraint = axis;
+                }
+            }
+        });
+    }
+
+    /// Draws the handles over `object` and applies drags to its transform. Returns
+    /// whether the gizmo has the pointer, so the viewport doesn't also use the drag.
+    pub fn show(
+        &mut self,
+        ui: &egui::Ui,
+        response: &egui::Response,
+        view_proj: Mat4,
+        camera: Vec3,
+        object: &mut SceneObject,
+    ) -> bool {
+        let origin = object.translation;
+        let Some(screen_origin) = project(view_proj, response.rect, origin) else {
+            self.drag = None;
+            return false;
+        };
+        let frame = Frame {
+            view_proj,
+            camera,
+            rect: response.rect,
+            origin,
+            screen_origin,
+            length: camera.distance(origin) * HANDLE_SCALE,
+        };
+        let axes: Vec<Axis> = match self.constraint {
+            Some(axis) => vec![axis],
+            None => Axis::ALL.to_vec(),
+        };
+
+        let pointer = response.hover_pos();
+        let hovered = pointer.and_then(|pointer| {
+            axes.iter().copied().find(|&axis| {
+                let handle = frame.handle(self.mode, axis);
+                handle
+                    .windows(2)
+                    .any(|w| distance_to_segment(pointer, w[0], w[1]) <= GRAB_DISTANCE)
+            })
+        });
+
+        if response.drag_started_by(egui::PointerButton::Primary) {
+            // With an axis constraint the drag can start anywhere.
+            let axis = hovered.or(self.constraint);
+            if let (Some(axis), Some(start_pointer)) = (axis, response.interact_pointer_pos()) {
+                self.drag = Some(Drag {
+                    axis,
+                    start_pointer,
+                    translation: object.translation,
+                    rotation: object.rotation,
+                    scale: object.scale,
+                });
+            }
+        }
+        if let (Some(drag), Some(pointer)) = (&self.drag, response.interact_pointer_pos()) {
+            self.apply(&frame, drag, pointer, object);
+        }
+        if response.drag_released() {
+            self.drag = None;
+        }
+
+        let painter = ui.painter_at(response.rect);
+        for &axis in &axes {
+            let active = self.drag.as_ref().map(|d| d.axis) == Some(axis) || hovered == Some(axis);
+            let color = if active {
+                Color32::YELLOW
+            } else {
+                axis.color()
+            };
+            let handle = frame.handle(self.mode, axis);
+            let stroke = Stroke::new(if active { 3.0 } else { 2.0 }, color);
+            painter.add(egui::Shape::line(handle.clone(), stroke));
+            if let ([start, end], GizmoMode::Translate | GizmoMode::Scale) =
+                (handle.as_slice(), self.mode)
+            {
+                let dir = (*end - *start).normalized();
+                if self.mode == GizmoMode::Translate {
+                    let back = *end - dir * 10.0;
+                    let side = dir.rot90() * 4.0;
+                    painter.add(egui::Shape::convex_polygon(
+                        vec![*end + dir * 4.0, back + side, back - side],
+                        color,
+                        Stroke::NONE,
+                    ));
+                } else {
+                    painter.rect_filled(
+                        Rect::from_center_size(*end, egui::vec2(8.0, 8.0)),
+                        0.0,
+                        color,
+                    );
+                }
+            }
+        }
+        let mode = match self.mode {
+            GizmoMode::Translate => "Translate",
+            GizmoMode::Rotate => "Rotate",
+            GizmoMode::Scale => "Scale",
+        };
+        let text = match self.constraint {
+            Some(axis) => format!("{} along {:?}", mode, axis),
+            None => mode.to_owned(),
+        };
+        painter.text(
+            response.rect.left_bottom() + egui::vec2(6.0, -6.0),
+            egui::Align2::LEFT_BOTTOM,
+            text,
+            egui::TextStyle::Small.resolve(ui.style()),
+            Color32::WHITE,
+        );
+
+        self.drag.is_some() || hovered.is_some()
+    }
+
+    fn apply(&self, frame: &Frame, drag: &Drag, pointer: Pos2, object: &mut SceneObject) {
+        let axis = drag.axis.dir();
+        // How far along the axis the pointer went, in world units.
+        let along_axis = || {
+            let end = frame.project(frame.origin + axis * frame.length)?;
+            let screen_axis = end - frame.screen_origin;
+            let moved = pointer - drag.start_pointer;
+            let per_length = screen_axis.length_sq().max(1e-6);
+            Some(moved.dot(screen_axis) / per_length * frame.length)
+        };
+        match self.mode {
+            GizmoMode::Translate => {
+                if let Some(amount) = along_axis() {
+                    object.translation = drag.translation + axis * amount;
+                }
+            }
+            GizmoMode::Scale => {
+                if let Some(amount) = along_axis() {
+                    let factor = (1.0 + amount / frame.length).max(0.01);
+                    object.scale = drag.scale * (Vec3::ONE + axis * (factor - 1.0));
+                }
+            }
+            GizmoMode::Rotate => {
+                let angle = |p: Pos2| {
+                    let v = p - frame.screen_origin;
+                    v.y.atan2(v.x)
+                };
+                let mut delta = angle(pointer) - angle(drag.start_pointer);
+                // Screen angles turn clockwise, which is counterclockwise around an axis
+                // pointing away from the camera.
+                if axis.dot(frame.camera - frame.origin) > 0.0 {
+                    delta = -delta;
+                }
+                object.rotation = Quat::from_axis_angle(axis, delta) * drag.rotation;
+            }
+        }
+    }
+}
+
+fn distance_to_segment(p: Pos2, a: Pos2, b: Pos2) -> f32 {
+    let ab = b - a;
+    let t = ((p - a).dot(ab) / ab.length_sq().max(1e-6)).clamp(0.0, 1.0);
+    p.distance(a + ab * t)
+}
@@ -0,0 +1,261 @@
+// wgpu's objects aren't `Send` on the web, where there's only the one thread.
+#![cfg_attr(target_arch = "wasm32", allow(clippy::arc_with_non_send_sync))]
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+
+/// How many frames a cached bind group or sampler is kept without being asked for.
+/// Bind groups keep what they bind alive, like targets reallocated with the window.
+const KEEP_FRAMES: u64 = 120;
+
+/// How often what's cached was asked for and found, or had to be made.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// How many are cached right now.
+    pub entries: usize,
+}
+
+/// A bound resource, by the id of what's bound.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Resource {
+    Buffer {
+        buffer: wgpu::Id<wgpu::Buffer>,
+        offset: u64,
+        size: Option<wgpu::BufferSize>,
+    },
+    Sampler(wgpu::Id<wgpu::Sampler>),
+    TextureView(wgpu::Id<wgpu::TextureView>),
+}
+
+impl Resource {
+    /// `None` for arrays of resources, which aren't cached.
+    fn of(resource: &wgpu::BindingResource<'_>) -> Option<Self> {
+        Some(match resource {
+            wgpu::BindingResource::Buffer(binding) => Resource::Buffer {
+                buffer: binding.buffer.global_id(),
+                offset: binding.offset,
+                size: binding.size,
+            },
+            wgpu::BindingResource::Sampler(sampler) => Resource::Sampler(sampler.global_id()),
+            wgpu::BindingResource::TextureView(view) => Resource::TextureView(view.global_id()),
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct BindGroupKey {
+    layout: wgpu::Id<wgpu::BindGroupLayout>,
+    entries: Vec<(u32, Resource)>,
+}
+
+/// A `SamplerDescriptor` without its label, with the floats as bits so it hashes.
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct SamplerKey {
+    address_modes: [wgpu::AddressMode; 3],
+    filters: [wgpu::FilterMode; 3],
+    lod_clamp: [u32; 2],
+    compare: Option<wgpu::CompareFunction>,
+    anisotropy_clamp: u16,
+    border_color: Option<wgpu::SamplerBorderColor>,
+}
+
+impl SamplerKey {
+    fn of(desc: &wgpu::SamplerDescriptor<'_>) -> Self {
+        Self {
+            address_modes: [
+                desc.address_mode_u,
+                desc.address_mode_v,
+                desc.address_mode_w,
+            ],
+            filters: [desc.mag_filter, desc.min_filter, desc.mipmap_filter],
+            lod_clamp: [desc.lod_min_clamp.to_bits(), desc.lod_max_clamp.to_bits()],
+            compare: desc.compare,
+            anisotropy_clamp: desc.anisotropy_clamp,
+            border_color: desc.border_color,
+        }
+    }
+}
+
+struct Cached<T> {
+    value: Arc<T>,
+    last_used: u64,
+}
+
+/// Made objects by what they were made from, counting how often they're found.
+struct Table<K, T> {
+    entries: HashMap<K, Cached<T>>,
+    hits: u64,
+    misses: u64,
+}
+
+impl<K, T> Default for Table<K, T> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+}
+
+impl<K: Eq + Hash, T> Table<K, T> {
+    fn get_or_make(&mut self, key: K, frame: u64, make: impl FnOnce() -> T) -> Arc<T> {
+        if let Some(cached) = self.entries.get_mut(&key) {
+            self.hits += 1;
+            cached.last_used = frame;
+            return cached.value.clone();
+        }
+        self.misses += 1;
+        let value = Arc::new(make());
+        let cached = Cached {
+            value: value.clone(),
+            last_used: frame,
+        };
+        self.entries.insert(key, cached);
+        value
+    }
+
+    fn evict(&mut self, frame: u64) {
+        self.entries
+            .retain(|_, cached| frame - cached.last_used <= KEEP_FRAMES);
+    }
+
+    fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            entries: self.entries.len(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    frame: u64,
+    bind_groups: Table<BindGroupKey, wgpu::BindGroup>,
+    samplers: Table<SamplerKey, wgpu::Sampler>,
+}
+
+/// Bind groups and samplers made once and handed out again, for the same layout and
+/// resources or the same descriptor, instead of making new ones every frame. What
+/// isn't asked for in a while is let go. Cloning it shares the same cache.
+#[derive(Clone, Default)]
+pub struct GpuCache {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl GpuCache {
+    /// A bind group of `entries` for `layout`, made with `label` if it wasn't cached.
+    /// Ones binding arrays are made every time.
+    pub fn bind_group(
+        &self,
+        device: &wgpu::Device,
+        label: Option<&str>,
+        layout: &wgpu::BindGroupLayout,
+        entries: &[wgpu::BindGroupEntry<'_>],
+    ) -> Arc<wgpu::BindGroup> {
+        let make = || {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label,
+                layout,
+                entries,
+            })
+        };
+        let Some(entries) = entries
+            .iter()
+            .map(|entry| Some((entry.binding, Resource::of(&entry.resource)?)))
+            .collect::<Option<Vec<_>>>()
+        else {
+            return Arc::new(make());
+        };
+        let key = BindGroupKey {
+            layout: layout.global_id(),
+            entries,
+        };
+        let mut inner = self.inner.lock().unwrap();
+        let frame = inner.frame;
+        inner.bind_groups.get_or_make(key, frame, make)
+    }
+
+    /// A sampler like `desc` describes, made with its label if it wasn't cached.
+    pub fn sampler(
+        &self,
+        device: &wgpu::Device,
+        desc: &wgpu::SamplerDescriptor<'_>,
+    ) -> Arc<wgpu::Sampler> {
+        let mut inner = self.inner.lock().unwrap();
+        let frame = inner.frame;
+        inner
+            .samplers
+            .get_or_make(SamplerKey::of(desc), frame, || device.create_sampler(desc))
+    }
+
+    /// Lets go of what wasn't asked for in the last `KEEP_FRAMES` frames. Called once a
+    /// frame is submitted.
+    pub fn end_frame(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.frame += 1;
+        let frame = inner.frame;
+        inner.bind_groups.evict(frame);
+        inner.samplers.evict(frame);
+    }
+
+    /// Drops everything, made with a device that's gone. The counts are kept.
+    pub fn clear(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.bind_groups.entries.clear();
+        inner.samplers.entries.clear();
+    }
+
+    pub fn bind_group_stats(&self) -> CacheStats {
+        self.inner.lock().unwrap().bind_groups.stats()
+    }
+
+    pub fn sampler_stats(&self) -> CacheStats {
+        self.inner.lock().unwrap().samplers.stats()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_same_key_is_a_hit_and_a_new_one_a_miss() {
+        let mut table = Table::<u32, String>::default();
+        let first = table.get_or_make(1, 0, || "one".to_owned());
+        let again = table.get_or_make(1, 0, || unreachable!());
+        assert!(Arc::ptr_eq(&first, &again));
+        table.get_or_make(2, 0, || "two".to_owned());
+        assert_eq!(
+            table.stats(),
+            CacheStats {
+                hits: 1,
+                misses: 2,
+                entries: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn entries_unused_for_keep_frames_are_let_go() {
+        let mut table = Table::<u32, String>::default();
+        table.get_or_make(1, 0, || "old".to_owned());
+        table.get_or_make(2, 0, || "used".to_owned());
+        table.get_or_make(2, 10, || unreachable!());
+        table.evict(KEEP_FRAMES);
+        assert_eq!(table.stats().entries, 2);
+        table.evict(KEEP_FRAMES + 1);
+        assert_eq!(table.stats().entries, 1);
+        // Made again, as it was let go of.
+        table.get_or_make(1, KEEP_FRAMES + 1, || "new".to_owned());
+        assert_eq!(table.stats().misses, 3);
+        table.evict(KEEP_FRAMES + 11);
+        assert_eq!(table.stats().entries, 1);
+        assert!(table.entries.contains_key(&1));
+    }
+}
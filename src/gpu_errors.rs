@@ -0,0 +1,170 @@
+use instant::Instant;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// The most errors kept, the oldest are dropped past it.
+const MAX_ERRORS: usize = 200;
+
+/// A wgpu error caught by an error scope or the uncaptured error handler.
+#[derive(Clone, Debug)]
+pub struct GpuError {
+    /// The scope it was caught in, "uncaptured" when it escaped all of them.
+    pub scope: &'static str,
+    pub out_of_memory: bool,
+    pub message: String,
+    pub at: Instant,
+}
+
+/// The GPU errors seen so far, shared between the device's error handler and whoever
+/// shows them. Cloning it shares the same log.
+#[derive(Clone)]
+pub struct GpuErrors {
+    errors: Arc<Mutex<Vec<GpuError>>>,
+    device_lost: Arc<AtomicBool>,
+    started: Instant,
+}
+
+impl Default for GpuErrors {
+    fn default() -> Self {
+        Self {
+            errors: Arc::default(),
+            device_lost: Arc::default(),
+            started: Instant::now(),
+        }
+    }
+}
+
+impl GpuErrors {
+    /// Collects every error of `device` that no scope catches, instead of wgpu's
+    /// default of panicking. A recreated device is attached to the same log.
+    pub fn attach(&self, device: &wgpu::Device) {
+        self.device_lost.store(false, Ordering::Relaxed);
+        let handler = self.clone();
+        device.on_uncaptured_error(Box::new(move |error| handler.push("uncaptured", error)));
+    }
+
+    /// Runs `create` inside validation and out-of-memory error scopes, logging what
+    /// they catch under `scope`.
+    pub fn scope<T>(
+        &self,
+        device: &wgpu::Device,
+        scope: &'static str,
+        create: impl FnOnce() -> T,
+    ) -> T {
+        device.push_error_scope(wgpu::ErrorFilter::OutOfMemory);
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let value = create();
+        // wgpu-core resolves these right away, natively as well as through WebGL.
+        for error in [device.pop_error_scope(), device.pop_error_scope()] {
+            if let Some(error) = pollster::block_on(error) {
+                self.push(scope, error);
+            }
+        }
+        value
+    }
+
+    fn push(&self, scope: &'static str, error: wgpu::Error) {
+        let (out_of_memory, message) = match error {
+            wgpu::Error::OutOfMemory { .. } => (true, "Out of memory".to_owned()),
+            wgpu::Error::Validation { description, .. } => (false, description),
+        };
+        // wgpu 0.17 has no device lost callback, this is how wgpu-core reports it.
+        if message.contains("Parent device is lost") {
+            self.device_lost.store(true, Ordering::Relaxed);
+        }
+        tracing::error!("GPU error in {}: {}", scope, message);
+        let mut errors = self.errors.lock().unwrap();
+        if errors.len() == MAX_ERRORS {
+            errors.remove(0);
+        }
+        errors.push(GpuError {
+            scope,
+            out_of_memory,
+            message,
+            at: Instant::now(),
+        });
+    }
+
+    pub fn count(&self) -> usize {
+        self.errors.lock().unwrap().len()
+    }
+
+    /// Whether an error said the device was lost.
+    pub fn device_lost(&self) -> bool {
+        self.device_lost.load(Ordering::Relaxed)
+    }
+
+    pub fn clear(&self) {
+        self.errors.lock().unwrap().clear();
+    }
+
+    /// Seconds from when the log was made to `error`.
+    fn seconds(&self, error: &GpuError) -> f32 {
+        error.at.duration_since(self.started).as_secs_f32()
+    }
+}
+
+/// The "GPU errors" window, listing what `GpuErrors` caught, newest first.
+#[derive(Default)]
+pub struct GpuErrorConsole {
+    pub open: bool,
+    errors: Option<GpuErrors>,
+    /// How many errors there were when the window was last drawn, so new ones open it.
+    seen: usize,
+}
+
+impl GpuErrorConsole {
+    pub fn set_errors(&mut self, errors: GpuErrors) {
+        self.errors = Some(errors);
+    }
+
+    pub fn count(&self) -> usize {
+        self.errors.as_ref().map_or(0, GpuErrors::count)
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        let Some(errors) = &self.errors else {
+            return;
+        };
+        let count = errors.count();
+        if count > self.seen {
+            self.open = true;
+        }
+        self.seen = count;
+
+        egui::Window::new("GPU errors")
+            .open(&mut self.open)
+            .default_size([480.0, 240.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} errors", count));
+                    if ui.button("Clear").clicked() {
+                        errors.clear();
+                    }
+                });
+                ui.separator();
+                let log = errors.errors.lock().unwrap().clone();
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false; 2])
+                    .show(ui, |ui| {
+                        for error in log.iter().rev() {
+                            ui.horizontal(|ui| {
+                                ui.monospace(format!("{:8.2}s", errors.seconds(error)));
+                                let kind = if error.out_of_memory {
+                                    "out of memory"
+                                } else {
+                                    "validation"
+                                };
+                                ui.colored_label(
+                                    ui.visuals().error_fg_color,
+                                    format!("{} in {}", kind, error.scope),
+                                );
+                            });
+                            ui.label(&error.message);
+                            ui.separator();
+                        }
+                    });
+            });
+    }
+}
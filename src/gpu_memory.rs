@@ -0,0 +1,228 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ResourceKind {
+    Texture,
+    Buffer,
+    /// A texture of egui's, uploaded from its `TexturesDelta`.
+    EguiTexture,
+}
+
+impl ResourceKind {
+    pub const ALL: [Self; 3] = [Self::Texture, Self::Buffer, Self::EguiTexture];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Texture => "Textures",
+            Self::Buffer => "Buffers",
+            Self::EguiTexture => "egui textures",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Entry {
+    kind: ResourceKind,
+    label: String,
+    bytes: u64,
+}
+
+#[derive(Default)]
+struct Registry {
+    next_id: u64,
+    entries: BTreeMap<u64, Entry>,
+    egui: HashMap<egui::TextureId, u64>,
+}
+
+/// The textures and buffers made on the device and roughly how much memory they take,
+/// counted from their descriptors. Cloning it shares the same registry.
+///
+/// wgpu doesn't tell how much memory is in use, so only what's tracked here counts:
+/// the scaffold's own targets and egui's textures, and whatever the app tracks itself.
+#[derive(Clone, Default)]
+pub struct GpuMemory {
+    registry: Arc<Mutex<Registry>>,
+}
+
+/// A tracked resource, counted until this is dropped. Keep it next to the resource.
+pub struct Allocation {
+    id: u64,
+    registry: Arc<Mutex<Registry>>,
+}
+
+impl Drop for Allocation {
+    fn drop(&mut self) {
+        self.registry.lock().unwrap().entries.remove(&self.id);
+    }
+}
+
+impl GpuMemory {
+    /// Counts a texture made from `desc`.
+    pub fn track_texture(&self, desc: &wgpu::TextureDescriptor<'_>) -> Allocation {
+        let label = desc.label.unwrap_or("unlabeled").to_owned();
+        self.track(ResourceKind::Texture, label, texture_bytes(desc))
+    }
+
+    /// Counts a buffer made from `desc`.
+    pub fn track_buffer(&self, desc: &wgpu::BufferDescriptor<'_>) -> Allocation {
+        let label = desc.label.unwrap_or("unlabeled").to_owned();
+        self.track(ResourceKind::Buffer, label, desc.size)
+    }
+
+    fn track(&self, kind: ResourceKind, label: String, bytes: u64) -> Allocation {
+        let mut registry = self.registry.lock().unwrap();
+        let id = registry.next_id;
+        registry.next_id += 1;
+        registry.entries.insert(id, Entry { kind, label, bytes });
+        Allocation {
+            id,
+            registry: self.registry.clone(),
+        }
+    }
+
+    /// Follows the textures egui allocates and frees, as the renderer uploads `delta`.
+    pub fn egui_textures(&self, delta: &egui::TexturesDelta) {
+        let mut registry = self.registry.lock().unwrap();
+        for (id, image) in &delta.set {
+            // Partial updates write into a texture that's already counted.
+            if image.pos.is_some() {
+                continue;
+            }
+            let [width, height] = image.image.size();
+            let bytes = (width * height * 4) as u64;
+            registry.egui.insert(*id, bytes);
+        }
+        for id in &delta.free {
+            registry.egui.remove(id);
+        }
+    }
+
+    /// Drops egui's textures, after its renderer was made again.
+    pub fn forget_egui_textures(&self) {
+        self.registry.lock().unwrap().egui.clear();
+    }
+
+    /// The count and bytes of each kind, indexed like `ResourceKind::ALL`.
+    pub fn totals(&self) -> [(usize, u64); ResourceKind::ALL.len()] {
+        let registry = self.registry.lock().unwrap();
+        let mut totals = [(0, 0); ResourceKind::ALL.len()];
+        for entry in registry.entries.values() {
+            let total = &mut totals[entry.kind as usize];
+            total.0 += 1;
+            total.1 += entry.bytes;
+        }
+        let egui = &mut totals[ResourceKind::EguiTexture as usize];
+        egui.0 = registry.egui.len();
+        egui.1 = registry.egui.values().sum();
+        totals
+    }
+
+    /// The tracked textures and buffers grouped by label, the largest first, with how
+    /// many share the label.
+    fn by_label(&self) -> Vec<(ResourceKind, String, usize, u64)> {
+        let registry = self.registry.lock().unwrap();
+        let mut groups: BTreeMap<(ResourceKind, &str), (usize, u64)> = BTreeMap::new();
+        for entry in registry.entries.values() {
+            let group = groups.entry((entry.kind, &entry.label)).or_default();
+            group.0 += 1;
+            group.1 += entry.bytes;
+        }
+        let mut groups: Vec<_> = groups
+            .into_iter()
+            .map(|((kind, label), (count, bytes))| (kind, label.to_owned(), count, bytes))
+            .collect();
+        groups.sort_by_key(|group| std::cmp::Reverse(group.3));
+        groups
+    }
+}
+
+/// The bytes of every mip level, layer and sample of a texture made from `desc`.
+fn texture_bytes(desc: &wgpu::TextureDescriptor<'_>) -> u64 {
+    let (block_width, block_height) = desc.format.block_dimensions();
+    // Depth24Plus has no defined size, it's 4 bytes on the hardware out there.
+    let block_size = desc.format.block_size(None).unwrap_or(4) as u64;
+    (0..desc.mip_level_count)
+        .filter_map(|level| desc.mip_level_size(level))
+        .map(|size| {
+            let blocks_wide = size.width.div_ceil(block_width) as u64;
+            let blocks_high = size.height.div_ceil(block_height) as u64;
+            blocks_wide * blocks_high * size.depth_or_array_layers as u64 * block_size
+        })
+        .sum::<u64>()
+        * desc.sample_count as u64
+}
+
+/// Bytes in the largest unit that keeps them above 1.
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// The "GPU memory" window, with the live totals of `GpuMemory`.
+#[derive(Default)]
+pub struct GpuMemoryWindow {
+    pub open: bool,
+    memory: Option<GpuMemory>,
+}
+
+impl GpuMemoryWindow {
+    pub fn set_memory(&mut self, memory: GpuMemory) {
+        self.memory = Some(memory);
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        let Some(memory) = &self.memory else {
+            return;
+        };
+        egui::Window::new("GPU memory")
+            .open(&mut self.open)
+            .default_size([360.0, 320.0])
+            .show(ctx, |ui| {
+                let totals = memory.totals();
+                egui::Grid::new("gpu memory totals").show(ui, |ui| {
+                    for kind in ResourceKind::ALL {
+                        let (count, bytes) = totals[kind as usize];
+                        ui.label(kind.name());
+                        ui.monospace(count.to_string());
+                        ui.monospace(format_bytes(bytes));
+                        ui.end_row();
+                    }
+                    ui.strong("Total");
+                    ui.label("");
+                    ui.monospace(format_bytes(totals.iter().map(|total| total.1).sum()));
+                    ui.end_row();
+                });
+                ui.weak("Only what's tracked with GpuMemory is counted");
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false; 2])
+                    .show(ui, |ui| {
+                        egui::Grid::new("gpu memory labels")
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for (kind, label, count, bytes) in memory.by_label() {
+                                    ui.label(label);
+                                    ui.weak(match kind {
+                                        ResourceKind::Buffer => "buffer",
+                                        _ => "texture",
+                                    });
+                                    ui.monospace(format!("×{}", count));
+                                    ui.monospace(format_bytes(bytes));
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            });
+    }
+}
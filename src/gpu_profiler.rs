@@ -0,0 +1,212 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+
+/// The most timestamps written in a frame, one more than the passes measured.
+const MAX_MARKS: u32 = 16;
+/// Frames whose timestamps can be waiting to be read back at once.
+const READBACK_SLOTS: usize = 3;
+
+const FREE: u8 = 0;
+const MAPPING: u8 = 1;
+const MAPPED: u8 = 2;
+
+/// How long the GPU spent on one pass of a frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GpuPassTime {
+    pub label: &'static str,
+    pub ms: f32,
+}
+
+/// A buffer the resolved timestamps are copied to, and the passes they measure.
+struct Readback {
+    buffer: wgpu::Buffer,
+    labels: Vec<&'static str>,
+    state: Arc<AtomicU8>,
+}
+
+/// Measures the GPU time of each pass with timestamp queries written between them.
+///
+/// Results come back a few frames late, as the buffers are only mapped once the GPU is
+/// done with them.
+pub struct GpuProfiler {
+    query_set: wgpu::QuerySet,
+    resolve: wgpu::Buffer,
+    slots: Vec<Readback>,
+    /// The slot this frame's timestamps go to, `None` while all are being read back.
+    current: Option<usize>,
+    /// The passes started so far this frame.
+    labels: Vec<&'static str>,
+    /// Nanoseconds per timestamp tick.
+    period: f32,
+    latest: Vec<GpuPassTime>,
+}
+
+impl GpuProfiler {
+    /// `None` when the device wasn't created with `TIMESTAMP_QUERY`.
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Option<Self> {
+        if !device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            return None;
+        }
+        let size = MAX_MARKS as u64 * std::mem::size_of::<u64>() as u64;
+        let slots = (0..READBACK_SLOTS)
+            .map(|_| Readback {
+                buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("timestamp readback"),
+                    size,
+                    usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                    mapped_at_creation: false,
+                }),
+                labels: Vec::new(),
+                state: Arc::new(AtomicU8::new(FREE)),
+            })
+            .collect();
+        Some(Self {
+            query_set: device.create_query_set(&wgpu::QuerySetDescriptor {
+                label: Some("timestamps"),
+                ty: wgpu::QueryType::Timestamp,
+                count: MAX_MARKS,
+            }),
+            resolve: device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("timestamp resolve"),
+                size,
+                usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            }),
+            slots,
+            current: None,
+            labels: Vec::new(),
+            period: queue.get_timestamp_period(),
+            latest: Vec::new(),
+        })
+    }
+
+    /// Ends the pass started before, if any, and starts timing `label`.
+    pub fn begin(&mut self, encoder: &mut wgpu::CommandEncoder, label: &'static str) {
+        if self.labels.is_empty() {
+            self.current = self
+                .slots
+                .iter()
+                .position(|slot| slot.state.load(Ordering::Acquire) == FREE);
+        }
+        if self.current.is_none() || self.labels.len() + 1 >= MAX_MARKS as usize {
+            return;
+        }
+        encoder.write_timestamp(&self.query_set, self.labels.len() as u32);
+        self.labels.push(label);
+    }
+
+    /// Ends the last pass and copies the frame's timestamps out, to be mapped once the
+    /// commands are submitted.
+    pub fn end(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        let Some(current) = self.current else {
+            self.labels.clear();
+            return;
+        };
+        if self.labels.is_empty() {
+            return;
+        }
+        let count = self.labels.len() as u32 + 1;
+        encoder.write_timestamp(&self.query_set, count - 1);
+        encoder.resolve_query_set(&self.query_set, 0..count, &self.resolve, 0);
+        let slot = &mut self.slots[current];
+        encoder.copy_buffer_to_buffer(
+            &self.resolve,
+            0,
+            &slot.buffer,
+            0,
+            count as u64 * std::mem::size_of::<u64>() as u64,
+        );
+        slot.labels = std::mem::take(&mut self.labels);
+    }
+
+    /// Starts reading back the frame's timestamps, after its commands were submitted.
+    pub fn submitted(&mut self) {
+        let Some(current) = self.current.take() else {
+            return;
+        };
+        let slot = &self.slots[current];
+        if slot.labels.is_empty() {
+            return;
+        }
+        slot.state.store(MAPPING, Ordering::Release);
+        let state = slot.state.clone();
+        slot.buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                state.store(
+                    if result.is_ok() { MAPPED } else { FREE },
+                    Ordering::Release,
+                );
+            });
+    }
+
+    /// The timings of the latest frame read back, reading any that are ready first.
+    pub fn collect(&mut self, device: &wgpu::Device) -> &[GpuPassTime] {
+        device.poll(wgpu::Maintain::Poll);
+        for slot in &mut self.slots {
+            if slot.state.load(Ordering::Acquire) != MAPPED {
+                continue;
+            }
+            {
+                let data = slot.buffer.slice(..).get_mapped_range();
+                let ticks: &[u64] = bytemuck::cast_slice(&data);
+                self.latest = slot
+                    .labels
+                    .iter()
+                    .zip(ticks.windows(2))
+                    .map(|(&label, pair)| GpuPassTime {
+                        label,
+                        ms: pair[1].saturating_sub(pair[0]) as f32 * self.period / 1e6,
+                    })
+                    .collect();
+            }
+            slot.buffer.unmap();
+            slot.state.store(FREE, Ordering::Release);
+        }
+        &self.latest
+    }
+}
+
+/// A corner overlay listing the GPU time of each pass.
+#[derive(Default)]
+pub struct GpuTimingsOverlay {
+    pub open: bool,
+    /// `None` when timestamp queries aren't supported.
+    times: Option<Vec<GpuPassTime>>,
+}
+
+impl GpuTimingsOverlay {
+    pub fn set_times(&mut self, times: Option<&[GpuPassTime]>) {
+        self.times = times.map(<[_]>::to_vec);
+    }
+
+    pub fn ui(&self, ctx: &egui::Context) {
+        egui::Area::new("gpu timings")
+            .anchor(egui::Align2::RIGHT_TOP, [-8.0, 32.0])
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.strong("GPU");
+                    let Some(times) = &self.times else {
+                        ui.weak("Timestamp queries aren't supported here");
+                        return;
+                    };
+                    if times.is_empty() {
+                        ui.weak("Waiting for the first frame");
+                        return;
+                    }
+                    egui::Grid::new("gpu timings").show(ui, |ui| {
+                        for time in times {
+                            ui.label(time.label);
+                            ui.monospace(format!("{:.3} ms", time.ms));
+                            ui.end_row();
+                        }
+                        ui.label("Total");
+                        let total: f32 = times.iter().map(|time| time.ms).sum();
+                        ui.monospace(format!("{:.3} ms", total));
+                        ui.end_row();
+                    });
+                });
+            });
+    }
+}
@@ -0,0 +1,405 @@
+use crate::config;
+use crate::gamepad::GamepadEvent;
+use crate::letterbox::Letterbox;
+use crate::stats::{Stage, StageTimer};
+use crate::theme::Theme;
+use crate::Application;
+
+use egui_wgpu::{renderer::ScreenDescriptor, Renderer};
+use egui_winit::{EventResponse, State};
+use winit::window::Window;
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+const MEMORY_FILE: &str = "egui_memory.json";
+
+/// The egui side of the window: its input state and the renderer drawing it.
+pub struct Gui {
+    pub context: egui::Context,
+    state: State,
+    ime: Ime,
+    /// Key presses the gamepads stand for, handed to egui with the next frame's input.
+    gamepad_events: Vec<egui::Event>,
+    /// Whether a screen reader asked for egui's accessibility tree, which a new context
+    /// has to be told again.
+    #[cfg(feature = "accesskit")]
+    accesskit: bool,
+    pub renderer: Renderer,
+}
+
+/// Composing text with an input method, as for CJK, in place of egui-winit.
+///
+/// egui-winit drops the empty preedit an IME sends when a composition is cancelled or
+/// backspaced away, leaving its text in the field, and places the candidate window in
+/// logical pixels, which are only egui's points at the OS's scale.
+#[derive(Default)]
+struct Ime {
+    /// Whether the window takes IME input, only while a text field has the focus so
+    /// that keys aren't composed into text anywhere else.
+    allowed: bool,
+    composing: bool,
+    events: Vec<egui::Event>,
+}
+
+impl Ime {
+    fn on_event(&mut self, ime: &winit::event::Ime) {
+        use winit::event::Ime;
+
+        match ime {
+            Ime::Enabled | Ime::Disabled => {}
+            // Also sent right before every commit.
+            Ime::Preedit(text, _) if text.is_empty() => {
+                if self.composing {
+                    self.events
+                        .push(egui::Event::CompositionUpdate(String::new()));
+                }
+            }
+            Ime::Preedit(text, _) => {
+                if !std::mem::replace(&mut self.composing, true) {
+                    self.events.push(egui::Event::CompositionStart);
+                }
+                self.events
+                    .push(egui::Event::CompositionUpdate(text.clone()));
+            }
+            Ime::Commit(text) => {
+                self.composing = false;
+                self.events.push(egui::Event::CompositionEnd(text.clone()));
+            }
+        }
+    }
+
+    /// Allows the IME while there's a text cursor, and puts its candidates below it.
+    /// `cursor` is in physical pixels.
+    fn update(&mut self, window: &Window, cursor: Option<egui::Pos2>) {
+        if self.allowed != cursor.is_some() {
+            self.allowed = cursor.is_some();
+            window.set_ime_allowed(self.allowed);
+        }
+        if let Some(cursor) = cursor {
+            window.set_ime_position(winit::dpi::PhysicalPosition::new(cursor.x, cursor.y));
+        }
+    }
+}
+
+/// What a UI frame hands over to be drawn.
+pub struct GuiFrame {
+    pub paint_jobs: Vec<egui::ClippedPrimitive>,
+    pub textures_delta: egui::TexturesDelta,
+    pub screen_descriptor: ScreenDescriptor,
+    /// How long until egui wants to be drawn again, zero for right away and
+    /// `Duration::MAX` for only when there is input.
+    pub repaint_after: Duration,
+}
+
+/// How opaque egui's panels and windows are in a transparent window.
+const TRANSLUCENT_FILL: f32 = 0.85;
+
+/// Lets the desktop show through the fills of egui's panels and windows a little, for
+/// transparent windows.
+pub(crate) fn make_translucent(visuals: &mut egui::Visuals) {
+    visuals.panel_fill = visuals.panel_fill.gamma_multiply(TRANSLUCENT_FILL);
+    visuals.window_fill = visuals.window_fill.gamma_multiply(TRANSLUCENT_FILL);
+}
+
+impl Gui {
+    /// `format` is the format of the target egui draws to. egui anti-aliases its
+    /// shapes itself, so it always draws with a single sample.
+    pub fn new(
+        window: &Window,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        theme: Theme,
+    ) -> Self {
+        let mut state = State::new(window);
+        state.set_pixels_per_point(window.scale_factor() as f32);
+
+        let context = egui::Context::default();
+        context.set_visuals(theme.resolve(window.theme()).visuals());
+
+        Self {
+            context,
+            state,
+            ime: Ime::default(),
+            gamepad_events: Vec::new(),
+            #[cfg(feature = "accesskit")]
+            accesskit: false,
+            renderer: Renderer::new(device, format, None, 1),
+        }
+    }
+
+    /// Starts over with a renderer on a new device. egui only uploads its font atlas
+    /// once per context, so a new context takes over this one's memory, which keeps the
+    /// window positions and the style.
+    pub fn recreate(&mut self, device: &wgpu::Device, format: wgpu::TextureFormat) {
+        let context = egui::Context::default();
+        let memory = self.context.memory(|memory| memory.clone());
+        context.memory_mut(|new| *new = memory);
+        #[cfg(feature = "accesskit")]
+        if self.accesskit {
+            context.enable_accesskit();
+        }
+        self.context = context;
+        self.renderer = Renderer::new(device, format, None, 1);
+    }
+
+    /// Restores egui's memory saved by an earlier run: where the windows were and how
+    /// large, which headers were open and the like. The style isn't part of it.
+    pub fn load_memory(&self) {
+        let Some(path) = memory_path() else {
+            return;
+        };
+        let Ok(text) = std::fs::read_to_string(&path) else {
+            return;
+        };
+        match serde_json::from_str::<egui::Memory>(&text) {
+            Ok(memory) => {
+                let style = self.context.style();
+                self.context.memory_mut(|old| *old = memory);
+                self.context.set_style(style);
+            }
+            Err(e) => tracing::warn!("Ignoring invalid egui memory {}: {}", path.display(), e),
+        }
+    }
+
+    /// Saves egui's memory next to the config file, for `load_memory` to restore.
+    pub fn save_memory(&self) {
+        let Some(path) = memory_path() else {
+            return;
+        };
+        let result = self
+            .context
+            .memory(serde_json::to_string)
+            .map_err(std::io::Error::from)
+            .and_then(|text| {
+                std::fs::create_dir_all(path.parent().unwrap())?;
+                std::fs::write(&path, text)
+            });
+        if let Err(e) = result {
+            tracing::warn!("Failed to save egui's memory: {}", e);
+        }
+    }
+
+    /// Lets screen readers ask for the UI through `UserEvent::AccessKitRequested`, and
+    /// act on it through `UserEvent::AccessKit`. Called before the window is shown.
+    /// There's no screen reader to reach from the browser's canvas.
+    #[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
+    pub fn init_accesskit(
+        &mut self,
+        window: &Window,
+        proxy: winit::event_loop::EventLoopProxy<crate::events::UserEvent>,
+    ) {
+        let requested = proxy.clone();
+        let context = self.context.clone();
+        self.state.init_accesskit(window, proxy, move || {
+            // Called from AccessKit's thread, the tree is built on the main one.
+            let _ = requested.send_event(crate::events::UserEvent::AccessKitRequested);
+            context.accesskit_placeholder_tree_update()
+        });
+    }
+
+    /// Has egui build its accessibility tree every frame, which goes to the screen
+    /// reader with the platform output.
+    #[cfg(feature = "accesskit")]
+    pub fn enable_accesskit(&mut self) {
+        self.accesskit = true;
+        self.context.enable_accesskit();
+    }
+
+    #[cfg(feature = "accesskit")]
+    pub fn on_accesskit_action_request(&mut self, request: egui::accesskit::ActionRequest) {
+        self.state.on_accesskit_action_request(request);
+    }
+
+    pub fn on_event(&mut self, event: &winit::event::WindowEvent<'_>) -> EventResponse {
+        if let winit::event::WindowEvent::Ime(ime) = event {
+            self.ime.on_event(ime);
+            return EventResponse {
+                repaint: true,
+                consumed: self.context.wants_keyboard_input(),
+            };
+        }
+        self.state.on_event(&self.context, event)
+    }
+
+    /// Moves egui's focus with the D-pad, returning whether the event was for egui.
+    pub fn on_gamepad(&mut self, event: &GamepadEvent) -> bool {
+        let Some(event) = event.to_egui() else {
+            return false;
+        };
+        self.gamepad_events.push(event);
+        true
+    }
+
+    /// Runs the app's UI for a frame `size_in_pixels` large, drawn through the letterbox
+    /// if there is one.
+    pub fn run(
+        &mut self,
+        window: &Window,
+        app: &mut impl Application,
+        letterbox: Option<&Letterbox>,
+        size_in_pixels: [u32; 2],
+        timer: &mut StageTimer,
+    ) -> GuiFrame {
+        self.frame(window, app, None, letterbox, size_in_pixels, timer)
+    }
+
+    /// Runs the app's UI for the window it listed as `id`, with `Application::window_ui`.
+    pub fn run_window(
+        &mut self,
+        window: &Window,
+        app: &mut impl Application,
+        id: &str,
+        size_in_pixels: [u32; 2],
+    ) -> GuiFrame {
+        let mut timer = StageTimer::start();
+        self.frame(window, app, Some(id), None, size_in_pixels, &mut timer)
+    }
+
+    /// `id` is `None` for the main window, which is the only one `end_frame` sees.
+    fn frame(
+        &mut self,
+        window: &Window,
+        app: &mut impl Application,
+        id: Option<&str>,
+        letterbox: Option<&Letterbox>,
+        size_in_pixels: [u32; 2],
+        timer: &mut StageTimer,
+    ) -> GuiFrame {
+        let pixels_per_point = window.scale_factor() as f32 * app.ui_zoom();
+        // Going fullscreen on another monitor can change the scale factor without every
+        // platform telling first, and egui has to lay out for the one drawn with.
+        self.state.set_pixels_per_point(pixels_per_point);
+
+        // Begin to draw the UI frame.
+        let mut input = self.state.take_egui_input(window);
+        input.events.append(&mut self.ime.events);
+        input.events.append(&mut self.gamepad_events);
+        if let Some(letterbox) = letterbox {
+            letterbox.map_input(&mut input, pixels_per_point);
+        }
+        timer.lap(Stage::Input);
+        self.context.begin_frame(input);
+
+        match id {
+            Some(id) => app.window_ui(id, &self.context),
+            None => app.ui(&self.context),
+        }
+        timer.lap(Stage::Ui);
+
+        // End the UI frame. We could now handle the output and draw the UI with the backend.
+        let mut full_output = self.context.end_frame();
+        if id.is_none() {
+            app.end_frame(&full_output);
+        }
+        let text_cursor = full_output.platform_output.text_cursor_pos.take();
+        self.ime.update(
+            window,
+            text_cursor.map(|pos| match letterbox {
+                Some(letterbox) => (letterbox.to_window(pos, pixels_per_point).to_vec2()
+                    * pixels_per_point)
+                    .to_pos2(),
+                None => (pos.to_vec2() * self.context.pixels_per_point()).to_pos2(),
+            }),
+        );
+        self.state
+            .handle_platform_output(window, &self.context, full_output.platform_output);
+        timer.lap(Stage::EndFrame);
+
+        let paint_jobs = self.context.tessellate(full_output.shapes);
+        timer.lap(Stage::Tessellate);
+
+        GuiFrame {
+            paint_jobs,
+            textures_delta: full_output.textures_delta,
+            repaint_after: full_output.repaint_after,
+            screen_descriptor: ScreenDescriptor {
+                size_in_pixels,
+                pixels_per_point,
+            },
+        }
+    }
+
+    /// Uploads the frame's textures and buffers, returning the command buffers of the
+    /// paint callbacks.
+    pub fn upload(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        frame: &GuiFrame,
+    ) -> Vec<wgpu::CommandBuffer> {
+        for (tid, deltas) in &frame.textures_delta.set {
+            self.renderer.update_texture(device, queue, *tid, deltas);
+        }
+        self.renderer.update_buffers(
+            device,
+            queue,
+            encoder,
+            &frame.paint_jobs,
+            &frame.screen_descriptor,
+        )
+    }
+
+    pub fn paint<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>, frame: &'a GuiFrame) {
+        self.renderer
+            .render(pass, &frame.paint_jobs, &frame.screen_descriptor);
+    }
+
+    /// Frees the textures egui is done with, once the frame was submitted.
+    pub fn free(&mut self, frame: &GuiFrame) {
+        for tid in &frame.textures_delta.free {
+            self.renderer.free_texture(tid);
+        }
+    }
+}
+
+fn memory_path() -> Option<PathBuf> {
+    config::config_dir().map(|dir| dir.join(MEMORY_FILE))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn memory_survives_json() {
+        let ctx = egui::Context::default();
+        let _ = ctx.run(egui::RawInput::default(), |ctx| {
+            egui::Window::new("Remembered")
+                .default_pos([120.0, 80.0])
+                .show(ctx, |ui| ui.collapsing("Header", |ui| ui.label("Inside")));
+        });
+        let text = ctx.memory(serde_json::to_string).unwrap();
+        let memory: egui::Memory = serde_json::from_str(&text).unwrap();
+        let id = egui::Id::new("Remembered");
+        assert_eq!(
+            memory.area_rect(id).map(|rect| rect.min),
+            ctx.memory(|memory| memory.area_rect(id))
+                .map(|rect| rect.min),
+        );
+        assert!(memory.area_rect(id).is_some());
+    }
+
+    #[test]
+    fn cancelled_composition_clears_its_text() {
+        use winit::event::Ime;
+
+        let mut ime = super::Ime::default();
+        ime.on_event(&Ime::Preedit("に".to_owned(), Some((0, 3))));
+        ime.on_event(&Ime::Preedit(String::new(), None));
+        ime.on_event(&Ime::Preedit("ほ".to_owned(), Some((0, 3))));
+        ime.on_event(&Ime::Preedit(String::new(), None));
+        ime.on_event(&Ime::Commit("本".to_owned()));
+        assert_eq!(
+            ime.events,
+            [
+                egui::Event::CompositionStart,
+                egui::Event::CompositionUpdate("に".to_owned()),
+                egui::Event::CompositionUpdate(String::new()),
+                egui::Event::CompositionUpdate("ほ".to_owned()),
+                egui::Event::CompositionUpdate(String::new()),
+                egui::Event::CompositionEnd("本".to_owned()),
+            ]
+        );
+        assert!(!ime.composing);
+    }
+}
@@ -0,0 +1,26 @@
+use crate::color_space::ColorSpace;
+
+/// The format the scene is drawn in before post-processing, with room above 1.0.
+pub const HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// The brightest an extended-range surface is driven to, in multiples of SDR white.
+/// wgpu can't ask the display, and this is within what EDR and HDR10 screens show.
+const EXTENDED_PEAK: f32 = 4.0;
+
+/// The brightest value a surface in `color_space` shows.
+pub fn peak(color_space: ColorSpace) -> f32 {
+    match color_space {
+        ColorSpace::Srgb => 1.0,
+        ColorSpace::ExtendedSrgb => EXTENDED_PEAK,
+    }
+}
+
+/// Whether shaders drawing to `format` write linear values, leaving the encoding to
+/// the hardware or to a float surface.
+pub fn is_linear(format: wgpu::TextureFormat) -> bool {
+    format.is_srgb()
+        || matches!(
+            format,
+            wgpu::TextureFormat::Rgba16Float | wgpu::TextureFormat::Rgba32Float
+        )
+}
@@ -0,0 +1,231 @@
+use crate::capabilities::Capabilities;
+use crate::error::AppError;
+use crate::gfx::{self, RenderTarget, DEPTH_FORMAT};
+use crate::gpu_cache::GpuCache;
+use crate::gpu_errors::GpuErrors;
+use crate::gpu_memory::GpuMemory;
+use crate::runtime;
+use crate::screenshot::Capture;
+use crate::stats::{self, ExportFormat, FrameRecord, Stage, StageTimer};
+use crate::theme::Theme;
+use crate::{Application, ColorSpace, RenderFrame, Setup};
+
+use egui_wgpu::{renderer::ScreenDescriptor, Renderer};
+
+use std::iter;
+use std::path::{Path, PathBuf};
+
+/// The format frames are rendered in, as the surface's usually is.
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+/// The time between frames egui is told, so every run draws the same frames.
+const FRAME_TIME: f32 = 1.0 / 60.0;
+
+/// Renders `frames` frames of `app` into `headless-0000.png` and on, in the current
+/// directory, without a window or a display to show one on.
+///
+/// There is no input, and only `setup`, `ui`, `end_frame`, `render` and `exit` are
+/// called. The frames are `size` large, with a single sample and no post-processing.
+/// With `bench`, how long each frame took is written there as JSON Lines, like the
+/// frame statistics export, leaving out writing the PNGs.
+pub(crate) fn run(
+    mut app: impl Application,
+    size: [u32; 2],
+    backends: wgpu::Backends,
+    compat: bool,
+    theme: Theme,
+    frames: u32,
+    bench: Option<&Path>,
+) -> Result<Vec<PathBuf>, String> {
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+        backends,
+        ..wgpu::InstanceDescriptor::default()
+    });
+    let adapter = runtime::block_on(gfx::request_adapter(&instance, None))
+        .ok_or_else(|| AppError::NoAdapter(backends).to_string())?;
+    tracing::info!("Rendering headless on {}", adapter.get_info().name);
+    let errors = GpuErrors::default();
+    let memory = GpuMemory::default();
+    let cache = GpuCache::default();
+    let (device, queue) = runtime::block_on(gfx::request_device(&adapter, compat, &errors))
+        .map_err(|e| AppError::from(e).to_string())?;
+
+    let context = egui::Context::default();
+    context.set_visuals(theme.visuals());
+    let mut renderer = Renderer::new(&device, FORMAT, None, 1);
+    let mut belt = wgpu::util::StagingBelt::new(gfx::BELT_CHUNK_SIZE);
+    app.setup(Setup {
+        window: None,
+        adapter: &adapter,
+        adapters: vec![adapter.get_info()],
+        present_modes: Vec::new(),
+        device: &device,
+        queue: &queue,
+        capabilities: Capabilities::of(&adapter, &device),
+        errors: &errors,
+        memory: &memory,
+        cache: &cache,
+        renderer: &mut renderer,
+        surface_format: FORMAT,
+        ui_format: FORMAT,
+        color_space: ColorSpace::Srgb,
+        alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+        sample_count: 1,
+        proxy: None,
+    });
+
+    let desc = wgpu::TextureDescriptor {
+        label: Some("headless frame"),
+        size: wgpu::Extent3d {
+            width: size[0],
+            height: size[1],
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    };
+    let target = device.create_texture(&desc);
+    let _allocation = memory.track_texture(&desc);
+    let view = target.create_view(&Default::default());
+    let mut depth = RenderTarget::new("depth", DEPTH_FORMAT, 1).tracked(&memory);
+    let screen_descriptor = ScreenDescriptor {
+        size_in_pixels: size,
+        pixels_per_point: 1.0,
+    };
+
+    let start = instant::Instant::now();
+    let mut records = Vec::new();
+    let mut paths = Vec::new();
+    for index in 0..frames {
+        let timestamp = start.elapsed().as_secs_f64();
+        let mut timer = StageTimer::start();
+        let input = egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(
+                egui::Pos2::ZERO,
+                egui::vec2(size[0] as f32, size[1] as f32),
+            )),
+            pixels_per_point: Some(1.0),
+            time: Some(index as f64 * FRAME_TIME as f64),
+            predicted_dt: FRAME_TIME,
+            ..Default::default()
+        };
+        timer.lap(Stage::Input);
+        context.begin_frame(input);
+        app.ui(&context);
+        timer.lap(Stage::Ui);
+        let full_output = context.end_frame();
+        app.end_frame(&full_output);
+        timer.lap(Stage::EndFrame);
+        let paint_jobs = context.tessellate(full_output.shapes);
+        let textures_delta = full_output.textures_delta;
+        timer.lap(Stage::Tessellate);
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("headless encoder"),
+        });
+        for (id, delta) in &textures_delta.set {
+            renderer.update_texture(&device, &queue, *id, delta);
+        }
+        memory.egui_textures(&textures_delta);
+        let callback_commands = renderer.update_buffers(
+            &device,
+            &queue,
+            &mut encoder,
+            &paint_jobs,
+            &screen_descriptor,
+        );
+        timer.lap(Stage::Upload);
+
+        let depth_view = depth.view(&device, size);
+        let clear_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("clear"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(app.clear_color()),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: depth_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: true,
+                }),
+                stencil_ops: None,
+            }),
+        });
+        drop(clear_pass);
+        app.render(RenderFrame {
+            device: &device,
+            queue: &queue,
+            encoder: &mut encoder,
+            errors: &errors,
+            memory: &memory,
+            cache: &cache,
+            renderer: &mut renderer,
+            view: &view,
+            depth: depth_view,
+            format: FORMAT,
+            sample_count: 1,
+            size,
+            belt: &mut belt,
+        });
+        let mut ui_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("ui"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        renderer.render(&mut ui_pass, &paint_jobs, &screen_descriptor);
+        drop(ui_pass);
+
+        let capture = Capture::copy(&device, &mut encoder, &target)?;
+        timer.lap(Stage::Encode);
+        belt.finish();
+        queue.submit(
+            callback_commands
+                .into_iter()
+                .chain(iter::once(encoder.finish())),
+        );
+        belt.recall();
+        cache.end_frame();
+        let screenshot = capture.read(&device)?;
+        timer.lap(Stage::Present);
+        records.push(FrameRecord {
+            frame: index.into(),
+            timestamp,
+            stage_ms: timer.stage_ms(),
+            gpu_ms: None,
+            vertices: stats::vertex_count(&paint_jobs),
+            texture_upload_bytes: stats::texture_upload_bytes(&textures_delta),
+            // Nothing is presented, so nothing waits for the display.
+            present_mode: wgpu::PresentMode::Immediate,
+        });
+        let path = PathBuf::from(format!("headless-{:04}.png", index));
+        screenshot
+            .write_png(&path)
+            .map_err(|e| format!("Writing {} failed: {}", path.display(), e))?;
+        paths.push(path);
+
+        for id in &textures_delta.free {
+            renderer.free_texture(id);
+        }
+    }
+    app.exit();
+    if let Some(bench) = bench {
+        stats::export(&records, ExportFormat::JsonLines, bench)
+            .map_err(|e| format!("Writing {} failed: {}", bench.display(), e))?;
+    }
+    Ok(paths)
+}
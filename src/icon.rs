@@ -0,0 +1,34 @@
+use winit::window::Icon;
+
+/// The example's own icon, a cube on blue.
+pub(crate) const EXAMPLE_ICON: &[u8] = include_bytes!("../assets/icon.png");
+
+/// Decodes a PNG into a window icon. Any color type will do, it's expanded to RGBA.
+pub(crate) fn decode(png: &[u8]) -> Result<Icon, String> {
+    let (rgba, width, height) = decode_rgba(png)?;
+    Icon::from_rgba(rgba, width, height).map_err(|e| e.to_string())
+}
+
+/// Decodes a PNG into RGBA pixels, with its width and height.
+pub(crate) fn decode_rgba(png: &[u8]) -> Result<(Vec<u8>, u32, u32), String> {
+    let mut decoder = png::Decoder::new(png);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+    let mut data = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut data).map_err(|e| e.to_string())?;
+    let data = &data[..info.buffer_size()];
+    let rgba = match info.color_type {
+        png::ColorType::Rgba => data.to_vec(),
+        png::ColorType::Rgb => data
+            .chunks_exact(3)
+            .flat_map(|c| [c[0], c[1], c[2], u8::MAX])
+            .collect(),
+        png::ColorType::Grayscale => data.iter().flat_map(|&v| [v, v, v, u8::MAX]).collect(),
+        png::ColorType::GrayscaleAlpha => data
+            .chunks_exact(2)
+            .flat_map(|c| [c[0], c[0], c[0], c[1]])
+            .collect(),
+        png::ColorType::Indexed => unreachable!("expanded by normalize_to_color8"),
+    };
+    Ok((rgba, info.width, info.height))
+}
@@ -0,0 +1,139 @@
+use crate::app::AppState;
+use crate::gpu_cache::CacheStats;
+use crate::logging::LogViewer;
+use crate::stats;
+use crate::WindowSpec;
+
+use std::time::Duration;
+
+/// The id the detached inspector's window is listed with.
+pub const WINDOW_ID: &str = "inspector";
+
+/// How often the detached frame stats are redrawn. They change with every frame of the
+/// main window, which doesn't redraw this one.
+const FRAME_REFRESH: Duration = Duration::from_millis(250);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Tab {
+    #[default]
+    Frame,
+    Gpu,
+    Log,
+}
+
+/// The "Inspector": the latest frame's stats, the GPU in use and the recent log lines,
+/// in a window inside the main one or detached into a window of its own.
+#[derive(Default)]
+pub struct Inspector {
+    pub open: bool,
+    pub detached: bool,
+    tab: Tab,
+    log: LogViewer,
+}
+
+impl Inspector {
+    /// Its own window, while it's open and detached.
+    pub fn window_spec(&self) -> Option<WindowSpec> {
+        (self.open && self.detached).then(|| WindowSpec::new(WINDOW_ID, "Inspector", [420, 520]))
+    }
+}
+
+/// The inspector inside the main window, unless it's detached.
+pub fn show(ctx: &egui::Context, app: &mut AppState) {
+    if app.inspector.detached {
+        return;
+    }
+    let mut open = app.inspector.open;
+    egui::Window::new("Inspector")
+        .open(&mut open)
+        .default_size([380.0, 420.0])
+        .show(ctx, |ui| contents(ui, app));
+    app.inspector.open = open;
+}
+
+/// The inspector filling its own window.
+pub fn show_detached(ctx: &egui::Context, app: &mut AppState) {
+    egui::CentralPanel::default().show(ctx, |ui| contents(ui, app));
+    if app.inspector.tab == Tab::Frame {
+        ctx.request_repaint_after(FRAME_REFRESH);
+    }
+}
+
+fn contents(ui: &mut egui::Ui, app: &mut AppState) {
+    let inspector = &mut app.inspector;
+    ui.horizontal(|ui| {
+        ui.selectable_value(&mut inspector.tab, Tab::Frame, "Frame");
+        ui.selectable_value(&mut inspector.tab, Tab::Gpu, "GPU");
+        ui.selectable_value(&mut inspector.tab, Tab::Log, "Log");
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            let (text, hover) = if inspector.detached {
+                ("Attach", "Move back into the main window")
+            } else {
+                ("Detach", "Move into a window of its own")
+            };
+            if ui.button(text).on_hover_text(hover).clicked() {
+                inspector.detached = !inspector.detached;
+            }
+        });
+    });
+    ui.separator();
+    match inspector.tab {
+        Tab::Frame => match app.stats.last() {
+            Some(last) => stats::record_grid(ui, "inspector frame", last),
+            None => {
+                ui.weak("No frame was drawn yet");
+            }
+        },
+        Tab::Gpu => gpu_tab(ui, app),
+        Tab::Log => inspector.log.ui(ui),
+    }
+}
+
+pub(crate) fn gpu_tab(ui: &mut egui::Ui, app: &AppState) {
+    let Some(adapter) = &app.current_adapter else {
+        ui.weak("No adapter yet");
+        return;
+    };
+    egui::Grid::new("inspector gpu").show(ui, |ui| {
+        let mut row = |name: &str, value: String| {
+            ui.label(name);
+            ui.monospace(value);
+            ui.end_row();
+        };
+        row("Adapter", adapter.name.clone());
+        row("Backend", format!("{:?}", adapter.backend));
+        row("Type", format!("{:?}", adapter.device_type));
+        row(
+            "Driver",
+            format!("{} {}", adapter.driver, adapter.driver_info),
+        );
+        row("Color space", format!("{:?}", app.color_space));
+        if let Some(present_mode) = app.active_present_mode {
+            row("Present mode", format!("{:?}", present_mode));
+        }
+        if let Some(samples) = app.scene_sample_count() {
+            row("Scene samples", samples.to_string());
+        }
+        row("Errors", app.gpu_errors.count().to_string());
+        let cached = |stats: CacheStats| {
+            format!(
+                "{} hits, {} misses, {} kept",
+                stats.hits, stats.misses, stats.entries
+            )
+        };
+        row("Bind groups", cached(app.gpu_cache.bind_group_stats()));
+        row("Samplers", cached(app.gpu_cache.sampler_stats()));
+        if let Some(capabilities) = &app.capabilities {
+            for (name, available) in capabilities.report() {
+                row(name, if available { "Yes" } else { "No" }.to_owned());
+            }
+            let compute = capabilities.compute_shaders();
+            row(
+                "Compute shaders",
+                if compute { "Yes" } else { "No" }.to_owned(),
+            );
+            let size = capabilities.limits.max_texture_dimension_2d;
+            row("Max texture size", size.to_string());
+        }
+    });
+}
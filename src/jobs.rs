@@ -0,0 +1,286 @@
+use crate::events::UserEvent;
+
+use winit::event_loop::EventLoopProxy;
+
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// The threads jobs run on.
+#[cfg(not(target_arch = "wasm32"))]
+const WORKERS: usize = 4;
+/// What the demo task counts the primes below.
+const PRIME_LIMIT: u32 = 2_000_000;
+
+/// A job in `Jobs`, from when it's submitted on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct JobId(u64);
+
+#[derive(Default)]
+struct JobState {
+    /// An `f32` from 0 to 1, in bits.
+    progress: AtomicU32,
+    cancelled: AtomicBool,
+    done: AtomicBool,
+}
+
+/// What a running job is handed, to report how far it got and to see if it should
+/// stop.
+pub struct JobContext {
+    state: Arc<JobState>,
+}
+
+impl JobContext {
+    pub fn set_progress(&self, fraction: f32) {
+        let fraction = fraction.clamp(0.0, 1.0);
+        self.state
+            .progress
+            .store(fraction.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Whether the job was cancelled, which it has to check for itself.
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+struct Running {
+    name: String,
+    state: Arc<JobState>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+type Task = Box<dyn FnOnce() + Send>;
+/// The page's proxy can't be sent, but nothing is.
+#[cfg(target_arch = "wasm32")]
+type Task = Box<dyn FnOnce()>;
+
+/// Runs closures on a pool of threads, posting what they return to the event loop.
+/// Jobs given to `spawn` come back as `UserEvent::JobFinished`, for `finish`, the
+/// app's own ones with `submit` as whatever event they make.
+#[derive(Default)]
+pub struct Jobs {
+    /// Shows the running jobs, and starts the demo task.
+    pub open: bool,
+    proxy: Option<EventLoopProxy<UserEvent>>,
+    next: u64,
+    running: BTreeMap<JobId, Running>,
+    /// The pool's queue, started with the first job.
+    #[cfg(not(target_arch = "wasm32"))]
+    queue: Option<std::sync::mpsc::Sender<Task>>,
+    demo: Option<JobId>,
+    demo_result: Option<String>,
+}
+
+impl Jobs {
+    pub fn set_proxy(&mut self, proxy: EventLoopProxy<UserEvent>) {
+        self.proxy = Some(proxy);
+    }
+
+    /// Runs `job`, sending the event it makes to the event loop once it's done.
+    /// Nothing is sent without an event loop, headless.
+    pub fn submit(
+        &mut self,
+        name: impl Into<String>,
+        job: impl FnOnce(&JobContext) -> UserEvent + Send + 'static,
+    ) -> JobId {
+        self.queue_job(name.into(), |_| job)
+    }
+
+    /// Runs `job`, its output coming back as `UserEvent::JobFinished`.
+    pub fn spawn<T: Send + 'static>(
+        &mut self,
+        name: impl Into<String>,
+        job: impl FnOnce(&JobContext) -> T + Send + 'static,
+    ) -> JobId {
+        self.queue_job(name.into(), |id| {
+            move |context: &JobContext| {
+                UserEvent::JobFinished(id, Box::new(job(context)) as Box<dyn Any + Send>)
+            }
+        })
+    }
+
+    fn queue_job<J>(&mut self, name: String, make: impl FnOnce(JobId) -> J) -> JobId
+    where
+        J: FnOnce(&JobContext) -> UserEvent + Send + 'static,
+    {
+        let id = JobId(self.next);
+        self.next += 1;
+        let state = Arc::new(JobState::default());
+        self.running.insert(
+            id,
+            Running {
+                name,
+                state: Arc::clone(&state),
+            },
+        );
+        let job = make(id);
+        let proxy = self.proxy.clone();
+        let task = move || {
+            let context = JobContext { state };
+            let event = job(&context);
+            context.state.done.store(true, Ordering::Relaxed);
+            if let Some(proxy) = proxy {
+                let _ = proxy.send_event(event);
+            }
+        };
+        self.run(Box::new(task));
+        id
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn run(&mut self, task: Task) {
+        let queue = self.queue.get_or_insert_with(|| {
+            let (sender, receiver) = std::sync::mpsc::channel::<Task>();
+            let receiver = Arc::new(std::sync::Mutex::new(receiver));
+            for i in 0..WORKERS {
+                let receiver = Arc::clone(&receiver);
+                let spawned = std::thread::Builder::new()
+                    .name(format!("jobs {}", i))
+                    .spawn(move || loop {
+                        // The lock is only held while waiting, not while running.
+                        let task = match receiver.lock() {
+                            Ok(receiver) => receiver.recv(),
+                            Err(_) => return,
+                        };
+                        // The queue is gone along with `Jobs`.
+                        let Ok(task) = task else {
+                            return;
+                        };
+                        task();
+                    });
+                if let Err(e) = spawned {
+                    tracing::warn!("Failed to start a job thread: {}", e);
+                }
+            }
+            sender
+        });
+        if queue.send(task).is_err() {
+            tracing::warn!("No job thread is left to run a job");
+        }
+    }
+
+    /// The browser has no threads to spare, so it's run right away.
+    #[cfg(target_arch = "wasm32")]
+    fn run(&mut self, task: Task) {
+        task();
+    }
+
+    /// Asks the job to stop. It's still running until it notices.
+    pub fn cancel(&self, id: JobId) {
+        if let Some(job) = self.running.get(&id) {
+            job.state.cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+
+    pub fn is_running(&self, id: JobId) -> bool {
+        self.running
+            .get(&id)
+            .is_some_and(|job| !job.state.done.load(Ordering::Relaxed))
+    }
+
+    /// How far a running job got, from 0 to 1.
+    pub fn progress(&self, id: JobId) -> Option<f32> {
+        let job = self.running.get(&id)?;
+        Some(f32::from_bits(job.state.progress.load(Ordering::Relaxed)))
+    }
+
+    /// Takes in the output of a job given to `spawn`, returning whether it was the
+    /// demo task's. The app's own are left for it to downcast.
+    pub fn finish(&mut self, id: JobId, output: &(dyn Any + Send)) -> bool {
+        let cancelled = self
+            .running
+            .remove(&id)
+            .is_some_and(|job| job.state.cancelled.load(Ordering::Relaxed));
+        if self.demo != Some(id) {
+            return false;
+        }
+        self.demo = None;
+        self.demo_result = Some(match output.downcast_ref::<u32>() {
+            Some(_) if cancelled => "Cancelled".to_owned(),
+            Some(primes) => format!("There are {} primes below {}", primes, PRIME_LIMIT),
+            None => "The task returned something else".to_owned(),
+        });
+        true
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        // Those made with `submit` are only known to be done by their flag.
+        self.running
+            .retain(|id, job| Some(*id) == self.demo || !job.state.done.load(Ordering::Relaxed));
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        let mut cancel = None;
+        let mut start = false;
+        egui::Window::new("Background tasks")
+            .open(&mut open)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let idle = self.demo.is_none();
+                    start = ui
+                        .add_enabled(idle, egui::Button::new("Count primes"))
+                        .on_hover_text(format!("Counts the primes below {}", PRIME_LIMIT))
+                        .clicked();
+                    if let Some(result) = &self.demo_result {
+                        ui.label(result);
+                    }
+                });
+                ui.separator();
+                if self.running.is_empty() {
+                    ui.weak("Nothing is running");
+                }
+                for (&id, job) in &self.running {
+                    ui.horizontal(|ui| {
+                        ui.label(&job.name);
+                        let progress = f32::from_bits(job.state.progress.load(Ordering::Relaxed));
+                        ui.add(
+                            egui::ProgressBar::new(progress)
+                                .desired_width(160.0)
+                                .show_percentage(),
+                        );
+                        let cancelled = job.state.cancelled.load(Ordering::Relaxed);
+                        if ui
+                            .add_enabled(!cancelled, egui::Button::new("Cancel"))
+                            .clicked()
+                        {
+                            cancel = Some(id);
+                        }
+                    });
+                }
+                if !self.running.is_empty() {
+                    // Only a job's end wakes the event loop.
+                    ctx.request_repaint();
+                }
+            });
+        if let Some(id) = cancel {
+            self.cancel(id);
+        }
+        if start {
+            self.demo_result = None;
+            self.demo = Some(self.spawn("Counting primes", count_primes));
+        }
+        self.open = open;
+    }
+}
+
+/// The demo task, slow enough to watch.
+fn count_primes(context: &JobContext) -> u32 {
+    let mut primes = 0;
+    for n in 2..PRIME_LIMIT {
+        if n % 10_000 == 0 {
+            if context.is_cancelled() {
+                break;
+            }
+            context.set_progress(n as f32 / PRIME_LIMIT as f32);
+        }
+        if (2..).take_while(|d| d * d <= n).all(|d| n % d != 0) {
+            primes += 1;
+        }
+    }
+    primes
+}
@@ -0,0 +1,233 @@
+use crate::gpu_memory::{Allocation, GpuMemory};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scaling {
+    /// Fills as much of the window as the aspect ratio allows.
+    #[default]
+    Fractional,
+    /// Only whole multiples of the virtual size, for crisp pixels. Falls back to
+    /// fractional when the window is smaller than the virtual size.
+    Integer,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LetterboxConfig {
+    /// Renders at a fixed virtual size instead of the window size.
+    pub enabled: bool,
+    /// The virtual size in pixels.
+    pub width: u32,
+    pub height: u32,
+    pub scaling: Scaling,
+    /// The color of the bars around the scaled frame, as RGB.
+    pub bar_color: [u8; 3],
+}
+
+impl Default for LetterboxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            width: 1280,
+            height: 720,
+            scaling: Scaling::Fractional,
+            bar_color: [0, 0, 0],
+        }
+    }
+}
+
+/// Renders the frame at a fixed virtual size and scales it into the window, keeping the
+/// aspect ratio with bars on the sides.
+///
+/// Everything that would draw to the surface draws into `target_view` instead, and
+/// egui sees a screen of the virtual size, so input has to be mapped with `map_input`.
+pub struct Letterbox {
+    config: LetterboxConfig,
+    window_size: [u32; 2],
+    pipeline: wgpu::RenderPipeline,
+    target_view: wgpu::TextureView,
+    _target_allocation: Allocation,
+    bind_group: wgpu::BindGroup,
+}
+
+impl Letterbox {
+    pub fn new(
+        device: &wgpu::Device,
+        memory: &GpuMemory,
+        config: &LetterboxConfig,
+        format: wgpu::TextureFormat,
+        window_size: [u32; 2],
+    ) -> Self {
+        let config = LetterboxConfig {
+            width: config.width.max(1),
+            height: config.height.max(1),
+            ..config.clone()
+        };
+
+        let target = wgpu::TextureDescriptor {
+            label: Some("letterbox target"),
+            size: wgpu::Extent3d {
+                width: config.width,
+                height: config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        };
+        let target_view = device
+            .create_texture(&target)
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let filter = match config.scaling {
+            Scaling::Integer => wgpu::FilterMode::Nearest,
+            Scaling::Fractional => wgpu::FilterMode::Linear,
+        };
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("letterbox"),
+            mag_filter: filter,
+            min_filter: filter,
+            ..Default::default()
+        });
+
+        let shader = device.create_shader_module(wgpu::include_wgsl!("shaders/letterbox.wgsl"));
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("letterbox"),
+            layout: None,
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(format.into())],
+            }),
+            multiview: None,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("letterbox"),
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&target_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+
+        Self {
+            config,
+            window_size,
+            pipeline,
+            target_view,
+            _target_allocation: memory.track_texture(&target),
+            bind_group,
+        }
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.window_size = [width, height];
+    }
+
+    /// The virtual size in pixels.
+    pub fn size(&self) -> [u32; 2] {
+        [self.config.width, self.config.height]
+    }
+
+    /// The texture the frame has to be rendered into.
+    pub fn target_view(&self) -> &wgpu::TextureView {
+        &self.target_view
+    }
+
+    /// The scale and the rect of the window, in pixels, the frame is shown in.
+    fn viewport(&self) -> (f32, egui::Rect) {
+        let [width, height] = self.size().map(|v| v as f32);
+        let [window_width, window_height] = self.window_size.map(|v| v as f32);
+        let fit = (window_width / width).min(window_height / height);
+        let scale = match self.config.scaling {
+            Scaling::Integer if fit >= 1.0 => fit.floor(),
+            Scaling::Integer | Scaling::Fractional => fit,
+        };
+        let size = egui::vec2(width, height) * scale;
+        let min = ((egui::vec2(window_width, window_height) - size) / 2.0).floor();
+        (scale, egui::Rect::from_min_size(min.to_pos2(), size))
+    }
+
+    /// Moves the input from window points to the points of the virtual screen.
+    pub fn map_input(&self, input: &mut egui::RawInput, pixels_per_point: f32) {
+        let [width, height] = self.size().map(|v| v as f32);
+        input.screen_rect = Some(egui::Rect::from_min_size(
+            egui::Pos2::ZERO,
+            egui::vec2(width, height) / pixels_per_point,
+        ));
+
+        let (scale, viewport) = self.viewport();
+        let offset = viewport.min.to_vec2() / pixels_per_point;
+        let map = |pos: &mut egui::Pos2| *pos = ((pos.to_vec2() - offset) / scale).to_pos2();
+        for event in &mut input.events {
+            match event {
+                egui::Event::PointerMoved(pos)
+                | egui::Event::PointerButton { pos, .. }
+                | egui::Event::Touch { pos, .. } => map(pos),
+                _ => {}
+            }
+        }
+    }
+
+    /// Moves a position from the points of the virtual screen back to window points.
+    pub fn to_window(&self, pos: egui::Pos2, pixels_per_point: f32) -> egui::Pos2 {
+        let (scale, viewport) = self.viewport();
+        (pos.to_vec2() * scale + viewport.min.to_vec2() / pixels_per_point).to_pos2()
+    }
+
+    /// Draws the frame scaled into the surface, filling the rest with the bar color.
+    pub fn present(&self, encoder: &mut wgpu::CommandEncoder, surface_view: &wgpu::TextureView) {
+        let [r, g, b] = self.config.bar_color;
+        let bar = egui::Rgba::from(egui::Color32::from_rgb(r, g, b));
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("letterbox"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: surface_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color {
+                        r: bar.r() as f64,
+                        g: bar.g() as f64,
+                        b: bar.b() as f64,
+                        a: 1.0,
+                    }),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        let (_, viewport) = self.viewport();
+        if viewport.width() < 1.0 || viewport.height() < 1.0 {
+            return;
+        }
+        pass.set_viewport(
+            viewport.min.x,
+            viewport.min.y,
+            viewport.width(),
+            viewport.height(),
+            0.0,
+            1.0,
+        );
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,456 @@
+//! A winit + wgpu + egui scaffold. Implement `Application` and hand it to `run`, the
+//! window, the surface and the egui integration are taken care of.
+
+#[cfg(target_os = "android")]
+mod android;
+mod app;
+mod assets;
+mod bezier;
+mod big_table;
+mod block_diagram;
+mod builder;
+mod camera;
+mod camera_path;
+mod capabilities;
+mod cli;
+mod clipboard;
+mod clipboard_image;
+mod color_space;
+mod commands;
+mod config;
+mod crash;
+mod data_table;
+mod debug_draw;
+mod deeplink;
+mod dock;
+mod drawing;
+mod dropped_files;
+mod ecs;
+mod ecs_inspector;
+mod error;
+mod events;
+mod file_dialog;
+mod fonts;
+mod fps_overlay;
+mod frame_dump;
+mod frame_limiter;
+mod fullscreen;
+mod gamepad;
+mod gfx;
+mod gizmo;
+mod gpu_cache;
+mod gpu_errors;
+mod gpu_memory;
+mod gpu_profiler;
+mod gui;
+mod hdr;
+mod headless;
+mod icon;
+mod inspector;
+mod jobs;
+mod letterbox;
+mod logging;
+mod material;
+mod measure;
+mod model;
+mod offscreen;
+mod palette;
+mod particles;
+mod perf_plots;
+mod pipeline;
+mod platform;
+mod pointer_lock;
+mod post;
+mod recorder;
+mod runtime;
+mod scene;
+mod screenshot;
+mod shader_reload;
+mod split_editor;
+mod state_machine;
+mod stats;
+#[cfg(any(test, feature = "testing"))]
+mod testing;
+mod texture_gen;
+mod textures;
+mod theme;
+mod title_bar;
+mod toasts;
+mod touch;
+mod tray;
+mod ui;
+mod update;
+mod viewport;
+mod watermark;
+mod widgets;
+mod window_geometry;
+mod windows;
+
+pub use assets::{AssetData, AssetId, AssetKind, Assets, LoadState};
+pub use builder::AppBuilder;
+pub use camera::{Camera, CameraMode};
+pub use capabilities::{Capabilities, OPTIONAL_FEATURES};
+pub use color_space::ColorSpace;
+pub use dock::DockTab;
+pub use drawing::DrawnStroke;
+pub use error::AppError;
+pub use events::UserEvent;
+pub use file_dialog::FileKind;
+pub use fonts::CustomFonts;
+pub use gamepad::{Gamepad, GamepadAxis, GamepadButton, GamepadEvent, Gamepads};
+pub use gfx::{GraphicsContext, SurfaceFrame, DEPTH_FORMAT};
+pub use gpu_cache::{CacheStats, GpuCache};
+pub use gpu_errors::{GpuError, GpuErrors};
+pub use gpu_memory::{Allocation, GpuMemory};
+pub use gpu_profiler::GpuPassTime;
+pub use hdr::HDR_FORMAT;
+pub use jobs::{JobContext, JobId, Jobs};
+pub use letterbox::LetterboxConfig;
+pub use model::{Material, ModelData, ModelImage, ModelNode, ModelVertex, Primitive};
+pub use pointer_lock::PointerLock;
+pub use post::{PostEffect, PostPass};
+pub use screenshot::Screenshot;
+pub use stats::Stage;
+#[cfg(any(test, feature = "testing"))]
+pub use testing::{find_text, key_event, UiDriver};
+pub use textures::{DecodedImage, TextureHandle, Textures};
+pub use theme::Theme;
+pub use title_bar::{title_bar, WindowAction};
+pub use tray::TrayEvent;
+pub use widgets::{ColorWheel, Knob, LevelMeter};
+pub use windows::WindowSpec;
+
+use app::AppState;
+use clap::Parser;
+use cli::Cli;
+use config::AppConfig;
+use egui_wgpu::renderer::ScreenDescriptor;
+use egui_wgpu::Renderer;
+use winit::{
+    event::Event,
+    event_loop::EventLoopProxy,
+    window::Window,
+};
+
+use std::path::Path;
+
+/// What the application gets to set itself up with, once everything is created.
+pub struct Setup<'a> {
+    /// `None` when rendering headless.
+    pub window: Option<&'a Window>,
+    pub adapter: &'a wgpu::Adapter,
+    /// Every adapter that can present to the window, for `Application::adapter`.
+    pub adapters: Vec<wgpu::AdapterInfo>,
+    /// The present modes the surface supports, for `Application::present_mode`.
+    pub present_modes: Vec<wgpu::PresentMode>,
+    pub device: &'a wgpu::Device,
+    pub queue: &'a wgpu::Queue,
+    /// What the device can do beyond the defaults.
+    pub capabilities: Capabilities,
+    /// Collects the device's errors, resources made here can be created in its scopes.
+    pub errors: &'a GpuErrors,
+    /// Counts the memory of the resources tracked with it, shown in the GPU memory window.
+    pub memory: &'a GpuMemory,
+    /// Hands out the same bind groups and samplers for the same resources.
+    pub cache: &'a GpuCache,
+    /// The egui renderer, paint callback resources go in its `callback_resources`.
+    pub renderer: &'a mut Renderer,
+    pub surface_format: wgpu::TextureFormat,
+    /// The format egui, and so its paint callbacks, draw in.
+    pub ui_format: wgpu::TextureFormat,
+    /// The color space the surface actually ended up in.
+    pub color_space: ColorSpace,
+    /// How the surface blends with what's behind the window. Anything but `Opaque`
+    /// means that what's left transparent of the frame shows the desktop, see
+    /// `AppBuilder::transparent`.
+    pub alpha_mode: wgpu::CompositeAlphaMode,
+    /// Samples per pixel of the target `Application::render` draws in. Paint callbacks
+    /// draw in egui's pass, which always has a single sample.
+    pub sample_count: u32,
+    /// For sending `UserEvent`s to the event loop from other threads, `None` when
+    /// rendering headless.
+    pub proxy: Option<EventLoopProxy<UserEvent>>,
+}
+
+/// The target the application renders to beneath the UI, already cleared.
+pub struct RenderFrame<'a> {
+    pub device: &'a wgpu::Device,
+    pub queue: &'a wgpu::Queue,
+    pub encoder: &'a mut wgpu::CommandEncoder,
+    pub errors: &'a GpuErrors,
+    pub memory: &'a GpuMemory,
+    /// For bind groups of resources that change with the frame, like targets
+    /// reallocated with the window.
+    pub cache: &'a GpuCache,
+    /// The egui renderer, for registering textures rendered here with egui.
+    pub renderer: &'a mut Renderer,
+    pub view: &'a wgpu::TextureView,
+    /// The depth buffer going with `view`, in `DEPTH_FORMAT` and cleared to 1.
+    pub depth: &'a wgpu::TextureView,
+    /// The surface's format, or `HDR_FORMAT` while there are `Application::post_passes`.
+    pub format: wgpu::TextureFormat,
+    /// Changes when `Application::sample_count` does, pipelines drawing here have to
+    /// be rebuilt to match.
+    pub sample_count: u32,
+    /// The size of `view` in pixels.
+    pub size: [u32; 2],
+    /// Stages what's written with `write_buffer`, finished and recalled around the
+    /// frame's submit.
+    pub belt: &'a mut wgpu::util::StagingBelt,
+}
+
+impl RenderFrame<'_> {
+    /// Like `Queue::write_buffer`, for what changes every frame: `data` is copied
+    /// through the belt's chunks, in the encoder ahead of what's recorded after, rather
+    /// than into a staging buffer the queue allocates each time.
+    pub fn write_buffer(&mut self, buffer: &wgpu::Buffer, offset: u64, data: &[u8]) {
+        let Some(size) = wgpu::BufferSize::new(data.len() as u64) else {
+            return;
+        };
+        self.belt
+            .write_buffer(self.encoder, buffer, offset, size, self.device)
+            .copy_from_slice(data);
+    }
+}
+
+/// What went into a frame, once it's presented.
+pub struct FrameReport<'a> {
+    pub paint_jobs: &'a [egui::ClippedPrimitive],
+    pub textures_delta: &'a egui::TexturesDelta,
+    pub screen_descriptor: &'a ScreenDescriptor,
+    /// Milliseconds spent in each stage, indexed like `Stage::ALL`.
+    pub stage_ms: [f32; Stage::ALL.len()],
+    pub present_mode: wgpu::PresentMode,
+    /// The GPU time of each pass, from a few frames back. `None` without timestamp
+    /// query support.
+    pub gpu_times: Option<&'a [GpuPassTime]>,
+    /// The frame as it was presented, when `Application::screenshot_requested` asked for
+    /// it, or why it couldn't be read back.
+    pub screenshot: Option<Result<&'a Screenshot, &'static str>>,
+}
+
+/// An app built on the scaffold. Only `ui` is required, the other hooks default to
+/// doing nothing.
+pub trait Application: 'static {
+    /// Called once before the first frame.
+    fn setup(&mut self, _setup: Setup<'_>) {}
+
+    /// Called after the device was lost and made again, along with egui's renderer.
+    /// Everything from the old device is unusable, and the callback resources are empty.
+    fn device_recreated(&mut self, _setup: Setup<'_>) {}
+
+    /// Called at the start of every frame, before `ui`.
+    fn update(&mut self, _window: &Window) {}
+
+    fn ui(&mut self, ctx: &egui::Context);
+
+    /// Looks at egui's output for the frame, before it's tessellated and applied to the
+    /// window.
+    fn end_frame(&mut self, _output: &egui::FullOutput) {}
+
+    /// Draws beneath the UI.
+    fn render(&mut self, _frame: RenderFrame<'_>) {}
+
+    /// Called once the frame was presented.
+    fn frame_done(&mut self, _report: &FrameReport<'_>) {}
+
+    /// Sees the events egui didn't consume first, besides those of the `windows`.
+    /// Returning true stops the scaffold from handling the event itself.
+    fn on_event(&mut self, _event: &Event<'_, UserEvent>, _window: &Window) -> bool {
+        false
+    }
+
+    /// Checked every frame, the surface is reconfigured when it changes. `None` keeps
+    /// what the `AppBuilder` was set up with.
+    fn present_mode(&self) -> Option<wgpu::PresentMode> {
+        None
+    }
+
+    /// Samples per pixel to render beneath the UI with, checked every frame. `None`
+    /// keeps what the `AppBuilder` was set up with.
+    fn sample_count(&self) -> Option<u32> {
+        None
+    }
+
+    /// The adapter to run on, checked every frame. Changing it recreates the device as
+    /// after a device loss. `None` keeps the one wgpu picked.
+    fn adapter(&self) -> Option<&wgpu::AdapterInfo> {
+        None
+    }
+
+    /// Fullscreen passes run in order over what's rendered beneath the UI, checked every
+    /// frame. With any, it's rendered into a `HDR_FORMAT` target first and may go beyond
+    /// 1.0.
+    fn post_passes(&self) -> Vec<PostPass> {
+        Vec::new()
+    }
+
+    /// Multiplies the OS's scale factor for egui, checked every frame.
+    fn ui_zoom(&self) -> f32 {
+        1.0
+    }
+
+    /// What the frame is cleared to before `render`, checked every frame.
+    fn clear_color(&self) -> wgpu::Color {
+        wgpu::Color::TRANSPARENT
+    }
+
+    /// The most frames a second to draw, checked every frame. `None` draws as fast as
+    /// the present mode allows.
+    fn fps_limit(&self) -> Option<u32> {
+        None
+    }
+
+    /// Checked every frame, returning true reads the frame back into
+    /// `FrameReport::screenshot`.
+    fn screenshot_requested(&mut self) -> bool {
+        false
+    }
+
+    /// Checked every frame, the presented frames are recorded into a GIF at the path
+    /// while it's `Some`. Once it's `None` again the file is finished, and the result
+    /// comes as `UserEvent::RecordingSaved`.
+    fn recording(&self) -> Option<&Path> {
+        None
+    }
+
+    /// The windows to have open besides the main one, checked every time the event
+    /// loop is done with the events it had. Each has its own egui context, drawn with
+    /// `window_ui`.
+    fn windows(&self) -> Vec<WindowSpec> {
+        Vec::new()
+    }
+
+    /// Draws the UI of the window `windows` listed as `id`.
+    fn window_ui(&mut self, _id: &str, _ctx: &egui::Context) {}
+
+    /// Called when the window listed as `id` is asked to close. It stays open until
+    /// `windows` no longer lists it.
+    fn window_closed(&mut self, _id: &str) {}
+
+    /// Called when the app is suspended and its surface dropped. Mobile platforms may
+    /// kill it from there without `exit` ever being called.
+    fn suspended(&mut self) {}
+
+    /// Checked after every batch of events, returning true closes the window as if the
+    /// user had, which `exit` is called for.
+    fn close_requested(&mut self) -> bool {
+        false
+    }
+
+    /// Called when the window is closed, before the event loop exits.
+    fn exit(&mut self) {}
+}
+
+/// Runs the application with the default `AppBuilder`. Never returns.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn run(app: impl Application) -> ! {
+    AppBuilder::new().run(app)
+}
+
+/// Runs the application with the default `AppBuilder`, in a canvas on the page.
+#[cfg(target_arch = "wasm32")]
+pub fn run(app: impl Application) {
+    AppBuilder::new().run(app)
+}
+
+/// The example itself: `AppState` run on the scaffold, with its command line handling.
+pub fn run_example() {
+    example(AppBuilder::new());
+}
+
+/// The entry point android-activity calls, running the example in the activity.
+#[cfg(target_os = "android")]
+#[no_mangle]
+fn android_main(app: winit::platform::android::activity::AndroidApp) {
+    example(AppBuilder::new().android_app(app));
+}
+
+fn example(builder: AppBuilder) {
+    logging::init();
+
+    let cli = Cli::parse();
+    if cli.register_url_scheme {
+        if let Err(e) = platform::register_url_scheme() {
+            tracing::error!(
+                "Failed to register the {}:// URL scheme: {}",
+                deeplink::SCHEME,
+                e
+            );
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // The OS passes the clicked link as argument, if the example is already running the
+    // link is handed over to it instead of opening a second window. Headless runs keep
+    // theirs.
+    let links: Vec<String> = cli
+        .links
+        .iter()
+        .filter(|arg| deeplink::is_link(arg))
+        .cloned()
+        .collect();
+    if !links.is_empty() && cli.headless.is_none() && platform::forward_to_running_instance(&links)
+    {
+        tracing::info!("Handed {} link(s) to the running instance", links.len());
+        return;
+    }
+
+    let config = AppConfig::load();
+    // Reports can't be written in the browser, so panics end up in its console, which
+    // the crash hook runs after its own attempt.
+    #[cfg(target_arch = "wasm32")]
+    console_error_panic_hook::set_once();
+    crash::install(&config);
+
+    let mut builder = builder
+        .backend(cli.backend.unwrap_or(wgpu::Backends::PRIMARY))
+        .compat(cli.compat)
+        .color_space(config.color_space)
+        .letterbox(config.letterbox.clone())
+        .theme(config.theme.unwrap_or_default())
+        .transparent(config.window.transparent)
+        .icon(icon::EXAMPLE_ICON)
+        .decorations(!config.window.custom_title_bar);
+    if let Some(title) = cli.title.as_ref().or(config.window.title.as_ref()) {
+        builder = builder.title(title);
+    }
+    if let Some([width, height]) = cli.size().or(config.window.size) {
+        builder = builder.size(width, height);
+    }
+    if let Some(fullscreen) = cli.fullscreen {
+        builder = builder.fullscreen(fullscreen);
+    }
+    if let Some(path) = &cli.bench_json {
+        builder = builder.bench_json(path);
+    }
+    let mut app = AppState::default();
+    app.crash_report = crash::CrashReport::find_unseen();
+    app.config = config;
+    app.load_saved();
+    app.apply_config_overrides();
+    if let Some(vsync) = cli.vsync {
+        app.settings.vsync = vsync;
+    }
+    if let Some(demo) = cli.demo {
+        app.show_demo = demo;
+    }
+    for link in &links {
+        app.handle_link(link);
+    }
+    if let Some(frames) = cli.headless {
+        match builder.run_headless(app, frames) {
+            Ok(paths) => tracing::info!("Rendered {} frames", paths.len()),
+            Err(e) => {
+                tracing::error!("Rendering headless failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    builder
+        .vsync(app.settings.vsync)
+        .msaa(app.settings.msaa)
+        .run(app);
+}
@@ -0,0 +1,152 @@
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::sync::Mutex;
+
+/// How many of the latest log lines are kept around for crash reports and the log
+/// viewer.
+pub const RECENT_LINES: usize = 200;
+
+/// Prints the lines and keeps the latest around, as the last layer of the subscriber.
+struct Recorder;
+
+static RECENT: Mutex<VecDeque<(Level, String)>> = Mutex::new(VecDeque::new());
+
+/// Called after every line, from whatever thread logged it.
+static LISTENER: Mutex<Option<Box<dyn Fn() + Send>>> = Mutex::new(None);
+
+impl<S: Subscriber> Layer<S> for Recorder {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut message = Message::default();
+        event.record(&mut message);
+        let line = format!(
+            "[{} {}] {}",
+            metadata.level(),
+            metadata.target(),
+            message.text
+        );
+        #[cfg(not(target_arch = "wasm32"))]
+        eprintln!("{}", line);
+        #[cfg(target_arch = "wasm32")]
+        web_sys::console::log_1(&line.as_str().into());
+
+        let mut recent = RECENT.lock().unwrap_or_else(|e| e.into_inner());
+        if recent.len() == RECENT_LINES {
+            recent.pop_front();
+        }
+        recent.push_back((*metadata.level(), line));
+        drop(recent);
+
+        // Not blocking keeps whatever the listener logs itself from deadlocking.
+        if let Ok(listener) = LISTENER.try_lock() {
+            if let Some(listener) = listener.as_ref() {
+                listener();
+            }
+        }
+    }
+}
+
+/// An event's message followed by its other fields, leaving out those `log` records
+/// forwarded by `tracing-log` come with.
+#[derive(Default)]
+struct Message {
+    text: String,
+}
+
+impl Visit for Message {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.text, "{:?}", value);
+        } else if !field.name().starts_with("log.") {
+            let _ = write!(self.text, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+/// Installs the subscriber: the example's own lines from info level up, and only
+/// warnings and errors from dependencies, which are very chatty at info level. The
+/// `log` records of wgpu and naga go through it as well.
+pub fn init() {
+    let filter = Targets::new()
+        .with_default(Level::WARN)
+        .with_target(env!("CARGO_CRATE_NAME"), Level::INFO);
+    let subscriber = tracing_subscriber::registry().with(filter).with(Recorder);
+    if tracing::subscriber::set_global_default(subscriber).is_ok() {
+        let _ = tracing_log::LogTracer::builder()
+            .with_max_level(tracing_log::log::LevelFilter::Info)
+            .init();
+    }
+}
+
+/// Calls `listener` after each line is logged, in place of the last one set.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn set_listener(listener: impl Fn() + Send + 'static) {
+    *LISTENER.lock().unwrap_or_else(|e| e.into_inner()) = Some(Box::new(listener));
+}
+
+/// Calls `f` with each of the latest log lines and their level, oldest first.
+///
+/// Gives up instead of blocking if the lines are locked, so that it's safe to call
+/// from a panic hook, even when the panic happened while logging.
+pub fn for_each_recent(mut f: impl FnMut(Level, &str)) {
+    if let Ok(recent) = RECENT.try_lock() {
+        for (level, line) in recent.iter() {
+            f(*level, line);
+        }
+    }
+}
+
+/// The latest log lines, of the levels and with the text the user chose.
+pub struct LogViewer {
+    /// The least severe level shown.
+    level: Level,
+    filter: String,
+}
+
+impl Default for LogViewer {
+    fn default() -> Self {
+        Self {
+            level: Level::INFO,
+            filter: String::new(),
+        }
+    }
+}
+
+impl LogViewer {
+    /// The filters and the lines they let through.
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source("log level")
+                .selected_text(self.level.as_str())
+                .show_ui(ui, |ui| {
+                    for level in [Level::ERROR, Level::WARN, Level::INFO, Level::DEBUG] {
+                        ui.selectable_value(&mut self.level, level, level.as_str());
+                    }
+                });
+            ui.add(egui::TextEdit::singleline(&mut self.filter).hint_text("Filter"));
+        });
+        ui.separator();
+        egui::ScrollArea::vertical()
+            .auto_shrink([false; 2])
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                for_each_recent(|level, line| {
+                    // Levels compare by verbosity, ERROR being the least.
+                    if level > self.level || !line.contains(self.filter.as_str()) {
+                        return;
+                    }
+                    let text = egui::RichText::new(line).monospace();
+                    match level {
+                        Level::ERROR => ui.colored_label(ui.visuals().error_fg_color, text),
+                        Level::WARN => ui.colored_label(ui.visuals().warn_fg_color, text),
+                        _ => ui.label(text),
+                    };
+                });
+            });
+    }
+}
@@ -0,0 +1,567 @@
+use egui::Color32;
+
+use std::path::Path;
+use std::sync::Arc;
+
+const PREVIEW_SIZE: f32 = 256.0;
+
+/// The standard PBR parameters, in the metallic-roughness model.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Material {
+    pub base_color: Color32,
+    pub metallic: f32,
+    pub roughness: f32,
+    pub emissive: Color32,
+    pub emissive_strength: f32,
+    pub normal_scale: f32,
+    pub ao_strength: f32,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self {
+            base_color: Color32::from_rgb(200, 60, 50),
+            metallic: 0.0,
+            roughness: 0.5,
+            emissive: Color32::BLACK,
+            emissive_strength: 1.0,
+            normal_scale: 1.0,
+            ao_strength: 1.0,
+        }
+    }
+}
+
+impl Material {
+    fn uniforms(&self, rotation: f32) -> MaterialUniforms {
+        let linear = |c: Color32| {
+            let rgba = egui::Rgba::from(c);
+            [rgba.r(), rgba.g(), rgba.b()]
+        };
+        let [r, g, b] = linear(self.base_color);
+        let [er, eg, eb] = linear(self.emissive);
+        MaterialUniforms {
+            base_color: [r, g, b, 1.0],
+            emissive: [er, eg, eb, self.emissive_strength],
+            metallic: self.metallic,
+            roughness: self.roughness,
+            normal_scale: self.normal_scale,
+            ao_strength: self.ao_strength,
+            rotation,
+            gamma_output: 0.0,
+            _pad: [0.0; 2],
+        }
+    }
+}
+
+/// The properties a texture can be assigned to, in the order of the shader's bindings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Slot {
+    BaseColor,
+    Metallic,
+    Roughness,
+    Emissive,
+    Normal,
+    Occlusion,
+}
+
+impl Slot {
+    const ALL: [Slot; 6] = [
+        Slot::BaseColor,
+        Slot::Metallic,
+        Slot::Roughness,
+        Slot::Emissive,
+        Slot::Normal,
+        Slot::Occlusion,
+    ];
+
+    /// Colors are sampled from sRGB textures, everything else is stored linearly.
+    fn format(self) -> wgpu::TextureFormat {
+        match self {
+            Slot::BaseColor | Slot::Emissive => wgpu::TextureFormat::Rgba8UnormSrgb,
+            _ => wgpu::TextureFormat::Rgba8Unorm,
+        }
+    }
+
+    /// What the shader samples without a texture: white, or a flat normal.
+    fn fallback(self) -> egui::ColorImage {
+        let color = match self {
+            Slot::Normal => Color32::from_rgb(128, 128, 255),
+            _ => Color32::WHITE,
+        };
+        egui::ColorImage::new([1, 1], color)
+    }
+}
+
+/// An image assigned to a slot. The generation tells the renderer it changed.
+#[derive(Clone)]
+struct SlotTexture {
+    generation: u64,
+    image: Arc<egui::ColorImage>,
+    thumbnail: egui::TextureHandle,
+    path: String,
+}
+
+/// A window editing a `Material`, with a preview sphere drawn by `MaterialResources`.
+///
+/// The preview follows the edits as they're made, "Apply" copies them to the material's
+/// uniform buffer, the one scene passes bind.
+pub struct MaterialEditor {
+    pub open: bool,
+    material: Material,
+    applied: Material,
+    /// Set by "Apply", the next frame's callback writes the uniform buffer.
+    apply: bool,
+    textures: [Option<SlotTexture>; 6],
+    next_generation: u64,
+    texture_path: String,
+    rotate: bool,
+    rotation: f32,
+    status: Option<String>,
+}
+
+impl Default for MaterialEditor {
+    fn default() -> Self {
+        Self {
+            open: false,
+            material: Material::default(),
+            applied: Material::default(),
+            apply: true,
+            textures: Default::default(),
+            next_generation: 1,
+            texture_path: "noise.png".to_owned(),
+            rotate: true,
+            rotation: 0.0,
+            status: None,
+        }
+    }
+}
+
+impl MaterialEditor {
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        let mut open = self.open;
+        egui::Window::new("Material editor")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal_top(|ui| {
+                    ui.vertical(|ui| self.properties_ui(ui));
+                    self.preview_ui(ui);
+                });
+            });
+        self.open = open;
+    }
+
+    fn properties_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Texture file");
+            ui.text_edit_singleline(&mut self.texture_path);
+        });
+        egui::Grid::new("material_grid")
+            .num_columns(3)
+            .show(ui, |ui| {
+                let m = &mut self.material;
+                ui.label("Base color");
+                ui.color_edit_button_srgba(&mut m.base_color);
+                self.slot_ui(ui, Slot::BaseColor);
+                ui.end_row();
+
+                let m = &mut self.material;
+                ui.label("Metallic");
+                ui.add(
+                    egui::DragValue::new(&mut m.metallic)
+                        .speed(0.01)
+                        .clamp_range(0.0..=1.0),
+                );
+                self.slot_ui(ui, Slot::Metallic);
+                ui.end_row();
+
+                let m = &mut self.material;
+                ui.label("Roughness");
+                ui.add(
+                    egui::DragValue::new(&mut m.roughness)
+                        .speed(0.01)
+                        .clamp_range(0.0..=1.0),
+                );
+                self.slot_ui(ui, Slot::Roughness);
+                ui.end_row();
+
+                let m = &mut self.material;
+                ui.label("Emissive");
+                ui.color_edit_button_srgba(&mut m.emissive);
+                self.slot_ui(ui, Slot::Emissive);
+                ui.end_row();
+
+                let m = &mut self.material;
+                ui.label("Emissive strength");
+                ui.add(
+                    egui::DragValue::new(&mut m.emissive_strength)
+                        .speed(0.05)
+                        .clamp_range(0.0..=100.0),
+                );
+                ui.end_row();
+
+                ui.label("Normal scale");
+                ui.add(
+                    egui::DragValue::new(&mut m.normal_scale)
+                        .speed(0.01)
+                        .clamp_range(0.0..=4.0),
+                );
+                self.slot_ui(ui, Slot::Normal);
+                ui.end_row();
+
+                let m = &mut self.material;
+                ui.label("AO strength");
+                ui.add(
+                    egui::DragValue::new(&mut m.ao_strength)
+                        .speed(0.01)
+                        .clamp_range(0.0..=1.0),
+                );
+                self.slot_ui(ui, Slot::Occlusion);
+                ui.end_row();
+            });
+
+        ui.horizontal(|ui| {
+            let changed = self.material != self.applied;
+            if ui
+                .add_enabled(changed, egui::Button::new("Apply"))
+                .clicked()
+            {
+                self.applied = self.material;
+                self.apply = true;
+            }
+            if ui
+                .add_enabled(changed, egui::Button::new("Revert"))
+                .clicked()
+            {
+                self.material = self.applied;
+            }
+            if changed {
+                ui.weak("Unapplied changes");
+            }
+        });
+        ui.checkbox(&mut self.rotate, "Rotate the preview");
+        if let Some(status) = &self.status {
+            ui.weak(status);
+        }
+    }
+
+    /// The texture button of a property: its thumbnail, or a button to assign one.
+    fn slot_ui(&mut self, ui: &mut egui::Ui, slot: Slot) {
+        let index = slot as usize;
+        ui.horizontal(|ui| match &self.textures[index] {
+            Some(texture) => {
+                ui.image((texture.thumbnail.id(), egui::vec2(20.0, 20.0)))
+                    .on_hover_text(&texture.path);
+                if ui
+                    .small_button("✖")
+                    .on_hover_text("Remove the texture")
+                    .clicked()
+                {
+                    self.textures[index] = None;
+                }
+            }
+            None => {
+                if ui
+                    .small_button("Texture…")
+                    .on_hover_text("Assign the texture file")
+                    .clicked()
+                {
+                    self.assign(ui.ctx(), slot);
+                }
+            }
+        });
+    }
+
+    fn assign(&mut self, ctx: &egui::Context, slot: Slot) {
+        match load_png(Path::new(&self.texture_path)) {
+            Ok(image) => {
+                let thumbnail = ctx.load_texture(
+                    format!("material_{:?}", slot),
+                    image.clone(),
+                    Default::default(),
+                );
+                self.textures[slot as usize] = Some(SlotTexture {
+                    generation: self.next_generation,
+                    image: Arc::new(image),
+                    thumbnail,
+                    path: self.texture_path.clone(),
+                });
+                self.next_generation += 1;
+                self.status = None;
+            }
+            Err(e) => self.status = Some(format!("Couldn't load {}: {}", self.texture_path, e)),
+        }
+    }
+
+    fn preview_ui(&mut self, ui: &mut egui::Ui) {
+        if self.rotate {
+            self.rotation =
+                (self.rotation + ui.input(|i| i.stable_dt) * 0.5) % std::f32::consts::TAU;
+            ui.ctx().request_repaint();
+        }
+        let (rect, _) =
+            ui.allocate_exact_size(egui::Vec2::splat(PREVIEW_SIZE), egui::Sense::hover());
+        // egui sets the viewport to the callback rect, and the sphere would get squashed
+        // if that were clipped to the screen, so it's only drawn while entirely visible.
+        if !ui.ctx().screen_rect().contains_rect(rect) {
+            return;
+        }
+        let callback = MaterialCallback {
+            preview: self.material.uniforms(self.rotation),
+            applied: std::mem::take(&mut self.apply).then(|| self.applied.uniforms(0.0)),
+            textures: std::array::from_fn(|i| {
+                self.textures[i]
+                    .as_ref()
+                    .map(|t| (t.generation, Arc::clone(&t.image)))
+            }),
+        };
+        ui.painter()
+            .add(egui_wgpu::Callback::new_paint_callback(rect, callback));
+    }
+}
+
+fn load_png(path: &Path) -> std::io::Result<egui::ColorImage> {
+    let mut decoder = png::Decoder::new(std::io::BufReader::new(std::fs::File::open(path)?));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(std::io::Error::other)?;
+    let mut data = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut data)
+        .map_err(std::io::Error::other)?;
+    let size = [info.width as usize, info.height as usize];
+    let data = &data[..info.buffer_size()];
+    Ok(match info.color_type {
+        png::ColorType::Rgba => egui::ColorImage::from_rgba_unmultiplied(size, data),
+        png::ColorType::Rgb => egui::ColorImage::from_rgb(size, data),
+        png::ColorType::Grayscale => {
+            let rgb: Vec<u8> = data.iter().flat_map(|&v| [v, v, v]).collect();
+            egui::ColorImage::from_rgb(size, &rgb)
+        }
+        png::ColorType::GrayscaleAlpha => {
+            let rgba: Vec<u8> = data
+                .chunks_exact(2)
+                .flat_map(|c| [c[0], c[0], c[0], c[1]])
+                .collect();
+            egui::ColorImage::from_rgba_unmultiplied(size, &rgba)
+        }
+        png::ColorType::Indexed => unreachable!("expanded by normalize_to_color8"),
+    })
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct MaterialUniforms {
+    base_color: [f32; 4],
+    emissive: [f32; 4],
+    metallic: f32,
+    roughness: f32,
+    normal_scale: f32,
+    ao_strength: f32,
+    rotation: f32,
+    gamma_output: f32,
+    _pad: [f32; 2],
+}
+
+/// The preview pipeline and the applied material, kept in the egui renderer's callback
+/// resources.
+pub struct MaterialResources {
+    pipeline: wgpu::RenderPipeline,
+    preview: wgpu::Buffer,
+    /// The applied material, for the scene's passes to bind.
+    material: wgpu::Buffer,
+    sampler: wgpu::Sampler,
+    textures: [(u64, wgpu::TextureView); 6],
+    bind_group: wgpu::BindGroup,
+    /// False when the target isn't sRGB, so the output has to be gamma encoded.
+    srgb_target: bool,
+}
+
+impl MaterialResources {
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue, format: wgpu::TextureFormat) -> Self {
+        let shader = device.create_shader_module(wgpu::include_wgsl!("shaders/material.wgsl"));
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("material preview"),
+            layout: None,
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+        let uniform_buffer = |label| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size: std::mem::size_of::<MaterialUniforms>() as u64,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            })
+        };
+        let preview = uniform_buffer("material preview");
+        let material = uniform_buffer("material");
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("material"),
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let textures =
+            Slot::ALL.map(|slot| (0, create_texture(device, queue, slot, &slot.fallback())));
+        let bind_group = create_bind_group(device, &pipeline, &preview, &sampler, &textures);
+        Self {
+            pipeline,
+            preview,
+            material,
+            sampler,
+            textures,
+            bind_group,
+            srgb_target: format.is_srgb(),
+        }
+    }
+}
+
+fn create_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    slot: Slot,
+    image: &egui::ColorImage,
+) -> wgpu::TextureView {
+    let size = wgpu::Extent3d {
+        width: image.size[0] as u32,
+        height: image.size[1] as u32,
+        depth_or_array_layers: 1,
+    };
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("material slot"),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: slot.format(),
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+    let data: Vec<u8> = image.pixels.iter().flat_map(|c| c.to_array()).collect();
+    queue.write_texture(
+        texture.as_image_copy(),
+        &data,
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(4 * size.width),
+            rows_per_image: None,
+        },
+        size,
+    );
+    texture.create_view(&Default::default())
+}
+
+fn create_bind_group(
+    device: &wgpu::Device,
+    pipeline: &wgpu::RenderPipeline,
+    uniforms: &wgpu::Buffer,
+    sampler: &wgpu::Sampler,
+    textures: &[(u64, wgpu::TextureView); 6],
+) -> wgpu::BindGroup {
+    let mut entries = vec![
+        wgpu::BindGroupEntry {
+            binding: 0,
+            resource: uniforms.as_entire_binding(),
+        },
+        wgpu::BindGroupEntry {
+            binding: 1,
+            resource: wgpu::BindingResource::Sampler(sampler),
+        },
+    ];
+    entries.extend(
+        textures
+            .iter()
+            .enumerate()
+            .map(|(i, (_, view))| wgpu::BindGroupEntry {
+                binding: 2 + i as u32,
+                resource: wgpu::BindingResource::TextureView(view),
+            }),
+    );
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("material preview"),
+        layout: &pipeline.get_bind_group_layout(0),
+        entries: &entries,
+    })
+}
+
+struct MaterialCallback {
+    preview: MaterialUniforms,
+    /// The material to write to the applied buffer, after "Apply".
+    applied: Option<MaterialUniforms>,
+    /// The image and generation of every slot with a texture.
+    textures: [Option<(u64, Arc<egui::ColorImage>)>; 6],
+}
+
+impl egui_wgpu::CallbackTrait for MaterialCallback {
+    fn prepare(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        _egui_encoder: &mut wgpu::CommandEncoder,
+        callback_resources: &mut egui_wgpu::CallbackResources,
+    ) -> Vec<wgpu::CommandBuffer> {
+        let Some(resources) = callback_resources.get_mut::<MaterialResources>() else {
+            return Vec::new();
+        };
+        let mut changed = false;
+        for (slot, texture) in Slot::ALL.into_iter().zip(&self.textures) {
+            let generation = texture.as_ref().map_or(0, |(generation, _)| *generation);
+            let current = &mut resources.textures[slot as usize];
+            if current.0 != generation {
+                let fallback = slot.fallback();
+                let image = texture.as_ref().map_or(&fallback, |(_, image)| &**image);
+                *current = (generation, create_texture(device, queue, slot, image));
+                changed = true;
+            }
+        }
+        if changed {
+            resources.bind_group = create_bind_group(
+                device,
+                &resources.pipeline,
+                &resources.preview,
+                &resources.sampler,
+                &resources.textures,
+            );
+        }
+
+        let mut preview = self.preview;
+        preview.gamma_output = if resources.srgb_target { 0.0 } else { 1.0 };
+        queue.write_buffer(&resources.preview, 0, bytemuck::bytes_of(&preview));
+        if let Some(applied) = &self.applied {
+            queue.write_buffer(&resources.material, 0, bytemuck::bytes_of(applied));
+        }
+        Vec::new()
+    }
+
+    fn paint<'a>(
+        &'a self,
+        _info: egui::PaintCallbackInfo,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        callback_resources: &'a egui_wgpu::CallbackResources,
+    ) {
+        let Some(resources) = callback_resources.get::<MaterialResources>() else {
+            return;
+        };
+        render_pass.set_pipeline(&resources.pipeline);
+        render_pass.set_bind_group(0, &resources.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,129 @@
+use egui::{Color32, Key, KeyboardShortcut, Modifiers, Order, Pos2, Rect, Sense, Stroke};
+
+/// How close to an edge, in points, the ruler endpoints snap to it.
+const SNAP_DISTANCE: f32 = 6.0;
+
+const TOGGLE: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::M);
+
+/// An on-screen ruler for checking spacing, toggled with Ctrl+M.
+///
+/// While active every drag adds a measurement, endpoints snap to the edges of windows
+/// and other areas and Escape clears everything.
+#[derive(Default)]
+pub struct MeasureTool {
+    pub active: bool,
+    measurements: Vec<[Pos2; 2]>,
+}
+
+impl MeasureTool {
+    /// `native_pixels_per_point` is the OS scale factor, used to report CSS pixels.
+    pub fn show(&mut self, ctx: &egui::Context, native_pixels_per_point: f32) {
+        if ctx.input_mut(|i| i.consume_shortcut(&TOGGLE)) {
+            self.active = !self.active;
+        }
+        if !self.active {
+            return;
+        }
+        if ctx.input_mut(|i| i.consume_key(Modifiers::NONE, Key::Escape)) {
+            self.measurements.clear();
+        }
+
+        let edges = snap_edges(ctx);
+        let screen_rect = ctx.screen_rect();
+        egui::Area::new("measure_tool")
+            .order(Order::Foreground)
+            .fixed_pos(screen_rect.min)
+            .show(ctx, |ui| {
+                // Cover the whole screen so drags measure instead of reaching the widgets.
+                let response = ui.allocate_rect(screen_rect, Sense::drag());
+                if let Some(pos) = response.interact_pointer_pos() {
+                    let pos = snap(pos, &edges);
+                    if response.drag_started() {
+                        self.measurements.push([pos, pos]);
+                    } else if let Some(last) = self.measurements.last_mut() {
+                        last[1] = pos;
+                    }
+                }
+
+                let painter = ui.painter();
+                painter.text(
+                    screen_rect.left_top() + egui::vec2(8.0, 8.0),
+                    egui::Align2::LEFT_TOP,
+                    "Measuring: drag to add a ruler, Esc to clear, Ctrl+M to exit",
+                    egui::FontId::proportional(14.0),
+                    Color32::YELLOW,
+                );
+
+                let pixels_per_point = ctx.pixels_per_point();
+                for &[a, b] in &self.measurements {
+                    let stroke = Stroke::new(1.5, Color32::YELLOW);
+                    painter.line_segment([a, b], stroke);
+
+                    // Endpoint markers, perpendicular to the ruler.
+                    let dir = if a == b {
+                        egui::Vec2::X
+                    } else {
+                        (b - a).normalized()
+                    };
+                    let normal = egui::vec2(-dir.y, dir.x) * 6.0;
+                    for end in [a, b] {
+                        painter.line_segment([end - normal, end + normal], stroke);
+                        painter.circle_filled(end, 2.5, Color32::YELLOW);
+                    }
+
+                    let points = (b - a).length();
+                    let pixels = points * pixels_per_point;
+                    let css = pixels / native_pixels_per_point;
+                    let label = format!("{:.1} pt | {:.0} px | {:.1} css px", points, pixels, css);
+                    let galley = painter.layout_no_wrap(
+                        label,
+                        egui::FontId::monospace(12.0),
+                        Color32::BLACK,
+                    );
+                    let rect = Rect::from_center_size(
+                        a + (b - a) * 0.5 + normal * 3.0,
+                        galley.size() + egui::vec2(6.0, 2.0),
+                    );
+                    painter.rect_filled(rect, 2.0, Color32::YELLOW);
+                    painter.galley(rect.min + egui::vec2(3.0, 1.0), galley);
+                }
+            });
+    }
+}
+
+/// The x and y coordinates of the edges of every area.
+///
+/// egui only keeps the rects of whole layers (windows, popups, ...) around between
+/// frames, so those and the screen edges are what we can snap to.
+fn snap_edges(ctx: &egui::Context) -> (Vec<f32>, Vec<f32>) {
+    let mut xs = Vec::new();
+    let mut ys = Vec::new();
+    let mut add = |rect: Rect| {
+        xs.extend([rect.min.x, rect.max.x]);
+        ys.extend([rect.min.y, rect.max.y]);
+    };
+    add(ctx.screen_rect());
+    add(ctx.available_rect());
+    ctx.memory(|m| {
+        for layer in m.layer_ids() {
+            if layer.id != egui::Id::new("measure_tool") {
+                if let Some(rect) = m.area_rect(layer.id) {
+                    add(rect);
+                }
+            }
+        }
+    });
+    (xs, ys)
+}
+
+fn snap(pos: Pos2, (xs, ys): &(Vec<f32>, Vec<f32>)) -> Pos2 {
+    let nearest = |v: f32, edges: &[f32]| {
+        edges
+            .iter()
+            .copied()
+            .filter(|e| (e - v).abs() <= SNAP_DISTANCE)
+            .min_by(|a, b| (a - v).abs().total_cmp(&(b - v).abs()))
+            .unwrap_or(v)
+    };
+    Pos2::new(nearest(pos.x, xs), nearest(pos.y, ys))
+}
@@ -0,0 +1,729 @@
+use crate::gpu_cache::GpuCache;
+use crate::hdr;
+use crate::pipeline::{Pipeline, PipelineConfig};
+use crate::textures;
+use crate::{RenderFrame, DEPTH_FORMAT};
+
+use glam::{Mat4, Vec3};
+use wgpu::util::DeviceExt;
+
+use std::path::Path;
+
+/// Whether models can be opened, which takes the `gltf` feature.
+pub const AVAILABLE: bool = cfg!(feature = "gltf");
+/// How large the model is drawn, the longest side of its bounds scaled to this.
+const FIT_SIZE: f32 = 2.0;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ModelVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub uv: [f32; 2],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct CameraUniforms {
+    view_proj: [[f32; 4]; 4],
+    encode_srgb: f32,
+    _pad: [f32; 3],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct DrawUniforms {
+    model: [[f32; 4]; 4],
+    /// The inverse transpose of `model`, which keeps normals square to scaled surfaces.
+    normal: [[f32; 4]; 4],
+    base_color: [f32; 4],
+}
+
+/// Triangles drawn with one material.
+#[derive(Clone, Debug)]
+pub struct Primitive {
+    pub vertices: Vec<ModelVertex>,
+    pub indices: Vec<u32>,
+    pub material: Option<usize>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Material {
+    /// Linear RGBA, multiplied with the texture.
+    pub base_color: [f32; 4],
+    /// An index into `ModelData::images`.
+    pub texture: Option<usize>,
+}
+
+/// An image in sRGB RGBA8.
+#[derive(Clone, Debug)]
+pub struct ModelImage {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ModelNode {
+    pub name: String,
+    /// Relative to the parent.
+    pub transform: Mat4,
+    pub children: Vec<usize>,
+    /// An index into `ModelData::meshes`.
+    pub mesh: Option<usize>,
+    /// Hiding a node hides its children as well.
+    pub visible: bool,
+}
+
+/// A model's scene as read from a glTF file, before it's uploaded.
+#[derive(Clone, Debug)]
+pub struct ModelData {
+    pub name: String,
+    pub nodes: Vec<ModelNode>,
+    pub roots: Vec<usize>,
+    pub meshes: Vec<Vec<Primitive>>,
+    pub materials: Vec<Material>,
+    pub images: Vec<ModelImage>,
+}
+
+impl ModelData {
+    /// Reads the default scene of a glTF or GLB file, `path` being where buffers and
+    /// images it refers to are looked for.
+    #[cfg(feature = "gltf")]
+    pub fn parse(path: &Path, bytes: &[u8]) -> Result<Self, String> {
+        let gltf = gltf::Gltf::from_slice(bytes).map_err(|e| e.to_string())?;
+        let base = path.parent();
+        let buffers = gltf::import_buffers(&gltf.document, base, gltf.blob.clone())
+            .map_err(|e| e.to_string())?;
+        let images =
+            gltf::import_images(&gltf.document, base, &buffers).map_err(|e| e.to_string())?;
+        let document = gltf.document;
+
+        let meshes = document
+            .meshes()
+            .map(|mesh| {
+                mesh.primitives()
+                    .filter(|primitive| primitive.mode() == gltf::mesh::Mode::Triangles)
+                    .filter_map(|primitive| {
+                        let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+                        let positions: Vec<[f32; 3]> = reader.read_positions()?.collect();
+                        let indices: Vec<u32> = match reader.read_indices() {
+                            Some(indices) => indices.into_u32().collect(),
+                            None => (0..positions.len() as u32).collect(),
+                        };
+                        if indices.is_empty() {
+                            return None;
+                        }
+                        let normals: Vec<[f32; 3]> = match reader.read_normals() {
+                            Some(normals) => normals.collect(),
+                            None => face_normals(&positions, &indices),
+                        };
+                        let uvs: Vec<[f32; 2]> = match reader.read_tex_coords(0) {
+                            Some(uvs) => uvs.into_f32().collect(),
+                            None => vec![[0.0; 2]; positions.len()],
+                        };
+                        let vertices = positions
+                            .iter()
+                            .zip(normals.iter().zip(&uvs))
+                            .map(|(&position, (&normal, &uv))| ModelVertex {
+                                position,
+                                normal,
+                                uv,
+                            })
+                            .collect();
+                        Some(Primitive {
+                            vertices,
+                            indices,
+                            material: primitive.material().index(),
+                        })
+                    })
+                    .collect()
+            })
+            .collect();
+        let materials = document
+            .materials()
+            .map(|material| {
+                let pbr = material.pbr_metallic_roughness();
+                Material {
+                    base_color: pbr.base_color_factor(),
+                    texture: pbr
+                        .base_color_texture()
+                        .map(|info| info.texture().source().index()),
+                }
+            })
+            .collect();
+        let images = images.into_iter().map(image_of).collect();
+        let nodes = document
+            .nodes()
+            .map(|node| ModelNode {
+                name: node
+                    .name()
+                    .map_or_else(|| format!("Node {}", node.index()), str::to_owned),
+                transform: Mat4::from_cols_array_2d(&node.transform().matrix()),
+                children: node.children().map(|child| child.index()).collect(),
+                mesh: node.mesh().map(|mesh| mesh.index()),
+                visible: true,
+            })
+            .collect();
+        let roots = document
+            .default_scene()
+            .or_else(|| document.scenes().next())
+            .map(|scene| scene.nodes().map(|node| node.index()).collect())
+            .unwrap_or_default();
+        Ok(Self {
+            name: path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            nodes,
+            roots,
+            meshes,
+            materials,
+            images,
+        })
+    }
+
+    #[cfg(not(feature = "gltf"))]
+    pub fn parse(_path: &Path, _bytes: &[u8]) -> Result<Self, String> {
+        Err("Built without the gltf feature".to_owned())
+    }
+
+    /// Each node with its transform from the model's root, and whether it's visible
+    /// along with all its parents, in the order of `nodes`.
+    pub fn world(&self) -> Vec<Option<(Mat4, bool)>> {
+        let mut world = vec![None; self.nodes.len()];
+        let mut stack: Vec<_> = self
+            .roots
+            .iter()
+            .map(|&root| (root, Mat4::IDENTITY, true))
+            .collect();
+        while let Some((index, parent, parent_visible)) = stack.pop() {
+            // glTF forbids cycles, but a broken file shouldn't hang the app.
+            let Some(node) = self.nodes.get(index).filter(|_| world[index].is_none()) else {
+                continue;
+            };
+            let transform = parent * node.transform;
+            let visible = parent_visible && node.visible;
+            world[index] = Some((transform, visible));
+            stack.extend(
+                node.children
+                    .iter()
+                    .map(|&child| (child, transform, visible)),
+            );
+        }
+        world
+    }
+
+    /// Scales and moves the model's bounds to `FIT_SIZE` about the origin.
+    fn fit(&self) -> Mat4 {
+        let mut min = Vec3::splat(f32::INFINITY);
+        let mut max = Vec3::splat(f32::NEG_INFINITY);
+        for (node, world) in self.nodes.iter().zip(self.world()) {
+            let (Some(mesh), Some((transform, _))) = (node.mesh, world) else {
+                continue;
+            };
+            for primitive in self.meshes.get(mesh).into_iter().flatten() {
+                for vertex in &primitive.vertices {
+                    let position = transform.transform_point3(vertex.position.into());
+                    min = min.min(position);
+                    max = max.max(position);
+                }
+            }
+        }
+        let size = (max - min).max_element();
+        if !size.is_finite() || size <= 0.0 {
+            return Mat4::IDENTITY;
+        }
+        Mat4::from_scale(Vec3::splat(FIT_SIZE / size)) * Mat4::from_translation(-(min + max) / 2.0)
+    }
+}
+
+/// Normals of the faces each vertex is part of, for primitives that have none.
+#[cfg(feature = "gltf")]
+fn face_normals(positions: &[[f32; 3]], indices: &[u32]) -> Vec<[f32; 3]> {
+    let mut normals = vec![Vec3::ZERO; positions.len()];
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| triangle[i] as usize);
+        let (Some(&pa), Some(&pb), Some(&pc)) =
+            (positions.get(a), positions.get(b), positions.get(c))
+        else {
+            continue;
+        };
+        let (pa, pb, pc) = (Vec3::from(pa), Vec3::from(pb), Vec3::from(pc));
+        let normal = (pb - pa).cross(pc - pa);
+        for i in [a, b, c] {
+            normals[i] += normal;
+        }
+    }
+    normals
+        .into_iter()
+        .map(|normal| normal.normalize_or_zero().into())
+        .collect()
+}
+
+/// The 8 bit images as RGBA, those with more bits being left white.
+#[cfg(feature = "gltf")]
+fn image_of(data: gltf::image::Data) -> ModelImage {
+    use gltf::image::Format;
+
+    let channels = match data.format {
+        Format::R8 => 1,
+        Format::R8G8 => 2,
+        Format::R8G8B8 => 3,
+        Format::R8G8B8A8 => 4,
+        format => {
+            tracing::warn!("Leaving out a {:?} texture", format);
+            return ModelImage {
+                width: 1,
+                height: 1,
+                rgba: vec![255; 4],
+            };
+        }
+    };
+    let rgba = data
+        .pixels
+        .chunks_exact(channels)
+        .flat_map(|pixel| match *pixel {
+            [l] => [l, l, l, 255],
+            [l, a] => [l, l, l, a],
+            [r, g, b] => [r, g, b, 255],
+            [r, g, b, a] => [r, g, b, a],
+            _ => unreachable!(),
+        })
+        .collect();
+    ModelImage {
+        width: data.width,
+        height: data.height,
+        rgba,
+    }
+}
+
+struct GpuPrimitive {
+    vertices: wgpu::Buffer,
+    indices: wgpu::Buffer,
+    index_count: u32,
+}
+
+/// A primitive of a node's mesh with that node's transform and the primitive's material.
+struct Draw {
+    node: usize,
+    mesh: usize,
+    primitive: usize,
+    bind_group: wgpu::BindGroup,
+}
+
+/// A `ModelData` in wgpu buffers and textures, drawn in the 3D scene's pass.
+pub struct GpuModel {
+    pipeline: Pipeline,
+    camera: wgpu::Buffer,
+    camera_group: wgpu::BindGroup,
+    meshes: Vec<Vec<GpuPrimitive>>,
+    draws: Vec<Draw>,
+    srgb_target: bool,
+}
+
+impl GpuModel {
+    /// Its sampler comes from `cache`, models sample alike.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+        depth_format: Option<wgpu::TextureFormat>,
+        data: &ModelData,
+        wgsl: &str,
+        cache: &GpuCache,
+    ) -> Self {
+        let pipeline = Pipeline::new(
+            device,
+            &PipelineConfig {
+                label: "model",
+                wgsl,
+                vertex_layout: wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<ModelVertex>() as u64,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![
+                        0 => Float32x3,
+                        1 => Float32x3,
+                        2 => Float32x2,
+                    ],
+                },
+                format,
+                sample_count,
+                // Materials can be double sided, and the depth buffer sorts it out.
+                cull_mode: None,
+                depth_format,
+                blend: None,
+            },
+        );
+        let camera = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("model camera"),
+            size: std::mem::size_of::<CameraUniforms>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let camera_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("model camera"),
+            layout: &pipeline.bind_group_layout(0),
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: camera.as_entire_binding(),
+            }],
+        });
+
+        let meshes = data
+            .meshes
+            .iter()
+            .map(|primitives| {
+                primitives
+                    .iter()
+                    .map(|primitive| GpuPrimitive {
+                        vertices: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                            label: Some("model vertices"),
+                            contents: bytemuck::cast_slice(&primitive.vertices),
+                            usage: wgpu::BufferUsages::VERTEX,
+                        }),
+                        indices: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                            label: Some("model indices"),
+                            contents: bytemuck::cast_slice(&primitive.indices),
+                            usage: wgpu::BufferUsages::INDEX,
+                        }),
+                        index_count: primitive.indices.len() as u32,
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let white = ModelImage {
+            width: 1,
+            height: 1,
+            rgba: vec![255; 4],
+        };
+        let textures: Vec<_> = data
+            .images
+            .iter()
+            .chain(std::iter::once(&white))
+            .map(|image| upload(device, queue, image))
+            .collect();
+        let sampler = cache.sampler(
+            device,
+            &wgpu::SamplerDescriptor {
+                label: Some("model"),
+                address_mode_u: wgpu::AddressMode::Repeat,
+                address_mode_v: wgpu::AddressMode::Repeat,
+                mag_filter: wgpu::FilterMode::Linear,
+                min_filter: wgpu::FilterMode::Linear,
+                ..Default::default()
+            },
+        );
+        let draw_layout = pipeline.bind_group_layout(1);
+        let fit = data.fit();
+        let mut draws = Vec::new();
+        for (node, world) in data.nodes.iter().enumerate().zip(data.world()) {
+            let ((index, node), Some((transform, _))) = (node, world) else {
+                continue;
+            };
+            let Some(mesh) = node.mesh.filter(|&mesh| mesh < data.meshes.len()) else {
+                continue;
+            };
+            let model = fit * transform;
+            for (primitive, part) in data.meshes[mesh].iter().enumerate() {
+                let material = part.material.and_then(|i| data.materials.get(i));
+                let base_color = material.map_or([1.0; 4], |material| material.base_color);
+                let texture = material
+                    .and_then(|material| material.texture)
+                    .and_then(|i| textures.get(i))
+                    .unwrap_or(&textures[textures.len() - 1]);
+                let uniforms = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("model draw"),
+                    contents: bytemuck::bytes_of(&DrawUniforms {
+                        model: model.to_cols_array_2d(),
+                        normal: model.inverse().transpose().to_cols_array_2d(),
+                        base_color,
+                    }),
+                    usage: wgpu::BufferUsages::UNIFORM,
+                });
+                let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("model draw"),
+                    layout: &draw_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: uniforms.as_entire_binding(),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::Sampler(&sampler),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 2,
+                            resource: wgpu::BindingResource::TextureView(texture),
+                        },
+                    ],
+                });
+                draws.push(Draw {
+                    node: index,
+                    mesh,
+                    primitive,
+                    bind_group,
+                });
+            }
+        }
+
+        Self {
+            pipeline,
+            camera,
+            camera_group,
+            meshes,
+            draws,
+            srgb_target: hdr::is_linear(format),
+        }
+    }
+
+    /// Draws the nodes of `data`, which this was made from, that are visible, into a
+    /// target that was already cleared.
+    pub fn render(
+        &self,
+        frame: &mut RenderFrame<'_>,
+        view: &wgpu::TextureView,
+        depth: Option<&wgpu::TextureView>,
+        view_proj: Mat4,
+        data: &ModelData,
+    ) {
+        let uniforms = CameraUniforms {
+            view_proj: view_proj.to_cols_array_2d(),
+            encode_srgb: if self.srgb_target { 0.0 } else { 1.0 },
+            _pad: [0.0; 3],
+        };
+        frame.write_buffer(&self.camera, 0, bytemuck::bytes_of(&uniforms));
+        let world = data.world();
+
+        let mut pass = frame
+            .encoder
+            .begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("model"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: depth.map(|view| {
+                    wgpu::RenderPassDepthStencilAttachment {
+                        view,
+                        depth_ops: Some(wgpu::Operations {
+                            load: wgpu::LoadOp::Load,
+                            store: true,
+                        }),
+                        stencil_ops: None,
+                    }
+                }),
+            });
+        pass.set_pipeline(self.pipeline.render_pipeline());
+        pass.set_bind_group(0, &self.camera_group, &[]);
+        for draw in &self.draws {
+            if !world
+                .get(draw.node)
+                .copied()
+                .flatten()
+                .is_some_and(|(_, visible)| visible)
+            {
+                continue;
+            }
+            let primitive = &self.meshes[draw.mesh][draw.primitive];
+            pass.set_bind_group(1, &draw.bind_group, &[]);
+            pass.set_vertex_buffer(0, primitive.vertices.slice(..));
+            pass.set_index_buffer(primitive.indices.slice(..), wgpu::IndexFormat::Uint32);
+            pass.draw_indexed(0..primitive.index_count, 0, 0..1);
+        }
+    }
+}
+
+fn upload(device: &wgpu::Device, queue: &wgpu::Queue, image: &ModelImage) -> wgpu::TextureView {
+    let max_side = device.limits().max_texture_dimension_2d;
+    let shrunk = textures::shrink(&image.rgba, image.width, image.height, max_side);
+    let (rgba, width, height) = match &shrunk {
+        Some((rgba, width, height)) => (&rgba[..], *width, *height),
+        None => (&image.rgba[..], image.width, image.height),
+    };
+    let texture = device.create_texture_with_data(
+        queue,
+        &wgpu::TextureDescriptor {
+            label: Some("model texture"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        },
+        rgba,
+    );
+    texture.create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+/// The model opened last, drawn in place of the cube, and a window with its nodes.
+#[derive(Default)]
+pub struct ModelViewer {
+    pub open: bool,
+    pub data: Option<ModelData>,
+    gpu: Option<GpuModel>,
+    /// The format and sample count `gpu` was made for.
+    target: Option<(wgpu::TextureFormat, u32)>,
+    /// An edit of `model.wgsl` to draw with instead.
+    shader: Option<String>,
+}
+
+impl ModelViewer {
+    /// Shows `data` in place of what was open, uploading it with the next frame.
+    pub fn set(&mut self, data: ModelData) {
+        self.data = Some(data);
+        self.gpu = None;
+        self.open = true;
+    }
+
+    pub fn close(&mut self) {
+        self.data = None;
+        self.gpu = None;
+    }
+
+    /// Draws with `wgsl`, an edit of `model.wgsl`, from the next `render` on.
+    pub fn set_shader(&mut self, wgsl: String) {
+        self.shader = Some(wgsl);
+        self.gpu = None;
+    }
+
+    /// Drops what was uploaded, for after the device it was made with was lost. The
+    /// model is uploaded again on the next `render`.
+    pub fn forget_gpu(&mut self) {
+        self.gpu = None;
+    }
+
+    /// Draws the model if there's one, uploading it first. Returns whether it drew.
+    pub fn render(&mut self, frame: &mut RenderFrame<'_>, view_proj: Mat4) -> bool {
+        let Some(data) = &self.data else {
+            return false;
+        };
+        let target = (frame.format, frame.sample_count);
+        if self.target != Some(target) {
+            self.gpu = None;
+        }
+        let shader = self
+            .shader
+            .as_deref()
+            .unwrap_or(include_str!("shaders/model.wgsl"));
+        let gpu = self.gpu.get_or_insert_with(|| {
+            frame.errors.scope(frame.device, "model", || {
+                GpuModel::new(
+                    frame.device,
+                    frame.queue,
+                    frame.format,
+                    frame.sample_count,
+                    Some(DEPTH_FORMAT),
+                    data,
+                    shader,
+                    frame.cache,
+                )
+            })
+        });
+        self.target = Some(target);
+        let (view, depth) = (frame.view, frame.depth);
+        gpu.render(frame, view, Some(depth), view_proj, data);
+        true
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        let mut close = false;
+        egui::Window::new("Model")
+            .open(&mut open)
+            .default_width(280.0)
+            .show(ctx, |ui| {
+                let Some(data) = &mut self.data else {
+                    ui.weak(if AVAILABLE {
+                        "Open a glTF or GLB file from the File menu"
+                    } else {
+                        "Built without the gltf feature"
+                    });
+                    return;
+                };
+                ui.horizontal(|ui| {
+                    ui.strong(&data.name);
+                    close = ui.small_button("Close").clicked();
+                });
+                ui.separator();
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for root in data.roots.clone() {
+                        node_ui(ui, data, root, 0);
+                    }
+                });
+            });
+        if close {
+            self.close();
+        }
+        self.open = open;
+    }
+}
+
+/// A node with a checkbox for showing it, and its children beneath. `depth` stops a
+/// broken file's cycles.
+fn node_ui(ui: &mut egui::Ui, data: &mut ModelData, index: usize, depth: usize) {
+    if depth > data.nodes.len() {
+        return;
+    }
+    let Some(node) = data.nodes.get_mut(index) else {
+        return;
+    };
+    let children = node.children.clone();
+    if children.is_empty() {
+        ui.checkbox(&mut node.visible, node.name.as_str());
+        return;
+    }
+    let id = ui.make_persistent_id(("model node", index));
+    egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, depth == 0)
+        .show_header(ui, |ui| {
+            ui.checkbox(&mut node.visible, node.name.as_str());
+        })
+        .body(|ui| {
+            for child in children {
+                node_ui(ui, data, child, depth + 1);
+            }
+        });
+}
+
+#[cfg(all(test, feature = "gltf"))]
+mod tests {
+    use super::*;
+
+    /// A red triangle in a child node, its buffer in a data URI.
+    const TRIANGLE: &str = r#"{"asset":{"version":"2.0"},"scene":0,"scenes":[{"nodes":[0]}],"nodes":[{"name":"Root","children":[1]},{"name":"Triangle","mesh":0,"translation":[0,0,1]}],"meshes":[{"primitives":[{"attributes":{"POSITION":0},"material":0}]}],"materials":[{"pbrMetallicRoughness":{"baseColorFactor":[1,0,0,1]}}],"accessors":[{"bufferView":0,"componentType":5126,"count":3,"type":"VEC3","min":[0,0,0],"max":[1,1,0]}],"bufferViews":[{"buffer":0,"byteLength":36}],"buffers":[{"byteLength":36,"uri":"data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAA"}]}"#;
+
+    #[test]
+    fn a_gltf_file_is_read_into_nodes_meshes_and_materials() {
+        let data = ModelData::parse(Path::new("triangle.gltf"), TRIANGLE.as_bytes()).unwrap();
+        assert_eq!(data.roots, [0]);
+        assert_eq!(data.nodes[0].children, [1]);
+        assert_eq!(data.nodes[1].name, "Triangle");
+        let primitive = &data.meshes[0][0];
+        assert_eq!(primitive.indices, [0, 1, 2]);
+        // Without normals of its own, the face's is used.
+        assert_eq!(primitive.vertices[0].normal, [0.0, 0.0, 1.0]);
+        assert_eq!(data.materials[0].base_color, [1.0, 0.0, 0.0, 1.0]);
+
+        let (transform, visible) = data.world()[1].unwrap();
+        assert!(visible);
+        assert_eq!(transform.transform_point3(Vec3::ZERO), Vec3::Z);
+    }
+}
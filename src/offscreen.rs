@@ -0,0 +1,145 @@
+use crate::gpu_memory::Allocation;
+use crate::scene::CubeScene;
+use crate::RenderFrame;
+
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+/// The offscreen texture and the id egui knows it by.
+struct Target {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    id: egui::TextureId,
+    _allocation: Allocation,
+}
+
+/// The cube scene rendered into a texture of its own, shown as an `egui::Image` in a
+/// resizable window. The texture follows the window's size, a frame late since it's
+/// rendered after the UI.
+#[derive(Default)]
+pub struct OffscreenView {
+    pub open: bool,
+    /// The size the image has in the window, in pixels.
+    wanted: [u32; 2],
+    target: Option<Target>,
+    scene: Option<CubeScene>,
+}
+
+impl OffscreenView {
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        let mut open = self.open;
+        egui::Window::new("Offscreen render")
+            .open(&mut open)
+            .default_size([320.0, 240.0])
+            .show(ctx, |ui| {
+                let size = ui.available_size().max(egui::vec2(32.0, 32.0));
+                let pixels = size * ctx.pixels_per_point();
+                self.wanted = [pixels.x.round() as u32, pixels.y.round() as u32];
+                match &self.target {
+                    Some(target) => {
+                        ui.image((target.id, size));
+                    }
+                    None => {
+                        ui.allocate_ui(size, |ui| ui.spinner());
+                    }
+                }
+            });
+        self.open = open;
+    }
+
+    /// Drops the texture and the scene, for after the device they were made with was
+    /// lost. Both are made again on the next `render`.
+    pub fn forget_textures(&mut self) {
+        self.target = None;
+        self.scene = None;
+    }
+
+    /// Renders the scene `time` seconds in, reallocating the texture first if the window
+    /// changed size.
+    pub fn render(&mut self, frame: &mut RenderFrame<'_>, time: f32) {
+        if !self.open || self.wanted.contains(&0) {
+            return;
+        }
+        let scene = self
+            .scene
+            .get_or_insert_with(|| CubeScene::new(frame.device, frame.queue, FORMAT, 1, None));
+
+        let stale = self
+            .target
+            .as_ref()
+            .is_none_or(|target| [target.texture.width(), target.texture.height()] != self.wanted);
+        if stale {
+            let desc = wgpu::TextureDescriptor {
+                label: Some("offscreen"),
+                size: wgpu::Extent3d {
+                    width: self.wanted[0],
+                    height: self.wanted[1],
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: FORMAT,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            };
+            let texture = frame.errors.scope(frame.device, "offscreen target", || {
+                frame.device.create_texture(&desc)
+            });
+            let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+            let filter = wgpu::FilterMode::Linear;
+            // The id stays the same across reallocations, so the UI keeps showing it.
+            let id = match &self.target {
+                Some(target) => {
+                    frame.renderer.update_egui_texture_from_wgpu_texture(
+                        frame.device,
+                        &view,
+                        filter,
+                        target.id,
+                    );
+                    target.id
+                }
+                None => frame
+                    .renderer
+                    .register_native_texture(frame.device, &view, filter),
+            };
+            self.target = Some(Target {
+                texture,
+                view,
+                id,
+                _allocation: frame.memory.track_texture(&desc),
+            });
+        }
+        let Some(target) = &self.target else {
+            return;
+        };
+
+        let clear_pass = frame
+            .encoder
+            .begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("offscreen clear"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &target.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color {
+                            r: 0.1,
+                            g: 0.1,
+                            b: 0.12,
+                            a: 1.0,
+                        }),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+        drop(clear_pass);
+        scene.render(
+            frame,
+            &target.view,
+            None,
+            CubeScene::camera(self.wanted[0] as f32 / self.wanted[1].max(1) as f32),
+            CubeScene::spin(time),
+        );
+    }
+}
@@ -0,0 +1,649 @@
+use egui::{Color32, Id, Sense, Ui};
+
+use std::fmt;
+use std::path::Path;
+
+/// The most swatches a single palette can hold.
+pub const MAX_SWATCHES: usize = 64;
+
+const SWATCH_SIZE: f32 = 24.0;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Swatch {
+    pub name: String,
+    pub color: Color32,
+}
+
+/// A named list of color swatches.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Palette {
+    pub name: String,
+    pub swatches: Vec<Swatch>,
+}
+
+#[derive(Debug)]
+pub enum PaletteError {
+    Io(std::io::Error),
+    Toml(String),
+    Format(String),
+    UnsupportedExtension(String),
+    /// The file has more than `MAX_SWATCHES`, rather than losing the rest quietly.
+    TooManySwatches,
+}
+
+impl fmt::Display for PaletteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaletteError::Io(e) => write!(f, "{}", e),
+            PaletteError::Toml(e) => write!(f, "invalid palette: {}", e),
+            PaletteError::Format(e) => write!(f, "invalid palette file: {}", e),
+            PaletteError::UnsupportedExtension(ext) => {
+                write!(f, "unsupported palette extension '{}'", ext)
+            }
+            PaletteError::TooManySwatches => write!(
+                f,
+                "the palette has more than the {} swatches one can hold",
+                MAX_SWATCHES
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PaletteError {}
+
+impl From<std::io::Error> for PaletteError {
+    fn from(e: std::io::Error) -> Self {
+        PaletteError::Io(e)
+    }
+}
+
+impl Palette {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            swatches: Vec::new(),
+        }
+    }
+
+    /// Adds a swatch, returns false if the palette is already full. A name that's taken
+    /// gets a number after it, as swatches are saved by name.
+    pub fn push(&mut self, name: impl Into<String>, color: Color32) -> bool {
+        if self.swatches.len() >= MAX_SWATCHES {
+            return false;
+        }
+        let name = self.unique_name(name.into(), None);
+        self.swatches.push(Swatch { name, color });
+        true
+    }
+
+    /// `push` for the parsers, where a full palette means the file doesn't fit.
+    fn push_read(&mut self, name: impl Into<String>, color: Color32) -> Result<(), PaletteError> {
+        if self.push(name, color) {
+            Ok(())
+        } else {
+            Err(PaletteError::TooManySwatches)
+        }
+    }
+
+    /// Renames the swatch at `index`, numbered like in `push` if another has the name.
+    pub fn rename(&mut self, index: usize, name: String) {
+        let name = self.unique_name(name, Some(index));
+        if let Some(swatch) = self.swatches.get_mut(index) {
+            swatch.name = name;
+        }
+    }
+
+    fn is_taken(&self, name: &str, except: Option<usize>) -> bool {
+        self.swatches
+            .iter()
+            .enumerate()
+            .any(|(i, swatch)| Some(i) != except && swatch.name == name)
+    }
+
+    /// `name`, or `name` and the lowest number from 2 that no swatch but `except` has.
+    fn unique_name(&self, name: String, except: Option<usize>) -> String {
+        if !self.is_taken(&name, except) {
+            return name;
+        }
+        (2..)
+            .map(|n| format!("{} {}", name, n).trim_start().to_owned())
+            .find(|numbered| !self.is_taken(numbered, except))
+            .unwrap()
+    }
+
+    /// "Color 1" and on, the first that isn't taken.
+    fn next_color_name(&self) -> String {
+        (self.swatches.len() + 1..)
+            .map(|n| format!("Color {}", n))
+            .find(|name| !self.is_taken(name, None))
+            .unwrap()
+    }
+
+    /// Serializes to a TOML document mapping each swatch name to its hex color.
+    pub fn to_toml(&self) -> String {
+        let mut swatches = toml::Table::new();
+        for swatch in &self.swatches {
+            swatches.insert(swatch.name.clone(), to_hex(swatch.color).into());
+        }
+        let mut root = toml::Table::new();
+        root.insert("name".to_owned(), self.name.clone().into());
+        root.insert("swatches".to_owned(), swatches.into());
+        root.to_string()
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, PaletteError> {
+        let root: toml::Table = text
+            .parse()
+            .map_err(|e: toml::de::Error| PaletteError::Toml(e.to_string()))?;
+        let mut palette = Palette::new(root.get("name").and_then(|v| v.as_str()).unwrap_or(""));
+        if let Some(swatches) = root.get("swatches").and_then(|v| v.as_table()) {
+            for (name, value) in swatches {
+                let color = value
+                    .as_str()
+                    .and_then(from_hex)
+                    .ok_or_else(|| PaletteError::Toml(format!("bad color for '{}'", name)))?;
+                palette.push_read(name.clone(), color)?;
+            }
+        }
+        Ok(palette)
+    }
+
+    /// Serializes to the GIMP palette format.
+    pub fn to_gpl(&self) -> String {
+        let mut out = format!("GIMP Palette\nName: {}\nColumns: 8\n#\n", self.name);
+        for swatch in &self.swatches {
+            let [r, g, b, _] = swatch.color.to_array();
+            out += &format!("{:3} {:3} {:3}\t{}\n", r, g, b, swatch.name);
+        }
+        out
+    }
+
+    pub fn from_gpl(text: &str) -> Result<Self, PaletteError> {
+        let mut lines = text.lines();
+        if lines.next().map(str::trim) != Some("GIMP Palette") {
+            return Err(PaletteError::Format(
+                "missing 'GIMP Palette' header".to_owned(),
+            ));
+        }
+
+        let mut palette = Palette::default();
+        for line in lines {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with("Columns:") {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix("Name:") {
+                palette.name = name.trim().to_owned();
+                continue;
+            }
+
+            let mut fields = line.split_whitespace();
+            let mut channel = || -> Result<u8, PaletteError> {
+                fields
+                    .next()
+                    .and_then(|f| f.parse().ok())
+                    .ok_or_else(|| PaletteError::Format(format!("bad color line '{}'", line)))
+            };
+            let (r, g, b) = (channel()?, channel()?, channel()?);
+            let name = fields.collect::<Vec<_>>().join(" ");
+            palette.push_read(name, Color32::from_rgb(r, g, b))?;
+        }
+        Ok(palette)
+    }
+
+    /// Parses an Adobe Color Swatch file, preferring the named version 2 section.
+    pub fn from_aco(bytes: &[u8]) -> Result<Self, PaletteError> {
+        let mut reader = AcoReader { bytes, pos: 0 };
+        let mut palette = Palette::default();
+
+        let mut version = reader.u16()?;
+        if version == 1 {
+            let count = reader.u16()?;
+            for i in 0..count {
+                if let Some(color) = reader.color()? {
+                    palette.push_read(format!("Color {}", i + 1), color)?;
+                }
+            }
+            // Files usually carry a version 2 section with names after the version 1 one.
+            if reader.pos >= bytes.len() {
+                return Ok(palette);
+            }
+            version = reader.u16()?;
+        }
+        if version != 2 {
+            return Err(PaletteError::Format(format!(
+                "unknown ACO version {}",
+                version
+            )));
+        }
+
+        palette.swatches.clear();
+        let count = reader.u16()?;
+        for _ in 0..count {
+            let color = reader.color()?;
+            let name = reader.name()?;
+            if let Some(color) = color {
+                palette.push_read(name, color)?;
+            }
+        }
+        Ok(palette)
+    }
+
+    /// Loads a palette, choosing the format from the file extension.
+    pub fn load(path: &Path) -> Result<Self, PaletteError> {
+        let mut palette = match extension(path).as_str() {
+            "toml" => Palette::from_toml(&std::fs::read_to_string(path)?)?,
+            "gpl" => Palette::from_gpl(&std::fs::read_to_string(path)?)?,
+            "aco" => Palette::from_aco(&std::fs::read(path)?)?,
+            ext => return Err(PaletteError::UnsupportedExtension(ext.to_owned())),
+        };
+        if palette.name.is_empty() {
+            palette.name = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default();
+        }
+        Ok(palette)
+    }
+
+    /// Saves a palette as TOML or GIMP palette, depending on the file extension.
+    pub fn save(&self, path: &Path) -> Result<(), PaletteError> {
+        let text = match extension(path).as_str() {
+            "toml" => self.to_toml(),
+            "gpl" => self.to_gpl(),
+            ext => return Err(PaletteError::UnsupportedExtension(ext.to_owned())),
+        };
+        Ok(std::fs::write(path, text)?)
+    }
+}
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .map(|e| e.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default()
+}
+
+pub fn to_hex(color: Color32) -> String {
+    let [r, g, b, _] = color.to_array();
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+pub fn from_hex(hex: &str) -> Option<Color32> {
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    Some(Color32::from_rgb(channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// Big endian reader for the ACO format.
+struct AcoReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl AcoReader<'_> {
+    fn u16(&mut self) -> Result<u16, PaletteError> {
+        let bytes = self
+            .bytes
+            .get(self.pos..self.pos + 2)
+            .ok_or_else(|| PaletteError::Format("unexpected end of file".to_owned()))?;
+        self.pos += 2;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    /// Reads a color record, returns `None` for color spaces we can't convert.
+    fn color(&mut self) -> Result<Option<Color32>, PaletteError> {
+        let space = self.u16()?;
+        let w = [self.u16()?, self.u16()?, self.u16()?, self.u16()?];
+        let unit = |v: u16| v as f32 / 65535.0;
+        let byte = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let color = match space {
+            // RGB
+            0 => Some(Color32::from_rgb(
+                (w[0] / 257) as u8,
+                (w[1] / 257) as u8,
+                (w[2] / 257) as u8,
+            )),
+            // HSB
+            1 => {
+                let rgba = egui::Rgba::from(egui::ecolor::Hsva::new(
+                    unit(w[0]),
+                    unit(w[1]),
+                    unit(w[2]),
+                    1.0,
+                ));
+                Some(rgba.into())
+            }
+            // CMYK, stored inverted (0 is full ink)
+            2 => {
+                let k = unit(w[3]);
+                Some(Color32::from_rgb(
+                    byte(unit(w[0]) * k),
+                    byte(unit(w[1]) * k),
+                    byte(unit(w[2]) * k),
+                ))
+            }
+            // Grayscale, 0..=10000
+            8 => Some(Color32::from_gray(byte(1.0 - w[0] as f32 / 10000.0))),
+            _ => None,
+        };
+        Ok(color)
+    }
+
+    /// Reads a UTF-16 name with its length prefix and null terminator.
+    fn name(&mut self) -> Result<String, PaletteError> {
+        let len = (self.u16()? as u32) << 16 | self.u16()? as u32;
+        // Checked before allocating, a broken file can claim billions of units.
+        if len as usize > (self.bytes.len() - self.pos) / 2 {
+            return Err(PaletteError::Format("name longer than the file".to_owned()));
+        }
+        let mut units = Vec::with_capacity(len as usize);
+        for _ in 0..len {
+            units.push(self.u16()?);
+        }
+        if units.last() == Some(&0) {
+            units.pop();
+        }
+        Ok(String::from_utf16_lossy(&units))
+    }
+}
+
+#[derive(Clone, Default)]
+struct PickerState {
+    renaming: Option<(usize, String)>,
+    editing: Option<usize>,
+}
+
+/// A grid of swatches linked to an active color.
+///
+/// Clicking a swatch picks its color, right-clicking opens a menu to rename, edit or
+/// delete it.
+pub struct PalettePicker<'a> {
+    palette: &'a mut Palette,
+    active: &'a mut Color32,
+    id: Id,
+}
+
+impl<'a> PalettePicker<'a> {
+    pub fn new(
+        id_source: impl std::hash::Hash,
+        palette: &'a mut Palette,
+        active: &'a mut Color32,
+    ) -> Self {
+        Self {
+            palette,
+            active,
+            id: Id::new(id_source),
+        }
+    }
+
+    pub fn show(self, ui: &mut Ui) {
+        let mut state: PickerState = ui.data_mut(|d| d.get_temp(self.id)).unwrap_or_default();
+        let mut delete = None;
+
+        ui.horizontal_wrapped(|ui| {
+            for (i, swatch) in self.palette.swatches.iter().enumerate() {
+                let (rect, response) =
+                    ui.allocate_exact_size(egui::vec2(SWATCH_SIZE, SWATCH_SIZE), Sense::click());
+                let stroke = if swatch.color == *self.active {
+                    ui.visuals().selection.stroke
+                } else {
+                    ui.visuals().widgets.noninteractive.bg_stroke
+                };
+                ui.painter().rect(rect, 2.0, swatch.color, stroke);
+
+                if response.clicked() {
+                    *self.active = swatch.color;
+                }
+                response
+                    .on_hover_text(format!("{} ({})", swatch.name, to_hex(swatch.color)))
+                    .context_menu(|ui| {
+                        if ui.button("Rename").clicked() {
+                            state.renaming = Some((i, swatch.name.clone()));
+                            ui.close_menu();
+                        }
+                        if ui.button("Edit").clicked() {
+                            state.editing = Some(i);
+                            ui.close_menu();
+                        }
+                        if ui.button("Delete").clicked() {
+                            delete = Some(i);
+                            ui.close_menu();
+                        }
+                    });
+            }
+        });
+
+        if let Some((i, name)) = &mut state.renaming {
+            let mut done = false;
+            ui.horizontal(|ui| {
+                let response = ui.text_edit_singleline(name);
+                done = ui.button("OK").clicked()
+                    || response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if done {
+                    self.palette.rename(*i, name.clone());
+                }
+                done |= ui.button("Cancel").clicked();
+            });
+            if done {
+                state.renaming = None;
+            }
+        }
+
+        if let Some(swatch) = state.editing.and_then(|i| self.palette.swatches.get_mut(i)) {
+            ui.label(format!("Editing '{}'", swatch.name));
+            egui::color_picker::color_picker_color32(
+                ui,
+                &mut swatch.color,
+                egui::color_picker::Alpha::Opaque,
+            );
+            if ui.button("Done").clicked() {
+                state.editing = None;
+            }
+        }
+
+        if let Some(i) = delete {
+            self.palette.swatches.remove(i);
+            // Indices after the deleted swatch moved, so drop any pending edit.
+            state = PickerState::default();
+        }
+        ui.data_mut(|d| d.insert_temp(self.id, state));
+    }
+}
+
+/// A palette shown in its own window, with import/export controls.
+pub struct PaletteWindow {
+    id: Id,
+    pub palette: Palette,
+    path: String,
+    status: Option<String>,
+}
+
+impl PaletteWindow {
+    pub fn new(id_source: impl std::hash::Hash, palette: Palette) -> Self {
+        Self {
+            id: Id::new(id_source),
+            palette,
+            path: String::new(),
+            status: None,
+        }
+    }
+
+    /// Shows the window, returns false once the user closed it.
+    pub fn show(&mut self, ctx: &egui::Context, active: &mut Color32) -> bool {
+        let mut open = true;
+        let title = if self.palette.name.is_empty() {
+            "Palette"
+        } else {
+            self.palette.name.as_str()
+        };
+        egui::Window::new(title)
+            .id(self.id)
+            .open(&mut open)
+            .default_width(240.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Name");
+                    ui.text_edit_singleline(&mut self.palette.name);
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Current");
+                    ui.color_edit_button_srgba(active);
+                    let full = self.palette.swatches.len() >= MAX_SWATCHES;
+                    if ui
+                        .add_enabled(!full, egui::Button::new("Add from current"))
+                        .clicked()
+                    {
+                        let name = self.palette.next_color_name();
+                        self.palette.push(name, *active);
+                    }
+                });
+                ui.label(format!(
+                    "{}/{} swatches",
+                    self.palette.swatches.len(),
+                    MAX_SWATCHES
+                ));
+                ui.separator();
+
+                PalettePicker::new(self.id.with("picker"), &mut self.palette, active).show(ui);
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label("File");
+                    ui.text_edit_singleline(&mut self.path)
+                        .on_hover_text(".toml, .gpl or .aco");
+                });
+                ui.horizontal(|ui| {
+                    let path = Path::new(&self.path);
+                    if ui.button("Import").clicked() {
+                        self.status = Some(match Palette::load(path) {
+                            Ok(palette) => {
+                                let count = palette.swatches.len();
+                                self.palette = palette;
+                                format!("Imported {} swatches", count)
+                            }
+                            Err(e) => e.to_string(),
+                        });
+                    }
+                    if ui.button("Export").clicked() {
+                        self.status = Some(match self.palette.save(path) {
+                            Ok(()) => format!("Saved to {}", path.display()),
+                            Err(e) => e.to_string(),
+                        });
+                    }
+                });
+                if let Some(status) = &self.status {
+                    ui.weak(status);
+                }
+            });
+        open
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn an_aco_name_longer_than_the_file_is_an_error() {
+        // Version 2, one RGB color, then a name claiming 0x7fffffff units.
+        let mut bytes = vec![0, 2, 0, 1, 0, 0, 0xff, 0xff, 0, 0, 0, 0, 0, 0];
+        bytes.extend([0x7f, 0xff, 0xff, 0xff, 0, b'a']);
+        assert!(matches!(
+            Palette::from_aco(&bytes),
+            Err(PaletteError::Format(_))
+        ));
+    }
+
+    fn sample() -> Palette {
+        let mut palette = Palette::new("Sample");
+        palette.push("Red", Color32::from_rgb(255, 0, 0));
+        palette.push("Sea green", Color32::from_rgb(46, 139, 87));
+        palette.push("Grey", Color32::from_gray(128));
+        palette
+    }
+
+    #[test]
+    fn palettes_round_trip_through_toml_and_gpl() {
+        let palette = sample();
+        assert_eq!(Palette::from_toml(&palette.to_toml()).unwrap(), palette);
+        assert_eq!(Palette::from_gpl(&palette.to_gpl()).unwrap(), palette);
+        assert!(matches!(
+            Palette::from_gpl("Not a palette"),
+            Err(PaletteError::Format(_))
+        ));
+        assert!(matches!(
+            Palette::from_toml("[swatches]\nRed = \"red\""),
+            Err(PaletteError::Toml(_))
+        ));
+    }
+
+    #[test]
+    fn a_palette_file_with_more_swatches_than_fit_is_an_error() {
+        let mut palette = Palette::new("Greys");
+        for i in 0..MAX_SWATCHES {
+            palette.push(format!("Grey {}", i), Color32::from_gray(i as u8));
+        }
+        let full = palette.to_toml();
+        assert_eq!(Palette::from_toml(&full).unwrap(), palette);
+        assert!(matches!(
+            Palette::from_toml(&format!("{}Extra = \"#ffffff\"\n", full)),
+            Err(PaletteError::TooManySwatches)
+        ));
+        assert!(matches!(
+            Palette::from_gpl(&format!("{}255 255 255\tExtra\n", palette.to_gpl())),
+            Err(PaletteError::TooManySwatches)
+        ));
+    }
+
+    #[test]
+    fn an_aco_file_is_read_with_the_names_of_its_version_2_section() {
+        let palette = sample();
+        let color = |bytes: &mut Vec<u8>, color: Color32| {
+            let [r, g, b, _] = color.to_array();
+            bytes.extend([0, 0]);
+            for channel in [r, g, b, 0] {
+                bytes.extend((u16::from(channel) * 257).to_be_bytes());
+            }
+        };
+        let count = (palette.swatches.len() as u16).to_be_bytes();
+        let mut bytes = vec![0, 1, count[0], count[1]];
+        for swatch in &palette.swatches {
+            color(&mut bytes, swatch.color);
+        }
+        bytes.extend([0, 2, count[0], count[1]]);
+        for swatch in &palette.swatches {
+            color(&mut bytes, swatch.color);
+            let units: Vec<u16> = swatch.name.encode_utf16().chain([0]).collect();
+            bytes.extend((units.len() as u32).to_be_bytes());
+            bytes.extend(units.iter().flat_map(|unit| unit.to_be_bytes()));
+        }
+
+        let read = Palette::from_aco(&bytes).unwrap();
+        assert_eq!(read.swatches, palette.swatches);
+        // Only version 1, the colors are numbered instead.
+        let version_1 = Palette::from_aco(&bytes[..4 + 3 * 10]).unwrap();
+        assert_eq!(version_1.swatches[1].name, "Color 2");
+        assert_eq!(version_1.swatches[1].color, palette.swatches[1].color);
+    }
+
+    #[test]
+    fn swatches_with_the_same_name_are_numbered_and_all_saved() {
+        let gpl = "GIMP Palette\n255 0 0\tUntitled\n0 255 0\tUntitled\n0 0 255\tUntitled\n";
+        let palette = Palette::from_gpl(gpl).unwrap();
+        let names: Vec<_> = palette.swatches.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["Untitled", "Untitled 2", "Untitled 3"]);
+        assert_eq!(Palette::from_toml(&palette.to_toml()).unwrap(), palette);
+
+        let mut palette = sample();
+        palette.rename(2, "Red".to_owned());
+        assert_eq!(palette.swatches[2].name, "Red 2");
+        palette.swatches.remove(0);
+        palette.push(palette.next_color_name(), Color32::BLACK);
+        palette.push(palette.next_color_name(), Color32::WHITE);
+        let names: Vec<_> = palette.swatches.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["Sea green", "Red 2", "Color 3", "Color 4"]);
+    }
+}
@@ -0,0 +1,297 @@
+use crate::gpu_memory::{Allocation, GpuMemory};
+use crate::RenderFrame;
+
+use wgpu::util::DeviceExt;
+
+/// How many particles are simulated.
+const PARTICLE_COUNT: u32 = 1 << 18;
+/// Matches `@workgroup_size` in particles_sim.wgsl.
+const WORKGROUP_SIZE: u32 = 256;
+/// The longest step the simulation takes, so a stalled frame doesn't fling everything.
+const MAX_DT: f32 = 1.0 / 30.0;
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Particle {
+    position: [f32; 2],
+    velocity: [f32; 2],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct SimParams {
+    attractor: [f32; 2],
+    dt: f32,
+    count: u32,
+}
+
+/// The render pipeline and the format and sample count it was built for.
+struct DrawPipeline {
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+    pipeline: wgpu::RenderPipeline,
+}
+
+/// A few hundred thousand particles simulated by a compute pass in a storage buffer,
+/// which the render pass that follows draws as points beneath the UI.
+pub struct ParticleSystem {
+    compute: wgpu::ComputePipeline,
+    compute_bind_group: wgpu::BindGroup,
+    draw_layout: wgpu::BindGroupLayout,
+    draw_bind_group: wgpu::BindGroup,
+    /// Rebuilt when the frame's format or sample count changes, without resetting the
+    /// particles.
+    draw: Option<DrawPipeline>,
+    params: wgpu::Buffer,
+    last_time: Option<f32>,
+    _allocation: Allocation,
+}
+
+impl ParticleSystem {
+    pub fn new(device: &wgpu::Device, memory: &GpuMemory) -> Self {
+        let contents = initial_particles();
+        let particles = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("particles"),
+            contents: bytemuck::cast_slice(&contents),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        let allocation = memory.track_buffer(&wgpu::BufferDescriptor {
+            label: Some("particles"),
+            size: std::mem::size_of_val(contents.as_slice()) as u64,
+            usage: wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("particle params"),
+            size: std::mem::size_of::<SimParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        // The layouts are spelled out rather than derived from the shaders, as the
+        // buffer is writable in the compute pass and read-only when drawing.
+        let compute_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("particle sim"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let draw_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("particle draw"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let shader = device.create_shader_module(wgpu::include_wgsl!("shaders/particles_sim.wgsl"));
+        let compute = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("particle sim"),
+            layout: Some(
+                &device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("particle sim"),
+                    bind_group_layouts: &[&compute_layout],
+                    push_constant_ranges: &[],
+                }),
+            ),
+            module: &shader,
+            entry_point: "cs_main",
+        });
+        let compute_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("particle sim"),
+            layout: &compute_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: params.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: particles.as_entire_binding(),
+                },
+            ],
+        });
+        let draw_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("particle draw"),
+            layout: &draw_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: particles.as_entire_binding(),
+            }],
+        });
+
+        Self {
+            compute,
+            compute_bind_group,
+            draw_layout,
+            draw_bind_group,
+            draw: None,
+            params,
+            last_time: None,
+            _allocation: allocation,
+        }
+    }
+
+    /// Steps the simulation to `time` seconds in and draws it over the frame.
+    pub fn render(&mut self, frame: &mut RenderFrame<'_>, time: f32) {
+        let dt = self
+            .last_time
+            .map_or(0.0, |last| (time - last).clamp(0.0, MAX_DT));
+        self.last_time = Some(time);
+        let params = SimParams {
+            attractor: [(time * 0.7).cos() * 0.5, (time * 1.1).sin() * 0.5],
+            dt,
+            count: PARTICLE_COUNT,
+        };
+        frame.write_buffer(&self.params, 0, bytemuck::bytes_of(&params));
+
+        let stale = self.draw.as_ref().is_none_or(|draw| {
+            draw.format != frame.format || draw.sample_count != frame.sample_count
+        });
+        if stale {
+            self.draw = Some(DrawPipeline {
+                format: frame.format,
+                sample_count: frame.sample_count,
+                pipeline: create_draw_pipeline(
+                    frame.device,
+                    &self.draw_layout,
+                    frame.format,
+                    frame.sample_count,
+                ),
+            });
+        }
+        let Some(draw) = &self.draw else {
+            return;
+        };
+
+        let mut compute_pass = frame
+            .encoder
+            .begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("particle sim"),
+            });
+        compute_pass.set_pipeline(&self.compute);
+        compute_pass.set_bind_group(0, &self.compute_bind_group, &[]);
+        compute_pass.dispatch_workgroups(PARTICLE_COUNT.div_ceil(WORKGROUP_SIZE), 1, 1);
+        drop(compute_pass);
+
+        // wgpu tracks the buffer going from storage writes to storage reads, and puts
+        // a barrier between the passes, so the draw sees this frame's positions.
+        let mut pass = frame
+            .encoder
+            .begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("particles"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: frame.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+        pass.set_pipeline(&draw.pipeline);
+        pass.set_bind_group(0, &self.draw_bind_group, &[]);
+        pass.draw(0..PARTICLE_COUNT, 0..1);
+    }
+
+    /// Forgets the last step's time, so the simulation resumes where it was after not
+    /// being rendered for a while.
+    pub fn pause(&mut self) {
+        self.last_time = None;
+    }
+}
+
+fn create_draw_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+) -> wgpu::RenderPipeline {
+    let shader = device.create_shader_module(wgpu::include_wgsl!("shaders/particles_draw.wgsl"));
+    let additive = wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::One,
+        dst_factor: wgpu::BlendFactor::One,
+        operation: wgpu::BlendOperation::Add,
+    };
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("particle draw"),
+        layout: Some(
+            &device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("particle draw"),
+                bind_group_layouts: &[layout],
+                push_constant_ranges: &[],
+            }),
+        ),
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: "vs_main",
+            buffers: &[],
+        },
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::PointList,
+            ..Default::default()
+        },
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState {
+            count: sample_count,
+            ..Default::default()
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState {
+                    color: additive,
+                    alpha: wgpu::BlendComponent::OVER,
+                }),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        multiview: None,
+    })
+}
+
+/// Particles scattered over the screen at rest, from a fixed seed.
+fn initial_particles() -> Vec<Particle> {
+    let mut seed = 0x2545_f491_u32;
+    let mut random = move || {
+        // xorshift32, good enough to scatter points.
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+        seed as f32 / u32::MAX as f32 * 2.0 - 1.0
+    };
+    (0..PARTICLE_COUNT)
+        .map(|_| Particle {
+            position: [random(), random()],
+            velocity: [0.0, 0.0],
+        })
+        .collect()
+}
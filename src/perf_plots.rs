@@ -0,0 +1,197 @@
+use crate::gpu_memory::GpuMemory;
+use crate::stats::FrameRecord;
+
+use egui_plot::{Legend, Line, Plot, PlotPoints};
+
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// The most seconds the plots can look back over, and so how long samples are kept.
+const MAX_SECONDS: f64 = 60.0;
+const EXPORT_FILE: &str = "perf_plots.csv";
+
+#[derive(Clone, Copy, Debug)]
+struct Sample {
+    /// Seconds since the start of the app.
+    timestamp: f64,
+    /// From the frame before, which the first one has none of.
+    frame_ms: Option<f32>,
+    cpu_ms: f32,
+    gpu_ms: Option<f32>,
+    /// What `GpuMemory` counted, in bytes.
+    memory: u64,
+}
+
+/// The "Performance plots" window: frame, CPU and GPU times and the tracked GPU memory
+/// of the last seconds, plotted as they come in.
+pub struct PerfPlots {
+    pub open: bool,
+    /// Keeps the plots as they were, new frames aren't sampled.
+    pub paused: bool,
+    /// How many of the last seconds are plotted.
+    pub seconds: f64,
+    memory: Option<GpuMemory>,
+    samples: VecDeque<Sample>,
+    /// Kept while paused too, so the first frame time after isn't the pause.
+    last_timestamp: Option<f64>,
+    status: Option<String>,
+}
+
+impl Default for PerfPlots {
+    fn default() -> Self {
+        Self {
+            open: false,
+            paused: false,
+            seconds: 10.0,
+            memory: None,
+            samples: VecDeque::new(),
+            last_timestamp: None,
+            status: None,
+        }
+    }
+}
+
+impl PerfPlots {
+    pub fn set_memory(&mut self, memory: GpuMemory) {
+        self.memory = Some(memory);
+    }
+
+    pub fn record(&mut self, record: &FrameRecord) {
+        let frame_ms = self
+            .last_timestamp
+            .map(|last| ((record.timestamp - last) * 1000.0) as f32);
+        self.last_timestamp = Some(record.timestamp);
+        if self.paused {
+            return;
+        }
+        let memory = self.memory.as_ref().map_or(0, |memory| {
+            memory.totals().iter().map(|(_, bytes)| bytes).sum()
+        });
+        self.samples.push_back(Sample {
+            timestamp: record.timestamp,
+            frame_ms,
+            cpu_ms: record.cpu_ms(),
+            gpu_ms: record.gpu_ms,
+            memory,
+        });
+        while self
+            .samples
+            .front()
+            .is_some_and(|first| record.timestamp - first.timestamp > MAX_SECONDS)
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    /// The samples within the plotted seconds.
+    fn shown(&self) -> impl Iterator<Item = &Sample> + '_ {
+        let latest = self.samples.back().map_or(0.0, |last| last.timestamp);
+        self.samples
+            .iter()
+            .filter(move |sample| latest - sample.timestamp <= self.seconds)
+    }
+
+    fn export(&mut self) {
+        let path = Path::new(EXPORT_FILE);
+        let samples: Vec<_> = self.shown().copied().collect();
+        let written = std::fs::File::create(path)
+            .and_then(|file| write_csv(&samples, io::BufWriter::new(file)));
+        self.status = Some(match written {
+            Ok(()) => format!("Exported {} frames to {}", samples.len(), path.display()),
+            Err(e) => format!("Export failed: {}", e),
+        });
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        let mut export = false;
+        egui::Window::new("Performance plots")
+            .open(&mut open)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let pause = if self.paused { "Resume" } else { "Pause" };
+                    if ui.button(pause).clicked() {
+                        self.paused = !self.paused;
+                    }
+                    export = ui.button("Export").clicked();
+                    ui.add(
+                        egui::Slider::new(&mut self.seconds, 1.0..=MAX_SECONDS)
+                            .integer()
+                            .suffix(" s")
+                            .text("Last"),
+                    );
+                });
+                let shown: Vec<_> = self.shown().copied().collect();
+                let span = match (shown.first(), shown.last()) {
+                    (Some(first), Some(last)) => last.timestamp - first.timestamp,
+                    _ => 0.0,
+                };
+                ui.weak(format!("{} frames over {:.1} s", shown.len(), span));
+                if let Some(status) = &self.status {
+                    ui.weak(status);
+                }
+
+                // Seconds before the latest sample, which is at 0.
+                let latest = shown.last().map_or(0.0, |last| last.timestamp);
+                let line = |value: &dyn Fn(&Sample) -> Option<f64>| -> PlotPoints {
+                    shown
+                        .iter()
+                        .filter_map(|sample| Some([sample.timestamp - latest, value(sample)?]))
+                        .collect()
+                };
+                Plot::new("perf plots times")
+                    .height(160.0)
+                    .legend(Legend::default())
+                    .include_x(-self.seconds)
+                    .include_y(0.0)
+                    .y_axis_label("ms")
+                    .allow_drag(self.paused)
+                    .allow_zoom(self.paused)
+                    .show(ui, |plot| {
+                        plot.line(Line::new(line(&|s| Some(s.frame_ms? as f64))).name("Frame"));
+                        plot.line(Line::new(line(&|s| Some(s.cpu_ms as f64))).name("CPU"));
+                        plot.line(Line::new(line(&|s| Some(s.gpu_ms? as f64))).name("GPU"));
+                    });
+                Plot::new("perf plots memory")
+                    .height(120.0)
+                    .legend(Legend::default())
+                    .include_x(-self.seconds)
+                    .include_y(0.0)
+                    .x_axis_label("s")
+                    .y_axis_label("MiB")
+                    .allow_drag(self.paused)
+                    .allow_zoom(self.paused)
+                    .show(ui, |plot| {
+                        let mib = |s: &Sample| Some(s.memory as f64 / (1024.0 * 1024.0));
+                        plot.line(Line::new(line(&mib)).name("GPU memory"));
+                    });
+            });
+        if export {
+            self.export();
+        }
+        self.open = open;
+    }
+}
+
+/// Writes the samples as CSV, one row per frame.
+fn write_csv(samples: &[Sample], mut out: impl Write) -> io::Result<()> {
+    writeln!(out, "timestamp_s,frame_ms,cpu_ms,gpu_ms,gpu_memory_bytes")?;
+    for sample in samples {
+        write!(out, "{:.6},", sample.timestamp)?;
+        if let Some(ms) = sample.frame_ms {
+            write!(out, "{:.4}", ms)?;
+        }
+        write!(out, ",{:.4},", sample.cpu_ms)?;
+        if let Some(ms) = sample.gpu_ms {
+            write!(out, "{:.4}", ms)?;
+        }
+        writeln!(out, ",{}", sample.memory)?;
+    }
+    out.flush()
+}
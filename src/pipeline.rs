@@ -0,0 +1,178 @@
+use std::marker::PhantomData;
+
+/// The smallest buffer a mesh allocates, in elements.
+const MIN_CAPACITY: u64 = 64;
+
+/// How a `Pipeline` is built from a WGSL shader with `vs_main` and `fs_main` entry
+/// points.
+pub struct PipelineConfig<'a> {
+    pub label: &'a str,
+    pub wgsl: &'a str,
+    pub vertex_layout: wgpu::VertexBufferLayout<'a>,
+    /// The target's format, usually the surface's.
+    pub format: wgpu::TextureFormat,
+    pub sample_count: u32,
+    pub cull_mode: Option<wgpu::Face>,
+    /// Depth tests against and writes to a depth buffer of this format.
+    pub depth_format: Option<wgpu::TextureFormat>,
+    pub blend: Option<wgpu::BlendState>,
+}
+
+/// A render pipeline drawing indexed triangles out of `Mesh`es.
+pub struct Pipeline {
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl Pipeline {
+    pub fn new(device: &wgpu::Device, config: &PipelineConfig) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(config.label),
+            source: wgpu::ShaderSource::Wgsl(config.wgsl.into()),
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(config.label),
+            layout: None,
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: std::slice::from_ref(&config.vertex_layout),
+            },
+            primitive: wgpu::PrimitiveState {
+                cull_mode: config.cull_mode,
+                ..Default::default()
+            },
+            depth_stencil: config.depth_format.map(|format| wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: Default::default(),
+                bias: Default::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: config.sample_count,
+                ..Default::default()
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: config.format,
+                    blend: config.blend,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+        Self { pipeline }
+    }
+
+    /// The layout of bind group `index`, as derived from the shader.
+    pub fn bind_group_layout(&self, index: u32) -> wgpu::BindGroupLayout {
+        self.pipeline.get_bind_group_layout(index)
+    }
+
+    /// For drawing what isn't a `Mesh`, or with more than one bind group.
+    pub fn render_pipeline(&self) -> &wgpu::RenderPipeline {
+        &self.pipeline
+    }
+
+    pub fn draw<'a, V>(
+        &'a self,
+        pass: &mut wgpu::RenderPass<'a>,
+        bind_group: &'a wgpu::BindGroup,
+        mesh: &'a Mesh<V>,
+    ) {
+        if mesh.index_count == 0 {
+            return;
+        }
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, bind_group, &[]);
+        pass.set_vertex_buffer(0, mesh.vertices.slice(..));
+        pass.set_index_buffer(mesh.indices.slice(..), wgpu::IndexFormat::Uint16);
+        pass.draw_indexed(0..mesh.index_count, 0, 0..1);
+    }
+}
+
+/// Vertex and index buffers of `V`s, which grow when geometry that doesn't fit is
+/// uploaded.
+pub struct Mesh<V> {
+    label: String,
+    vertices: wgpu::Buffer,
+    indices: wgpu::Buffer,
+    index_count: u32,
+    _vertex: PhantomData<V>,
+}
+
+impl<V: bytemuck::Pod> Mesh<V> {
+    pub fn new(device: &wgpu::Device, label: &str) -> Self {
+        Self {
+            label: label.to_owned(),
+            vertices: create_buffer::<V>(device, label, MIN_CAPACITY, wgpu::BufferUsages::VERTEX),
+            indices: create_buffer::<u16>(device, label, MIN_CAPACITY, wgpu::BufferUsages::INDEX),
+            index_count: 0,
+            _vertex: PhantomData,
+        }
+    }
+
+    /// Replaces the geometry, reallocating the buffers if it outgrew them.
+    pub fn upload(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        vertices: &[V],
+        indices: &[u16],
+    ) {
+        grow::<V>(
+            device,
+            &self.label,
+            &mut self.vertices,
+            vertices.len(),
+            wgpu::BufferUsages::VERTEX,
+        );
+        grow::<u16>(
+            device,
+            &self.label,
+            &mut self.indices,
+            indices.len().next_multiple_of(2),
+            wgpu::BufferUsages::INDEX,
+        );
+        queue.write_buffer(&self.vertices, 0, bytemuck::cast_slice(vertices));
+        // Buffer writes have to be a multiple of 4 bytes, so odd index counts are padded.
+        let mut index_data: Vec<u16> = indices.to_vec();
+        if index_data.len() % 2 == 1 {
+            index_data.push(0);
+        }
+        queue.write_buffer(&self.indices, 0, bytemuck::cast_slice(&index_data));
+        self.index_count = indices.len() as u32;
+    }
+}
+
+fn create_buffer<T>(
+    device: &wgpu::Device,
+    label: &str,
+    capacity: u64,
+    usage: wgpu::BufferUsages,
+) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some(label),
+        size: capacity * std::mem::size_of::<T>() as u64,
+        usage: usage | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+/// Makes room for `len` elements, doubling the capacity so repeated growth stays cheap.
+fn grow<T>(
+    device: &wgpu::Device,
+    label: &str,
+    buffer: &mut wgpu::Buffer,
+    len: usize,
+    usage: wgpu::BufferUsages,
+) {
+    let needed = len as u64 * std::mem::size_of::<T>() as u64;
+    if needed <= buffer.size() {
+        return;
+    }
+    let capacity = needed.next_power_of_two() / std::mem::size_of::<T>() as u64;
+    *buffer = create_buffer::<T>(device, label, capacity.max(MIN_CAPACITY), usage);
+}
@@ -0,0 +1,187 @@
+#[cfg(any(windows, all(unix, not(target_os = "macos"))))]
+use crate::deeplink::SCHEME;
+
+use std::io::{self, BufRead, Write};
+#[cfg(any(windows, all(unix, not(target_os = "macos"))))]
+use std::process::Command;
+
+/// Registers the executable as handler of `myexample://` links for the current user.
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn register_url_scheme() -> io::Result<()> {
+    let exe = std::env::current_exe()?;
+    let data_dir = match std::env::var_os("XDG_DATA_HOME") {
+        Some(dir) => std::path::PathBuf::from(dir),
+        None => std::path::PathBuf::from(
+            std::env::var_os("HOME")
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "HOME is not set"))?,
+        )
+        .join(".local/share"),
+    };
+    let applications = data_dir.join("applications");
+    std::fs::create_dir_all(&applications)?;
+
+    let desktop_file = format!("{}-url-handler.desktop", SCHEME);
+    let entry = format!(
+        "[Desktop Entry]\nType=Application\nName=winit-wgpu-egui example\nExec=\"{}\" %u\nNoDisplay=true\nMimeType=x-scheme-handler/{};\n",
+        exe.display(),
+        SCHEME
+    );
+    std::fs::write(applications.join(&desktop_file), entry)?;
+
+    run(Command::new("xdg-mime").args([
+        "default",
+        &desktop_file,
+        &format!("x-scheme-handler/{}", SCHEME),
+    ]))
+}
+
+/// Registers the executable as handler of `myexample://` links for the current user.
+#[cfg(windows)]
+pub fn register_url_scheme() -> io::Result<()> {
+    let exe = std::env::current_exe()?;
+    let key = format!("HKCU\\Software\\Classes\\{}", SCHEME);
+    let command = format!("\"{}\" \"%1\"", exe.display());
+
+    run(Command::new("reg").args(["add", &key, "/ve", "/d", &format!("URL:{}", SCHEME), "/f"]))?;
+    run(Command::new("reg").args(["add", &key, "/v", "URL Protocol", "/d", "", "/f"]))?;
+    run(Command::new("reg").args([
+        "add",
+        &format!("{}\\shell\\open\\command", key),
+        "/ve",
+        "/d",
+        &command,
+        "/f",
+    ]))
+}
+
+/// On macOS the scheme is declared by `CFBundleURLTypes` in the bundle's `Info.plist`.
+#[cfg(not(any(windows, all(unix, not(target_os = "macos")))))]
+pub fn register_url_scheme() -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "declare the URL scheme with CFBundleURLTypes in the application bundle",
+    ))
+}
+
+#[cfg(any(windows, all(unix, not(target_os = "macos"))))]
+fn run(command: &mut Command) -> io::Result<()> {
+    let status = command.status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "{:?} exited with {}",
+            command, status
+        )))
+    }
+}
+
+/// Opens a folder in the system's file manager.
+pub fn open_folder(path: &std::path::Path) -> io::Result<()> {
+    #[cfg(windows)]
+    let program = "explorer";
+    #[cfg(target_os = "macos")]
+    let program = "open";
+    #[cfg(not(any(windows, target_os = "macos")))]
+    let program = "xdg-open";
+
+    std::process::Command::new(program)
+        .arg(path)
+        .spawn()
+        .map(|_| ())
+}
+
+#[cfg(unix)]
+fn socket_path() -> std::path::PathBuf {
+    let dir = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir);
+    dir.join(format!("{}.sock", env!("CARGO_PKG_NAME")))
+}
+
+#[cfg(not(unix))]
+const INSTANCE_ADDR: &str = "127.0.0.1:47813";
+
+/// The line a running instance greets each connection with, so whatever else happens
+/// to listen there isn't taken for one.
+const HANDSHAKE: &str = concat!(env!("CARGO_PKG_NAME"), " links 1");
+
+/// How long to wait for the greeting before deciding nothing of ours is listening.
+const HANDSHAKE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Whether the first line read is the running instance's greeting.
+fn is_greeting(reader: &mut impl BufRead) -> bool {
+    let mut line = String::new();
+    reader.read_line(&mut line).is_ok() && line.trim_end() == HANDSHAKE
+}
+
+/// Hands the links over to an already running instance.
+///
+/// Returns false if no other instance is listening, in which case this process should
+/// become the running instance itself.
+pub fn forward_to_running_instance(links: &[String]) -> bool {
+    #[cfg(unix)]
+    let stream = std::os::unix::net::UnixStream::connect(socket_path());
+    #[cfg(not(unix))]
+    let stream = std::net::TcpStream::connect(INSTANCE_ADDR);
+
+    let Ok(mut stream) = stream else {
+        return false;
+    };
+    let greeted = stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT)).is_ok()
+        && stream
+            .try_clone()
+            .is_ok_and(|reader| is_greeting(&mut io::BufReader::new(reader)));
+    if !greeted {
+        tracing::warn!("Something else is listening for links, not handing them over");
+        return false;
+    }
+    for link in links {
+        if let Err(e) = writeln!(stream, "{}", link) {
+            tracing::warn!("Failed to forward link to the running instance: {}", e);
+            return false;
+        }
+    }
+    true
+}
+
+/// Listens on a background thread for links forwarded by later instances.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn listen_for_links(mut on_link: impl FnMut(String) + Send + 'static) -> io::Result<()> {
+    use std::io::BufReader;
+
+    #[cfg(unix)]
+    let listener = {
+        // Nobody answered on the socket, so whatever file is left there is stale.
+        let path = socket_path();
+        let _ = std::fs::remove_file(&path);
+        std::os::unix::net::UnixListener::bind(path)?
+    };
+    #[cfg(not(unix))]
+    let listener = std::net::TcpListener::bind(INSTANCE_ADDR)?;
+
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            if writeln!(stream, "{}", HANDSHAKE).is_err() {
+                continue;
+            }
+            for line in BufReader::new(stream).lines().map_while(Result::ok) {
+                on_link(line);
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_running_instances_greeting_is_taken_for_one() {
+        let greeting = format!("{}\n", HANDSHAKE);
+        assert!(is_greeting(&mut greeting.as_bytes()));
+        assert!(!is_greeting(&mut "SSH-2.0-OpenSSH_9.6\n".as_bytes()));
+        assert!(!is_greeting(&mut "".as_bytes()));
+    }
+}
@@ -0,0 +1,104 @@
+use crate::events::UserEvent;
+
+use egui::Vec2;
+use winit::event::{DeviceEvent, ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent};
+use winit::window::{CursorGrabMode, Window};
+
+/// Grabs and hides the cursor to read the mouse's raw motion, as for turning a camera.
+/// Escape or the window losing the focus lets go of it again.
+#[derive(Default)]
+pub struct PointerLock {
+    locked: bool,
+    /// A change applied to the window with the next `update`.
+    requested: Option<bool>,
+    delta: Vec2,
+}
+
+impl PointerLock {
+    pub fn lock(&mut self) {
+        self.requested = Some(true);
+    }
+
+    pub fn unlock(&mut self) {
+        self.requested = Some(false);
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    /// The change waiting for the next `update`, locking when true.
+    pub fn pending(&self) -> Option<bool> {
+        self.requested
+    }
+
+    /// Applies the change asked for last. Where the cursor can't be locked in place it's
+    /// kept inside the window instead, and the other way around, as each platform only
+    /// has one of them.
+    pub fn update(&mut self, window: &Window) {
+        let Some(lock) = self.requested.take().filter(|&lock| lock != self.locked) else {
+            return;
+        };
+        if lock {
+            let grabbed = window
+                .set_cursor_grab(CursorGrabMode::Locked)
+                .or_else(|_| window.set_cursor_grab(CursorGrabMode::Confined));
+            if let Err(e) = grabbed {
+                tracing::warn!("Failed to grab the cursor: {}", e);
+                return;
+            }
+        } else if let Err(e) = window.set_cursor_grab(CursorGrabMode::None) {
+            tracing::warn!("Failed to release the cursor: {}", e);
+        }
+        window.set_cursor_visible(!lock);
+        self.locked = lock;
+        self.delta = Vec2::ZERO;
+    }
+
+    /// Adds up the mouse's motion while locked, and unlocks on Escape or when the window
+    /// loses the focus. Returns true for the events it acted on, which take a frame to
+    /// show.
+    pub fn on_event(&mut self, event: &Event<'_, UserEvent>) -> bool {
+        if !self.locked {
+            return false;
+        }
+        match event {
+            Event::DeviceEvent {
+                event: DeviceEvent::MouseMotion { delta: (x, y) },
+                ..
+            } => {
+                self.delta += egui::vec2(*x as f32, *y as f32);
+                true
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                state: ElementState::Pressed,
+                                virtual_keycode: Some(VirtualKeyCode::Escape),
+                                ..
+                            },
+                        ..
+                    },
+                ..
+            } => {
+                self.unlock();
+                true
+            }
+            Event::WindowEvent {
+                event: WindowEvent::Focused(false),
+                ..
+            } => {
+                self.unlock();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// The motion since it was last taken, in the mouse's own units rather than pixels.
+    pub fn take_delta(&mut self) -> Vec2 {
+        std::mem::take(&mut self.delta)
+    }
+}
@@ -0,0 +1,292 @@
+use crate::color_space::ColorSpace;
+use crate::gfx::RenderTarget;
+use crate::gpu_cache::GpuCache;
+use crate::gpu_memory::GpuMemory;
+use crate::hdr::{self, HDR_FORMAT};
+
+use serde::{Deserialize, Serialize};
+
+/// A fullscreen pass of the post-processing chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PostEffect {
+    /// Scales by the exposure, then maps the HDR values into the surface's range with
+    /// an ACES curve.
+    Tonemap,
+    /// Raises colors to one over the gamma, brightening the midtones above 1.
+    Gamma,
+    /// Darkens the corners, by up to the amount.
+    Vignette,
+}
+
+impl PostEffect {
+    pub const ALL: [Self; 3] = [Self::Tonemap, Self::Gamma, Self::Vignette];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Tonemap => "Tonemap",
+            Self::Gamma => "Gamma",
+            Self::Vignette => "Vignette",
+        }
+    }
+
+    fn entry_point(self) -> &'static str {
+        match self {
+            Self::Tonemap => "fs_tonemap",
+            Self::Gamma => "fs_gamma",
+            Self::Vignette => "fs_vignette",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// One pass of the chain and how strong it is.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PostPass {
+    pub effect: PostEffect,
+    pub amount: f32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct PostParams {
+    amount: f32,
+    peak: f32,
+    encode_srgb: f32,
+    _pad: f32,
+}
+
+/// Runs `PostPass`es over a scene drawn in `HDR_FORMAT`, ping-ponging between two
+/// targets with the last pass drawing into the frame.
+pub struct PostChain {
+    /// Per effect, the pipeline drawing into `HDR_FORMAT` and the one drawing into the
+    /// surface's format.
+    pipelines: Vec<[wgpu::RenderPipeline; 2]>,
+    /// Per effect, as each runs at most once a frame.
+    params: Vec<wgpu::Buffer>,
+    targets: [RenderTarget; 2],
+    peak: f32,
+    encode_srgb: bool,
+    cache: GpuCache,
+}
+
+impl PostChain {
+    pub fn new(
+        device: &wgpu::Device,
+        memory: &GpuMemory,
+        cache: &GpuCache,
+        color_space: ColorSpace,
+        surface_format: wgpu::TextureFormat,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::include_wgsl!("shaders/post.wgsl"));
+        let pipeline = |effect: PostEffect, format: wgpu::TextureFormat| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(effect.name()),
+                layout: None,
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_main",
+                    buffers: &[],
+                },
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: effect.entry_point(),
+                    targets: &[Some(format.into())],
+                }),
+                multiview: None,
+            })
+        };
+        let pipelines = PostEffect::ALL
+            .iter()
+            .map(|&effect| {
+                [
+                    pipeline(effect, HDR_FORMAT),
+                    pipeline(effect, surface_format),
+                ]
+            })
+            .collect();
+        let params = PostEffect::ALL
+            .iter()
+            .map(|effect| {
+                device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some(effect.name()),
+                    size: std::mem::size_of::<PostParams>() as u64,
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                })
+            })
+            .collect();
+        Self {
+            pipelines,
+            params,
+            targets: [
+                RenderTarget::new("post ping", HDR_FORMAT, 1)
+                    .sampled()
+                    .tracked(memory),
+                RenderTarget::new("post pong", HDR_FORMAT, 1)
+                    .sampled()
+                    .tracked(memory),
+            ],
+            peak: hdr::peak(color_space),
+            encode_srgb: !hdr::is_linear(surface_format),
+            cache: cache.clone(),
+        }
+    }
+
+    /// The `size` large target the scene is drawn into before `run`.
+    pub fn input_view(&mut self, device: &wgpu::Device, size: [u32; 2]) -> &wgpu::TextureView {
+        self.targets[0].view(device, size)
+    }
+
+    /// Runs `passes` in order over the input, the last one drawing into `output`.
+    pub fn run(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        passes: &[PostPass],
+        output: &wgpu::TextureView,
+        size: [u32; 2],
+    ) {
+        let [ping, pong] = &mut self.targets;
+        let views = [ping.view(device, size), pong.view(device, size)];
+        for (i, pass) in passes.iter().enumerate() {
+            let last = i + 1 == passes.len();
+            let index = pass.effect.index();
+            let params = PostParams {
+                amount: pass.amount,
+                peak: self.peak,
+                encode_srgb: if last && self.encode_srgb { 1.0 } else { 0.0 },
+                _pad: 0.0,
+            };
+            queue.write_buffer(&self.params[index], 0, bytemuck::bytes_of(&params));
+
+            let pipeline = &self.pipelines[index][last as usize];
+            let target = if last { output } else { views[(i + 1) % 2] };
+            // The targets are reallocated with the window, so bind groups are looked up
+            // per frame.
+            let bind_group = self.cache.bind_group(
+                device,
+                Some(pass.effect.name()),
+                &pipeline.get_bind_group_layout(0),
+                &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(views[i % 2]),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: self.params[index].as_entire_binding(),
+                    },
+                ],
+            );
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some(pass.effect.name()),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+            render_pass.set_pipeline(pipeline);
+            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+    }
+}
+
+/// The chain as shown in the settings window: every effect in the order it runs, each
+/// with a switch and its amount.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PostSettings {
+    pub effects: Vec<PostSlot>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PostSlot {
+    pub effect: PostEffect,
+    pub enabled: bool,
+    pub amount: f32,
+}
+
+impl Default for PostSettings {
+    fn default() -> Self {
+        let slot = |effect, enabled, amount| PostSlot {
+            effect,
+            enabled,
+            amount,
+        };
+        Self {
+            effects: vec![
+                slot(PostEffect::Tonemap, false, 1.0),
+                slot(PostEffect::Gamma, false, 1.0),
+                slot(PostEffect::Vignette, false, 0.5),
+            ],
+        }
+    }
+}
+
+impl PostSettings {
+    /// The enabled effects, in order.
+    pub fn passes(&self) -> Vec<PostPass> {
+        self.effects
+            .iter()
+            .filter(|slot| slot.enabled)
+            .map(|slot| PostPass {
+                effect: slot.effect,
+                amount: slot.amount,
+            })
+            .collect()
+    }
+
+    /// A row per effect, with buttons moving it up and down the chain.
+    pub fn ui(&mut self, ui: &mut egui::Ui, color_space: ColorSpace) {
+        let mut swap = None;
+        let count = self.effects.len();
+        for (i, slot) in self.effects.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                if ui.add_enabled(i > 0, egui::Button::new("⬆")).clicked() {
+                    swap = Some(i - 1);
+                }
+                if ui
+                    .add_enabled(i + 1 < count, egui::Button::new("⬇"))
+                    .clicked()
+                {
+                    swap = Some(i);
+                }
+                ui.checkbox(&mut slot.enabled, slot.effect.name());
+                let (range, text) = match slot.effect {
+                    PostEffect::Tonemap => (0.1..=8.0, "Exposure"),
+                    PostEffect::Gamma => (0.5..=2.5, "Gamma"),
+                    PostEffect::Vignette => (0.0..=1.0, "Strength"),
+                };
+                ui.add_enabled(
+                    slot.enabled,
+                    egui::Slider::new(&mut slot.amount, range).text(text),
+                );
+            });
+        }
+        if let Some(i) = swap {
+            self.effects.swap(i, i + 1);
+        }
+        if self.passes().is_empty() {
+            ui.weak("The scene is drawn straight to the surface");
+        } else {
+            ui.weak(match color_space {
+                ColorSpace::Srgb => "The scene is drawn in HDR and mapped to the sRGB surface",
+                ColorSpace::ExtendedSrgb => "Tonemapped highlights reach above SDR white",
+            });
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use crate::events::UserEvent;
+use crate::gpu_memory::{Allocation, GpuMemory};
+use crate::screenshot;
+
+use instant::Instant;
+use winit::event_loop::EventLoopProxy;
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Frames whose copies can be waiting to be read back at once.
+const READBACK_SLOTS: usize = 3;
+/// Frames read back but not encoded yet, more are dropped until the encoder catches up.
+const QUEUED_FRAMES: usize = 4;
+/// GIF delays are in hundredths of a second, and viewers slow down anything below two.
+const FRAME_INTERVAL: Duration = Duration::from_millis(20);
+
+const FREE: u8 = 0;
+const MAPPING: u8 = 1;
+const MAPPED: u8 = 2;
+
+/// A frame read back, on its way to the encoder.
+#[cfg_attr(
+    not(all(feature = "recording", not(target_arch = "wasm32"))),
+    allow(dead_code)
+)]
+struct RecordedFrame {
+    width: u32,
+    height: u32,
+    rgba: Vec<u8>,
+    /// Since the recording started.
+    time: Duration,
+}
+
+/// A buffer a presented frame is copied to.
+struct Slot {
+    buffer: wgpu::Buffer,
+    _allocation: Allocation,
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+    padded_row: u32,
+    time: Duration,
+    state: Arc<AtomicU8>,
+}
+
+/// Records the presented frames into an animated GIF at `path`.
+///
+/// Each frame is copied into one of a few buffers, read back once the GPU is done with
+/// it and encoded on a worker thread, so recording doesn't stall the frame. Frames are
+/// dropped instead while all the buffers are in flight or the encoder is behind.
+///
+/// Dropping it finishes the file, the worker then sends `UserEvent::RecordingSaved`.
+pub(crate) struct Recorder {
+    memory: GpuMemory,
+    slots: Vec<Option<Slot>>,
+    /// The slot this frame was copied to, `None` when it's dropped.
+    current: Option<usize>,
+    frames: SyncSender<RecordedFrame>,
+    started: Instant,
+    last: Option<Duration>,
+}
+
+impl Recorder {
+    /// Starts the worker encoding into `path`.
+    pub fn start(
+        path: PathBuf,
+        memory: &GpuMemory,
+        proxy: EventLoopProxy<UserEvent>,
+    ) -> Result<Self, &'static str> {
+        let (frames, receiver) = mpsc::sync_channel(QUEUED_FRAMES);
+        spawn_encoder(path, receiver, proxy)?;
+        Ok(Self {
+            memory: memory.clone(),
+            slots: (0..READBACK_SLOTS).map(|_| None).collect(),
+            current: None,
+            frames,
+            started: Instant::now(),
+            last: None,
+        })
+    }
+
+    /// Records copying `texture`, the frame about to be presented, unless the last one
+    /// kept was too recent or no buffer is free.
+    pub fn copy(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+    ) {
+        let time = self.started.elapsed();
+        if self.last.is_some_and(|last| time < last + FRAME_INTERVAL) {
+            return;
+        }
+        let Some(padded_row) = screenshot::padded_row(texture.format(), texture.width()) else {
+            return;
+        };
+        let Some(index) = self.slots.iter().position(|slot| {
+            slot.as_ref()
+                .is_none_or(|slot| slot.state.load(Ordering::Acquire) == FREE)
+        }) else {
+            return;
+        };
+        let (width, height) = (texture.width(), texture.height());
+        let slot = &mut self.slots[index];
+        // The buffer only fits frames of the size and format it was made for.
+        if !slot.as_ref().is_some_and(|slot| {
+            (slot.format, slot.width, slot.height) == (texture.format(), width, height)
+        }) {
+            let desc = screenshot::readback_buffer("recording", padded_row, height);
+            *slot = Some(Slot {
+                buffer: device.create_buffer(&desc),
+                _allocation: self.memory.track_buffer(&desc),
+                format: texture.format(),
+                width,
+                height,
+                padded_row,
+                time,
+                state: Arc::new(AtomicU8::new(FREE)),
+            });
+        }
+        let slot = slot.as_mut().unwrap();
+        screenshot::copy_to_buffer(encoder, texture, &slot.buffer, padded_row);
+        slot.time = time;
+        self.current = Some(index);
+        self.last = Some(time);
+    }
+
+    /// Starts reading back the frame's copy, after its commands were submitted.
+    pub fn submitted(&mut self) {
+        let Some(slot) = self
+            .current
+            .take()
+            .and_then(|index| self.slots[index].as_ref())
+        else {
+            return;
+        };
+        slot.state.store(MAPPING, Ordering::Release);
+        let state = slot.state.clone();
+        slot.buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                state.store(
+                    if result.is_ok() { MAPPED } else { FREE },
+                    Ordering::Release,
+                );
+            });
+    }
+
+    /// Hands the frames that were read back to the encoder, oldest first.
+    pub fn collect(&mut self, device: &wgpu::Device) {
+        device.poll(wgpu::Maintain::Poll);
+        let mut mapped: Vec<&Slot> = self
+            .slots
+            .iter()
+            .flatten()
+            .filter(|slot| slot.state.load(Ordering::Acquire) == MAPPED)
+            .collect();
+        mapped.sort_by_key(|slot| slot.time);
+        for slot in mapped {
+            let rgba = screenshot::to_rgba(
+                &slot.buffer.slice(..).get_mapped_range(),
+                slot.format,
+                slot.width,
+                slot.padded_row,
+            );
+            slot.buffer.unmap();
+            slot.state.store(FREE, Ordering::Release);
+            // A full queue drops the frame, a closed one is reported by the worker.
+            let _ = self.frames.try_send(RecordedFrame {
+                width: slot.width,
+                height: slot.height,
+                rgba,
+                time: slot.time,
+            });
+        }
+    }
+
+    /// Drops the buffers made with the old device, after it was made again.
+    pub fn device_recreated(&mut self) {
+        self.slots.iter_mut().for_each(|slot| *slot = None);
+        self.current = None;
+    }
+}
+
+#[cfg(all(feature = "recording", not(target_arch = "wasm32")))]
+fn spawn_encoder(
+    path: PathBuf,
+    frames: Receiver<RecordedFrame>,
+    proxy: EventLoopProxy<UserEvent>,
+) -> Result<(), &'static str> {
+    std::thread::Builder::new()
+        .name("recorder".to_owned())
+        .spawn(move || {
+            let result = encode(path, frames);
+            if let Err(e) = &result {
+                tracing::error!("Recording failed: {}", e);
+            }
+            let _ = proxy.send_event(UserEvent::RecordingSaved(result));
+        })
+        .map(drop)
+        .map_err(|_| "Starting the encoder failed")
+}
+
+#[cfg(not(feature = "recording"))]
+fn spawn_encoder(
+    _path: PathBuf,
+    _frames: Receiver<RecordedFrame>,
+    _proxy: EventLoopProxy<UserEvent>,
+) -> Result<(), &'static str> {
+    Err("Built without the recording feature")
+}
+
+#[cfg(all(feature = "recording", target_arch = "wasm32"))]
+fn spawn_encoder(
+    _path: PathBuf,
+    _frames: Receiver<RecordedFrame>,
+    _proxy: EventLoopProxy<UserEvent>,
+) -> Result<(), &'static str> {
+    Err("The browser has no thread to encode on")
+}
+
+/// Encodes the frames until the `Recorder` is dropped. The GIF is as large as the first
+/// frame, later frames of another size are skipped.
+#[cfg(all(feature = "recording", not(target_arch = "wasm32")))]
+fn encode(path: PathBuf, frames: Receiver<RecordedFrame>) -> Result<PathBuf, String> {
+    let mut frames = frames.into_iter();
+    let Some(first) = frames.next() else {
+        return Err("No frames were recorded".to_owned());
+    };
+    let (Ok(width), Ok(height)) = (u16::try_from(first.width), u16::try_from(first.height)) else {
+        return Err("The window is too large for a GIF".to_owned());
+    };
+    let file = std::fs::File::create(&path).map_err(|e| e.to_string())?;
+    let mut encoder = gif::Encoder::new(std::io::BufWriter::new(file), width, height, &[])
+        .map_err(|e| e.to_string())?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(|e| e.to_string())?;
+
+    // A frame's delay is only known once the next one arrives.
+    let size = (first.width, first.height);
+    let mut previous = first;
+    for next in frames.filter(|frame| (frame.width, frame.height) == size) {
+        write_frame(&mut encoder, &mut previous, next.time)?;
+        previous = next;
+    }
+    let end = previous.time + FRAME_INTERVAL;
+    write_frame(&mut encoder, &mut previous, end)?;
+    Ok(path)
+}
+
+#[cfg(all(feature = "recording", not(target_arch = "wasm32")))]
+fn write_frame(
+    encoder: &mut gif::Encoder<impl std::io::Write>,
+    frame: &mut RecordedFrame,
+    until: Duration,
+) -> Result<(), String> {
+    let mut gif_frame =
+        gif::Frame::from_rgba_speed(frame.width as u16, frame.height as u16, &mut frame.rgba, 10);
+    gif_frame.delay = ((until - frame.time).as_millis() / 10).clamp(2, u16::MAX as u128) as u16;
+    encoder.write_frame(&gif_frame).map_err(|e| e.to_string())
+}
@@ -0,0 +1,137 @@
+use std::future::Future;
+
+// Only dialogs and the update check are run in the background, neither of which the
+// browser or Android have.
+#[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+use crate::events::UserEvent;
+#[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+use winit::event_loop::EventLoopProxy;
+
+/// The runtime background work runs on, started by whatever needs it first.
+#[cfg(all(feature = "tokio", not(target_arch = "wasm32")))]
+fn runtime() -> &'static tokio::runtime::Runtime {
+    static RUNTIME: std::sync::OnceLock<tokio::runtime::Runtime> = std::sync::OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .thread_name("tokio")
+            .build()
+            .unwrap_or_else(|e| panic!("Failed to start the tokio runtime: {}", e))
+    })
+}
+
+/// Runs `future` to the end on this thread, for when nothing can go on without it.
+#[cfg(all(feature = "tokio", not(target_arch = "wasm32")))]
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    runtime().block_on(future)
+}
+
+/// Runs `future` to the end on this thread, for when nothing can go on without it.
+#[cfg(not(all(feature = "tokio", not(target_arch = "wasm32"))))]
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    pollster::block_on(future)
+}
+
+/// Runs `future` in the background, sending the event it makes to the event loop,
+/// which it wakes. Nothing is sent for `None`.
+#[cfg(all(
+    feature = "tokio",
+    not(any(target_arch = "wasm32", target_os = "android"))
+))]
+pub fn spawn(
+    name: &str,
+    proxy: EventLoopProxy<UserEvent>,
+    future: impl Future<Output = Option<UserEvent>> + Send + 'static,
+) -> std::io::Result<()> {
+    tracing::debug!("Running {} on tokio", name);
+    runtime().spawn(async move {
+        if let Some(event) = future.await {
+            let _ = proxy.send_event(event);
+        }
+    });
+    Ok(())
+}
+
+/// Runs `future` on a thread of its own named `name`, sending the event it makes to
+/// the event loop, which it wakes. Nothing is sent for `None`.
+#[cfg(not(any(feature = "tokio", target_arch = "wasm32", target_os = "android")))]
+pub fn spawn(
+    name: &str,
+    proxy: EventLoopProxy<UserEvent>,
+    future: impl Future<Output = Option<UserEvent>> + Send + 'static,
+) -> std::io::Result<()> {
+    std::thread::Builder::new()
+        .name(name.to_owned())
+        .spawn(move || {
+            if let Some(event) = pollster::block_on(future) {
+                let _ = proxy.send_event(event);
+            }
+        })
+        .map(drop)
+}
+
+/// Like `spawn`, for work that blocks, like `ureq`'s requests, which has a thread of
+/// tokio's blocking pool to itself.
+#[cfg(all(
+    feature = "tokio",
+    not(any(target_arch = "wasm32", target_os = "android"))
+))]
+pub fn spawn_blocking(
+    name: &str,
+    proxy: EventLoopProxy<UserEvent>,
+    work: impl FnOnce() -> Option<UserEvent> + Send + 'static,
+) -> std::io::Result<()> {
+    tracing::debug!("Running {} on tokio's blocking threads", name);
+    runtime().spawn_blocking(move || {
+        if let Some(event) = work() {
+            let _ = proxy.send_event(event);
+        }
+    });
+    Ok(())
+}
+
+/// Like `spawn`, for work that blocks, like `ureq`'s requests.
+#[cfg(not(any(feature = "tokio", target_arch = "wasm32", target_os = "android")))]
+pub fn spawn_blocking(
+    name: &str,
+    proxy: EventLoopProxy<UserEvent>,
+    work: impl FnOnce() -> Option<UserEvent> + Send + 'static,
+) -> std::io::Result<()> {
+    std::thread::Builder::new()
+        .name(name.to_owned())
+        .spawn(move || {
+            if let Some(event) = work() {
+                let _ = proxy.send_event(event);
+            }
+        })
+        .map(drop)
+}
+
+/// Reads the file at `path` without holding up the runtime's threads.
+#[cfg(all(
+    feature = "tokio",
+    not(any(target_arch = "wasm32", target_os = "android"))
+))]
+pub async fn read(path: std::path::PathBuf) -> std::io::Result<Vec<u8>> {
+    tokio::fs::read(path).await
+}
+
+/// Reads the file at `path`, on the thread it was spawned on.
+#[cfg(not(any(feature = "tokio", target_arch = "wasm32", target_os = "android")))]
+pub async fn read(path: std::path::PathBuf) -> std::io::Result<Vec<u8>> {
+    std::fs::read(path)
+}
+
+/// Writes `bytes` to the file at `path` without holding up the runtime's threads.
+#[cfg(all(
+    feature = "tokio",
+    not(any(target_arch = "wasm32", target_os = "android"))
+))]
+pub async fn write(path: std::path::PathBuf, bytes: Vec<u8>) -> std::io::Result<()> {
+    tokio::fs::write(path, bytes).await
+}
+
+/// Writes `bytes` to the file at `path`, on the thread it was spawned on.
+#[cfg(not(any(feature = "tokio", target_arch = "wasm32", target_os = "android")))]
+pub async fn write(path: std::path::PathBuf, bytes: Vec<u8>) -> std::io::Result<()> {
+    std::fs::write(path, bytes)
+}
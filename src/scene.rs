@@ -0,0 +1,401 @@
+use crate::camera::Camera;
+use crate::hdr;
+use crate::pipeline::{Mesh, Pipeline, PipelineConfig};
+use crate::RenderFrame;
+
+use glam::{Mat4, Vec3};
+use wgpu::util::DeviceExt;
+
+/// Radians per second the cube turns at.
+const SPIN_SPEED: f32 = 0.6;
+const CHECKER_SIZE: u32 = 64;
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Vertex {
+    position: [f32; 3],
+    normal: [f32; 3],
+    uv: [f32; 2],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct SceneUniforms {
+    view_proj: [[f32; 4]; 4],
+    model: [[f32; 4]; 4],
+    encode_srgb: f32,
+    _pad: [f32; 3],
+}
+
+/// A rotating textured cube drawn beneath the UI, to show the app's own rendering
+/// composited with egui.
+pub struct CubeScene {
+    pipeline: Pipeline,
+    mesh: Mesh<Vertex>,
+    uniforms: wgpu::Buffer,
+    sampler: wgpu::Sampler,
+    /// The checkerboard the cube has until `set_texture` gives it another texture.
+    checker: wgpu::TextureView,
+    bind_group: wgpu::BindGroup,
+    /// False when the target isn't sRGB or float, so the output has to be gamma encoded.
+    srgb_target: bool,
+    /// What the pipeline was made for, to make it again with another shader.
+    target: (wgpu::TextureFormat, u32, Option<wgpu::TextureFormat>),
+}
+
+impl CubeScene {
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+        depth_format: Option<wgpu::TextureFormat>,
+    ) -> Self {
+        let pipeline = pipeline(
+            device,
+            include_str!("shaders/scene.wgsl"),
+            format,
+            sample_count,
+            depth_format,
+        );
+
+        let (vertices, indices) = cube();
+        let mut mesh = Mesh::new(device, "scene cube");
+        mesh.upload(device, queue, &vertices, &indices);
+        let uniforms = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("scene uniforms"),
+            size: std::mem::size_of::<SceneUniforms>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let texture = device.create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label: Some("scene checker"),
+                size: wgpu::Extent3d {
+                    width: CHECKER_SIZE,
+                    height: CHECKER_SIZE,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+            &checker(),
+        );
+        let checker = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("scene"),
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let bind_group = bind_group(device, &pipeline, &uniforms, &sampler, &checker);
+
+        Self {
+            pipeline,
+            mesh,
+            uniforms,
+            sampler,
+            checker,
+            bind_group,
+            srgb_target: hdr::is_linear(format),
+            target: (format, sample_count, depth_format),
+        }
+    }
+
+    /// Draws with `wgsl` from now on, an edit of `scene.wgsl`. The cube's back to the
+    /// checkerboard, as the bind group was laid out by the old shader.
+    pub fn set_shader(&mut self, device: &wgpu::Device, wgsl: &str) {
+        let (format, sample_count, depth_format) = self.target;
+        self.pipeline = pipeline(device, wgsl, format, sample_count, depth_format);
+        self.set_texture(device, None);
+    }
+
+    /// Draws the cube's faces with `texture`, or the checkerboard again for `None`.
+    pub fn set_texture(&mut self, device: &wgpu::Device, texture: Option<&wgpu::TextureView>) {
+        self.bind_group = bind_group(
+            device,
+            &self.pipeline,
+            &self.uniforms,
+            &self.sampler,
+            texture.unwrap_or(&self.checker),
+        );
+    }
+
+    /// The camera looking at the cube as it starts out, for a target of `aspect` width
+    /// over height.
+    pub fn camera(aspect: f32) -> Mat4 {
+        Camera::default().view_proj(aspect)
+    }
+
+    /// How the cube is turned `time` seconds in.
+    pub fn spin(time: f32) -> Mat4 {
+        Mat4::from_rotation_y(time * SPIN_SPEED) * Mat4::from_rotation_x(time * SPIN_SPEED * 0.5)
+    }
+
+    fn uniforms(&self, view_proj: Mat4, model: Mat4) -> SceneUniforms {
+        SceneUniforms {
+            view_proj: view_proj.to_cols_array_2d(),
+            model: model.to_cols_array_2d(),
+            encode_srgb: if self.srgb_target { 0.0 } else { 1.0 },
+            _pad: [0.0; 3],
+        }
+    }
+
+    /// Writes the uniforms the next `draw` uses.
+    pub fn prepare(&self, queue: &wgpu::Queue, view_proj: Mat4, model: Mat4) {
+        let uniforms = self.uniforms(view_proj, model);
+        queue.write_buffer(&self.uniforms, 0, bytemuck::bytes_of(&uniforms));
+    }
+
+    pub fn draw<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>) {
+        self.pipeline.draw(pass, &self.bind_group, &self.mesh);
+    }
+
+    /// Draws the cube turned by `model`, like `spin`, through the camera's `view_proj`
+    /// into a target that was already cleared. `depth` has to be given if the scene was
+    /// made with a depth format.
+    pub fn render(
+        &self,
+        frame: &mut RenderFrame<'_>,
+        view: &wgpu::TextureView,
+        depth: Option<&wgpu::TextureView>,
+        view_proj: Mat4,
+        model: Mat4,
+    ) {
+        let uniforms = self.uniforms(view_proj, model);
+        frame.write_buffer(&self.uniforms, 0, bytemuck::bytes_of(&uniforms));
+
+        let mut pass = frame
+            .encoder
+            .begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("scene"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: depth.map(|view| {
+                    wgpu::RenderPassDepthStencilAttachment {
+                        view,
+                        depth_ops: Some(wgpu::Operations {
+                            load: wgpu::LoadOp::Load,
+                            store: true,
+                        }),
+                        stencil_ops: None,
+                    }
+                }),
+            });
+        self.draw(&mut pass);
+    }
+}
+
+/// The cube scene once more, drawn by a paint callback inside a window instead of
+/// beneath the whole UI. Dragging it turns the cube.
+pub struct ScenePanel {
+    pub open: bool,
+    yaw: f32,
+}
+
+impl Default for ScenePanel {
+    fn default() -> Self {
+        Self {
+            open: false,
+            yaw: 0.0,
+        }
+    }
+}
+
+impl ScenePanel {
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        let mut open = self.open;
+        egui::Window::new("Scene panel")
+            .open(&mut open)
+            .default_size([400.0, 300.0])
+            .show(ctx, |ui| {
+                ui.weak("A wgpu scene inside egui, drag it to turn the cube");
+                egui::Frame::canvas(ui.style()).show(ui, |ui| self.canvas(ui));
+            });
+        self.open = open;
+    }
+
+    pub(crate) fn canvas(&mut self, ui: &mut egui::Ui) {
+        let size = ui.available_size().max(egui::vec2(100.0, 100.0));
+        let (rect, response) = ui.allocate_exact_size(size, egui::Sense::drag());
+        self.yaw += response.drag_delta().x * 0.01;
+
+        // egui sets the viewport to the callback's rect, which can't reach past the
+        // screen. The callback only covers the visible part, and the projection is
+        // cropped to match so the cube keeps its place and shape when clipped.
+        let visible = rect
+            .intersect(ui.clip_rect())
+            .intersect(ui.ctx().screen_rect());
+        if !visible.is_positive() {
+            return;
+        }
+        let crop = Mat4::from_translation(Vec3::new(
+            (rect.center().x - visible.center().x) * 2.0 / visible.width(),
+            (visible.center().y - rect.center().y) * 2.0 / visible.height(),
+            0.0,
+        )) * Mat4::from_scale(Vec3::new(
+            rect.width() / visible.width(),
+            rect.height() / visible.height(),
+            1.0,
+        ));
+        let time = ui.input(|i| i.time) as f32;
+        let callback = SceneCallback {
+            view_proj: crop * CubeScene::camera(rect.aspect_ratio()),
+            model: Mat4::from_rotation_y(self.yaw) * CubeScene::spin(time),
+        };
+        ui.painter()
+            .add(egui_wgpu::Callback::new_paint_callback(visible, callback));
+        ui.ctx().request_repaint();
+    }
+}
+
+/// The `CubeScene` in the egui renderer's callback resources, drawing in egui's format.
+pub struct SceneResources(pub CubeScene);
+
+struct SceneCallback {
+    view_proj: Mat4,
+    model: Mat4,
+}
+
+impl egui_wgpu::CallbackTrait for SceneCallback {
+    fn prepare(
+        &self,
+        _device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        _egui_encoder: &mut wgpu::CommandEncoder,
+        callback_resources: &mut egui_wgpu::CallbackResources,
+    ) -> Vec<wgpu::CommandBuffer> {
+        if let Some(SceneResources(scene)) = callback_resources.get::<SceneResources>() {
+            scene.prepare(queue, self.view_proj, self.model);
+        }
+        Vec::new()
+    }
+
+    fn paint<'a>(
+        &'a self,
+        _info: egui::PaintCallbackInfo,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        callback_resources: &'a egui_wgpu::CallbackResources,
+    ) {
+        // The viewport is already the callback's rect and the clip rect the scissor.
+        if let Some(SceneResources(scene)) = callback_resources.get::<SceneResources>() {
+            scene.draw(render_pass);
+        }
+    }
+}
+
+/// The cube's pipeline, drawing with `wgsl`, `scene.wgsl` or an edit of it.
+fn pipeline(
+    device: &wgpu::Device,
+    wgsl: &str,
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+    depth_format: Option<wgpu::TextureFormat>,
+) -> Pipeline {
+    Pipeline::new(
+        device,
+        &PipelineConfig {
+            label: "scene",
+            wgsl,
+            vertex_layout: wgpu::VertexBufferLayout {
+                array_stride: std::mem::size_of::<Vertex>() as u64,
+                step_mode: wgpu::VertexStepMode::Vertex,
+                attributes: &wgpu::vertex_attr_array![
+                    0 => Float32x3,
+                    1 => Float32x3,
+                    2 => Float32x2,
+                ],
+            },
+            format,
+            sample_count,
+            // Without a depth buffer culling the back faces is enough, the cube is
+            // convex.
+            cull_mode: Some(wgpu::Face::Back),
+            depth_format,
+            blend: None,
+        },
+    )
+}
+
+fn bind_group(
+    device: &wgpu::Device,
+    pipeline: &Pipeline,
+    uniforms: &wgpu::Buffer,
+    sampler: &wgpu::Sampler,
+    texture: &wgpu::TextureView,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("scene"),
+        layout: &pipeline.bind_group_layout(0),
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniforms.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(sampler),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: wgpu::BindingResource::TextureView(texture),
+            },
+        ],
+    })
+}
+
+/// A unit cube with its own four vertices per face, so each face gets its normal and
+/// the whole texture.
+fn cube() -> (Vec<Vertex>, Vec<u16>) {
+    // Each face's normal, with the two axes spanning it in counterclockwise order.
+    let faces = [
+        (Vec3::X, Vec3::NEG_Z, Vec3::Y),
+        (Vec3::NEG_X, Vec3::Z, Vec3::Y),
+        (Vec3::Y, Vec3::X, Vec3::NEG_Z),
+        (Vec3::NEG_Y, Vec3::X, Vec3::Z),
+        (Vec3::Z, Vec3::X, Vec3::Y),
+        (Vec3::NEG_Z, Vec3::NEG_X, Vec3::Y),
+    ];
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    for (normal, u, v) in faces {
+        let base = vertices.len() as u16;
+        for (s, t) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+            let position = (normal + u * s + v * t) * 0.5;
+            vertices.push(Vertex {
+                position: position.into(),
+                normal: normal.into(),
+                uv: [(s + 1.0) * 0.5, (1.0 - t) * 0.5],
+            });
+        }
+        indices.extend([0, 1, 2, 0, 2, 3].map(|i| base + i));
+    }
+    (vertices, indices)
+}
+
+/// An 8 by 8 checkerboard, in sRGB.
+fn checker() -> Vec<u8> {
+    let cell = CHECKER_SIZE / 8;
+    (0..CHECKER_SIZE * CHECKER_SIZE)
+        .flat_map(|i| {
+            let (x, y) = (i % CHECKER_SIZE / cell, i / CHECKER_SIZE / cell);
+            if (x + y) % 2 == 0 {
+                [230, 140, 40, 255]
+            } else {
+                [40, 40, 50, 255]
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,218 @@
+use instant::SystemTime;
+
+use std::path::{Path, PathBuf};
+
+/// A frame read back from the surface, as sRGB RGBA8. It's opaque, as the surface is
+/// shown without alpha whatever the frame left in it.
+pub struct Screenshot {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+impl Screenshot {
+    /// Writes it to a PNG named after the time it's saved at, in the current directory.
+    pub fn save_png(&self) -> std::io::Result<PathBuf> {
+        let millis = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let path = PathBuf::from(format!("screenshot-{}.png", millis));
+        self.write_png(&path)?;
+        Ok(path)
+    }
+
+    /// The part of it inside `rect`, in pixels, clamped to the frame.
+    pub fn crop(&self, rect: egui::Rect) -> Screenshot {
+        let clamp = |v: f32, max: u32| (v.round().max(0.0) as u32).min(max);
+        let (left, right) = (clamp(rect.min.x, self.width), clamp(rect.max.x, self.width));
+        let (top, bottom) = (
+            clamp(rect.min.y, self.height),
+            clamp(rect.max.y, self.height),
+        );
+        let row = |y: u32| {
+            let start = (y * self.width + left) as usize * 4;
+            &self.rgba[start..start + (right - left) as usize * 4]
+        };
+        Screenshot {
+            width: right - left,
+            height: bottom - top,
+            rgba: (top..bottom).flat_map(row).copied().collect(),
+        }
+    }
+
+    pub fn write_png(&self, path: &Path) -> std::io::Result<()> {
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        let mut encoder = png::Encoder::new(file, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&self.rgba))
+            .map_err(std::io::Error::other)
+    }
+}
+
+/// A copy of the surface texture on its way to a mappable buffer.
+pub(crate) struct Capture {
+    buffer: wgpu::Buffer,
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+    /// Rows are padded to `COPY_BYTES_PER_ROW_ALIGNMENT` in the buffer.
+    padded_row: u32,
+}
+
+impl Capture {
+    /// Records copying `texture` into a buffer, to be read once the commands are
+    /// submitted. Fails for the surface formats that can't be converted to RGBA8.
+    pub fn copy(
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+    ) -> Result<Self, &'static str> {
+        let format = texture.format();
+        let (width, height) = (texture.width(), texture.height());
+        let padded_row = padded_row(format, width).ok_or("The surface's format can't be saved")?;
+        let buffer = device.create_buffer(&readback_buffer("screenshot", padded_row, height));
+        copy_to_buffer(encoder, texture, &buffer, padded_row);
+        Ok(Self {
+            buffer,
+            format,
+            width,
+            height,
+            padded_row,
+        })
+    }
+
+    /// Waits for the copy and converts it, after the commands were submitted.
+    pub fn read(self, device: &wgpu::Device) -> Result<Screenshot, &'static str> {
+        let slice = self.buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        device.poll(wgpu::Maintain::Wait);
+        if !matches!(receiver.recv(), Ok(Ok(()))) {
+            return Err("Reading the frame back failed");
+        }
+
+        let rgba = to_rgba(
+            &slice.get_mapped_range(),
+            self.format,
+            self.width,
+            self.padded_row,
+        );
+        self.buffer.unmap();
+        Ok(Screenshot {
+            width: self.width,
+            height: self.height,
+            rgba,
+        })
+    }
+}
+
+/// The bytes of a row of `width` pixels of `format` in a buffer copied to, `None` for
+/// the formats `to_rgba` can't convert.
+pub(crate) fn padded_row(format: wgpu::TextureFormat, width: u32) -> Option<u32> {
+    let bytes_per_pixel = match format {
+        wgpu::TextureFormat::Rgba8Unorm
+        | wgpu::TextureFormat::Rgba8UnormSrgb
+        | wgpu::TextureFormat::Bgra8Unorm
+        | wgpu::TextureFormat::Bgra8UnormSrgb => 4,
+        wgpu::TextureFormat::Rgba16Float => 8,
+        _ => return None,
+    };
+    Some((width * bytes_per_pixel).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT))
+}
+
+/// A buffer to map `height` rows of `padded_row` bytes copied from a texture into.
+pub(crate) fn readback_buffer(
+    label: &'static str,
+    padded_row: u32,
+    height: u32,
+) -> wgpu::BufferDescriptor<'static> {
+    wgpu::BufferDescriptor {
+        label: Some(label),
+        size: padded_row as u64 * height as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    }
+}
+
+pub(crate) fn copy_to_buffer(
+    encoder: &mut wgpu::CommandEncoder,
+    texture: &wgpu::Texture,
+    buffer: &wgpu::Buffer,
+    padded_row: u32,
+) {
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::ImageCopyBuffer {
+            buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_row),
+                rows_per_image: None,
+            },
+        },
+        texture.size(),
+    );
+}
+
+/// Opaque sRGB RGBA8 pixels from the rows of a buffer a texture of `format` was copied
+/// to.
+pub(crate) fn to_rgba(
+    data: &[u8],
+    format: wgpu::TextureFormat,
+    width: u32,
+    padded_row: u32,
+) -> Vec<u8> {
+    let rows = data.chunks_exact(padded_row as usize);
+    let mut rgba = Vec::with_capacity(width as usize * 4 * rows.len());
+    for row in rows {
+        match format {
+            wgpu::TextureFormat::Rgba16Float => {
+                let halves: &[u16] = bytemuck::cast_slice(row);
+                for pixel in halves.chunks_exact(4).take(width as usize) {
+                    let [r, g, b] = [0, 1, 2].map(|i| encode_srgb(f16_to_f32(pixel[i])));
+                    rgba.extend_from_slice(&[r, g, b, 255]);
+                }
+            }
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => {
+                for pixel in row.chunks_exact(4).take(width as usize) {
+                    rgba.extend_from_slice(&[pixel[2], pixel[1], pixel[0], 255]);
+                }
+            }
+            _ => {
+                for pixel in row.chunks_exact(4).take(width as usize) {
+                    rgba.extend_from_slice(&[pixel[0], pixel[1], pixel[2], 255]);
+                }
+            }
+        }
+    }
+    rgba
+}
+
+/// A linear value in sRGB, with what's beyond SDR white clipped.
+fn encode_srgb(linear: f32) -> u8 {
+    let linear = linear.clamp(0.0, 1.0);
+    let encoded = if linear <= 0.003_130_8 {
+        linear * 12.92
+    } else {
+        1.055 * linear.powf(1.0 / 2.4) - 0.055
+    };
+    (encoded * 255.0).round() as u8
+}
+
+fn f16_to_f32(half: u16) -> f32 {
+    let sign = if half & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = (half >> 10) & 0x1f;
+    let mantissa = (half & 0x3ff) as f32;
+    sign * match exponent {
+        0 => mantissa * 2f32.powi(-24),
+        0x1f if mantissa == 0.0 => f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => (1.0 + mantissa / 1024.0) * 2f32.powi(exponent as i32 - 15),
+    }
+}
@@ -0,0 +1,196 @@
+#[cfg(not(target_arch = "wasm32"))]
+use crate::events::UserEvent;
+
+#[cfg(not(target_arch = "wasm32"))]
+use winit::event_loop::EventLoopProxy;
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// The shaders whose pipelines are rebuilt when they're edited. Edits to the others are
+/// only checked, they're seen after a restart.
+pub const RELOADED: [&str; 2] = ["scene.wgsl", "model.wgsl"];
+
+/// The shaders of the checkout the app was built from, which are only there to watch
+/// when it's run from it, like with `cargo run`.
+pub fn shaders_dir() -> PathBuf {
+    PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/src/shaders"))
+}
+
+/// Parses and validates `wgsl` with naga like wgpu would, but returns what's wrong
+/// with it, pointing at the lines, instead of failing the device.
+pub fn check(name: &str, wgsl: &str) -> Result<(), String> {
+    use naga::valid::{Capabilities, ValidationFlags, Validator};
+
+    let module =
+        naga::front::wgsl::parse_str(wgsl).map_err(|e| e.emit_to_string_with_path(wgsl, name))?;
+    Validator::new(ValidationFlags::all(), Capabilities::all())
+        .validate(&module)
+        .map(drop)
+        .map_err(|e| e.emit_to_string_with_path(wgsl, name))
+}
+
+/// Calls `on_change` on a watcher thread with the file name and new contents of each
+/// shader saved in `shaders_dir`. Keep the watcher to keep watching.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn watch(
+    on_change: impl Fn(String, String) + Send + 'static,
+) -> Result<notify::RecommendedWatcher, String> {
+    use notify::{EventKind, RecursiveMode, Watcher};
+
+    let dir = shaders_dir();
+    if !dir.is_dir() {
+        return Err(format!("{} isn't there", dir.display()));
+    }
+    let mut last = BTreeMap::<String, String>::new();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let Ok(event) = event else {
+            return;
+        };
+        if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+            return;
+        }
+        for path in event.paths {
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            if !name.ends_with(".wgsl") {
+                continue;
+            }
+            let Ok(text) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            // Editors tend to write a file in several steps.
+            if last.get(name) == Some(&text) {
+                continue;
+            }
+            last.insert(name.to_owned(), text.clone());
+            on_change(name.to_owned(), text);
+        }
+    })
+    .map_err(|e| e.to_string())?;
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .map_err(|e| e.to_string())?;
+    Ok(watcher)
+}
+
+#[derive(Default)]
+struct EditedShader {
+    /// The last edit that compiled.
+    source: Option<String>,
+    /// What's wrong with the last edit, if it didn't compile.
+    error: Option<String>,
+    reloads: u32,
+}
+
+/// The "Shaders" window and the shaders edited while the app runs. An edit that
+/// compiles replaces the shader of the pipelines made from it; one that doesn't is
+/// shown here, and the last one that did is kept.
+#[derive(Default)]
+pub struct ShaderReload {
+    pub open: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    watcher: Option<notify::RecommendedWatcher>,
+    shaders: BTreeMap<String, EditedShader>,
+    /// Edits that compiled and weren't rebuilt with yet.
+    changed: Vec<String>,
+}
+
+impl ShaderReload {
+    /// Watches `shaders_dir`, if the app's run from its checkout.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn watch(&mut self, proxy: EventLoopProxy<UserEvent>) {
+        match watch(move |name, source| {
+            let _ = proxy.send_event(UserEvent::ShaderChanged(name, source));
+        }) {
+            Ok(watcher) => {
+                tracing::info!("Watching {} for edits", shaders_dir().display());
+                self.watcher = Some(watcher);
+            }
+            Err(e) => tracing::debug!("Edited shaders will not be reloaded: {}", e),
+        }
+    }
+
+    /// Checks the new `source` of the shader named `name`, like "scene.wgsl", opening
+    /// the window if it doesn't compile.
+    pub fn on_change(&mut self, name: String, source: String) {
+        let shader = self.shaders.entry(name.clone()).or_default();
+        match check(&name, &source) {
+            Ok(()) => {
+                tracing::info!("Reloading {}", name);
+                shader.source = Some(source);
+                shader.error = None;
+                shader.reloads += 1;
+                self.mark_changed(&name);
+            }
+            Err(e) => {
+                tracing::warn!("{} doesn't compile:\n{}", name, e);
+                shader.error = Some(e);
+                self.open = true;
+            }
+        }
+    }
+
+    /// The last edit to the shader named `name` that compiled, if it was edited.
+    pub fn source(&self, name: &str) -> Option<&str> {
+        self.shaders.get(name)?.source.as_deref()
+    }
+
+    /// Has the pipelines made from `name` rebuilt with its edit again, if it was
+    /// edited, like after they were made anew with the shader that was built in.
+    pub fn mark_changed(&mut self, name: &str) {
+        if self.source(name).is_some() && !self.changed.iter().any(|changed| changed == name) {
+            self.changed.push(name.to_owned());
+        }
+    }
+
+    /// The shaders that compiled since this was last called, to rebuild with.
+    pub fn take_changed(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.changed)
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        egui::Window::new("Shaders")
+            .open(&mut open)
+            .default_width(460.0)
+            .show(ctx, |ui| {
+                #[cfg(not(target_arch = "wasm32"))]
+                let watching = self.watcher.is_some();
+                #[cfg(target_arch = "wasm32")]
+                let watching = false;
+                if watching {
+                    ui.weak(format!("Watching {}", shaders_dir().display()));
+                } else {
+                    ui.weak("Not watching, edits are only seen when run from the checkout");
+                }
+                if self.shaders.is_empty() {
+                    ui.weak("No shader was edited yet");
+                }
+                for (name, shader) in &self.shaders {
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.strong(name);
+                        if !RELOADED.contains(&name.as_str()) {
+                            ui.weak("(seen after a restart)");
+                        }
+                    });
+                    if shader.reloads > 0 {
+                        ui.label(format!("Compiled {} times", shader.reloads));
+                    }
+                    if let Some(error) = &shader.error {
+                        ui.colored_label(
+                            ui.visuals().error_fg_color,
+                            egui::RichText::new(error).monospace(),
+                        );
+                    }
+                }
+            });
+        self.open = open;
+    }
+}
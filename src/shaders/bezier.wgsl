@@ -0,0 +1,60 @@
+// Draws the Bezier editor's path as a thick line with anti-aliased edges.
+//
+// The path arrives flattened into a triangle strip along its center line, each vertex
+// carries the offset to the edge and its distance along the path.
+
+struct Uniforms {
+    // The size of the screen in points.
+    screen_size: vec2<f32>,
+    half_width: f32,
+    pixels_per_point: f32,
+    // Premultiplied, already in the encoding of the target.
+    color: vec4<f32>,
+    // The length of the dashes in points, 0 draws a solid line.
+    dash: f32,
+    _pad0: f32,
+    _pad1: f32,
+    _pad2: f32,
+};
+
+struct VertexInput {
+    @location(0) center: vec2<f32>,
+    @location(1) normal: vec2<f32>,
+    @location(2) side: f32,
+    @location(3) arc_length: f32,
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    // The distance from the center line in pixels, signed by side.
+    @location(0) distance: f32,
+    @location(1) arc_length: f32,
+};
+
+@group(0) @binding(0) var<uniform> u: Uniforms;
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    // One pixel wider than the line, room for the anti-aliased edge.
+    let extent = u.half_width + 1.0 / u.pixels_per_point;
+    let pos = in.center + in.normal * in.side * extent;
+    var out: VertexOutput;
+    out.position = vec4<f32>(
+        pos.x / u.screen_size.x * 2.0 - 1.0,
+        1.0 - pos.y / u.screen_size.y * 2.0,
+        0.0,
+        1.0,
+    );
+    out.distance = in.side * extent * u.pixels_per_point;
+    out.arc_length = in.arc_length;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    var coverage = clamp(u.half_width * u.pixels_per_point + 0.5 - abs(in.distance), 0.0, 1.0);
+    if u.dash > 0.0 && fract(in.arc_length / (2.0 * u.dash)) > 0.5 {
+        coverage = 0.0;
+    }
+    return u.color * coverage;
+}
@@ -0,0 +1,26 @@
+// Blends the UI, rendered to an offscreen sRGB texture, over the wide-gamut surface.
+
+struct Params {
+    // The columns of the matrix taking linear sRGB to the output color space.
+    m0: vec4<f32>,
+    m1: vec4<f32>,
+    m2: vec4<f32>,
+};
+
+@group(0) @binding(0) var ui_texture: texture_2d<f32>;
+@group(0) @binding(1) var<uniform> params: Params;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    // A single triangle covering the whole target.
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0), 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    // The texture is sRGB encoded, so this is already linear and premultiplied.
+    let color = textureLoad(ui_texture, vec2<i32>(position.xy), 0);
+    let m = mat3x3<f32>(params.m0.xyz, params.m1.xyz, params.m2.xyz);
+    return vec4<f32>(m * color.rgb, color.a);
+}
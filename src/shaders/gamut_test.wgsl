@@ -0,0 +1,37 @@
+// Two rows of patches: the sRGB primaries on top, the Display P3 primaries below.
+// The colors are linear extended sRGB, on an sRGB surface the P3 ones get clipped and
+// look just like the row above.
+
+var<private> COLORS: array<vec3<f32>, 6> = array<vec3<f32>, 6>(
+    vec3<f32>(1.0, 0.0, 0.0),
+    vec3<f32>(0.0, 1.0, 0.0),
+    vec3<f32>(0.0, 0.0, 1.0),
+    vec3<f32>(1.2249, -0.0421, -0.0196),
+    vec3<f32>(-0.2249, 1.0421, -0.0786),
+    vec3<f32>(0.0, 0.0, 1.0983),
+);
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec3<f32>,
+};
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) vertex: u32,
+    @builtin(instance_index) cell_index: u32,
+) -> VertexOutput {
+    let corner = vec2<f32>(f32(vertex & 1u), f32((vertex >> 1u) & 1u));
+    let cell = vec2<f32>(f32(cell_index % 3u), f32(1u - cell_index / 3u));
+    let origin = vec2<f32>(-0.95, -0.95) + cell * 0.15;
+
+    var out: VertexOutput;
+    out.position = vec4<f32>(origin + corner * 0.14, 0.0, 1.0);
+    out.color = COLORS[cell_index];
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(in.color, 1.0);
+}
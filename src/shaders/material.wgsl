@@ -0,0 +1,131 @@
+// Draws the material editor's preview: a lit sphere filling the callback's viewport.
+//
+// The sphere is traced analytically per pixel, so there's no mesh: a fullscreen triangle
+// covers the viewport and pixels outside the unit circle come out transparent.
+
+struct Material {
+    // Linear, the alpha is unused.
+    base_color: vec4<f32>,
+    // Linear, with the strength in w.
+    emissive: vec4<f32>,
+    metallic: f32,
+    roughness: f32,
+    normal_scale: f32,
+    ao_strength: f32,
+    // Around the vertical axis, in radians.
+    rotation: f32,
+    // 1 when the target isn't sRGB and the output has to be gamma encoded.
+    gamma_output: f32,
+    _pad0: f32,
+    _pad1: f32,
+};
+
+@group(0) @binding(0) var<uniform> material: Material;
+@group(0) @binding(1) var material_sampler: sampler;
+@group(0) @binding(2) var base_color_map: texture_2d<f32>;
+@group(0) @binding(3) var metallic_map: texture_2d<f32>;
+@group(0) @binding(4) var roughness_map: texture_2d<f32>;
+@group(0) @binding(5) var emissive_map: texture_2d<f32>;
+@group(0) @binding(6) var normal_map: texture_2d<f32>;
+@group(0) @binding(7) var occlusion_map: texture_2d<f32>;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    // From -1 to 1 across the viewport, y up.
+    @location(0) ndc: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.ndc = uv * 2.0 - 1.0;
+    out.position = vec4<f32>(out.ndc, 0.0, 1.0);
+    return out;
+}
+
+const PI: f32 = 3.14159265;
+
+fn distribution_ggx(n_dot_h: f32, roughness: f32) -> f32 {
+    let a = roughness * roughness;
+    let a2 = a * a;
+    let d = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+    return a2 / (PI * d * d);
+}
+
+fn geometry_smith(n_dot_v: f32, n_dot_l: f32, roughness: f32) -> f32 {
+    let k = (roughness + 1.0) * (roughness + 1.0) / 8.0;
+    let gv = n_dot_v / (n_dot_v * (1.0 - k) + k);
+    let gl = n_dot_l / (n_dot_l * (1.0 - k) + k);
+    return gv * gl;
+}
+
+fn fresnel_schlick(cos_theta: f32, f0: vec3<f32>) -> vec3<f32> {
+    return f0 + (1.0 - f0) * pow(1.0 - cos_theta, 5.0);
+}
+
+fn encode_gamma(linear: vec3<f32>) -> vec3<f32> {
+    let low = linear * 12.92;
+    let high = 1.055 * pow(linear, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, linear <= vec3<f32>(0.0031308));
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let r2 = dot(in.ndc, in.ndc);
+    // The edge is anti-aliased over about a pixel.
+    let edge = fwidth(sqrt(r2));
+    // Outside the sphere this is 0, and the premultiplied output transparent. Not
+    // discarding keeps the texture sampling below in uniform control flow.
+    let coverage = 1.0 - smoothstep(1.0 - edge, 1.0, sqrt(r2));
+    let n = normalize(vec3<f32>(in.ndc, sqrt(max(1.0 - r2, 0.0))));
+
+    // Spherical texture coordinates, turned by the rotation.
+    let uv = vec2<f32>(
+        fract(atan2(n.x, n.z) / (2.0 * PI) + 0.5 + material.rotation / (2.0 * PI)),
+        acos(clamp(n.y, -1.0, 1.0)) / PI,
+    );
+    let tangent = normalize(vec3<f32>(n.z, 0.0, -n.x) + vec3<f32>(1e-5, 0.0, 0.0));
+    let bitangent = cross(n, tangent);
+    var detail = textureSample(normal_map, material_sampler, uv).xyz * 2.0 - 1.0;
+    detail = vec3<f32>(detail.xy * material.normal_scale, detail.z);
+    let normal = normalize(mat3x3<f32>(tangent, bitangent, n) * detail);
+
+    let base = material.base_color.rgb * textureSample(base_color_map, material_sampler, uv).rgb;
+    let metallic = material.metallic * textureSample(metallic_map, material_sampler, uv).r;
+    let roughness = clamp(
+        material.roughness * textureSample(roughness_map, material_sampler, uv).r,
+        0.04,
+        1.0,
+    );
+    let occlusion = mix(
+        1.0,
+        textureSample(occlusion_map, material_sampler, uv).r,
+        material.ao_strength,
+    );
+    let emissive = material.emissive.rgb * material.emissive.w
+        * textureSample(emissive_map, material_sampler, uv).rgb;
+
+    let view = vec3<f32>(0.0, 0.0, 1.0);
+    let light = normalize(vec3<f32>(-0.5, 0.7, 0.6));
+    let halfway = normalize(view + light);
+    let n_dot_v = max(dot(normal, view), 1e-4);
+    let n_dot_l = max(dot(normal, light), 0.0);
+    let n_dot_h = max(dot(normal, halfway), 0.0);
+
+    let f0 = mix(vec3<f32>(0.04), base, metallic);
+    let fresnel = fresnel_schlick(max(dot(halfway, view), 0.0), f0);
+    let specular = distribution_ggx(n_dot_h, roughness) * geometry_smith(n_dot_v, n_dot_l, roughness)
+        * fresnel / (4.0 * n_dot_v * max(n_dot_l, 1e-4));
+    let diffuse = (1.0 - fresnel) * (1.0 - metallic) * base / PI;
+    let radiance = vec3<f32>(3.0);
+    let ambient = vec3<f32>(0.08) * base * occlusion;
+    var color = (diffuse + specular) * radiance * n_dot_l + ambient + emissive;
+
+    // Reinhard, so bright highlights and strong emission don't clip.
+    color = color / (1.0 + color);
+    if material.gamma_output > 0.5 {
+        color = encode_gamma(color);
+    }
+    return vec4<f32>(color * coverage, coverage);
+}
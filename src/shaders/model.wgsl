@@ -0,0 +1,61 @@
+// Draws a glTF model's primitives, lit by the same fixed light as the scene's cube.
+
+struct Camera {
+    view_proj: mat4x4<f32>,
+    // 1 when the target isn't sRGB, so the output has to be gamma encoded.
+    encode_srgb: f32,
+    _pad0: f32,
+    _pad1: f32,
+    _pad2: f32,
+};
+
+struct Part {
+    model: mat4x4<f32>,
+    normal: mat4x4<f32>,
+    base_color: vec4<f32>,
+};
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) normal: vec3<f32>,
+    @location(1) uv: vec2<f32>,
+};
+
+@group(0) @binding(0) var<uniform> camera: Camera;
+@group(1) @binding(0) var<uniform> part: Part;
+@group(1) @binding(1) var base_sampler: sampler;
+@group(1) @binding(2) var base_texture: texture_2d<f32>;
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = camera.view_proj * part.model * vec4<f32>(in.position, 1.0);
+    out.normal = (part.normal * vec4<f32>(in.normal, 0.0)).xyz;
+    out.uv = in.uv;
+    return out;
+}
+
+fn encode_srgb(linear: vec3<f32>) -> vec3<f32> {
+    let low = linear * 12.92;
+    let high = 1.055 * pow(linear, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, linear <= vec3<f32>(0.0031308));
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let light = normalize(vec3<f32>(0.4, 0.8, 0.6));
+    // Both sides of double sided faces are lit.
+    let diffuse = abs(dot(normalize(in.normal), light));
+    let albedo = textureSample(base_texture, base_sampler, in.uv).rgb * part.base_color.rgb;
+    var color = albedo * (0.2 + 0.8 * diffuse);
+    if camera.encode_srgb > 0.5 {
+        color = encode_srgb(color);
+    }
+    return vec4<f32>(color, 1.0);
+}
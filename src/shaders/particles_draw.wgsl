@@ -0,0 +1,30 @@
+// Draws each particle as a point, read straight out of the simulation's buffer.
+
+struct Particle {
+    position: vec2<f32>,
+    velocity: vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec3<f32>,
+};
+
+@group(0) @binding(0) var<storage, read> particles: array<Particle>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let p = particles[index];
+    var out: VertexOutput;
+    out.position = vec4<f32>(p.position, 0.0, 1.0);
+    // Slow particles are blue, fast ones orange.
+    let speed = clamp(length(p.velocity), 0.0, 1.0);
+    out.color = mix(vec3<f32>(0.1, 0.3, 1.0), vec3<f32>(1.0, 0.5, 0.1), speed);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Blended additively, so dense clusters glow.
+    return vec4<f32>(in.color * 0.25, 1.0);
+}
@@ -0,0 +1,39 @@
+// Moves every particle one step, pulled towards an attractor circling the screen.
+
+struct Particle {
+    position: vec2<f32>,
+    velocity: vec2<f32>,
+};
+
+struct SimParams {
+    attractor: vec2<f32>,
+    dt: f32,
+    count: u32,
+};
+
+@group(0) @binding(0) var<uniform> params: SimParams;
+@group(0) @binding(1) var<storage, read_write> particles: array<Particle>;
+
+@compute @workgroup_size(256)
+fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    if i >= params.count {
+        return;
+    }
+    var p = particles[i];
+    let to_attractor = params.attractor - p.position;
+    let distance = length(to_attractor);
+    p.velocity += to_attractor / (distance * distance + 0.05) * 0.2 * params.dt;
+    p.velocity *= 1.0 - 0.3 * params.dt;
+    p.position += p.velocity * params.dt;
+    // Bounce off the edges of the screen.
+    if abs(p.position.x) > 1.0 {
+        p.velocity.x = -p.velocity.x;
+        p.position.x = clamp(p.position.x, -1.0, 1.0);
+    }
+    if abs(p.position.y) > 1.0 {
+        p.velocity.y = -p.velocity.y;
+        p.position.y = clamp(p.position.y, -1.0, 1.0);
+    }
+    particles[i] = p;
+}
@@ -0,0 +1,65 @@
+// The fullscreen passes of the post-processing chain, one fragment entry point each.
+
+struct Params {
+    // The effect's strength: the exposure, the gamma or how dark the vignette gets.
+    amount: f32,
+    // The brightest value the output can show, 1 for SDR.
+    peak: f32,
+    // 1 when the target isn't sRGB or float, so the output has to be gamma encoded.
+    encode_srgb: f32,
+    _pad: f32,
+};
+
+@group(0) @binding(0) var input: texture_2d<f32>;
+@group(0) @binding(1) var<uniform> params: Params;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    // A single triangle covering the whole target.
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0), 0.0, 1.0);
+}
+
+fn load(position: vec4<f32>) -> vec3<f32> {
+    return textureLoad(input, vec2<i32>(position.xy), 0).rgb;
+}
+
+fn encode_srgb(linear: vec3<f32>) -> vec3<f32> {
+    let low = linear * 12.92;
+    let high = 1.055 * pow(linear, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, linear <= vec3<f32>(0.0031308));
+}
+
+fn output(color: vec3<f32>) -> vec4<f32> {
+    if params.encode_srgb > 0.5 {
+        return vec4<f32>(encode_srgb(clamp(color, vec3<f32>(0.0), vec3<f32>(1.0))), 1.0);
+    }
+    return vec4<f32>(color, 1.0);
+}
+
+// Narkowicz's fit of the ACES filmic curve, reaching 1 at about 10.
+fn aces(x: vec3<f32>) -> vec3<f32> {
+    return clamp((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14), vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+@fragment
+fn fs_tonemap(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    // The curve is stretched to the peak, so an extended-range surface keeps the
+    // highlights an SDR one has to roll off.
+    let hdr = load(position) * params.amount;
+    return output(aces(hdr / params.peak) * params.peak);
+}
+
+@fragment
+fn fs_gamma(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    return output(pow(max(load(position), vec3<f32>(0.0)), vec3<f32>(1.0 / params.amount)));
+}
+
+@fragment
+fn fs_vignette(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let uv = position.xy / vec2<f32>(textureDimensions(input));
+    // 0 in the center and 1 in the corners.
+    let distance = length(uv - 0.5) * 1.4142135;
+    let shade = 1.0 - params.amount * smoothstep(0.3, 1.0, distance);
+    return output(load(position) * shade);
+}
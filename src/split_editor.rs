@@ -0,0 +1,199 @@
+use instant::Instant;
+
+use std::time::Duration;
+
+/// How long the source has to stay unchanged before the preview catches up.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+const DIVIDER_WIDTH: f32 = 6.0;
+
+/// Dragging the divider this close to an edge collapses the pane on that side.
+const SNAP_RATIO: f32 = 0.03;
+
+/// How many earlier versions of the source `undo` can go back to.
+const HISTORY_LEN: usize = 100;
+
+const INITIAL_SOURCE: &str = "# Split editor
+
+Edit the *EasyMark* source on the left, the preview on the right follows \
+half a second after you stop typing.
+
+- Drag the divider to resize the panes
+- Drag it all the way to an edge to collapse a pane
+";
+
+/// A source editor and its rendered preview side by side, split by a draggable divider.
+pub struct SplitEditor {
+    pub open: bool,
+    /// The editor's share of the width, 0 collapses the editor and 1 the preview.
+    pub ratio: f32,
+    source: String,
+    /// The source as of the last time the preview was brought up to date.
+    preview: String,
+    last_edit: Option<Instant>,
+    /// The earlier versions of the source, oldest first, one per debounced edit.
+    history: Vec<String>,
+    #[cfg(feature = "demo")]
+    highlighter: egui_demo_lib::easy_mark::MemoizedEasymarkHighlighter,
+}
+
+impl Default for SplitEditor {
+    fn default() -> Self {
+        Self {
+            open: false,
+            ratio: 0.5,
+            source: INITIAL_SOURCE.to_owned(),
+            preview: INITIAL_SOURCE.to_owned(),
+            last_edit: None,
+            history: Vec::new(),
+            #[cfg(feature = "demo")]
+            highlighter: Default::default(),
+        }
+    }
+}
+
+impl SplitEditor {
+    /// The id of the source editor, the app's document.
+    pub fn source_id() -> egui::Id {
+        egui::Id::new("split_editor_source_text")
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Reverts the edits made since the preview last caught up, or else goes back to
+    /// the version before that.
+    pub fn undo(&mut self) {
+        if self.last_edit.take().is_none() {
+            let Some(previous) = self.history.pop() else {
+                return;
+            };
+            self.preview = previous;
+        }
+        self.source.clone_from(&self.preview);
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        let mut open = self.open;
+        egui::Window::new("Split editor")
+            .open(&mut open)
+            .default_size([800.0, 500.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("Maximize editor").clicked() {
+                        self.ratio = 1.0;
+                    }
+                    if ui.button("Split").clicked() {
+                        self.ratio = 0.5;
+                    }
+                    if ui.button("Maximize preview").clicked() {
+                        self.ratio = 0.0;
+                    }
+                });
+                ui.separator();
+                self.panes_ui(ui);
+            });
+        self.open = open;
+
+        if let Some(last_edit) = self.last_edit {
+            let elapsed = last_edit.elapsed();
+            if elapsed >= DEBOUNCE {
+                let previous = std::mem::replace(&mut self.preview, self.source.clone());
+                if self.history.len() == HISTORY_LEN {
+                    self.history.remove(0);
+                }
+                self.history.push(previous);
+                self.last_edit = None;
+            } else {
+                ctx.request_repaint_after(DEBOUNCE - elapsed);
+            }
+        }
+    }
+
+    fn panes_ui(&mut self, ui: &mut egui::Ui) {
+        let rect = ui.available_rect_before_wrap();
+        let width = (rect.width() - DIVIDER_WIDTH).max(0.0);
+        let left =
+            egui::Rect::from_min_size(rect.min, egui::vec2(width * self.ratio, rect.height()));
+        let divider =
+            egui::Rect::from_min_size(left.right_top(), egui::vec2(DIVIDER_WIDTH, rect.height()));
+        let right = egui::Rect::from_min_max(divider.right_top(), rect.max);
+
+        if self.ratio > 0.0 {
+            ui.allocate_ui_at_rect(left.shrink2(egui::vec2(2.0, 0.0)), |ui| {
+                egui::ScrollArea::vertical()
+                    .id_source("split_editor_source")
+                    .show(ui, |ui| self.editor_ui(ui));
+            });
+        }
+
+        let response = ui
+            .interact(divider, ui.id().with("divider"), egui::Sense::drag())
+            .on_hover_cursor(egui::CursorIcon::ResizeHorizontal);
+        if response.dragged() && width > 0.0 {
+            self.ratio = (self.ratio + response.drag_delta().x / width).clamp(0.0, 1.0);
+        }
+        if response.drag_released() {
+            if self.ratio < SNAP_RATIO {
+                self.ratio = 0.0;
+            } else if self.ratio > 1.0 - SNAP_RATIO {
+                self.ratio = 1.0;
+            }
+        }
+        let stroke = if response.hovered() || response.dragged() {
+            ui.visuals().widgets.hovered.fg_stroke
+        } else {
+            ui.visuals().widgets.noninteractive.bg_stroke
+        };
+        ui.painter()
+            .vline(divider.center().x, divider.y_range(), stroke);
+
+        if self.ratio < 1.0 {
+            ui.allocate_ui_at_rect(right.shrink2(egui::vec2(2.0, 0.0)), |ui| {
+                egui::ScrollArea::vertical()
+                    .id_source("split_editor_preview")
+                    .show(ui, |ui| self.preview_ui(ui));
+            });
+        }
+
+        ui.allocate_rect(rect, egui::Sense::hover());
+    }
+
+    fn editor_ui(&mut self, ui: &mut egui::Ui) {
+        #[cfg(feature = "demo")]
+        let response = {
+            let highlighter = &mut self.highlighter;
+            let mut layouter = |ui: &egui::Ui, source: &str, wrap_width: f32| {
+                let mut job = highlighter.highlight(ui.style(), source);
+                job.wrap.max_width = wrap_width;
+                ui.fonts(|f| f.layout_job(job))
+            };
+            ui.add(
+                egui::TextEdit::multiline(&mut self.source)
+                    .id(Self::source_id())
+                    .code_editor()
+                    .desired_width(f32::INFINITY)
+                    .layouter(&mut layouter),
+            )
+        };
+        #[cfg(not(feature = "demo"))]
+        let response = ui.add(
+            egui::TextEdit::multiline(&mut self.source)
+                .id(Self::source_id())
+                .code_editor()
+                .desired_width(f32::INFINITY),
+        );
+
+        if response.changed() {
+            self.last_edit = Some(Instant::now());
+        }
+    }
+
+    fn preview_ui(&self, ui: &mut egui::Ui) {
+        #[cfg(feature = "demo")]
+        egui_demo_lib::easy_mark::easy_mark(ui, &self.preview);
+        #[cfg(not(feature = "demo"))]
+        ui.label(&self.preview);
+    }
+}
@@ -0,0 +1,456 @@
+use egui::{Color32, Pos2, Rect, Sense, Stroke, Vec2};
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::hash::Hash;
+
+const STATE_SIZE: Vec2 = Vec2::new(120.0, 40.0);
+const LAYER_GAP: f32 = 80.0;
+const ROW_GAP: f32 = 40.0;
+
+pub struct Transition<E> {
+    pub from: usize,
+    pub to: usize,
+    pub event: E,
+}
+
+/// A finite state machine, with the current state.
+pub struct StateMachine<S, E> {
+    states: Vec<S>,
+    index: HashMap<S, usize>,
+    transitions: Vec<Transition<E>>,
+    current: usize,
+}
+
+impl<S: Hash + Eq + Clone + Display, E: Display> StateMachine<S, E> {
+    pub fn new(initial: S) -> Self {
+        let mut machine = Self {
+            states: Vec::new(),
+            index: HashMap::new(),
+            transitions: Vec::new(),
+            current: 0,
+        };
+        machine.state(initial);
+        machine
+    }
+
+    fn state(&mut self, state: S) -> usize {
+        if let Some(&i) = self.index.get(&state) {
+            return i;
+        }
+        self.index.insert(state.clone(), self.states.len());
+        self.states.push(state);
+        self.states.len() - 1
+    }
+
+    pub fn add_transition(&mut self, from: S, event: E, to: S) {
+        let from = self.state(from);
+        let to = self.state(to);
+        self.transitions.push(Transition { from, to, event });
+    }
+
+    pub fn current(&self) -> &S {
+        &self.states[self.current]
+    }
+
+    /// The indices of the transitions leaving `state`.
+    pub fn outgoing(&self, state: usize) -> impl Iterator<Item = usize> + '_ {
+        (0..self.transitions.len()).filter(move |&t| self.transitions[t].from == state)
+    }
+
+    /// Takes the transition, if it leaves the current state.
+    pub fn fire(&mut self, transition: usize) -> bool {
+        match self.transitions.get(transition) {
+            Some(t) if t.from == self.current => {
+                self.current = t.to;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// The layer and the row within it of every state, Sugiyama style.
+    ///
+    /// Back edges found by a DFS from the initial state are ignored, states are layered
+    /// by their longest path from a root and then reordered within each layer with a few
+    /// barycenter sweeps to cut down on crossings.
+    fn layout(&self) -> Vec<(usize, usize)> {
+        let n = self.states.len();
+        let mut back = vec![false; self.transitions.len()];
+        let mut visited = vec![false; n];
+        let mut on_stack = vec![false; n];
+        for root in 0..n {
+            if !visited[root] {
+                self.mark_back_edges(root, &mut visited, &mut on_stack, &mut back);
+            }
+        }
+        let forward: Vec<&Transition<E>> = self
+            .transitions
+            .iter()
+            .zip(&back)
+            .filter(|(t, back)| !**back && t.from != t.to)
+            .map(|(t, _)| t)
+            .collect();
+
+        // Longest path layering, relaxing the edges until nothing changes. The forward
+        // edges form a DAG so this takes at most `n` rounds.
+        let mut layer = vec![0; n];
+        for _ in 0..n {
+            let mut changed = false;
+            for t in &forward {
+                if layer[t.to] < layer[t.from] + 1 {
+                    layer[t.to] = layer[t.from] + 1;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        let layers = layer.iter().max().map_or(0, |max| max + 1);
+        let mut rows: Vec<Vec<usize>> = vec![Vec::new(); layers];
+        for (state, &l) in layer.iter().enumerate() {
+            rows[l].push(state);
+        }
+        let mut position = vec![0.0; n];
+        for _ in 0..4 {
+            for row in &rows {
+                for (i, &state) in row.iter().enumerate() {
+                    position[state] = i as f32;
+                }
+            }
+            for row in rows.iter_mut().skip(1) {
+                let barycenter = |state: usize| {
+                    let preds: Vec<f32> = forward
+                        .iter()
+                        .filter(|t| t.to == state)
+                        .map(|t| position[t.from])
+                        .collect();
+                    if preds.is_empty() {
+                        position[state]
+                    } else {
+                        preds.iter().sum::<f32>() / preds.len() as f32
+                    }
+                };
+                let mut keyed: Vec<(f32, usize)> = row
+                    .iter()
+                    .map(|&state| (barycenter(state), state))
+                    .collect();
+                keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+                *row = keyed.into_iter().map(|(_, state)| state).collect();
+                for (i, &state) in row.iter().enumerate() {
+                    position[state] = i as f32;
+                }
+            }
+        }
+
+        let mut result = vec![(0, 0); n];
+        for (l, row) in rows.iter().enumerate() {
+            for (i, &state) in row.iter().enumerate() {
+                result[state] = (l, i);
+            }
+        }
+        result
+    }
+
+    fn mark_back_edges(
+        &self,
+        state: usize,
+        visited: &mut [bool],
+        on_stack: &mut [bool],
+        back: &mut [bool],
+    ) {
+        visited[state] = true;
+        on_stack[state] = true;
+        for t in self.outgoing(state).collect::<Vec<_>>() {
+            let to = self.transitions[t].to;
+            if on_stack[to] {
+                back[t] = true;
+            } else if !visited[to] {
+                self.mark_back_edges(to, visited, on_stack, back);
+            }
+        }
+        on_stack[state] = false;
+    }
+}
+
+/// A window showing a state machine as a diagram, with a sidebar to inspect states and
+/// fire events.
+pub struct StateMachineView<S, E> {
+    pub open: bool,
+    title: String,
+    machine: StateMachine<S, E>,
+    /// The state clicked in the diagram.
+    selected: Option<usize>,
+    /// The transition picked in the "Trigger Event" dropdown.
+    trigger: Option<usize>,
+}
+
+impl<S: Hash + Eq + Clone + Display, E: Display> StateMachineView<S, E> {
+    pub fn new(title: impl Into<String>, machine: StateMachine<S, E>) -> Self {
+        Self {
+            open: false,
+            title: title.into(),
+            machine,
+            selected: None,
+            trigger: None,
+        }
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        let mut open = self.open;
+        egui::Window::new(&self.title)
+            .open(&mut open)
+            .default_size([760.0, 420.0])
+            .show(ctx, |ui| {
+                egui::SidePanel::right(egui::Id::new(&self.title).with("sidebar"))
+                    .resizable(false)
+                    .default_width(200.0)
+                    .show_inside(ui, |ui| self.sidebar_ui(ui));
+                egui::CentralPanel::default().show_inside(ui, |ui| self.diagram_ui(ui));
+            });
+        self.open = open;
+    }
+
+    fn sidebar_ui(&mut self, ui: &mut egui::Ui) {
+        let machine = &self.machine;
+        ui.label(format!("Current state: {}", machine.current()));
+        ui.separator();
+
+        let valid: Vec<usize> = machine.outgoing(machine.current).collect();
+        if self.trigger.is_some_and(|t| !valid.contains(&t)) {
+            self.trigger = None;
+        }
+        let selected_text = match self.trigger {
+            Some(t) => machine.transitions[t].event.to_string(),
+            None => "Pick an event".to_owned(),
+        };
+        egui::ComboBox::from_id_source(egui::Id::new(&self.title).with("trigger"))
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                for &t in &valid {
+                    let text = machine.transitions[t].event.to_string();
+                    ui.selectable_value(&mut self.trigger, Some(t), text);
+                }
+            });
+        let trigger = ui.add_enabled(self.trigger.is_some(), egui::Button::new("Trigger Event"));
+        if trigger.clicked() {
+            if let Some(t) = self.trigger.take() {
+                self.machine.fire(t);
+            }
+        }
+        if valid.is_empty() {
+            ui.weak("No events leave this state");
+        }
+
+        ui.separator();
+        match self.selected {
+            Some(state) => {
+                let machine = &self.machine;
+                ui.strong(machine.states[state].to_string());
+                let mut any = false;
+                for t in machine.outgoing(state) {
+                    let t = &machine.transitions[t];
+                    ui.label(format!("{} → {}", t.event, machine.states[t.to]));
+                    any = true;
+                }
+                if !any {
+                    ui.weak("No outgoing transitions");
+                }
+            }
+            None => {
+                ui.weak("Click a state to see its transitions");
+            }
+        }
+    }
+
+    fn diagram_ui(&mut self, ui: &mut egui::Ui) {
+        let machine = &self.machine;
+        let layout = machine.layout();
+        let rows = layout.iter().map(|&(_, row)| row + 1).max().unwrap_or(0);
+        let layers = layout
+            .iter()
+            .map(|&(layer, _)| layer + 1)
+            .max()
+            .unwrap_or(0);
+        let size = egui::vec2(
+            layers as f32 * (STATE_SIZE.x + LAYER_GAP),
+            rows as f32 * (STATE_SIZE.y + ROW_GAP) + ROW_GAP,
+        );
+
+        egui::ScrollArea::both().show(ui, |ui| {
+            let (response, painter) =
+                ui.allocate_painter(size.max(ui.available_size()), Sense::hover());
+            let origin = response.rect.min + egui::vec2(LAYER_GAP / 2.0, ROW_GAP);
+            let rects: Vec<Rect> = layout
+                .iter()
+                .map(|&(layer, row)| {
+                    let min = origin
+                        + egui::vec2(
+                            layer as f32 * (STATE_SIZE.x + LAYER_GAP),
+                            row as f32 * (STATE_SIZE.y + ROW_GAP),
+                        );
+                    Rect::from_min_size(min, STATE_SIZE)
+                })
+                .collect();
+
+            let visuals = ui.visuals().clone();
+            let font = egui::TextStyle::Small.resolve(ui.style());
+            let edge_stroke = Stroke::new(1.5, visuals.widgets.inactive.fg_stroke.color);
+            for (i, t) in machine.transitions.iter().enumerate() {
+                let label = t.event.to_string();
+                // Transitions between the same pair of states bend apart.
+                let twins = machine.transitions[..i]
+                    .iter()
+                    .filter(|o| {
+                        (o.from, o.to) == (t.from, t.to) || (o.from, o.to) == (t.to, t.from)
+                    })
+                    .count();
+                draw_transition(
+                    &painter,
+                    rects[t.from],
+                    rects[t.to],
+                    twins,
+                    edge_stroke,
+                    &label,
+                    &font,
+                    visuals.text_color(),
+                );
+            }
+
+            for (state, rect) in rects.iter().enumerate() {
+                let id = response.id.with(state);
+                let state_response = ui.interact(*rect, id, Sense::click());
+                if state_response.clicked() {
+                    self.selected = Some(state);
+                }
+                let (fill, text_color) = if state == machine.current {
+                    (visuals.selection.bg_fill, visuals.selection.stroke.color)
+                } else {
+                    (visuals.widgets.inactive.bg_fill, visuals.text_color())
+                };
+                let stroke = if Some(state) == self.selected {
+                    Stroke::new(2.0, visuals.strong_text_color())
+                } else if state_response.hovered() {
+                    visuals.widgets.hovered.bg_stroke
+                } else {
+                    visuals.widgets.inactive.bg_stroke
+                };
+                painter.rect(*rect, 8.0, fill, stroke);
+                painter.text(
+                    rect.center(),
+                    egui::Align2::CENTER_CENTER,
+                    machine.states[state].to_string(),
+                    egui::TextStyle::Body.resolve(ui.style()),
+                    text_color,
+                );
+            }
+        });
+    }
+}
+
+/// The point where the ray from the center of `rect` towards `dir` leaves it.
+fn border_point(rect: Rect, dir: Vec2) -> Pos2 {
+    let half = rect.size() / 2.0;
+    let scale = (half.x / dir.x.abs().max(1e-6)).min(half.y / dir.y.abs().max(1e-6));
+    rect.center() + dir * scale
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_transition(
+    painter: &egui::Painter,
+    from: Rect,
+    to: Rect,
+    twins: usize,
+    stroke: Stroke,
+    label: &str,
+    font: &egui::FontId,
+    text_color: Color32,
+) {
+    let (points, tip_dir) = if from == to {
+        // A loop over the top of the state.
+        let spread = 20.0 + 10.0 * twins as f32;
+        let start = from.center_top() - egui::vec2(spread / 2.0, 0.0);
+        let end = from.center_top() + egui::vec2(spread / 2.0, 0.0);
+        let lift = egui::vec2(0.0, -(30.0 + 15.0 * twins as f32));
+        ([start, start + lift, end + lift, end], egui::vec2(0.0, 1.0))
+    } else {
+        let dir = (to.center() - from.center()).normalized();
+        let normal = dir.rot90();
+        let bend = 20.0 + 25.0 * twins as f32;
+        let start = border_point(from, dir + normal * 0.3);
+        let end = border_point(to, -dir + normal * 0.3);
+        let control = start.lerp(end, 0.5) + normal * bend;
+        let c1 = start.lerp(control, 2.0 / 3.0);
+        let c2 = end.lerp(control, 2.0 / 3.0);
+        ([start, c1, c2, end], (end - c2).normalized())
+    };
+    painter.add(egui::epaint::CubicBezierShape::from_points_stroke(
+        points,
+        false,
+        Color32::TRANSPARENT,
+        stroke,
+    ));
+
+    let tip = points[3];
+    let back = tip - tip_dir * 10.0;
+    let side = tip_dir.rot90() * 5.0;
+    painter.add(egui::Shape::convex_polygon(
+        vec![tip, back + side, back - side],
+        stroke.color,
+        Stroke::NONE,
+    ));
+
+    let mid = egui::epaint::CubicBezierShape::from_points_stroke(
+        points,
+        false,
+        Color32::TRANSPARENT,
+        stroke,
+    )
+    .sample(0.5);
+    painter.text(
+        mid,
+        egui::Align2::CENTER_BOTTOM,
+        label,
+        font.clone(),
+        text_color,
+    );
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TrafficLight {
+    Red,
+    Green,
+    Yellow,
+    FlashingYellow,
+}
+
+impl Display for TrafficLight {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            TrafficLight::Red => "Red",
+            TrafficLight::Green => "Green",
+            TrafficLight::Yellow => "Yellow",
+            TrafficLight::FlashingYellow => "Flashing yellow",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The demo machine: a traffic light with a pedestrian button and a fault mode.
+pub fn traffic_light() -> StateMachineView<TrafficLight, &'static str> {
+    use TrafficLight::*;
+
+    let mut machine = StateMachine::new(Red);
+    machine.add_transition(Red, "timer", Green);
+    machine.add_transition(Green, "timer", Yellow);
+    machine.add_transition(Green, "pedestrian", Yellow);
+    machine.add_transition(Yellow, "timer", Red);
+    for state in [Red, Green, Yellow] {
+        machine.add_transition(state, "fault", FlashingYellow);
+    }
+    machine.add_transition(FlashingYellow, "reset", Red);
+    machine.add_transition(FlashingYellow, "blink", FlashingYellow);
+    StateMachineView::new("Traffic light", machine)
+}
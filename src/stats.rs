@@ -0,0 +1,514 @@
+use crate::file_dialog::FileDialogs;
+
+use instant::Instant;
+
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Captures warn once they get this long, and stop on their own at ten times that.
+const CAPTURE_WARN_FRAMES: usize = 10_000;
+const CAPTURE_MAX_FRAMES: usize = 10 * CAPTURE_WARN_FRAMES;
+
+/// The CPU side stages of a frame, in the order they run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stage {
+    /// Waiting for the surface's next texture, which is where vsync usually blocks.
+    Acquire,
+    /// The app's `update` and gathering egui's input.
+    Input,
+    /// `begin_frame` and the app's `ui`.
+    Ui,
+    /// `end_frame` and handing its output to the window.
+    EndFrame,
+    Tessellate,
+    Upload,
+    Encode,
+    Present,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 8] = [
+        Stage::Acquire,
+        Stage::Input,
+        Stage::Ui,
+        Stage::EndFrame,
+        Stage::Tessellate,
+        Stage::Upload,
+        Stage::Encode,
+        Stage::Present,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::Acquire => "acquire",
+            Stage::Input => "input",
+            Stage::Ui => "ui",
+            Stage::EndFrame => "end_frame",
+            Stage::Tessellate => "tessellate",
+            Stage::Upload => "upload",
+            Stage::Encode => "encode",
+            Stage::Present => "present",
+        }
+    }
+}
+
+/// Everything measured about one frame.
+///
+/// This is plain data so that recording a frame is a single push, turning it into
+/// text is left to the export.
+#[derive(Clone, Copy, Debug)]
+pub struct FrameRecord {
+    pub frame: u64,
+    /// Seconds since the start of the app.
+    pub timestamp: f64,
+    /// Milliseconds spent in each stage, indexed like `Stage::ALL`.
+    pub stage_ms: [f32; Stage::ALL.len()],
+    pub gpu_ms: Option<f32>,
+    pub vertices: u32,
+    pub texture_upload_bytes: u64,
+    pub present_mode: wgpu::PresentMode,
+}
+
+impl FrameRecord {
+    pub fn cpu_ms(&self) -> f32 {
+        self.stage_ms.iter().sum()
+    }
+}
+
+/// Measures the time between consecutive stages of a frame, and has each stage in a
+/// `stage` span of its own for tracing.
+pub struct StageTimer {
+    last: Instant,
+    stage_ms: [f32; Stage::ALL.len()],
+    /// The span of the stage after the last lap, stages coming in the order of `ALL`.
+    span: Option<tracing::span::EnteredSpan>,
+}
+
+impl StageTimer {
+    pub fn start() -> Self {
+        Self {
+            last: Instant::now(),
+            stage_ms: Default::default(),
+            span: Some(stage_span(Stage::ALL[0])),
+        }
+    }
+
+    /// Attributes the time since the previous lap to `stage`.
+    pub fn lap(&mut self, stage: Stage) {
+        let now = Instant::now();
+        self.stage_ms[stage as usize] += (now - self.last).as_secs_f32() * 1000.0;
+        self.last = now;
+        // Leaving the last stage before entering the next keeps them siblings.
+        self.span = None;
+        self.span = Stage::ALL
+            .get(stage as usize + 1)
+            .map(|&next| stage_span(next));
+    }
+
+    pub fn stage_ms(&self) -> [f32; Stage::ALL.len()] {
+        self.stage_ms
+    }
+}
+
+fn stage_span(stage: Stage) -> tracing::span::EnteredSpan {
+    tracing::debug_span!("stage", name = stage.name()).entered()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    JsonLines,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::JsonLines => "jsonl",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "CSV",
+            ExportFormat::JsonLines => "JSON Lines",
+        }
+    }
+
+    pub fn write(self, records: &[FrameRecord], out: impl Write) -> io::Result<()> {
+        match self {
+            ExportFormat::Csv => write_csv(records, out),
+            ExportFormat::JsonLines => write_json_lines(records, out),
+        }
+    }
+}
+
+/// Writes the records as CSV, one row per frame.
+pub fn write_csv(records: &[FrameRecord], mut out: impl Write) -> io::Result<()> {
+    write!(out, "frame,timestamp_s")?;
+    for stage in Stage::ALL {
+        write!(out, ",{}_ms", stage.name())?;
+    }
+    writeln!(out, ",gpu_ms,vertices,texture_upload_bytes,present_mode")?;
+
+    for record in records {
+        write!(out, "{},{:.6}", record.frame, record.timestamp)?;
+        for ms in record.stage_ms {
+            write!(out, ",{:.4}", ms)?;
+        }
+        match record.gpu_ms {
+            Some(ms) => write!(out, ",{:.4}", ms)?,
+            None => write!(out, ",")?,
+        }
+        writeln!(
+            out,
+            ",{},{},{:?}",
+            record.vertices, record.texture_upload_bytes, record.present_mode
+        )?;
+    }
+    out.flush()
+}
+
+/// Writes the records as JSON Lines, one object per frame.
+pub fn write_json_lines(records: &[FrameRecord], mut out: impl Write) -> io::Result<()> {
+    for record in records {
+        let stages: serde_json::Map<String, serde_json::Value> = Stage::ALL
+            .iter()
+            .map(|stage| {
+                let ms = record.stage_ms[*stage as usize];
+                (stage.name().to_owned(), ms.into())
+            })
+            .collect();
+        let row = serde_json::json!({
+            "frame": record.frame,
+            "timestamp_s": record.timestamp,
+            "stage_ms": stages,
+            "gpu_ms": record.gpu_ms,
+            "vertices": record.vertices,
+            "texture_upload_bytes": record.texture_upload_bytes,
+            "present_mode": format!("{:?}", record.present_mode),
+        });
+        serde_json::to_writer(&mut out, &row)?;
+        writeln!(out)?;
+    }
+    out.flush()
+}
+
+/// Writes the records to `path` in `format`, for what isn't saved through a dialog.
+pub fn export(records: &[FrameRecord], format: ExportFormat, path: &Path) -> io::Result<()> {
+    format.write(records, io::BufWriter::new(std::fs::File::create(path)?))
+}
+
+/// Collects the per-frame records and shows them in the "Frame statistics" window.
+pub struct FrameStats {
+    pub open: bool,
+    start: Instant,
+    frame: u64,
+    last: Option<FrameRecord>,
+    capture: Option<Vec<FrameRecord>>,
+    /// A stopped capture, until the save dialog can be shown for it.
+    stopped: Option<Vec<FrameRecord>>,
+    format: ExportFormat,
+    status: Option<String>,
+}
+
+impl Default for FrameStats {
+    fn default() -> Self {
+        Self {
+            open: false,
+            start: Instant::now(),
+            frame: 0,
+            last: None,
+            capture: None,
+            stopped: None,
+            format: ExportFormat::Csv,
+            status: None,
+        }
+    }
+}
+
+impl FrameStats {
+    /// The index and timestamp for the frame being started.
+    pub fn next_frame(&mut self) -> (u64, f64) {
+        self.frame += 1;
+        (self.frame, self.start.elapsed().as_secs_f64())
+    }
+
+    pub fn record(&mut self, record: FrameRecord) {
+        self.last = Some(record);
+        if let Some(capture) = &mut self.capture {
+            capture.push(record);
+            if capture.len() >= CAPTURE_MAX_FRAMES {
+                self.stopped = self.capture.take();
+            }
+        }
+    }
+
+    /// The record of the latest frame, once there was one.
+    pub fn last(&self) -> Option<&FrameRecord> {
+        self.last.as_ref()
+    }
+
+    pub fn is_capturing(&self) -> bool {
+        self.capture.is_some()
+    }
+
+    pub fn start_capture(&mut self) {
+        self.capture = Some(Vec::with_capacity(CAPTURE_WARN_FRAMES));
+        self.status = None;
+    }
+
+    /// Asks where to save a stopped capture, once a dialog can be shown. It's written
+    /// into memory first, the dialog writing the file.
+    fn export(&mut self, dialogs: &FileDialogs) {
+        if !dialogs.available() {
+            return;
+        }
+        let Some(records) = self.stopped.take() else {
+            return;
+        };
+        let mut bytes = Vec::new();
+        if let Err(e) = self.format.write(&records, &mut bytes) {
+            self.status = Some(format!("Export failed: {}", e));
+            return;
+        }
+        let extension = self.format.extension();
+        dialogs.save_as(
+            format!("frame_stats.{}", extension),
+            self.format.name(),
+            &[extension],
+            bytes,
+        );
+        self.status = Some(format!("Exporting {} frames", records.len()));
+    }
+
+    /// Called every frame, as a capture that filled up is saved with the window closed.
+    pub fn ui(&mut self, ctx: &egui::Context, dialogs: &FileDialogs) {
+        self.export(dialogs);
+        let mut open = self.open;
+        egui::Window::new("Frame statistics")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                if let Some(last) = &self.last {
+                    record_grid(ui, "frame_stats_grid", last);
+                }
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label("Format");
+                    for format in [ExportFormat::Csv, ExportFormat::JsonLines] {
+                        ui.selectable_value(&mut self.format, format, format.name());
+                    }
+                });
+
+                match &self.capture {
+                    Some(capture) => {
+                        ui.label(format!("Capturing: {} frames", capture.len()));
+                        if capture.len() >= CAPTURE_WARN_FRAMES {
+                            ui.colored_label(
+                                ui.visuals().warn_fg_color,
+                                format!(
+                                    "Long capture, it stops on its own at {} frames",
+                                    CAPTURE_MAX_FRAMES
+                                ),
+                            );
+                        }
+                        if ui.button("Stop & export").clicked() {
+                            self.stopped = self.capture.take();
+                        }
+                    }
+                    None if self.stopped.is_some() => {
+                        ui.label("Waiting for the save dialog");
+                    }
+                    None => {
+                        // Captures are only ever written by the save dialog.
+                        let start = ui
+                            .add_enabled(dialogs.available(), egui::Button::new("Start capture"))
+                            .on_disabled_hover_text("There's no file dialog here");
+                        if start.clicked() {
+                            self.start_capture();
+                        }
+                    }
+                }
+                if let Some(status) = &self.status {
+                    ui.weak(status);
+                }
+            });
+        self.open = open;
+    }
+}
+
+/// The fields of `record` in a grid, one a row.
+pub fn record_grid(ui: &mut egui::Ui, id: &str, record: &FrameRecord) {
+    egui::Grid::new(id).show(ui, |ui| {
+        ui.label("Frame");
+        ui.label(record.frame.to_string());
+        ui.end_row();
+        for stage in Stage::ALL {
+            ui.label(stage.name());
+            ui.label(format!("{:.3} ms", record.stage_ms[stage as usize]));
+            ui.end_row();
+        }
+        ui.label("CPU total");
+        ui.label(format!("{:.3} ms", record.cpu_ms()));
+        ui.end_row();
+        ui.label("GPU");
+        ui.label(match record.gpu_ms {
+            Some(ms) => format!("{:.3} ms", ms),
+            None => "n/a".to_owned(),
+        });
+        ui.end_row();
+        ui.label("Vertices");
+        ui.label(record.vertices.to_string());
+        ui.end_row();
+        ui.label("Texture uploads");
+        ui.label(format!("{} bytes", record.texture_upload_bytes));
+        ui.end_row();
+        ui.label("Present mode");
+        ui.label(format!("{:?}", record.present_mode));
+        ui.end_row();
+    });
+}
+
+/// How many frames the stage breakdown averages over.
+const BREAKDOWN_FRAMES: usize = 120;
+
+/// The "CPU stages" window: how long each stage of the frame took on average, as a
+/// bar of the frame's whole CPU time.
+#[derive(Default)]
+pub struct StageBreakdown {
+    pub open: bool,
+    recent: VecDeque<[f32; Stage::ALL.len()]>,
+}
+
+impl StageBreakdown {
+    pub fn record(&mut self, stage_ms: [f32; Stage::ALL.len()]) {
+        if self.recent.len() == BREAKDOWN_FRAMES {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(stage_ms);
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        let mut average = [0.0; Stage::ALL.len()];
+        for stage_ms in &self.recent {
+            for (sum, ms) in average.iter_mut().zip(stage_ms) {
+                *sum += ms / self.recent.len() as f32;
+            }
+        }
+        let total: f32 = average.iter().sum();
+        egui::Window::new("CPU stages")
+            .open(&mut self.open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.weak(format!(
+                    "Averaged over the last {} frames",
+                    self.recent.len()
+                ));
+                egui::Grid::new("cpu stages").show(ui, |ui| {
+                    for stage in Stage::ALL {
+                        let ms = average[stage as usize];
+                        ui.label(stage.name());
+                        ui.monospace(format!("{:7.3} ms", ms));
+                        let fraction = if total > 0.0 { ms / total } else { 0.0 };
+                        ui.add(
+                            egui::ProgressBar::new(fraction)
+                                .desired_width(160.0)
+                                .text(format!("{:.0}%", fraction * 100.0)),
+                        );
+                        ui.end_row();
+                    }
+                    ui.strong("Total");
+                    ui.monospace(format!("{:7.3} ms", total));
+                    ui.end_row();
+                });
+            });
+    }
+}
+
+/// The number of vertices in the tessellated UI.
+pub fn vertex_count(paint_jobs: &[egui::ClippedPrimitive]) -> u32 {
+    paint_jobs
+        .iter()
+        .map(|job| match &job.primitive {
+            egui::epaint::Primitive::Mesh(mesh) => mesh.vertices.len() as u32,
+            egui::epaint::Primitive::Callback(_) => 0,
+        })
+        .sum()
+}
+
+/// The number of bytes uploaded for the texture updates of a frame.
+pub fn texture_upload_bytes(delta: &egui::TexturesDelta) -> u64 {
+    delta
+        .set
+        .iter()
+        .map(|(_, image)| {
+            let [width, height] = image.image.size();
+            (width * height * 4) as u64
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records() -> Vec<FrameRecord> {
+        (1..=3)
+            .map(|frame| FrameRecord {
+                frame,
+                timestamp: frame as f64 / 60.0,
+                stage_ms: [0.5, 1.0, 1.5, 2.0, 2.5, 3.0, 3.5, 4.0],
+                gpu_ms: (frame != 2).then_some(0.25),
+                vertices: 100 * frame as u32,
+                texture_upload_bytes: 4096,
+                present_mode: wgpu::PresentMode::Fifo,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn csv_has_a_header_and_a_row_per_frame_in_its_order() {
+        let mut out = Vec::new();
+        write_csv(&records(), &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(lines.len(), 1 + 3);
+        assert_eq!(
+            lines[0],
+            "frame,timestamp_s,acquire_ms,input_ms,ui_ms,end_frame_ms,tessellate_ms,\
+             upload_ms,encode_ms,present_ms,gpu_ms,vertices,texture_upload_bytes,present_mode"
+        );
+        assert_eq!(
+            lines[1],
+            "1,0.016667,0.5000,1.0000,1.5000,2.0000,2.5000,3.0000,3.5000,4.0000,\
+             0.2500,100,4096,Fifo"
+        );
+        // Without a GPU time its column is left empty.
+        assert!(lines[2].starts_with("2,"));
+        assert!(lines[2].contains(",4.0000,,200,"));
+        let columns = lines[0].split(',').count();
+        assert!(lines.iter().all(|line| line.split(',').count() == columns));
+    }
+
+    #[test]
+    fn json_lines_have_an_object_per_frame_that_serde_json_reads_back() {
+        let records = records();
+        let mut out = Vec::new();
+        write_json_lines(&records, &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(text.lines().count(), records.len());
+        for (line, record) in text.lines().zip(&records) {
+            let row: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(row["frame"], record.frame);
+            assert_eq!(row["timestamp_s"], record.timestamp);
+            assert_eq!(row["stage_ms"]["present"], 4.0);
+            assert_eq!(row["stage_ms"].as_object().unwrap().len(), Stage::ALL.len());
+            assert_eq!(row["gpu_ms"].as_f64(), record.gpu_ms.map(|ms| ms as f64));
+            assert_eq!(row["vertices"], record.vertices);
+            assert_eq!(row["texture_upload_bytes"], 4096);
+            assert_eq!(row["present_mode"], "Fifo");
+        }
+    }
+}
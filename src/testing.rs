@@ -0,0 +1,175 @@
+use crate::Application;
+
+use egui::{Event, Key, Modifiers, Pos2};
+
+/// The time between frames egui is told.
+const FRAME_TIME: f32 = 1.0 / 60.0;
+
+/// Runs an app's UI without a window or a GPU, feeding it synthetic input, so what it
+/// does with clicks and keys can be tested.
+///
+/// Only `Application::ui` and `end_frame` are called, each `run` being a frame.
+pub struct UiDriver<A> {
+    pub ctx: egui::Context,
+    pub app: A,
+    screen_size: egui::Vec2,
+    time: f64,
+}
+
+impl<A: Application> UiDriver<A> {
+    /// A driver for a 1280×720 screen, at one pixel per point.
+    pub fn new(app: A) -> Self {
+        Self {
+            ctx: egui::Context::default(),
+            app,
+            screen_size: egui::vec2(1280.0, 720.0),
+            time: 0.0,
+        }
+    }
+
+    pub fn with_screen_size(mut self, width: f32, height: f32) -> Self {
+        self.screen_size = egui::vec2(width, height);
+        self
+    }
+
+    /// Runs a frame with `events` as its input.
+    pub fn run(&mut self, events: Vec<Event>) -> egui::FullOutput {
+        self.run_input(egui::RawInput {
+            events,
+            ..Default::default()
+        })
+    }
+
+    /// Runs a frame with `input`, for what isn't an event like dropped files. The
+    /// screen and the time are filled in.
+    pub fn run_input(&mut self, input: egui::RawInput) -> egui::FullOutput {
+        let input = egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(Pos2::ZERO, self.screen_size)),
+            time: Some(self.time),
+            predicted_dt: FRAME_TIME,
+            ..input
+        };
+        self.time += FRAME_TIME as f64;
+        let app = &mut self.app;
+        let output = self.ctx.run(input, |ctx| app.ui(ctx));
+        self.app.end_frame(&output);
+        output
+    }
+
+    /// Runs a couple of frames without input, so that new windows have been laid out.
+    pub fn settle(&mut self) -> egui::FullOutput {
+        self.run(Vec::new());
+        self.run(Vec::new())
+    }
+
+    /// Presses and releases the primary button at `pos`, over two frames.
+    pub fn click(&mut self, pos: Pos2) {
+        let button = |pressed| Event::PointerButton {
+            pos,
+            button: egui::PointerButton::Primary,
+            pressed,
+            modifiers: Modifiers::NONE,
+        };
+        self.run(vec![Event::PointerMoved(pos), button(true)]);
+        self.run(vec![button(false)]);
+    }
+
+    /// Clicks the text shown exactly as `text` after settling, returning whether it
+    /// was found.
+    pub fn click_text(&mut self, text: &str) -> bool {
+        let output = self.settle();
+        let Some(pos) = find_text(&output, text) else {
+            return false;
+        };
+        self.click(pos);
+        true
+    }
+
+    /// Types `text` into whatever has the keyboard focus.
+    pub fn type_text(&mut self, text: &str) -> egui::FullOutput {
+        self.run(vec![Event::Text(text.to_owned())])
+    }
+
+    /// Presses `key` with `modifiers` held.
+    pub fn press(&mut self, key: Key, modifiers: Modifiers) -> egui::FullOutput {
+        self.run(vec![key_event(key, modifiers)])
+    }
+}
+
+/// A press of `key`, with `modifiers` held.
+pub fn key_event(key: Key, modifiers: Modifiers) -> Event {
+    Event::Key {
+        key,
+        pressed: true,
+        repeat: false,
+        modifiers,
+    }
+}
+
+/// The center of the text shape showing exactly `text`.
+pub fn find_text(output: &egui::FullOutput, text: &str) -> Option<Pos2> {
+    fn find(shape: &egui::Shape, text: &str) -> Option<Pos2> {
+        match shape {
+            egui::Shape::Text(shape) if shape.galley.text() == text => {
+                Some(shape.pos + shape.galley.rect.center().to_vec2())
+            }
+            egui::Shape::Vec(shapes) => shapes.iter().find_map(|shape| find(shape, text)),
+            _ => None,
+        }
+    }
+    output
+        .shapes
+        .iter()
+        .find_map(|clipped| find(&clipped.shape, text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Counter {
+        clicks: u32,
+        name: String,
+    }
+
+    impl Application for Counter {
+        fn ui(&mut self, ctx: &egui::Context) {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                if ui.button("Count").clicked() {
+                    self.clicks += 1;
+                }
+                ui.text_edit_singleline(&mut self.name);
+                ui.label(format!("{} clicks", self.clicks));
+            });
+        }
+    }
+
+    #[test]
+    fn clicking_a_button_reaches_the_app() {
+        let mut driver = UiDriver::new(Counter::default());
+        assert!(driver.click_text("Count"));
+        assert!(driver.click_text("Count"));
+        assert_eq!(driver.app.clicks, 2);
+
+        let output = driver.settle();
+        assert!(find_text(&output, "2 clicks").is_some());
+        assert!(!driver.click_text("No such button"));
+    }
+
+    #[test]
+    fn text_goes_to_the_focused_field() {
+        let mut driver = UiDriver::new(Counter::default());
+        driver.settle();
+        driver.type_text("ignored");
+        assert!(driver.app.name.is_empty());
+
+        // The field is right below the button.
+        let output = driver.settle();
+        let button = find_text(&output, "Count").unwrap();
+        driver.click(button + egui::vec2(0.0, 24.0));
+        driver.type_text("Ferris");
+        driver.press(Key::Backspace, Modifiers::NONE);
+        assert_eq!(driver.app.name, "Ferri");
+    }
+}
@@ -0,0 +1,265 @@
+use noise::{Fbm, MultiFractal, NoiseFn, Perlin};
+
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver};
+
+const SIZE: usize = 512;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Colormap {
+    Grayscale,
+    Heat,
+    Cool,
+    Rainbow,
+}
+
+impl Colormap {
+    const ALL: [Colormap; 4] = [
+        Colormap::Grayscale,
+        Colormap::Heat,
+        Colormap::Cool,
+        Colormap::Rainbow,
+    ];
+
+    /// The colors the noise value is interpolated between, from low to high.
+    fn stops(self) -> &'static [[u8; 3]] {
+        match self {
+            Colormap::Grayscale => &[[0, 0, 0], [255, 255, 255]],
+            Colormap::Heat => &[[0, 0, 0], [200, 0, 0], [255, 200, 0], [255, 255, 255]],
+            Colormap::Cool => &[[0, 255, 255], [255, 0, 255]],
+            Colormap::Rainbow => &[
+                [128, 0, 255],
+                [0, 0, 255],
+                [0, 255, 0],
+                [255, 255, 0],
+                [255, 128, 0],
+                [255, 0, 0],
+            ],
+        }
+    }
+
+    fn lookup_table(self) -> [egui::Color32; 256] {
+        let stops = self.stops();
+        std::array::from_fn(|i| {
+            let t = i as f32 / 255.0 * (stops.len() - 1) as f32;
+            let low = (t.floor() as usize).min(stops.len() - 2);
+            let [r, g, b] = std::array::from_fn(|c| {
+                let (a, b) = (stops[low][c] as f32, stops[low + 1][c] as f32);
+                (a + (b - a) * (t - low as f32)).round() as u8
+            });
+            egui::Color32::from_rgb(r, g, b)
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct NoiseParams {
+    octaves: usize,
+    persistence: f64,
+    lacunarity: f64,
+    frequency: f64,
+    seed: u32,
+    colormap: Colormap,
+}
+
+impl Default for NoiseParams {
+    fn default() -> Self {
+        Self {
+            octaves: 6,
+            persistence: 0.5,
+            lacunarity: 2.0,
+            frequency: 4.0,
+            seed: 0,
+            colormap: Colormap::Grayscale,
+        }
+    }
+}
+
+impl NoiseParams {
+    fn generate(self) -> egui::ColorImage {
+        let fbm = Fbm::<Perlin>::new(self.seed)
+            .set_octaves(self.octaves)
+            .set_persistence(self.persistence)
+            .set_lacunarity(self.lacunarity)
+            .set_frequency(self.frequency);
+        let lut = self.colormap.lookup_table();
+        let pixels = (0..SIZE * SIZE)
+            .map(|i| {
+                let point = [
+                    (i % SIZE) as f64 / SIZE as f64,
+                    (i / SIZE) as f64 / SIZE as f64,
+                ];
+                let value = (fbm.get(point) * 0.5 + 0.5).clamp(0.0, 1.0);
+                lut[(value * 255.0).round() as usize]
+            })
+            .collect();
+        egui::ColorImage {
+            size: [SIZE, SIZE],
+            pixels,
+        }
+    }
+}
+
+/// The seed typed by the user: a number is used as is, anything else is hashed.
+fn parse_seed(text: &str) -> u32 {
+    text.trim().parse().unwrap_or_else(|_| {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        text.hash(&mut hasher);
+        hasher.finish() as u32
+    })
+}
+
+/// A window generating a Perlin noise texture from its parameters.
+///
+/// The texture is regenerated on a background thread every time a parameter changes,
+/// if they change again meanwhile the newest ones are picked up once it's done.
+pub struct TextureGenerator {
+    pub open: bool,
+    params: NoiseParams,
+    seed: String,
+    /// The parameters of the texture shown, or being generated.
+    generated: Option<NoiseParams>,
+    pending: Option<Receiver<egui::ColorImage>>,
+    image: Option<egui::ColorImage>,
+    texture: Option<egui::TextureHandle>,
+    path: String,
+    status: Option<String>,
+}
+
+impl Default for TextureGenerator {
+    fn default() -> Self {
+        Self {
+            open: false,
+            params: NoiseParams::default(),
+            seed: "0".to_owned(),
+            generated: None,
+            pending: None,
+            image: None,
+            texture: None,
+            path: "noise.png".to_owned(),
+            status: None,
+        }
+    }
+}
+
+impl TextureGenerator {
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        self.poll(ctx);
+
+        let mut open = self.open;
+        egui::Window::new("Texture generator")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                egui::Grid::new("texture_gen_grid").show(ui, |ui| {
+                    let params = &mut self.params;
+                    ui.label("Octaves");
+                    ui.add(egui::Slider::new(&mut params.octaves, 1..=8));
+                    ui.end_row();
+                    ui.label("Persistence");
+                    ui.add(egui::Slider::new(&mut params.persistence, 0.0..=1.0));
+                    ui.end_row();
+                    ui.label("Lacunarity");
+                    ui.add(egui::Slider::new(&mut params.lacunarity, 1.0..=4.0));
+                    ui.end_row();
+                    ui.label("Frequency");
+                    ui.add(egui::Slider::new(&mut params.frequency, 0.5..=32.0).logarithmic(true));
+                    ui.end_row();
+                    ui.label("Seed");
+                    if ui.text_edit_singleline(&mut self.seed).changed() {
+                        params.seed = parse_seed(&self.seed);
+                    }
+                    ui.end_row();
+                    ui.label("Colormap");
+                    egui::ComboBox::from_id_source("texture_gen_colormap")
+                        .selected_text(format!("{:?}", params.colormap))
+                        .show_ui(ui, |ui| {
+                            for colormap in Colormap::ALL {
+                                let text = format!("{:?}", colormap);
+                                ui.selectable_value(&mut params.colormap, colormap, text);
+                            }
+                        });
+                    ui.end_row();
+                });
+
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.path);
+                    let export =
+                        ui.add_enabled(self.image.is_some(), egui::Button::new("Export PNG"));
+                    if export.clicked() {
+                        if let Some(image) = &self.image {
+                            let path = Path::new(&self.path);
+                            self.status = Some(match write_png(image, path) {
+                                Ok(()) => format!("Saved {}", path.display()),
+                                Err(e) => format!("Export failed: {}", e),
+                            });
+                        }
+                    }
+                    if self.pending.is_some() {
+                        ui.spinner();
+                    }
+                });
+                if let Some(status) = &self.status {
+                    ui.weak(status);
+                }
+
+                if let Some(texture) = &self.texture {
+                    ui.image((texture.id(), egui::vec2(SIZE as f32, SIZE as f32) / 2.0));
+                }
+            });
+        self.open = open;
+    }
+
+    /// Picks up a finished texture, and starts the next one if the parameters changed.
+    fn poll(&mut self, ctx: &egui::Context) {
+        if let Some(pending) = &self.pending {
+            match pending.try_recv() {
+                Ok(image) => {
+                    match &mut self.texture {
+                        Some(texture) => texture.set(image.clone(), Default::default()),
+                        None => {
+                            self.texture =
+                                Some(ctx.load_texture("noise", image.clone(), Default::default()))
+                        }
+                    }
+                    self.image = Some(image);
+                    self.pending = None;
+                }
+                Err(mpsc::TryRecvError::Empty) => {
+                    ctx.request_repaint();
+                    return;
+                }
+                Err(mpsc::TryRecvError::Disconnected) => self.pending = None,
+            }
+        }
+
+        if self.open && self.generated != Some(self.params) {
+            let params = self.params;
+            let (sender, receiver) = mpsc::channel();
+            let ctx = ctx.clone();
+            let generate = move || {
+                let _ = sender.send(params.generate());
+                ctx.request_repaint();
+            };
+            // The browser has no threads to spare, so it's generated right away there.
+            #[cfg(not(target_arch = "wasm32"))]
+            std::thread::spawn(generate);
+            #[cfg(target_arch = "wasm32")]
+            generate();
+            self.generated = Some(params);
+            self.pending = Some(receiver);
+        }
+    }
+}
+
+fn write_png(image: &egui::ColorImage, path: &Path) -> std::io::Result<()> {
+    let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    let mut encoder = png::Encoder::new(file, image.size[0] as u32, image.size[1] as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let data: Vec<u8> = image.pixels.iter().flat_map(|c| c.to_array()).collect();
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&data))
+        .map_err(std::io::Error::other)
+}
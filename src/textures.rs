@@ -0,0 +1,385 @@
+use crate::file_dialog::{FileDialogs, FileKind};
+use crate::gpu_memory::Allocation;
+use crate::icon;
+use crate::RenderFrame;
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// The largest side a thumbnail in the textures window has, in points.
+const THUMBNAIL_SIZE: f32 = 96.0;
+
+/// Pixels decoded from an image file, in sRGB RGBA8.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodedImage {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+impl DecodedImage {
+    /// The image shrunk to have no side longer than `max_side`, or `None` if it hasn't.
+    pub fn fit(&self, max_side: u32) -> Option<DecodedImage> {
+        let (rgba, width, height) = shrink(&self.rgba, self.width, self.height, max_side)?;
+        Some(DecodedImage {
+            width,
+            height,
+            rgba,
+        })
+    }
+}
+
+/// RGBA8 `rgba`, `width` by `height`, shrunk to have no side longer than `max_side`,
+/// each pixel the average of those it covers. `None` if it fits already. For textures
+/// larger than the device takes, like past WebGL 2's 2048.
+pub(crate) fn shrink(
+    rgba: &[u8],
+    width: u32,
+    height: u32,
+    max_side: u32,
+) -> Option<(Vec<u8>, u32, u32)> {
+    if width <= max_side && height <= max_side {
+        return None;
+    }
+    let longest = width.max(height) as u64;
+    let scale = |side: u32| ((side as u64 * max_side as u64 / longest) as u32).max(1);
+    let (new_width, new_height) = (scale(width), scale(height));
+    // The source pixels from `at * side / new_side` up to where the next one starts.
+    let span = |at: u32, side: u32, new_side: u32| {
+        let start = at as u64 * side as u64 / new_side as u64;
+        let end = (at as u64 + 1) * side as u64 / new_side as u64;
+        start as usize..end.max(start + 1) as usize
+    };
+    let mut out = Vec::with_capacity(new_width as usize * new_height as usize * 4);
+    for y in 0..new_height {
+        let rows = span(y, height, new_height);
+        for x in 0..new_width {
+            let columns = span(x, width, new_width);
+            let mut sum = [0u32; 4];
+            for row in rows.clone() {
+                for column in columns.clone() {
+                    let at = (row * width as usize + column) * 4;
+                    for (sum, &channel) in sum.iter_mut().zip(&rgba[at..at + 4]) {
+                        *sum += u32::from(channel);
+                    }
+                }
+            }
+            let count = (rows.len() * columns.len()) as u32;
+            out.extend(sum.map(|sum| (sum / count) as u8));
+        }
+    }
+    tracing::info!(
+        "Shrinking a {}×{} texture to {}×{} to fit the device",
+        width,
+        height,
+        new_width,
+        new_height
+    );
+    Some((out, new_width, new_height))
+}
+
+/// Decodes a PNG or a JPEG, told apart by their first bytes.
+pub fn decode(bytes: &[u8]) -> Result<DecodedImage, String> {
+    if bytes.starts_with(b"\x89PNG") {
+        let (rgba, width, height) = icon::decode_rgba(bytes)?;
+        return Ok(DecodedImage {
+            width,
+            height,
+            rgba,
+        });
+    }
+    if !bytes.starts_with(&[0xff, 0xd8]) {
+        return Err("Not a PNG or JPEG file".to_owned());
+    }
+    let mut decoder = jpeg_decoder::Decoder::new(bytes);
+    let pixels = decoder.decode().map_err(|e| e.to_string())?;
+    let info = decoder.info().ok_or("The JPEG has no frame")?;
+    let rgba = match info.pixel_format {
+        jpeg_decoder::PixelFormat::L8 => pixels.iter().flat_map(|&l| [l, l, l, 255]).collect(),
+        jpeg_decoder::PixelFormat::L16 => pixels
+            .chunks_exact(2)
+            .flat_map(|l| {
+                let l = (u16::from_ne_bytes([l[0], l[1]]) >> 8) as u8;
+                [l, l, l, 255]
+            })
+            .collect(),
+        jpeg_decoder::PixelFormat::RGB24 => pixels
+            .chunks_exact(3)
+            .flat_map(|c| [c[0], c[1], c[2], 255])
+            .collect(),
+        jpeg_decoder::PixelFormat::CMYK32 => return Err("CMYK JPEGs aren't supported".to_owned()),
+    };
+    Ok(DecodedImage {
+        width: info.width.into(),
+        height: info.height.into(),
+        rgba,
+    })
+}
+
+/// A texture in `Textures`, valid until it's removed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TextureHandle(u64);
+
+/// The uploaded texture and the id egui draws it by.
+struct GpuTexture {
+    _texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    id: egui::TextureId,
+    _allocation: Allocation,
+}
+
+struct Entry {
+    name: String,
+    image: DecodedImage,
+    /// `None` until the next `upload`.
+    gpu: Option<GpuTexture>,
+}
+
+/// Images loaded to be drawn with, both by egui and in wgpu passes. The textures are
+/// owned here, uploaded by `upload` on the next frame and freed once removed, along
+/// with their egui ids. The pixels are kept to upload them again to a new device.
+#[derive(Default)]
+pub struct Textures {
+    next: u64,
+    entries: BTreeMap<TextureHandle, Entry>,
+    /// The ids of removed textures, for `upload` to free with the renderer.
+    freed: Vec<egui::TextureId>,
+}
+
+impl Textures {
+    pub fn insert(&mut self, name: impl Into<String>, image: DecodedImage) -> TextureHandle {
+        let handle = TextureHandle(self.next);
+        self.next += 1;
+        self.entries.insert(
+            handle,
+            Entry {
+                name: name.into(),
+                image,
+                gpu: None,
+            },
+        );
+        handle
+    }
+
+    /// Decodes `bytes` into a texture named after the file.
+    pub fn load(&mut self, path: &Path, bytes: &[u8]) -> Result<TextureHandle, String> {
+        let image =
+            decode(bytes).map_err(|e| format!("Failed to load {}: {}", path.display(), e))?;
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "Image".to_owned());
+        Ok(self.insert(name, image))
+    }
+
+    pub fn remove(&mut self, handle: TextureHandle) {
+        if let Some(Entry { gpu: Some(gpu), .. }) = self.entries.remove(&handle) {
+            self.freed.push(gpu.id);
+        }
+    }
+
+    pub fn handles(&self) -> impl Iterator<Item = TextureHandle> + '_ {
+        self.entries.keys().copied()
+    }
+
+    pub fn name(&self, handle: TextureHandle) -> Option<&str> {
+        self.entries.get(&handle).map(|entry| entry.name.as_str())
+    }
+
+    /// In pixels.
+    pub fn size(&self, handle: TextureHandle) -> Option<[u32; 2]> {
+        let image = &self.entries.get(&handle)?.image;
+        Some([image.width, image.height])
+    }
+
+    /// For egui to draw the texture with, once it's uploaded.
+    pub fn egui_id(&self, handle: TextureHandle) -> Option<egui::TextureId> {
+        Some(self.entries.get(&handle)?.gpu.as_ref()?.id)
+    }
+
+    /// For binding the texture in a pass, once it's uploaded.
+    pub fn view(&self, handle: TextureHandle) -> Option<&wgpu::TextureView> {
+        Some(&self.entries.get(&handle)?.gpu.as_ref()?.view)
+    }
+
+    /// Uploads what was inserted since and frees what was removed.
+    pub fn upload(&mut self, frame: &mut RenderFrame<'_>) {
+        for id in self.freed.drain(..) {
+            frame.renderer.free_texture(&id);
+        }
+        let max_side = frame.device.limits().max_texture_dimension_2d;
+        for entry in self
+            .entries
+            .values_mut()
+            .filter(|entry| entry.gpu.is_none())
+        {
+            // The full image is kept, for a device that takes it.
+            let fitted = entry.image.fit(max_side);
+            let image = fitted.as_ref().unwrap_or(&entry.image);
+            let desc = wgpu::TextureDescriptor {
+                label: Some(&entry.name),
+                size: wgpu::Extent3d {
+                    width: image.width,
+                    height: image.height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            };
+            let texture = frame.errors.scope(frame.device, "texture", || {
+                wgpu::util::DeviceExt::create_texture_with_data(
+                    frame.device,
+                    frame.queue,
+                    &desc,
+                    &image.rgba,
+                )
+            });
+            let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+            let id = frame.renderer.register_native_texture(
+                frame.device,
+                &view,
+                wgpu::FilterMode::Linear,
+            );
+            entry.gpu = Some(GpuTexture {
+                _texture: texture,
+                view,
+                id,
+                _allocation: frame.memory.track_texture(&desc),
+            });
+        }
+    }
+
+    /// Drops the textures, for after the device they were made with was lost. They're
+    /// uploaded again on the next `upload`, keeping their handles.
+    pub fn forget_gpu(&mut self) {
+        for entry in self.entries.values_mut() {
+            entry.gpu = None;
+        }
+        // The renderer they were registered with is gone too.
+        self.freed.clear();
+    }
+}
+
+/// Lists the loaded textures, shown as egui images, and picks the one the scene's cube
+/// is drawn with.
+#[derive(Default)]
+pub struct TexturesWindow {
+    pub open: bool,
+    pub textures: Textures,
+    /// Drawn on the cube in place of its checkerboard.
+    pub on_cube: Option<TextureHandle>,
+}
+
+impl TexturesWindow {
+    /// Images picked in `dialogs` come back as `UserEvent::FileOpened`, for the assets
+    /// to decode.
+    pub fn ui(&mut self, ctx: &egui::Context, dialogs: &FileDialogs) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        let mut remove = None;
+        egui::Window::new("Textures")
+            .open(&mut open)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                let load = ui
+                    .add_enabled(dialogs.available(), egui::Button::new("Load image…"))
+                    .on_disabled_hover_text("There's no file dialog here");
+                if load.clicked() {
+                    dialogs.open(FileKind::Image);
+                }
+                ui.separator();
+                ui.radio_value(&mut self.on_cube, None, "Checkerboard on the cube");
+                let handles: Vec<_> = self.textures.handles().collect();
+                for handle in handles {
+                    let (Some(name), Some([width, height])) =
+                        (self.textures.name(handle), self.textures.size(handle))
+                    else {
+                        continue;
+                    };
+                    ui.horizontal(|ui| {
+                        let size = egui::vec2(width as f32, height as f32);
+                        let size = size * (THUMBNAIL_SIZE / size.max_elem()).min(1.0);
+                        match self.textures.egui_id(handle) {
+                            Some(id) => {
+                                ui.image((id, size));
+                            }
+                            None => {
+                                ui.allocate_ui(size, |ui| ui.spinner());
+                            }
+                        }
+                        ui.vertical(|ui| {
+                            ui.strong(name);
+                            ui.weak(format!("{}×{}", width, height));
+                            ui.radio_value(&mut self.on_cube, Some(handle), "On the cube");
+                            if ui.small_button("Remove").clicked() {
+                                remove = Some(handle);
+                            }
+                        });
+                    });
+                }
+                if self.textures.handles().next().is_none() {
+                    ui.weak("Load a PNG or JPEG to draw it here and on the cube");
+                }
+            });
+        if let Some(handle) = remove {
+            self.textures.remove(handle);
+            if self.on_cube == Some(handle) {
+                self.on_cube = None;
+            }
+        }
+        self.open = open;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(width: u32, height: u32, rgba: [u8; 4]) -> DecodedImage {
+        DecodedImage {
+            width,
+            height,
+            rgba: rgba.repeat((width * height) as usize),
+        }
+    }
+
+    #[test]
+    fn images_that_fit_are_left_alone() {
+        assert_eq!(image(2048, 2048, [0; 4]).fit(2048), None);
+        assert_eq!(image(16, 4, [0; 4]).fit(2048), None);
+    }
+
+    #[test]
+    fn larger_images_shrink_to_the_limit_keeping_their_aspect() {
+        let fitted = image(4096, 1024, [10, 20, 30, 255]).fit(2048).unwrap();
+        assert_eq!([fitted.width, fitted.height], [2048, 512]);
+        assert_eq!(fitted.rgba.len(), 2048 * 512 * 4);
+        assert!(fitted.rgba.chunks_exact(4).all(|c| c == [10, 20, 30, 255]));
+
+        let fitted = image(300, 3000, [0; 4]).fit(1000).unwrap();
+        assert_eq!([fitted.width, fitted.height], [100, 1000]);
+        // A side too thin to shrink keeps a pixel.
+        let fitted = image(10_000, 1, [0; 4]).fit(100).unwrap();
+        assert_eq!([fitted.width, fitted.height], [100, 1]);
+    }
+
+    #[test]
+    fn shrunk_pixels_average_those_they_cover() {
+        // Black and white columns, two pixels each way into one.
+        let rgba = [[0, 0, 0, 255], [255, 255, 255, 255]].concat().repeat(2);
+        let image = DecodedImage {
+            width: 2,
+            height: 2,
+            rgba,
+        };
+        let fitted = image.fit(1).unwrap();
+        assert_eq!(fitted.rgba, [127, 127, 127, 255]);
+    }
+}
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+/// The look of the UI.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+    /// Dark or light as the OS is, following it when that changes.
+    System,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::Dark, Theme::Light, Theme::System];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Theme::Dark => "Dark",
+            Theme::Light => "Light",
+            Theme::System => "Follow system",
+        }
+    }
+
+    /// The theme to draw with, `System` taking the OS's or dark where it has none.
+    pub(crate) fn resolve(self, system: Option<winit::window::Theme>) -> Theme {
+        match (self, system) {
+            (Theme::System, Some(winit::window::Theme::Light)) => Theme::Light,
+            (Theme::System, _) => Theme::Dark,
+            (theme, _) => theme,
+        }
+    }
+
+    /// `System` looks dark, `resolve` it first to follow the OS.
+    pub(crate) fn visuals(self) -> egui::Visuals {
+        match self {
+            Theme::Dark | Theme::System => egui::Visuals::dark(),
+            Theme::Light => egui::Visuals::light(),
+        }
+    }
+
+    /// What's beneath the UI where the app renders nothing, the panels' color.
+    pub(crate) fn clear_color(self) -> wgpu::Color {
+        let [r, g, b, a] = egui::Rgba::from(self.visuals().panel_fill).to_array();
+        wgpu::Color {
+            r: r as f64,
+            g: g as f64,
+            b: b as f64,
+            a: a as f64,
+        }
+    }
+}
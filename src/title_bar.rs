@@ -0,0 +1,132 @@
+use winit::window::{ResizeDirection, Window};
+
+/// How far in from the window's edges a press resizes it, in points.
+const RESIZE_MARGIN: f32 = 5.0;
+const BUTTON_SIZE: egui::Vec2 = egui::vec2(32.0, 22.0);
+
+/// What the title bar asks of the window, done at the start of the next frame while the
+/// button that asked for it is still held.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindowAction {
+    Drag,
+    Resize(ResizeDirection),
+    Minimize,
+    ToggleMaximized,
+    Close,
+}
+
+impl WindowAction {
+    /// Runs it, except for `Close`, which goes through `Application::close_requested`.
+    pub fn apply(self, window: &Window) {
+        let result = match self {
+            WindowAction::Drag => window.drag_window(),
+            WindowAction::Resize(direction) => window.drag_resize_window(direction),
+            WindowAction::Minimize => {
+                window.set_minimized(true);
+                Ok(())
+            }
+            WindowAction::ToggleMaximized => {
+                window.set_maximized(!window.is_maximized());
+                Ok(())
+            }
+            WindowAction::Close => Ok(()),
+        };
+        if let Err(e) = result {
+            tracing::debug!("{:?} isn't supported here: {}", self, e);
+        }
+    }
+}
+
+/// The title bar of a window without decorations: dragging it moves the window, a
+/// double click maximizes it, and the buttons on the right minimize, maximize and
+/// close it. Presses at the window's edges resize it.
+///
+/// `maximized` picks the maximize button's symbol.
+pub fn title_bar(ctx: &egui::Context, title: &str, maximized: bool) -> Option<WindowAction> {
+    let mut action = resize_edge(ctx, maximized).map(WindowAction::Resize);
+    egui::TopBottomPanel::top("title_bar").show(ctx, |ui| {
+        let bar = ui.max_rect();
+        let response = ui.interact(bar, ui.id().with("drag"), egui::Sense::click_and_drag());
+        ui.painter().text(
+            bar.center(),
+            egui::Align2::CENTER_CENTER,
+            title,
+            egui::FontId::proportional(14.0),
+            ui.visuals().text_color(),
+        );
+        if response.double_clicked() {
+            action = Some(WindowAction::ToggleMaximized);
+        } else if action.is_none() && response.drag_started_by(egui::PointerButton::Primary) {
+            action = Some(WindowAction::Drag);
+        }
+
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            let mut button = |ui: &mut egui::Ui, text: &str, hover: &str, pressed| {
+                let clicked = ui
+                    .add(egui::Button::new(text).frame(false).min_size(BUTTON_SIZE))
+                    .on_hover_text(hover)
+                    .clicked();
+                if clicked {
+                    action = Some(pressed);
+                }
+            };
+            button(ui, "❌", "Close", WindowAction::Close);
+            let (symbol, hover) = if maximized {
+                ("🗗", "Restore")
+            } else {
+                ("🗖", "Maximize")
+            };
+            button(ui, symbol, hover, WindowAction::ToggleMaximized);
+            button(ui, "🗕", "Minimize", WindowAction::Minimize);
+        });
+    });
+    if action.is_some() {
+        ctx.request_repaint();
+    }
+    action
+}
+
+/// The edge or corner a press just started at, none while maximized as there's
+/// nothing to resize then.
+fn resize_edge(ctx: &egui::Context, maximized: bool) -> Option<ResizeDirection> {
+    if maximized {
+        return None;
+    }
+    let screen = ctx.screen_rect();
+    let (pressed, pos) = ctx.input(|i| {
+        (
+            i.pointer.primary_pressed(),
+            i.pointer.interact_pos().or(i.pointer.hover_pos()),
+        )
+    });
+    let pos = pos?;
+    let west = pos.x < screen.left() + RESIZE_MARGIN;
+    let east = pos.x > screen.right() - RESIZE_MARGIN;
+    let north = pos.y < screen.top() + RESIZE_MARGIN;
+    let south = pos.y > screen.bottom() - RESIZE_MARGIN;
+    let (direction, icon) = match (north, south, west, east) {
+        (true, _, true, _) => (
+            ResizeDirection::NorthWest,
+            egui::CursorIcon::ResizeNorthWest,
+        ),
+        (true, _, _, true) => (
+            ResizeDirection::NorthEast,
+            egui::CursorIcon::ResizeNorthEast,
+        ),
+        (_, true, true, _) => (
+            ResizeDirection::SouthWest,
+            egui::CursorIcon::ResizeSouthWest,
+        ),
+        (_, true, _, true) => (
+            ResizeDirection::SouthEast,
+            egui::CursorIcon::ResizeSouthEast,
+        ),
+        (true, ..) => (ResizeDirection::North, egui::CursorIcon::ResizeNorth),
+        (_, true, ..) => (ResizeDirection::South, egui::CursorIcon::ResizeSouth),
+        (.., true, _) => (ResizeDirection::West, egui::CursorIcon::ResizeWest),
+        (.., true) => (ResizeDirection::East, egui::CursorIcon::ResizeEast),
+        _ => return None,
+    };
+    ctx.set_cursor_icon(icon);
+    pressed.then_some(direction)
+}
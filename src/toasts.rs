@@ -0,0 +1,60 @@
+use instant::Instant;
+
+use std::time::Duration;
+
+const TOAST_DURATION: Duration = Duration::from_secs(8);
+
+struct Toast {
+    text: String,
+    shown_at: Option<Instant>,
+}
+
+/// Short notifications stacked in the bottom right corner, each one disappears on its
+/// own after a few seconds or when clicked.
+#[derive(Default)]
+pub struct Toasts {
+    toasts: Vec<Toast>,
+}
+
+impl Toasts {
+    pub fn push(&mut self, text: impl Into<String>) {
+        self.toasts.push(Toast {
+            text: text.into(),
+            shown_at: None,
+        });
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) {
+        let now = Instant::now();
+        self.toasts.retain(|toast| {
+            toast
+                .shown_at
+                .is_none_or(|shown_at| now - shown_at < TOAST_DURATION)
+        });
+        if self.toasts.is_empty() {
+            return;
+        }
+
+        let mut dismissed = None;
+        egui::Area::new("toasts")
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-8.0, -8.0))
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                for (i, toast) in self.toasts.iter_mut().enumerate() {
+                    // The timer starts once the toast is actually on screen.
+                    toast.shown_at.get_or_insert(now);
+                    let response = egui::Frame::popup(ui.style())
+                        .show(ui, |ui| ui.label(&toast.text))
+                        .response
+                        .interact(egui::Sense::click());
+                    if response.clicked() {
+                        dismissed = Some(i);
+                    }
+                }
+            });
+        if let Some(i) = dismissed {
+            self.toasts.remove(i);
+        }
+        ctx.request_repaint_after(Duration::from_millis(250));
+    }
+}
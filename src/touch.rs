@@ -0,0 +1,113 @@
+use egui::{Color32, Pos2, Rect, Sense, Stroke, Vec2};
+
+/// The zoom the canvas stays within.
+const ZOOM_RANGE: std::ops::RangeInclusive<f32> = 0.25..=8.0;
+/// The spacing of the canvas's grid, in its own units.
+const GRID: f32 = 32.0;
+
+/// What the fingers or the mouse did over a widget in a frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Gesture {
+    /// How much to scale by, above 1 when the fingers spread.
+    pub zoom: f32,
+    /// The point to zoom about, in points.
+    pub center: Pos2,
+    pub pan: Vec2,
+}
+
+impl Gesture {
+    /// A pinch of two or more fingers that started over `response`, or a drag of one
+    /// finger or the mouse on it. egui only turns touches into a pinch, leaving the
+    /// first finger to also move its pointer, so the pinch is looked for first.
+    pub fn read(ui: &egui::Ui, response: &egui::Response) -> Option<Gesture> {
+        if let Some(touch) = ui.input(|i| i.multi_touch()) {
+            return response.rect.contains(touch.start_pos).then_some(Gesture {
+                zoom: touch.zoom_delta,
+                center: touch.start_pos,
+                pan: touch.translation_delta,
+            });
+        }
+        let pan = response.drag_delta();
+        (response.dragged() && pan != Vec2::ZERO).then(|| Gesture {
+            zoom: 1.0,
+            center: response.rect.center(),
+            pan,
+        })
+    }
+}
+
+/// A grid to pinch and pan, trying out touch input.
+pub struct TouchCanvas {
+    pub open: bool,
+    pub zoom: f32,
+    /// Where the canvas's origin is from the middle of the view, in points.
+    pub pan: Vec2,
+}
+
+impl Default for TouchCanvas {
+    fn default() -> Self {
+        Self {
+            open: false,
+            zoom: 1.0,
+            pan: Vec2::ZERO,
+        }
+    }
+}
+
+impl TouchCanvas {
+    /// Scales about `gesture.center`, keeping what's under it in place, and pans.
+    pub fn apply(&mut self, gesture: Gesture, view: Rect) {
+        let zoom = (self.zoom * gesture.zoom).clamp(*ZOOM_RANGE.start(), *ZOOM_RANGE.end());
+        let from_origin = gesture.center - view.center() - self.pan;
+        self.pan += from_origin - from_origin * (zoom / self.zoom) + gesture.pan;
+        self.zoom = zoom;
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        egui::Window::new("Touch canvas")
+            .open(&mut open)
+            .default_size([360.0, 360.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{:.0}%", self.zoom * 100.0));
+                    if ui.button("Reset").clicked() {
+                        *self = Self {
+                            open: true,
+                            ..Self::default()
+                        };
+                    }
+                    ui.weak("Pinch to zoom, drag to pan");
+                });
+                let size = ui.available_size().max(egui::vec2(200.0, 200.0));
+                let (response, painter) = ui.allocate_painter(size, Sense::drag());
+                if let Some(gesture) = Gesture::read(ui, &response) {
+                    self.apply(gesture, response.rect);
+                }
+
+                let view = response.rect;
+                painter.rect_filled(view, 0.0, ui.visuals().extreme_bg_color);
+                let origin = view.center() + self.pan;
+                let step = GRID * self.zoom;
+                let stroke = ui.visuals().widgets.noninteractive.bg_stroke;
+                // The lines crossing the view, whichever part of the canvas it shows.
+                let first = ((view.min - origin) / step).floor();
+                let mut x = origin.x + first.x * step;
+                while x <= view.max.x {
+                    painter.vline(x, view.y_range(), stroke);
+                    x += step;
+                }
+                let mut y = origin.y + first.y * step;
+                while y <= view.max.y {
+                    painter.hline(view.x_range(), y, stroke);
+                    y += step;
+                }
+                painter.circle_stroke(origin, GRID * self.zoom, Stroke::new(2.0, Color32::YELLOW));
+            });
+        self.open = open;
+    }
+}
@@ -0,0 +1,125 @@
+use crate::events::UserEvent;
+
+use winit::event_loop::EventLoopProxy;
+
+/// What the tray icon tells the event loop.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrayEvent {
+    /// The icon is in the tray, so the window can be hidden there.
+    Created,
+    Show,
+    Hide,
+    Quit,
+}
+
+#[cfg(feature = "tray")]
+const SHOW: &str = "show";
+#[cfg(feature = "tray")]
+const HIDE: &str = "hide";
+#[cfg(feature = "tray")]
+const QUIT: &str = "quit";
+
+/// The icon in the system tray, with a menu to show, hide and quit the example.
+/// Without the `tray` feature there's none, and closing the window exits.
+#[derive(Default)]
+pub(crate) struct Tray {
+    /// Set once `TrayEvent::Created` came in.
+    pub created: bool,
+    #[cfg(feature = "tray")]
+    proxy: Option<EventLoopProxy<UserEvent>>,
+    /// Kept on the main thread, except on Linux where it's kept by the GTK thread.
+    #[cfg(all(feature = "tray", not(target_os = "linux")))]
+    icon: Option<tray_icon::TrayIcon>,
+}
+
+#[cfg(feature = "tray")]
+impl Tray {
+    /// Sends the clicks in the menu to the event loop, as `UserEvent::Tray`.
+    pub(crate) fn listen(&mut self, proxy: EventLoopProxy<UserEvent>) {
+        let menu_proxy = std::sync::Mutex::new(proxy.clone());
+        tray_icon::menu::MenuEvent::set_event_handler(Some(
+            move |event: tray_icon::menu::MenuEvent| {
+                let tray_event = if event.id == SHOW {
+                    TrayEvent::Show
+                } else if event.id == HIDE {
+                    TrayEvent::Hide
+                } else if event.id == QUIT {
+                    TrayEvent::Quit
+                } else {
+                    return;
+                };
+                if let Ok(proxy) = menu_proxy.lock() {
+                    let _ = proxy.send_event(UserEvent::Tray(tray_event));
+                }
+            },
+        ));
+        self.proxy = Some(proxy);
+    }
+
+    /// Puts the icon in the tray, which macOS only allows once the event loop runs.
+    /// On Linux it's drawn by GTK, whose own loop runs on another thread.
+    pub(crate) fn create(&mut self) {
+        let Some(proxy) = self.proxy.clone() else {
+            return;
+        };
+        #[cfg(target_os = "linux")]
+        {
+            let spawned = std::thread::Builder::new()
+                .name("tray".to_owned())
+                .spawn(move || {
+                    if let Err(e) = gtk::init() {
+                        tracing::warn!("No tray icon without GTK: {}", e);
+                        return;
+                    }
+                    match build() {
+                        Ok(icon) => {
+                            let _ = proxy.send_event(UserEvent::Tray(TrayEvent::Created));
+                            gtk::main();
+                            drop(icon);
+                        }
+                        Err(e) => tracing::warn!("No tray icon: {}", e),
+                    }
+                });
+            if let Err(e) = spawned {
+                tracing::warn!("No tray icon: {}", e);
+            }
+        }
+        #[cfg(not(target_os = "linux"))]
+        match build() {
+            Ok(icon) => {
+                self.icon = Some(icon);
+                let _ = proxy.send_event(UserEvent::Tray(TrayEvent::Created));
+            }
+            Err(e) => tracing::warn!("No tray icon: {}", e),
+        }
+    }
+}
+
+#[cfg(not(feature = "tray"))]
+impl Tray {
+    pub(crate) fn listen(&mut self, _proxy: EventLoopProxy<UserEvent>) {}
+
+    pub(crate) fn create(&mut self) {}
+}
+
+#[cfg(feature = "tray")]
+fn build() -> Result<tray_icon::TrayIcon, String> {
+    use tray_icon::menu::{IsMenuItem, Menu, MenuItem, PredefinedMenuItem};
+
+    let menu = Menu::new();
+    let show = MenuItem::with_id(SHOW, "Show", true, None);
+    let hide = MenuItem::with_id(HIDE, "Hide", true, None);
+    let separator = PredefinedMenuItem::separator();
+    let quit = MenuItem::with_id(QUIT, "Quit", true, None);
+    let items: [&dyn IsMenuItem; 4] = [&show, &hide, &separator, &quit];
+    menu.append_items(&items).map_err(|e| e.to_string())?;
+
+    let (rgba, width, height) = crate::icon::decode_rgba(crate::icon::EXAMPLE_ICON)?;
+    let icon = tray_icon::Icon::from_rgba(rgba, width, height).map_err(|e| e.to_string())?;
+    tray_icon::TrayIconBuilder::new()
+        .with_menu(Box::new(menu))
+        .with_tooltip(env!("CARGO_PKG_NAME"))
+        .with_icon(icon)
+        .build()
+        .map_err(|e| e.to_string())
+}
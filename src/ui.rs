@@ -0,0 +1,1429 @@
+use crate::app::{AppState, LoadedFile, Settings, UnsyncedMode, ZOOM_STEP};
+use crate::commands::{self, Command};
+use crate::data_table;
+use crate::dock;
+use crate::file_dialog::FileKind;
+use crate::fullscreen::FullscreenMode;
+use crate::inspector;
+use crate::model;
+use crate::platform;
+use crate::theme::Theme;
+use crate::title_bar;
+use crate::watermark;
+
+use egui::{Key, Modifiers};
+
+/// Draws all of the example's own UI for one frame.
+///
+/// Everything here only needs an `egui::Context`, so it can be run without a window
+/// or a GPU, which is what the tests below do.
+pub fn show(ctx: &egui::Context, app: &mut AppState) {
+    let document_focused = app.document_focused(ctx);
+    // While one is rebound, what's pressed is the new shortcut.
+    if !app.shortcut_editor.capturing() {
+        for command in app.shortcuts.dispatch(ctx, document_focused) {
+            app.run(command);
+        }
+    }
+    // egui-winit turns Ctrl+scroll and touchpad pinches into zooms, a pinch on a
+    // touchscreen is for the widget under it.
+    let zoom_delta: f32 = ctx.input(|i| {
+        i.events
+            .iter()
+            .filter_map(|event| match event {
+                egui::Event::Zoom(factor) => Some(*factor),
+                _ => None,
+            })
+            .product()
+    });
+    if zoom_delta != 1.0 {
+        app.settings.zoom_by(zoom_delta);
+    }
+
+    // Without the OS's title bar, the app's goes above the menu bar.
+    if !app.decorated && app.fullscreen == FullscreenMode::Windowed {
+        if let Some(action) = title_bar::title_bar(ctx, &app.title, app.maximized) {
+            app.window_action = Some(action);
+        }
+    }
+    menu_bar(ctx, app);
+    status_bar(ctx, app);
+
+    let active = &mut app.active_color;
+    app.palettes.retain_mut(|palette| palette.show(ctx, active));
+
+    load_file_window(ctx, app);
+    loaded_file_window(ctx, app);
+    app.stats.ui(ctx, &app.file_dialogs);
+    if app.split_editor.open {
+        app.split_editor.ui(ctx);
+    }
+    if app.texture_gen.open {
+        app.texture_gen.ui(ctx);
+    }
+    // Always run, files can be dropped while it's closed.
+    app.dropped_files.ui(ctx);
+    app.fonts.ui(ctx, &app.file_dialogs);
+    app.gamepads.ui(ctx);
+    app.touch_canvas.ui(ctx);
+    app.drawing.ui(ctx);
+    app.model.ui(ctx);
+    app.textures.ui(ctx, &app.file_dialogs);
+    app.assets.ui(ctx, &app.file_dialogs);
+    app.jobs.ui(ctx);
+    if app.bezier.open {
+        app.bezier.ui(ctx);
+    }
+    if app.material_editor.open {
+        app.material_editor.ui(ctx);
+    }
+    if app.viewport.open {
+        app.viewport.ui(ctx);
+    }
+    if app.scene_panel.open {
+        app.scene_panel.ui(ctx);
+    }
+    if app.offscreen.open {
+        app.offscreen.ui(ctx);
+    }
+    if app.traffic_light.open {
+        app.traffic_light.ui(ctx);
+    }
+    if app.ecs_inspector.open {
+        app.ecs_inspector.ui(ctx);
+    }
+    if app.gpu_timings.open {
+        app.gpu_timings.ui(ctx);
+    }
+    if app.fps_overlay.open {
+        app.fps_overlay.ui(ctx);
+    }
+    app.stage_breakdown.ui(ctx);
+    app.gpu_errors.ui(ctx);
+    app.gpu_memory.ui(ctx);
+    app.perf_plots.ui(ctx);
+    app.shaders.ui(ctx);
+    let frame_ms = app.stats.last().map(|record| record.cpu_ms());
+    app.big_table.ui(ctx, frame_ms);
+    app.widget_gallery.ui(ctx);
+    inspector::show(ctx, app);
+    dock::show(ctx, app);
+    files_window(ctx, app);
+    block_diagram_window(ctx, app);
+    camera_window(ctx, app);
+    app.frame_dump.ui(ctx);
+    app.clipboard.ui(ctx);
+    settings_window(ctx, app);
+    command_palette(ctx, app);
+    crash_report_window(ctx, app);
+    watermark::show(ctx, &app.config.watermark);
+    app.measure.show(ctx, app.native_pixels_per_point);
+    if let Some(region) = app.region_select.show(ctx) {
+        app.copy_region = Some(in_pixels(ctx, region));
+        app.screenshot_requested = true;
+    }
+    if let Some(canvas) = app.drawing.take_export() {
+        app.save_region = Some(in_pixels(ctx, canvas));
+        app.screenshot_requested = true;
+    }
+    app.pasted_images.ui(ctx);
+    app.toasts.show(ctx);
+    scene_controls(ctx, app);
+}
+
+/// `rect` in the screenshot's pixels.
+fn in_pixels(ctx: &egui::Context, rect: egui::Rect) -> egui::Rect {
+    let pixels_per_point = ctx.pixels_per_point();
+    egui::Rect::from_min_size(
+        (rect.min.to_vec2() * pixels_per_point).to_pos2(),
+        rect.size() * pixels_per_point,
+    )
+}
+
+/// Locks the pointer on a click of the scene showing between the UI, for the mouse to
+/// turn the camera, and tells how to let go of it. Scrolling the scene zooms, and the
+/// fly camera moves while locked. Run last to know of every area drawn.
+fn scene_controls(ctx: &egui::Context, app: &mut AppState) {
+    if !app.show_scene {
+        return;
+    }
+    let over_scene = app.pointer_lock.is_locked() || !ctx.is_pointer_over_area();
+    let scroll = ctx.input(|i| i.scroll_delta.y);
+    if over_scene && scroll != 0.0 {
+        app.camera.zoom(scroll);
+    }
+    if app.pointer_lock.is_locked() {
+        if !ctx.wants_keyboard_input() && ctx.input(|i| app.camera.fly(i)) {
+            ctx.request_repaint();
+        }
+        egui::Area::new("pointer_lock_hint")
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 48.0])
+            .interactable(false)
+            .show(ctx, |ui| {
+                ui.label("Press Esc to release the mouse");
+            });
+    } else if ctx.input(|i| i.pointer.primary_clicked()) && !ctx.is_pointer_over_area() {
+        app.pointer_lock.lock();
+    }
+}
+
+pub fn menu_bar(ctx: &egui::Context, app: &mut AppState) {
+    egui::TopBottomPanel::top("app_menu_bar").show(ctx, |ui| {
+        egui::menu::bar(ui, |ui| {
+            ui.menu_button("File", |ui| file_menu(ui, app));
+            ui.menu_button("Windows", |ui| {
+                if ui.button("New palette").clicked() {
+                    app.new_palette();
+                    ui.close_menu();
+                }
+                ui.checkbox(&mut app.split_editor.open, "Split editor");
+                ui.checkbox(&mut app.show_files, "Files");
+                ui.checkbox(&mut app.big_table.open, "Large table");
+                ui.checkbox(&mut app.show_block_diagram, "Block diagram");
+                ui.checkbox(&mut app.texture_gen.open, "Texture generator");
+                ui.checkbox(&mut app.dropped_files.open, "Dropped files");
+                ui.checkbox(&mut app.pasted_images.open, "Pasted images");
+                ui.checkbox(&mut app.fonts.open, "Fonts");
+                ui.checkbox(&mut app.gamepads.open, "Gamepads");
+                ui.checkbox(&mut app.touch_canvas.open, "Touch canvas");
+                ui.checkbox(&mut app.drawing.open, "Drawing");
+                ui.checkbox(&mut app.bezier.open, "Bezier editor");
+                ui.checkbox(&mut app.material_editor.open, "Material editor");
+                ui.checkbox(&mut app.widget_gallery.open, "Widget gallery");
+                ui.checkbox(&mut app.viewport.open, "3D viewport");
+                ui.checkbox(&mut app.show_camera, "Camera");
+                ui.checkbox(&mut app.model.open, "Model");
+                ui.checkbox(&mut app.textures.open, "Textures");
+                ui.checkbox(&mut app.assets.open, "Assets");
+                ui.checkbox(&mut app.jobs.open, "Background tasks");
+                ui.checkbox(&mut app.scene_panel.open, "Scene panel");
+                ui.checkbox(&mut app.offscreen.open, "Offscreen render");
+                ui.checkbox(&mut app.traffic_light.open, "State machine");
+                ui.checkbox(&mut app.ecs_inspector.open, "ECS inspector");
+                ui.checkbox(&mut app.stats.open, "Frame statistics");
+                ui.checkbox(&mut app.stage_breakdown.open, "CPU stages");
+                ui.checkbox(&mut app.gpu_timings.open, "GPU timings");
+                ui.checkbox(&mut app.fps_overlay.open, "FPS overlay (F3)");
+                let gpu_errors = app.gpu_errors.count();
+                ui.checkbox(
+                    &mut app.gpu_errors.open,
+                    format!("GPU errors ({})", gpu_errors),
+                );
+                ui.checkbox(&mut app.gpu_memory.open, "GPU memory");
+                ui.checkbox(&mut app.perf_plots.open, "Performance plots");
+                ui.checkbox(&mut app.shaders.open, "Shaders");
+                ui.checkbox(&mut app.inspector.open, "Inspector");
+                ui.checkbox(&mut app.dock.open, "Docked panels");
+                ui.checkbox(&mut app.clipboard.open, "Clipboard history (Ctrl+Shift+V)");
+                ui.checkbox(&mut app.measure.active, "Measure tool (Ctrl+M)");
+                ui.separator();
+                ui.checkbox(&mut app.show_scene, "3D scene");
+                ui.add_enabled(
+                    app.show_scene,
+                    egui::Checkbox::new(&mut app.spin_cube, "Spin the cube"),
+                );
+                ui.add_enabled(
+                    app.can_compute(),
+                    egui::Checkbox::new(&mut app.show_particles, "Compute particles"),
+                )
+                .on_disabled_hover_text("The device can't run compute shaders");
+                ui.checkbox(&mut app.show_gamut_test, "Wide-gamut test pattern")
+                    .on_hover_text(format!("Output color space: {:?}", app.color_space));
+                ui.separator();
+                ui.checkbox(&mut app.show_settings, "Settings");
+                let palette = egui::Button::new(Command::OpenCommandPalette.name()).shortcut_text(
+                    app.shortcuts
+                        .shortcut_text(ui.ctx(), Command::OpenCommandPalette)
+                        .unwrap_or_default(),
+                );
+                if ui.add(palette).clicked() {
+                    app.command_palette.show();
+                    ui.close_menu();
+                }
+            });
+            ui.menu_button("Debug", |ui| {
+                ui.checkbox(&mut app.frame_dump.open, "Frame data");
+                if ui.button("Dump frame data").clicked() {
+                    app.frame_dump.request();
+                    ui.close_menu();
+                }
+                if ui.button("Save screenshot (F12)").clicked() {
+                    app.run(Command::Screenshot);
+                    ui.close_menu();
+                }
+                let recording = if app.recording.is_some() {
+                    "Stop recording (Shift+F12)"
+                } else {
+                    "Start recording (Shift+F12)"
+                };
+                if ui.button(recording).clicked() {
+                    app.run(Command::ToggleRecording);
+                    ui.close_menu();
+                }
+            });
+            ui.menu_button("Help", |ui| match &app.update {
+                Some(update) => {
+                    ui.label(format!("Version {} is available", update.version));
+                    if ui.button("View release notes").clicked() {
+                        ctx.output_mut(|o| {
+                            o.open_url = Some(egui::OpenUrl::new_tab(&update.release_notes))
+                        });
+                        ui.close_menu();
+                    }
+                }
+                None => {
+                    ui.label(format!("Version {}", env!("CARGO_PKG_VERSION")));
+                }
+            });
+            if app.stats.is_capturing() {
+                ui.colored_label(egui::Color32::RED, "● Capturing frames");
+            }
+            if app.recording.is_some() {
+                ui.colored_label(egui::Color32::RED, "● Recording");
+            }
+        });
+    });
+}
+
+pub fn status_bar(ctx: &egui::Context, app: &mut AppState) {
+    egui::TopBottomPanel::bottom("app_status_bar").show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            ui.label(format!("{:?}", app.color_space));
+            ui.separator();
+            ui.label(if app.settings.vsync {
+                "VSync on"
+            } else {
+                "VSync off"
+            });
+            if let Some(mode) = app.active_present_mode {
+                ui.weak(format!("{:?}", mode));
+            }
+            ui.separator();
+            ui.label(format!(
+                "{} lines",
+                app.split_editor.source().lines().count()
+            ));
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                let dt = ctx.input(|i| i.stable_dt);
+                if dt > 0.0 {
+                    ui.weak(format!("{:.0} fps", 1.0 / dt));
+                }
+                app.assets.status_ui(ui);
+            });
+        });
+    });
+}
+
+pub fn settings_window(ctx: &egui::Context, app: &mut AppState) {
+    let scene_samples = app.scene_sample_count();
+    let color_space = app.color_space;
+    egui::Window::new("Settings")
+        .open(&mut app.show_settings)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Window");
+                for mode in FullscreenMode::ALL {
+                    let selected = app.fullscreen == mode;
+                    if ui.selectable_label(selected, mode.name()).clicked() && !selected {
+                        app.requested_fullscreen = Some(mode);
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Theme");
+                let current = app.config.theme.unwrap_or_default();
+                for theme in Theme::ALL {
+                    let selected = current == theme;
+                    if ui.selectable_label(selected, theme.name()).clicked() && !selected {
+                        app.requested_theme = Some(theme);
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("UI zoom");
+                if ui.small_button("−").clicked() {
+                    app.settings.zoom_by(1.0 / ZOOM_STEP);
+                }
+                ui.label(format!("{:.0}%", app.settings.zoom * 100.0));
+                if ui.small_button("+").clicked() {
+                    app.settings.zoom_by(ZOOM_STEP);
+                }
+                if ui.small_button("Reset").clicked() {
+                    app.settings.zoom = 1.0;
+                }
+            });
+            let on_top = ui.checkbox(&mut app.settings.always_on_top, "Always on top");
+            if let Some(shortcut) = app
+                .shortcuts
+                .shortcut_text(ui.ctx(), Command::ToggleAlwaysOnTop)
+            {
+                on_top.on_hover_text(shortcut);
+            }
+            ui.checkbox(&mut app.settings.vsync, "VSync");
+            present_mode_row(
+                ui,
+                &mut app.settings,
+                &app.present_modes,
+                app.active_present_mode,
+            );
+            ui.horizontal(|ui| {
+                ui.label("MSAA");
+                for samples in [1, 2, 4, 8] {
+                    ui.selectable_value(&mut app.settings.msaa, samples, format!("{}x", samples));
+                }
+            });
+            ui.checkbox(&mut app.settings.continuous, "Redraw continuously")
+                .on_hover_text("Otherwise frames are only drawn on input or animation");
+            ui.horizontal(|ui| {
+                ui.label("FPS cap");
+                for limit in [Some(30), Some(60), Some(144), None] {
+                    let text = limit.map_or("Unlimited".to_owned(), |fps| fps.to_string());
+                    ui.selectable_value(&mut app.settings.fps_limit, limit, text);
+                }
+            });
+            if let Some(active) = scene_samples.filter(|&n| n != app.settings.msaa) {
+                ui.weak(format!("Not supported here, using {}x", active));
+            }
+            ui.collapsing("Post-processing", |ui| {
+                app.settings.post.ui(ui, color_space);
+            });
+            ui.collapsing("Keyboard shortcuts", |ui| {
+                if app.shortcut_editor.ui(ui, &mut app.shortcuts) {
+                    app.save_shortcuts = true;
+                }
+            });
+            ui.collapsing("Adapter", |ui| {
+                for info in &app.adapters {
+                    let text =
+                        format!("{} ({:?}, {:?})", info.name, info.backend, info.device_type);
+                    let current = app.current_adapter.as_ref() == Some(info);
+                    if ui.selectable_label(current, text).clicked() {
+                        app.adapter = Some(info.clone());
+                    }
+                }
+                if app.adapters.is_empty() {
+                    ui.weak("No adapters were listed");
+                }
+            });
+            ui.checkbox(&mut app.show_demo, "Show the egui demo");
+        });
+}
+
+/// The modes the surface can present with, the one in use selected. Fifo is VSync on,
+/// the others are what's used while it's off.
+fn present_mode_row(
+    ui: &mut egui::Ui,
+    settings: &mut Settings,
+    supported: &[wgpu::PresentMode],
+    active: Option<wgpu::PresentMode>,
+) {
+    ui.horizontal(|ui| {
+        ui.label("Present mode");
+        let modes = [
+            (wgpu::PresentMode::Fifo, None),
+            (wgpu::PresentMode::Mailbox, Some(UnsyncedMode::Mailbox)),
+            (wgpu::PresentMode::Immediate, Some(UnsyncedMode::Immediate)),
+        ];
+        for (mode, unsynced) in modes {
+            // Nothing is listed before setup, so everything is offered.
+            let enabled = supported.is_empty() || supported.contains(&mode);
+            let selected = active == Some(mode);
+            let label = ui
+                .add_enabled(
+                    enabled,
+                    egui::SelectableLabel::new(selected, format!("{:?}", mode)),
+                )
+                .on_disabled_hover_text("Not supported by this surface");
+            if label.clicked() {
+                settings.vsync = unsynced.is_none();
+                if let Some(unsynced) = unsynced {
+                    settings.unsynced = unsynced;
+                }
+            }
+        }
+    });
+}
+
+/// A searchable list of every command, run with Enter or a click.
+pub fn command_palette(ctx: &egui::Context, app: &mut AppState) {
+    let palette = &mut app.command_palette;
+    if !palette.open {
+        return;
+    }
+    let matches = commands::filter(&palette.query);
+    let (up, down, enter, escape) = ctx.input_mut(|i| {
+        (
+            i.consume_key(Modifiers::NONE, Key::ArrowUp),
+            i.consume_key(Modifiers::NONE, Key::ArrowDown),
+            i.consume_key(Modifiers::NONE, Key::Enter),
+            i.consume_key(Modifiers::NONE, Key::Escape),
+        )
+    });
+    if up {
+        palette.selected = palette.selected.saturating_sub(1);
+    }
+    if down {
+        palette.selected += 1;
+    }
+    palette.selected = palette.selected.min(matches.len().saturating_sub(1));
+
+    let mut chosen = enter
+        .then(|| matches.get(palette.selected).copied())
+        .flatten();
+    egui::Window::new("Command palette")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 80.0))
+        .fixed_size([360.0, 0.0])
+        .show(ctx, |ui| {
+            let query = ui.add(
+                egui::TextEdit::singleline(&mut palette.query)
+                    .hint_text("Type a command")
+                    .desired_width(f32::INFINITY),
+            );
+            if std::mem::take(&mut palette.focus_query) {
+                query.request_focus();
+            }
+            if query.changed() {
+                palette.selected = 0;
+            }
+            ui.separator();
+            if matches.is_empty() {
+                ui.weak("No matching command");
+            }
+            for (i, command) in matches.iter().enumerate() {
+                let mut button = egui::Button::new(command.name())
+                    .frame(i == palette.selected)
+                    .min_size(egui::vec2(ui.available_width(), 0.0));
+                if let Some(text) = app.shortcuts.shortcut_text(ctx, *command) {
+                    button = button.shortcut_text(text);
+                }
+                if ui.add(button).clicked() {
+                    chosen = Some(*command);
+                }
+            }
+        });
+
+    if escape || chosen.is_some() {
+        palette.open = false;
+    }
+    if let Some(command) = chosen {
+        app.run(command);
+    }
+}
+
+/// Open and save go through the OS's dialogs, which are up without blocking frames.
+fn file_menu(ui: &mut egui::Ui, app: &mut AppState) {
+    let available = app.file_dialogs.available();
+    if ui
+        .add_enabled(available, egui::Button::new("Open…"))
+        .clicked()
+    {
+        app.file_dialogs.open(FileKind::Any);
+        ui.close_menu();
+    }
+    let open_model = ui
+        .add_enabled(
+            available && model::AVAILABLE,
+            egui::Button::new("Open model…"),
+        )
+        .on_disabled_hover_text(if model::AVAILABLE {
+            "There's no file dialog here"
+        } else {
+            "Built without the gltf feature"
+        });
+    if open_model.clicked() {
+        app.file_dialogs.open(FileKind::Model);
+        ui.close_menu();
+    }
+    let save = ui
+        .add_enabled(
+            available && app.loaded_file.is_some(),
+            egui::Button::new("Save a copy as…"),
+        )
+        .on_disabled_hover_text("Open a file first");
+    if save.clicked() {
+        if let Some(file) = &app.loaded_file {
+            let name = file
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            app.file_dialogs.save(name, file.bytes.clone());
+        }
+        ui.close_menu();
+    }
+}
+
+fn load_file_window(ctx: &egui::Context, app: &mut AppState) {
+    let Some(path) = &app.pending_file else {
+        return;
+    };
+    let mut confirmed = None;
+    egui::Window::new("Load file?")
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label("A link asked to load this file:");
+            ui.monospace(path.display().to_string());
+            ui.horizontal(|ui| {
+                if ui.button("Load").clicked() {
+                    confirmed = Some(true);
+                }
+                if ui.button("Cancel").clicked() {
+                    confirmed = Some(false);
+                }
+            });
+        });
+
+    match confirmed {
+        Some(true) => {
+            let path = app.pending_file.take().unwrap();
+            match std::fs::read(&path) {
+                Ok(bytes) => app.loaded_file = Some(LoadedFile { path, bytes }),
+                Err(e) => tracing::error!("Failed to load {}: {}", path.display(), e),
+            }
+        }
+        Some(false) => app.pending_file = None,
+        None => {}
+    }
+}
+
+fn loaded_file_window(ctx: &egui::Context, app: &mut AppState) {
+    let mut open = app.loaded_file.is_some();
+    if let Some(file) = &app.loaded_file {
+        egui::Window::new("Loaded file")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.monospace(file.path.display().to_string());
+                ui.label(format!("{} bytes", file.bytes.len()));
+            });
+    }
+    if !open {
+        app.loaded_file = None;
+    }
+}
+
+fn files_window(ctx: &egui::Context, app: &mut AppState) {
+    if !app.show_files {
+        return;
+    }
+    let files = app.files.get_or_insert_with(|| {
+        let root = std::env::current_dir().unwrap_or_default();
+        data_table::files_demo(&root)
+    });
+    egui::Window::new("Files")
+        .open(&mut app.show_files)
+        .default_size([500.0, 400.0])
+        .show(ctx, |ui| {
+            ui.weak("Right-click the header to change the grouping");
+            files.show(ui);
+        });
+}
+
+fn camera_window(ctx: &egui::Context, app: &mut AppState) {
+    egui::Window::new("Camera")
+        .open(&mut app.show_camera)
+        .default_width(320.0)
+        .show(ctx, |ui| app.camera.ui(ui));
+}
+
+fn block_diagram_window(ctx: &egui::Context, app: &mut AppState) {
+    egui::Window::new("Block diagram")
+        .open(&mut app.show_block_diagram)
+        .default_size([720.0, 420.0])
+        .show(ctx, |ui| {
+            ui.weak("Right-click the canvas to add a block, drag from an output to an input to wire them");
+            app.block_diagram.show(ui);
+        });
+}
+
+fn crash_report_window(ctx: &egui::Context, app: &mut AppState) {
+    let Some(report) = &app.crash_report else {
+        return;
+    };
+    let mut close = false;
+    egui::Window::new("The example crashed last time")
+        .collapsible(false)
+        .show(ctx, |ui| {
+            ui.label("A crash report was saved. Nothing has been sent anywhere.");
+            ui.monospace(report.path.display().to_string());
+            ui.horizontal(|ui| {
+                if ui.button("Open folder").clicked() {
+                    if let Err(e) = platform::open_folder(report.folder()) {
+                        tracing::warn!("Failed to open {}: {}", report.folder().display(), e);
+                    }
+                    close = true;
+                }
+                if ui.button("Copy to clipboard").clicked() {
+                    ctx.output_mut(|o| o.copied_text = report.text.clone());
+                    close = true;
+                }
+                close |= ui.button("Dismiss").clicked();
+            });
+        });
+    if close {
+        report.mark_seen();
+        app.crash_report = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::ShortcutRegistry;
+    use crate::config::AppConfig;
+    use crate::gamepad::{GamepadButton, GamepadEvent};
+    use crate::split_editor::SplitEditor;
+    use crate::testing::{find_text, key_event as key, UiDriver};
+    use crate::title_bar::WindowAction;
+    use crate::Application;
+
+    use egui::KeyboardShortcut;
+
+    fn focused_editor() -> UiDriver<AppState> {
+        let mut app = AppState::default();
+        app.split_editor.open = true;
+        let mut driver = UiDriver::new(app);
+        driver.settle();
+        driver
+            .ctx
+            .memory_mut(|m| m.request_focus(SplitEditor::source_id()));
+        driver.type_text("§§");
+        assert!(driver.app.split_editor.source().contains("§§"));
+        driver
+    }
+
+    /// The inspector's GPU tab, open on a made up adapter.
+    fn gpu_tab(mut app: AppState) -> UiDriver<AppState> {
+        app.show_demo = false;
+        app.inspector.open = true;
+        app.current_adapter = Some(wgpu::AdapterInfo {
+            name: "Test adapter".into(),
+            vendor: 0,
+            device: 0,
+            device_type: wgpu::DeviceType::Cpu,
+            driver: String::new(),
+            driver_info: String::new(),
+            backend: wgpu::Backend::Empty,
+        });
+        let mut driver = UiDriver::new(app);
+        driver.settle();
+        assert!(driver.click_text("GPU"));
+        driver
+    }
+
+    #[test]
+    fn vsync_checkbox_toggles_the_setting() {
+        let mut app = AppState::default();
+        app.show_settings = true;
+        assert!(app.settings.vsync);
+
+        let mut driver = UiDriver::new(app);
+        assert!(
+            driver.click_text("VSync"),
+            "the settings window shows VSync"
+        );
+        assert!(!driver.app.settings.vsync);
+
+        let output = driver.settle();
+        assert!(find_text(&output, "VSync off").is_some());
+    }
+
+    #[test]
+    fn following_the_system_theme_asks_to_save_it() {
+        let mut app = AppState::default();
+        app.show_settings = true;
+
+        let mut driver = UiDriver::new(app);
+        assert!(driver.click_text("Follow system"));
+        assert_eq!(driver.app.requested_theme, Some(Theme::System));
+
+        let light = Some(winit::window::Theme::Light);
+        assert_eq!(Theme::System.resolve(light), Theme::Light);
+        assert_eq!(Theme::System.resolve(None), Theme::Dark);
+        assert_eq!(Theme::Dark.resolve(light), Theme::Dark);
+    }
+
+    #[test]
+    fn inspector_detaches_into_its_own_window() {
+        let mut app = AppState::default();
+        app.inspector.open = true;
+        let mut driver = UiDriver::new(app);
+        assert!(driver.app.windows().is_empty());
+        assert!(driver.click_text("Detach"));
+        assert_eq!(driver.app.windows()[0].id, inspector::WINDOW_ID);
+
+        let output = driver.settle();
+        assert!(find_text(&output, "Attach").is_none());
+        driver.app.window_closed(inspector::WINDOW_ID);
+        assert!(driver.app.windows().is_empty());
+    }
+
+    #[test]
+    fn command_palette_filters_by_query() {
+        assert_eq!(commands::filter("split"), [Command::ToggleSplitEditor]);
+        assert_eq!(commands::filter("").len(), Command::ALL.len());
+        assert!(commands::filter("no such command").is_empty());
+
+        let mut driver = UiDriver::new(AppState::default());
+        driver.settle();
+        driver.press(Key::P, Modifiers::COMMAND.plus(Modifiers::SHIFT));
+        assert!(driver.app.command_palette.open);
+
+        driver.type_text("dump");
+        let output = driver.settle();
+        assert_eq!(driver.app.command_palette.query, "dump");
+        assert!(find_text(&output, "Dump frame data").is_some());
+        assert!(find_text(&output, "New palette").is_none());
+    }
+
+    #[test]
+    fn command_palette_runs_the_selection_on_enter() {
+        let mut app = AppState::default();
+        app.command_palette.show();
+        let mut driver = UiDriver::new(app);
+        driver.settle();
+
+        driver.type_text("frame");
+        driver.run(vec![
+            key(Key::ArrowDown, Modifiers::NONE),
+            key(Key::Enter, Modifiers::NONE),
+        ]);
+        // "frame" matches the statistics, the frame data and the dump, in this order.
+        assert!(!driver.app.command_palette.open);
+        assert!(driver.app.frame_dump.open);
+        assert!(!driver.app.stats.open);
+    }
+
+    #[test]
+    fn undo_shortcut_is_left_alone_without_document_focus() {
+        let ctx = egui::Context::default();
+        let registry = ShortcutRegistry::default();
+        ctx.begin_frame(egui::RawInput {
+            events: vec![key(Key::Z, Modifiers::COMMAND)],
+            ..Default::default()
+        });
+        assert!(registry.dispatch(&ctx, false).is_empty());
+        assert!(ctx.input(|i| i.key_pressed(Key::Z)));
+        let _ = ctx.end_frame();
+
+        let mut driver = focused_editor();
+        driver
+            .ctx
+            .memory_mut(|m| m.surrender_focus(SplitEditor::source_id()));
+        driver.press(Key::Z, Modifiers::COMMAND);
+        assert!(driver.app.split_editor.source().contains("§§"));
+    }
+
+    #[test]
+    fn undo_shortcut_is_consumed_when_document_focused() {
+        let ctx = egui::Context::default();
+        let registry = ShortcutRegistry::default();
+        ctx.begin_frame(egui::RawInput {
+            events: vec![key(Key::Z, Modifiers::COMMAND)],
+            ..Default::default()
+        });
+        assert_eq!(registry.dispatch(&ctx, true), [Command::Undo]);
+        assert!(!ctx.input(|i| i.key_pressed(Key::Z)));
+        let _ = ctx.end_frame();
+
+        let mut driver = focused_editor();
+        assert!(driver.app.document_focused(&driver.ctx));
+        driver.press(Key::Z, Modifiers::COMMAND);
+        assert!(!driver.app.split_editor.source().contains("§§"));
+    }
+
+    #[test]
+    fn fullscreen_shortcuts_toggle_their_mode() {
+        let mut driver = UiDriver::new(AppState::default());
+        driver.press(Key::F11, Modifiers::NONE);
+        assert_eq!(
+            driver.app.requested_fullscreen,
+            Some(FullscreenMode::Borderless)
+        );
+
+        driver.app.fullscreen = FullscreenMode::Exclusive;
+        driver.press(Key::Enter, Modifiers::ALT);
+        assert_eq!(
+            driver.app.requested_fullscreen,
+            Some(FullscreenMode::Windowed)
+        );
+    }
+
+    #[test]
+    fn rebinding_a_shortcut_takes_the_next_press_and_saves_it() {
+        let mut app = AppState::default();
+        app.show_settings = true;
+        let mut driver = UiDriver::new(app).with_screen_size(1280.0, 2000.0);
+        assert!(driver.click_text("Keyboard shortcuts"));
+        // The first row's, New palette.
+        assert!(driver.click_text("Rebind"));
+        driver.press(Key::F3, Modifiers::NONE);
+        assert!(!driver.app.fps_overlay.open, "F3 is bound, not run");
+
+        let f3 = KeyboardShortcut::new(Modifiers::NONE, Key::F3);
+        let shortcuts = &driver.app.shortcuts;
+        assert!(shortcuts.shortcuts_of(Command::NewPalette).eq([&f3]));
+        assert_eq!(shortcuts.shortcuts_of(Command::ToggleFpsOverlay).count(), 0);
+        assert!(driver.app.save_shortcuts);
+
+        let overrides = shortcuts.overrides();
+        assert_eq!(overrides[&Command::NewPalette], ["F3"]);
+        assert!(overrides[&Command::ToggleFpsOverlay].is_empty());
+        // They read back from the config the same.
+        let config = AppConfig {
+            shortcuts: overrides.clone(),
+            ..Default::default()
+        };
+        let text = toml::to_string(&config).unwrap();
+        let mut registry = ShortcutRegistry::default();
+        registry.apply(&AppConfig::parse(&text).unwrap().shortcuts);
+        assert_eq!(registry.overrides(), overrides);
+    }
+
+    #[test]
+    fn gamepad_buttons_are_tracked_and_the_d_pad_tabs() {
+        let mut app = AppState::default();
+        app.gamepads.on_event(&GamepadEvent::Connected {
+            id: 3,
+            name: "Pad".to_owned(),
+        });
+        let press = GamepadEvent::Button {
+            id: 3,
+            button: GamepadButton::DPadUp,
+            pressed: true,
+        };
+        app.gamepads.on_event(&press);
+        assert!(app.gamepads.pads()[0]
+            .pressed
+            .contains(&GamepadButton::DPadUp));
+        assert_eq!(press.to_egui(), Some(key(Key::Tab, Modifiers::SHIFT)));
+
+        app.gamepads.on_event(&GamepadEvent::Disconnected(3));
+        assert!(app.gamepads.pads().is_empty());
+    }
+
+    #[test]
+    fn pinching_the_touch_canvas_zooms_it_and_not_the_ui() {
+        let mut app = AppState::default();
+        app.touch_canvas.open = true;
+        let mut driver = UiDriver::new(app);
+        let hint = find_text(&driver.settle(), "Pinch to zoom, drag to pan").unwrap();
+        let center = egui::pos2(hint.x, hint.y + 100.0);
+        let touch = |id, phase, dx: f32| egui::Event::Touch {
+            device_id: egui::TouchDeviceId(0),
+            id: egui::TouchId(id),
+            phase,
+            pos: center + egui::vec2(dx, 0.0),
+            force: None,
+        };
+        // A pinch starts where egui's pointer was, which egui-winit moves with a finger.
+        driver.run(vec![egui::Event::PointerMoved(center)]);
+        driver.run(vec![
+            touch(0, egui::TouchPhase::Start, -20.0),
+            touch(1, egui::TouchPhase::Start, 20.0),
+        ]);
+        driver.run(vec![
+            touch(0, egui::TouchPhase::Move, -40.0),
+            touch(1, egui::TouchPhase::Move, 40.0),
+        ]);
+        assert!(driver.app.touch_canvas.zoom > 1.5);
+        assert_eq!(driver.app.settings.zoom, 1.0);
+    }
+
+    #[test]
+    fn zoom_shortcuts_and_ctrl_scroll_scale_the_ui() {
+        let mut driver = UiDriver::new(AppState::default());
+        driver.press(Key::PlusEquals, Modifiers::COMMAND);
+        assert!((driver.app.settings.zoom - 1.1).abs() < 1e-6);
+
+        driver.press(Key::Num0, Modifiers::COMMAND);
+        assert_eq!(driver.app.settings.zoom, 1.0);
+
+        // Far beyond the range, as a long scroll would be.
+        driver.run(vec![egui::Event::Zoom(100.0)]);
+        assert_eq!(driver.app.settings.zoom, 3.0);
+    }
+
+    #[test]
+    fn title_bar_close_button_asks_to_close() {
+        let mut app = AppState::default();
+        app.decorated = false;
+        app.title = "Example".to_owned();
+        let mut driver = UiDriver::new(app);
+        assert!(driver.click_text("❌"));
+        assert_eq!(driver.app.window_action, Some(WindowAction::Close));
+    }
+
+    #[test]
+    fn dragging_a_region_asks_for_a_screenshot_to_copy() {
+        let mut driver = UiDriver::new(AppState::default());
+        driver.app.run(Command::CopyRegion);
+        driver.settle();
+        let button = |pos, pressed| egui::Event::PointerButton {
+            pos,
+            button: egui::PointerButton::Primary,
+            pressed,
+            modifiers: Modifiers::NONE,
+        };
+        let (from, to) = (egui::pos2(100.0, 100.0), egui::pos2(300.0, 200.0));
+        driver.run(vec![egui::Event::PointerMoved(from), button(from, true)]);
+        driver.run(vec![egui::Event::PointerMoved(to)]);
+        driver.run(vec![button(to, false)]);
+
+        assert!(!driver.app.region_select.active);
+        assert!(driver.app.screenshot_requested);
+        assert_eq!(
+            driver.app.copy_region,
+            Some(egui::Rect::from_two_pos(from, to))
+        );
+    }
+
+    #[test]
+    fn loaded_font_is_drawn_with_once_egui_has_it() {
+        let mut driver = UiDriver::new(AppState::default());
+        let hack = egui::FontDefinitions::default().font_data["Hack"]
+            .font
+            .to_vec();
+        driver
+            .app
+            .fonts
+            .load(std::path::Path::new("Hack copy.ttf"), hack);
+        assert_eq!(driver.app.fonts.error, None);
+        driver.settle();
+        let family = egui::FontFamily::Name("Hack copy".into());
+        assert!(driver.ctx.fonts(|fonts| fonts.families()).contains(&family));
+
+        driver
+            .app
+            .fonts
+            .load(std::path::Path::new("broken.ttf"), b"not a font".to_vec());
+        assert!(driver.app.fonts.error.is_some());
+        assert_eq!(driver.app.fonts.fonts.names().count(), 1);
+    }
+
+    #[test]
+    fn dropped_png_is_listed_with_a_preview() {
+        let mut driver = UiDriver::new(AppState::default());
+        driver.run_input(egui::RawInput {
+            dropped_files: vec![egui::DroppedFile {
+                name: "icon.png".to_owned(),
+                bytes: Some(crate::icon::EXAMPLE_ICON.into()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        });
+        let output = driver.settle();
+        assert!(driver.app.dropped_files.open);
+        assert!(find_text(&output, "icon.png").is_some());
+        assert!(find_text(&output, "64×64").is_some(), "the PNG was decoded");
+    }
+
+    #[test]
+    fn new_palette_command_opens_a_palette_window() {
+        let mut driver = UiDriver::new(AppState::default());
+        driver.app.run(Command::NewPalette);
+        let output = driver.settle();
+        assert_eq!(driver.app.palettes.len(), 1);
+        assert!(find_text(&output, "Palette 1").is_some());
+    }
+
+    #[test]
+    fn clicking_the_scene_locks_the_pointer_but_not_through_the_ui() {
+        let mut app = AppState::default();
+        app.show_demo = false;
+        let mut driver = UiDriver::new(app);
+        let output = driver.settle();
+        driver.click(find_text(&output, "File").unwrap());
+        driver.press(egui::Key::Escape, egui::Modifiers::NONE);
+        assert_eq!(driver.app.pointer_lock.pending(), None);
+
+        driver.click(egui::pos2(400.0, 300.0));
+        assert_eq!(driver.app.pointer_lock.pending(), Some(true));
+    }
+
+    #[test]
+    fn scrolling_the_scene_zooms_the_camera_and_flying_starts_where_it_was() {
+        let mut app = AppState::default();
+        app.show_demo = false;
+        app.show_camera = true;
+        let mut driver = UiDriver::new(app);
+        let output = driver.settle();
+        let distance = driver.app.camera.distance;
+        let scroll = |pos| {
+            vec![
+                egui::Event::PointerMoved(pos),
+                egui::Event::Scroll(egui::vec2(0.0, 100.0)),
+            ]
+        };
+        driver.run(scroll(find_text(&output, "Field of view (°)").unwrap()));
+        assert_eq!(driver.app.camera.distance, distance);
+        driver.run(scroll(egui::pos2(700.0, 500.0)));
+        assert!(driver.app.camera.distance < distance);
+
+        let eye = driver.app.camera.eye();
+        let output = driver.settle();
+        driver.click(find_text(&output, "Fly").unwrap());
+        assert_eq!(driver.app.camera.mode, crate::CameraMode::Fly);
+        assert!(driver.app.camera.eye().abs_diff_eq(eye, 1e-5));
+    }
+
+    #[test]
+    fn hiding_a_model_node_hides_its_children() {
+        let node = |name: &str, children| crate::ModelNode {
+            name: name.to_owned(),
+            transform: glam::Mat4::IDENTITY,
+            children,
+            mesh: None,
+            visible: true,
+        };
+        let mut app = AppState::default();
+        app.show_demo = false;
+        app.model.open = true;
+        app.model.data = Some(crate::ModelData {
+            name: "robot.glb".to_owned(),
+            nodes: vec![node("Body", vec![1]), node("Arm", vec![])],
+            roots: vec![0],
+            meshes: Vec::new(),
+            materials: Vec::new(),
+            images: Vec::new(),
+        });
+        let mut driver = UiDriver::new(app);
+        let output = driver.settle();
+        assert!(find_text(&output, "Arm").is_some());
+        driver.click(find_text(&output, "Body").unwrap());
+
+        let world = driver.app.model.data.as_ref().unwrap().world();
+        assert!(!world[0].unwrap().1);
+        assert!(!world[1].unwrap().1);
+    }
+
+    #[test]
+    fn loaded_images_are_listed_and_can_go_on_the_cube() {
+        let mut app = AppState::default();
+        app.show_demo = false;
+        let icon = include_bytes!("../assets/icon.png");
+        app.textures.open = true;
+        let textures = &mut app.textures.textures;
+        textures
+            .load(std::path::Path::new("icon.png"), icon)
+            .unwrap();
+        let mut driver = UiDriver::new(app);
+        let output = driver.settle();
+        let textures = &driver.app.textures.textures;
+        let [width, height] = textures.size(textures.handles().next().unwrap()).unwrap();
+        assert!(find_text(&output, &format!("{}×{}", width, height)).is_some());
+        driver.click(find_text(&output, "On the cube").unwrap());
+        assert!(driver.app.textures.on_cube.is_some());
+
+        let textures = &mut driver.app.textures.textures;
+        let error = textures.load(std::path::Path::new("notes.txt"), b"Not an image");
+        assert_eq!(
+            error.unwrap_err(),
+            "Failed to load notes.txt: Not a PNG or JPEG file"
+        );
+        assert_eq!(textures.handles().count(), 1);
+    }
+
+    #[test]
+    fn assets_show_while_loading_and_keep_shader_sources() {
+        let mut app = AppState::default();
+        app.show_demo = false;
+        app.assets.open = true;
+        // Nothing delivers what the jobs load without an event loop, so it's finished
+        // by hand.
+        let jobs = &mut app.jobs;
+        let shader = app.assets.load(jobs, "missing/blur.wgsl".into()).unwrap();
+        let model = app.assets.load(jobs, "missing/robot.glb".into()).unwrap();
+        assert!(app.assets.load(jobs, "notes.txt".into()).is_err());
+        let mut driver = UiDriver::new(app);
+        let output = driver.settle();
+        assert!(find_text(&output, "blur.wgsl").is_some());
+        assert!(find_text(&output, "Loading 2 assets").is_some());
+
+        let source = "@vertex\nfn vs_main() {}".to_owned();
+        let assets = &mut driver.app.assets;
+        assert!(assets
+            .finish(shader, Ok(crate::AssetData::Shader(source)))
+            .is_none());
+        assets.finish(model, Err("Failed to read robot.glb".to_owned()));
+        assert_eq!(assets.source(shader), Some("@vertex\nfn vs_main() {}"));
+        let output = driver.settle();
+        assert!(find_text(&output, "Loaded, 2 lines").is_some());
+        assert!(find_text(&output, "Failed to read robot.glb").is_some());
+        assert!(find_text(&output, "Loading 2 assets").is_none());
+    }
+
+    #[test]
+    fn background_tasks_show_their_progress_and_can_be_cancelled() {
+        let mut app = AppState::default();
+        app.show_demo = false;
+        app.jobs.open = true;
+        let (sender, receiver) = std::sync::mpsc::channel::<()>();
+        let id = app.jobs.spawn("Waiting", move |context| {
+            context.set_progress(0.5);
+            let _ = receiver.recv();
+            7_u32
+        });
+        while app.jobs.progress(id) != Some(0.5) {
+            std::thread::yield_now();
+        }
+        let mut driver = UiDriver::new(app);
+        let output = driver.settle();
+        assert!(find_text(&output, "Waiting").is_some());
+        assert!(find_text(&output, "50%").is_some());
+
+        // Nothing delivers what the job returned without an event loop.
+        sender.send(()).unwrap();
+        while driver.app.jobs.is_running(id) {
+            std::thread::yield_now();
+        }
+        assert!(!driver.app.jobs.finish(id, &7_u32));
+        let output = driver.settle();
+        assert!(find_text(&output, "Nothing is running").is_some());
+
+        assert!(driver.click_text("Count primes"));
+        let output = driver.settle();
+        assert!(find_text(&output, "Counting primes").is_some());
+        assert!(driver.click_text("Cancel"));
+    }
+
+    #[test]
+    fn performance_plots_keep_what_they_had_while_paused() {
+        let mut app = AppState::default();
+        app.show_demo = false;
+        app.perf_plots.open = true;
+        let record = |frame: u64| crate::stats::FrameRecord {
+            frame,
+            timestamp: frame as f64 * 0.5,
+            stage_ms: [1.0; crate::Stage::ALL.len()],
+            gpu_ms: Some(2.0),
+            vertices: 0,
+            texture_upload_bytes: 0,
+            present_mode: wgpu::PresentMode::Fifo,
+        };
+        for frame in 0..5 {
+            app.perf_plots.record(&record(frame));
+        }
+        let mut driver = UiDriver::new(app);
+        let output = driver.settle();
+        assert!(find_text(&output, "5 frames over 2.0 s").is_some());
+
+        assert!(driver.click_text("Pause"));
+        for frame in 5..10 {
+            driver.app.perf_plots.record(&record(frame));
+        }
+        let output = driver.settle();
+        assert!(find_text(&output, "5 frames over 2.0 s").is_some());
+        assert!(driver.click_text("Resume"));
+        driver.app.perf_plots.record(&record(10));
+        let output = driver.settle();
+        assert!(find_text(&output, "6 frames over 5.0 s").is_some());
+    }
+
+    #[test]
+    fn the_large_table_sorts_a_million_rows_by_its_headers() {
+        let mut app = AppState::default();
+        app.show_demo = false;
+        app.big_table.open = true;
+        let mut driver = UiDriver::new(app);
+        let output = driver.settle();
+        assert!(find_text(&output, "1000000 rows").is_some());
+        assert!(find_text(&output, "0").is_some());
+        assert!(find_text(&output, "999999").is_none());
+
+        // Sorted by index already, so it's turned around.
+        assert!(driver.click_text("# ⏶"));
+        let output = driver.settle();
+        assert!(find_text(&output, "999999").is_some());
+        assert!(find_text(&output, "# ⏷").is_some());
+
+        assert!(driver.click_text("Name"));
+        let output = driver.settle();
+        assert!(find_text(&output, "Name ⏶").is_some());
+        assert!(find_text(&output, "bababa").is_some());
+    }
+
+    #[test]
+    fn dragging_a_gallery_knob_up_turns_it() {
+        let mut app = AppState::default();
+        app.show_demo = false;
+        app.widget_gallery.open = true;
+        let mut driver = UiDriver::new(app);
+        let output = driver.settle();
+        assert!(find_text(&output, "50%").is_some());
+        // The knob is right above its label.
+        let knob = find_text(&output, "Gain").unwrap() - egui::vec2(0.0, 30.0);
+        let button = |pressed| egui::Event::PointerButton {
+            pos: knob,
+            button: egui::PointerButton::Primary,
+            pressed,
+            modifiers: egui::Modifiers::NONE,
+        };
+        driver.run(vec![egui::Event::PointerMoved(knob), button(true)]);
+        driver.run(vec![egui::Event::PointerMoved(
+            knob - egui::vec2(0.0, 50.0),
+        )]);
+        driver.run(vec![button(false)]);
+        assert!((driver.app.widget_gallery.gain - 0.75).abs() < 1e-3);
+        let output = driver.settle();
+        assert!(find_text(&output, "75%").is_some());
+    }
+
+    #[test]
+    fn strokes_drawn_on_the_canvas_can_be_undone_and_exported() {
+        let mut app = AppState::default();
+        app.show_demo = false;
+        app.drawing.open = true;
+        let mut driver = UiDriver::new(app);
+        let output = driver.settle();
+        let start = find_text(&output, "Draw here").unwrap();
+        let button = |pos, pressed| egui::Event::PointerButton {
+            pos,
+            button: egui::PointerButton::Primary,
+            pressed,
+            modifiers: Modifiers::NONE,
+        };
+        let end = start + egui::vec2(40.0, 20.0);
+        driver.run(vec![egui::Event::PointerMoved(start), button(start, true)]);
+        driver.run(vec![egui::Event::PointerMoved(
+            start + egui::vec2(20.0, 0.0),
+        )]);
+        driver.run(vec![egui::Event::PointerMoved(end)]);
+        driver.run(vec![button(end, false)]);
+        driver.settle();
+        let strokes = &driver.app.drawing.strokes;
+        assert_eq!(strokes.len(), 1);
+        assert_eq!(strokes[0].points.len(), 3);
+
+        driver.press(Key::Z, Modifiers::COMMAND);
+        assert!(driver.app.drawing.strokes.is_empty());
+        assert!(driver.click_text("Redo"));
+        assert_eq!(driver.app.drawing.strokes.len(), 1);
+
+        assert!(driver.click_text("Export PNG"));
+        driver.settle();
+        assert!(driver.app.save_region.is_some());
+        assert!(driver.app.screenshot_requested);
+    }
+
+    #[test]
+    fn docked_panels_switch_tabs_and_keep_their_layout_through_a_save() {
+        use crate::DockTab;
+
+        let mut app = AppState::default();
+        app.show_demo = false;
+        app.dock.open = true;
+        let mut driver = UiDriver::new(app);
+        let output = driver.settle();
+        assert!(find_text(&output, "No frame was drawn yet").is_none());
+        assert!(driver.click_text("Stats"));
+        let output = driver.settle();
+        assert!(find_text(&output, "No frame was drawn yet").is_some());
+
+        let state = &driver.app.dock.state;
+        let text = serde_json::to_string(state).unwrap();
+        let restored: egui_dock::DockState<DockTab> = serde_json::from_str(&text).unwrap();
+        assert_eq!(serde_json::to_string(&restored).unwrap(), text);
+        for tab in [
+            DockTab::Scene,
+            DockTab::Inspector,
+            DockTab::Log,
+            DockTab::Stats,
+        ] {
+            assert_eq!(restored.find_tab(&tab), state.find_tab(&tab));
+        }
+    }
+
+    #[test]
+    fn a_shader_edit_that_does_not_compile_is_shown_and_the_last_good_one_kept() {
+        let good = include_str!("shaders/scene.wgsl");
+        let bad = good.replace("fn vs_main", "fn vs_main oops");
+        let error = crate::shader_reload::check("scene.wgsl", &bad).unwrap_err();
+        assert!(error.contains("scene.wgsl"), "{}", error);
+
+        let mut app = AppState::default();
+        app.show_demo = false;
+        app.shaders.on_change("scene.wgsl".into(), good.to_owned());
+        assert!(!app.shaders.open);
+        app.shaders.on_change("scene.wgsl".into(), bad);
+        assert!(app.shaders.open);
+        assert_eq!(app.shaders.source("scene.wgsl"), Some(good));
+        assert_eq!(app.shaders.take_changed(), ["scene.wgsl"]);
+        assert!(app.shaders.take_changed().is_empty());
+
+        let mut driver = UiDriver::new(app);
+        let output = driver.settle();
+        assert!(find_text(&output, "Compiled 1 times").is_some());
+        assert!(find_text(&output, &error).is_some());
+    }
+
+    #[test]
+    fn the_inspector_counts_the_bind_group_and_sampler_cache() {
+        let mut driver = gpu_tab(AppState::default());
+        let output = driver.settle();
+        assert!(find_text(&output, "Test adapter").is_some());
+        assert!(find_text(&output, "Bind groups").is_some());
+        assert!(find_text(&output, "Samplers").is_some());
+        assert!(find_text(&output, "0 hits, 0 misses, 0 kept").is_some());
+    }
+
+    #[test]
+    fn the_inspector_reports_which_optional_features_the_device_has() {
+        let capabilities = crate::Capabilities {
+            features: wgpu::Features::TIMESTAMP_QUERY,
+            limits: wgpu::Limits::downlevel_webgl2_defaults(),
+            downlevel: wgpu::DownlevelCapabilities::default(),
+        };
+        assert!(capabilities.timestamps());
+        assert!(!capabilities.wireframe());
+        assert!(capabilities.supports_format(wgpu::TextureFormat::Rgba8Unorm));
+        assert!(!capabilities.supports_format(wgpu::TextureFormat::Bc1RgbaUnorm));
+        let report: Vec<_> = capabilities.report().collect();
+        assert_eq!(report.len(), 5);
+        assert_eq!(report[0], ("Timestamp queries", true));
+        assert!(report[1..].iter().all(|&(_, available)| !available));
+
+        let mut app = AppState::default();
+        app.capabilities = Some(capabilities);
+        let mut driver = gpu_tab(app);
+        let output = driver.settle();
+        assert!(find_text(&output, "Line polygons").is_some());
+        assert!(find_text(&output, "2048").is_some());
+    }
+
+    #[test]
+    fn compat_limits_leave_out_the_compute_particles() {
+        let capabilities = crate::Capabilities {
+            features: wgpu::Features::empty(),
+            limits: wgpu::Limits::downlevel_webgl2_defaults(),
+            downlevel: wgpu::DownlevelCapabilities::default(),
+        };
+        assert!(!capabilities.compute_shaders());
+
+        let mut app = AppState::default();
+        assert!(app.can_compute());
+        app.capabilities = Some(capabilities);
+        assert!(!app.can_compute());
+        let mut driver = gpu_tab(app);
+        let output = driver.settle();
+        assert!(find_text(&output, "Compute shaders").is_some());
+    }
+
+    #[test]
+    fn an_idle_ui_with_a_still_cube_schedules_no_repaint() {
+        let mut app = AppState::default();
+        app.show_demo = false;
+        assert!(app.show_scene && !app.spin_cube);
+        let mut driver = UiDriver::new(app);
+        driver.settle();
+        let output = driver.settle();
+        assert!(output.repaint_after > std::time::Duration::ZERO);
+
+        driver.app.spin_cube = true;
+        let output = driver.settle();
+        assert_eq!(output.repaint_after, std::time::Duration::ZERO);
+    }
+}
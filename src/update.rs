@@ -0,0 +1,96 @@
+use crate::config::UpdateCheckConfig;
+use crate::events::UserEvent;
+
+use serde::Deserialize;
+use winit::event_loop::EventLoopProxy;
+
+#[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+const TIMESTAMP_FILE: &str = "last_update_check";
+
+/// The JSON document served at the configured update URL.
+#[derive(Clone, Debug, Deserialize)]
+pub struct UpdateInfo {
+    pub version: semver::Version,
+    pub release_notes: String,
+}
+
+/// Checks for a newer release on a background thread, if enabled and not done recently.
+///
+/// A newer version is delivered as `UserEvent::UpdateAvailable`, everything else
+/// (disabled, too soon, network errors, up to date) only ends up in the log.
+#[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+pub fn spawn_check(config: &UpdateCheckConfig, proxy: EventLoopProxy<UserEvent>) {
+    if !config.enabled {
+        return;
+    }
+    if config.url.is_empty() {
+        tracing::info!("Update check is enabled but no url is configured");
+        return;
+    }
+
+    let interval = Duration::from_secs(config.interval_hours.saturating_mul(60 * 60));
+    let now = SystemTime::now();
+    if let Some(last) = last_check() {
+        if now
+            .duration_since(last)
+            .is_ok_and(|elapsed| elapsed < interval)
+        {
+            return;
+        }
+    }
+
+    let url = config.url.clone();
+    let spawned = crate::runtime::spawn_blocking("update check", proxy, move || {
+        match fetch(&url) {
+            Ok(info) => {
+                save_last_check(now);
+                let current = semver::Version::parse(env!("CARGO_PKG_VERSION")).unwrap();
+                if info.version > current {
+                    tracing::info!("Version {} is available", info.version);
+                    return Some(UserEvent::UpdateAvailable(info));
+                }
+            }
+            Err(e) => tracing::info!("Update check failed: {}", e),
+        }
+        None
+    });
+    if let Err(e) = spawned {
+        tracing::warn!("Failed to start the update check: {}", e);
+    }
+}
+
+/// A page is updated by reloading it, and an Android app by its store.
+#[cfg(any(target_arch = "wasm32", target_os = "android"))]
+pub fn spawn_check(_config: &UpdateCheckConfig, _proxy: EventLoopProxy<UserEvent>) {}
+
+#[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+fn fetch(url: &str) -> Result<UpdateInfo, Box<dyn std::error::Error>> {
+    let response = ureq::get(url).timeout(Duration::from_secs(10)).call()?;
+    Ok(response.into_json()?)
+}
+
+#[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+fn last_check() -> Option<SystemTime> {
+    let path = crate::config::config_dir()?.join(TIMESTAMP_FILE);
+    let secs = std::fs::read_to_string(path).ok()?.trim().parse().ok()?;
+    Some(UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+#[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+fn save_last_check(time: SystemTime) {
+    let Some(dir) = crate::config::config_dir() else {
+        return;
+    };
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    if let Err(e) = std::fs::create_dir_all(&dir)
+        .and_then(|()| std::fs::write(dir.join(TIMESTAMP_FILE), secs.to_string()))
+    {
+        tracing::info!("Failed to save the update check time: {}", e);
+    }
+}
@@ -0,0 +1,280 @@
+use crate::camera_path::CameraPath;
+use crate::debug_draw::{self, DebugDraw};
+use crate::gizmo::Gizmo;
+
+use egui::{Color32, Key};
+use glam::{EulerRot, Mat4, Quat, Vec3};
+
+use std::path::Path;
+
+/// Units per second the debug camera flies at.
+const FLY_SPEED: f32 = 3.0;
+/// Radians per point the camera turns when dragged.
+const LOOK_SPEED: f32 = 0.005;
+
+/// A free flying camera, for looking around the scene.
+pub struct DebugCamera {
+    pub position: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub fov_y: f32,
+}
+
+impl Default for DebugCamera {
+    fn default() -> Self {
+        Self {
+            position: Vec3::new(0.0, 2.0, 8.0),
+            yaw: 0.0,
+            pitch: -0.2,
+            fov_y: 60f32.to_radians(),
+        }
+    }
+}
+
+impl DebugCamera {
+    pub fn rotation(&self) -> Quat {
+        Quat::from_euler(EulerRot::YXZ, self.yaw, self.pitch, 0.0)
+    }
+
+    pub fn set_rotation(&mut self, rotation: Quat) {
+        let (yaw, pitch, _) = rotation.to_euler(EulerRot::YXZ);
+        self.yaw = yaw;
+        self.pitch = pitch;
+    }
+
+    pub fn view_proj(&self, aspect: f32) -> Mat4 {
+        let view = Mat4::from_rotation_translation(self.rotation(), self.position).inverse();
+        Mat4::perspective_rh(self.fov_y, aspect, 0.1, 200.0) * view
+    }
+}
+
+pub struct SceneObject {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+    pub color: Color32,
+}
+
+impl SceneObject {
+    pub fn transform(&self) -> Mat4 {
+        Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation)
+    }
+}
+
+/// A window looking at a wireframe scene through the debug camera, with a camera path
+/// recorder.
+pub struct Viewport3d {
+    pub open: bool,
+    pub camera: DebugCamera,
+    pub objects: Vec<SceneObject>,
+    selected: Option<usize>,
+    gizmo: Gizmo,
+    camera_path: CameraPath,
+    debug_draw: DebugDraw,
+    export_path: String,
+    status: Option<String>,
+}
+
+impl Default for Viewport3d {
+    fn default() -> Self {
+        let object = |translation, color| SceneObject {
+            translation,
+            rotation: Quat::IDENTITY,
+            scale: Vec3::ONE,
+            color,
+        };
+        Self {
+            open: false,
+            camera: DebugCamera::default(),
+            objects: vec![
+                object(Vec3::new(-2.0, 0.5, 0.0), Color32::RED),
+                object(Vec3::new(0.0, 0.5, -2.0), Color32::GREEN),
+                object(Vec3::new(2.0, 0.5, 0.0), Color32::LIGHT_BLUE),
+            ],
+            selected: None,
+            gizmo: Gizmo::default(),
+            camera_path: CameraPath::default(),
+            debug_draw: DebugDraw::default(),
+            export_path: "camera_path.json".to_owned(),
+            status: None,
+        }
+    }
+}
+
+impl Viewport3d {
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        let mut open = self.open;
+        egui::Window::new("3D viewport")
+            .open(&mut open)
+            .default_size([640.0, 480.0])
+            .show(ctx, |ui| {
+                self.camera_path_ui(ui);
+                ui.weak(
+                    "Click an object to select it. Hold the right button to look around, \
+                     and fly with WASD, Q and E meanwhile",
+                );
+                ui.weak("G, R and S switch the gizmo's mode, 1, 2 and 3 lock it to an axis");
+                self.viewport(ui);
+            });
+        self.open = open;
+    }
+
+    fn camera_path_ui(&mut self, ui: &mut egui::Ui) {
+        let now = ui.input(|i| i.time);
+        let path = &mut self.camera_path;
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(!path.is_recording(), egui::Button::new("⏺ Record"))
+                .clicked()
+            {
+                path.record(now);
+            }
+            let busy = path.is_recording() || path.is_playing();
+            if ui.add_enabled(busy, egui::Button::new("⏹ Stop")).clicked() {
+                path.stop();
+            }
+            let can_play = !busy && path.keyframes().len() >= 2;
+            if ui
+                .add_enabled(can_play, egui::Button::new("▶ Play"))
+                .clicked()
+            {
+                path.play();
+            }
+            ui.add(egui::Slider::new(&mut path.speed, 0.1..=4.0).text("Speed"));
+            ui.label(format!("{} keyframes", path.keyframes().len()));
+        });
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.export_path);
+            let export = ui.add_enabled(
+                !self.camera_path.keyframes().is_empty(),
+                egui::Button::new("Export JSON"),
+            );
+            if export.clicked() {
+                let path = Path::new(&self.export_path);
+                self.status = Some(match self.camera_path.write_json(path) {
+                    Ok(()) => format!("Saved {}", path.display()),
+                    Err(e) => format!("Export failed: {}", e),
+                });
+            }
+        });
+        if let Some(status) = &self.status {
+            ui.weak(status);
+        }
+    }
+
+    fn viewport(&mut self, ui: &mut egui::Ui) {
+        let size = ui.available_size().max(egui::vec2(200.0, 150.0));
+        let (response, painter) = ui.allocate_painter(size, egui::Sense::click_and_drag());
+        let rect = response.rect;
+        painter.rect_filled(rect, 0.0, Color32::from_gray(20));
+
+        let (dt, now) = ui.input(|i| (i.stable_dt, i.time));
+        if let Some((position, rotation)) = self.camera_path.advance(dt as f64) {
+            self.camera.position = position;
+            self.camera.set_rotation(rotation);
+            ui.ctx().request_repaint();
+        } else {
+            self.fly(ui, &response, dt);
+        }
+        if self.camera_path.is_recording() {
+            self.camera_path
+                .capture(now, self.camera.position, self.camera.rotation());
+            ui.ctx().request_repaint();
+        }
+
+        let draw = &mut self.debug_draw;
+        draw.grid(10, Color32::from_gray(60));
+        draw.line(Vec3::ZERO, Vec3::X, Color32::RED);
+        draw.line(Vec3::ZERO, Vec3::Y, Color32::GREEN);
+        draw.line(Vec3::ZERO, Vec3::Z, Color32::BLUE);
+        for (i, object) in self.objects.iter().enumerate() {
+            let color = if self.selected == Some(i) {
+                Color32::WHITE
+            } else {
+                object.color
+            };
+            draw.cube(object.transform(), color);
+        }
+        let spline = self.camera_path.spline();
+        draw.polyline(&spline, Color32::YELLOW, Color32::from_rgb(255, 0, 255));
+        for keyframe in self.camera_path.keyframes() {
+            draw.cross(keyframe.position, 0.1, Color32::WHITE);
+        }
+        let view_proj = self.camera.view_proj(rect.aspect_ratio());
+        draw.paint(&painter, view_proj, rect);
+
+        let flying = response.dragged_by(egui::PointerButton::Secondary);
+        if response.hovered() && !flying && !ui.ctx().wants_keyboard_input() {
+            self.gizmo.keys(ui.ctx());
+        }
+        let gizmo_has_pointer = match self.selected.and_then(|i| self.objects.get_mut(i)) {
+            Some(object) => self
+                .gizmo
+                .show(ui, &response, view_proj, self.camera.position, object),
+            None => false,
+        };
+        if response.clicked() && !gizmo_has_pointer {
+            self.selected = response
+                .interact_pointer_pos()
+                .and_then(|pos| self.pick(view_proj, rect, pos));
+        }
+    }
+
+    /// The nearest object under the pointer.
+    fn pick(&self, view_proj: Mat4, rect: egui::Rect, pos: egui::Pos2) -> Option<usize> {
+        let (origin, dir) = debug_draw::unproject(view_proj, rect, pos);
+        self.objects
+            .iter()
+            .enumerate()
+            .filter_map(|(i, object)| {
+                // Into the object's space, where it's the unit cube.
+                let inverse = object.transform().inverse();
+                let local_origin = inverse.transform_point3(origin);
+                let local_dir = inverse.transform_vector3(dir);
+                let t0 = (Vec3::splat(-0.5) - local_origin) / local_dir;
+                let t1 = (Vec3::splat(0.5) - local_origin) / local_dir;
+                let near = t0.min(t1).max_element();
+                let far = t0.max(t1).min_element();
+                (near <= far && far >= 0.0).then_some((i, near))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    }
+
+    fn fly(&mut self, ui: &egui::Ui, response: &egui::Response, dt: f32) {
+        let camera = &mut self.camera;
+        if response.dragged_by(egui::PointerButton::Secondary) {
+            let delta = response.drag_delta();
+            camera.yaw -= delta.x * LOOK_SPEED;
+            camera.pitch = (camera.pitch - delta.y * LOOK_SPEED).clamp(-1.5, 1.5);
+        }
+        // Only while looking around, so the keys are left for the gizmo otherwise.
+        if !response.dragged_by(egui::PointerButton::Secondary) || ui.ctx().wants_keyboard_input() {
+            return;
+        }
+        let rotation = camera.rotation();
+        let mut direction = Vec3::ZERO;
+        ui.input(|i| {
+            for (key, dir) in [
+                (Key::W, Vec3::NEG_Z),
+                (Key::S, Vec3::Z),
+                (Key::A, Vec3::NEG_X),
+                (Key::D, Vec3::X),
+            ] {
+                if i.key_down(key) {
+                    direction += rotation * dir;
+                }
+            }
+            if i.key_down(Key::E) {
+                direction += Vec3::Y;
+            }
+            if i.key_down(Key::Q) {
+                direction -= Vec3::Y;
+            }
+        });
+        if direction != Vec3::ZERO {
+            camera.position += direction.normalize() * FLY_SPEED * dt;
+            ui.ctx().request_repaint();
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+/// The label set by the build script, if this build shows a watermark at all.
+pub const WATERMARK: Option<&str> = option_env!("WATERMARK");
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatermarkConfig {
+    pub corner: Corner,
+    pub font_size: f32,
+    /// From 0 (invisible) to 1 (opaque white).
+    pub opacity: f32,
+}
+
+impl Default for WatermarkConfig {
+    fn default() -> Self {
+        Self {
+            corner: Corner::BottomLeft,
+            font_size: 12.0,
+            opacity: 0.2,
+        }
+    }
+}
+
+/// Draws the build label on top of everything, without taking any input.
+pub fn show(ctx: &egui::Context, config: &WatermarkConfig) {
+    let Some(label) = WATERMARK else {
+        return;
+    };
+    let (align, offset) = match config.corner {
+        Corner::TopLeft => (egui::Align2::LEFT_TOP, egui::vec2(8.0, 8.0)),
+        Corner::TopRight => (egui::Align2::RIGHT_TOP, egui::vec2(-8.0, 8.0)),
+        Corner::BottomLeft => (egui::Align2::LEFT_BOTTOM, egui::vec2(8.0, -8.0)),
+        Corner::BottomRight => (egui::Align2::RIGHT_BOTTOM, egui::vec2(-8.0, -8.0)),
+    };
+    let alpha = (config.opacity.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+    egui::Area::new("watermark")
+        .anchor(align, offset)
+        .order(egui::Order::Tooltip)
+        .interactable(false)
+        .show(ctx, |ui| {
+            ui.label(
+                egui::RichText::new(label)
+                    .size(config.font_size)
+                    .color(egui::Color32::from_white_alpha(alpha)),
+            );
+        });
+}
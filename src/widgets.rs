@@ -0,0 +1,372 @@
+use egui::ecolor::Hsva;
+use egui::{Color32, Key, Pos2, Response, Sense, Shape, Stroke, Ui, Vec2, Widget};
+
+use std::f32::consts::{PI, TAU};
+use std::ops::RangeInclusive;
+
+/// What a knob's arc spans, a quarter short of a circle with the gap at the bottom.
+const KNOB_SWEEP: f32 = 1.5 * PI;
+/// Where the arc starts, at the bottom left, with angles going clockwise on screen.
+const KNOB_START: f32 = 0.75 * PI;
+/// How far the pointer is dragged to turn a knob from one end to the other, in points.
+const KNOB_DRAG: f32 = 200.0;
+/// The line segments the arcs are drawn with.
+const ARC_STEPS: usize = 48;
+/// The wheel's mesh, in rings out from the middle and segments around it.
+const WHEEL_RINGS: usize = 8;
+const WHEEL_SEGMENTS: usize = 64;
+/// The lit segments of a level meter.
+const METER_SEGMENTS: usize = 20;
+
+/// A rotary knob, turned by dragging up or right and with the arrow keys once focused.
+/// Shift turns it slowly.
+pub struct Knob<'a> {
+    value: &'a mut f32,
+    range: RangeInclusive<f32>,
+    diameter: f32,
+    label: Option<String>,
+}
+
+impl<'a> Knob<'a> {
+    pub fn new(value: &'a mut f32, range: RangeInclusive<f32>) -> Self {
+        Self {
+            value,
+            range,
+            diameter: 48.0,
+            label: None,
+        }
+    }
+
+    pub fn diameter(mut self, diameter: f32) -> Self {
+        self.diameter = diameter;
+        self
+    }
+
+    /// Drawn under the knob.
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+}
+
+impl Widget for Knob<'_> {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let (min, max) = (*self.range.start(), *self.range.end());
+        let label = self.label.map(|label| {
+            let font = egui::TextStyle::Small.resolve(ui.style());
+            ui.painter()
+                .layout_no_wrap(label, font, ui.visuals().text_color())
+        });
+        let label_height = label.as_ref().map_or(0.0, |label| label.size().y + 2.0);
+        let size = Vec2::new(self.diameter, self.diameter + label_height);
+        let (rect, mut response) = ui.allocate_exact_size(size, Sense::click_and_drag());
+
+        let old = *self.value;
+        let slow = if ui.input(|i| i.modifiers.shift) {
+            0.1
+        } else {
+            1.0
+        };
+        let delta = response.drag_delta();
+        let mut value = old + (delta.x - delta.y) / KNOB_DRAG * (max - min) * slow;
+        if response.has_focus() {
+            let step = (max - min) / 100.0 * slow;
+            ui.input(|i| {
+                for key in [Key::ArrowUp, Key::ArrowRight] {
+                    if i.key_pressed(key) {
+                        value += step;
+                    }
+                }
+                for key in [Key::ArrowDown, Key::ArrowLeft] {
+                    if i.key_pressed(key) {
+                        value -= step;
+                    }
+                }
+            });
+        }
+        *self.value = value.clamp(min, max);
+        if *self.value != old {
+            response.mark_changed();
+        }
+        response.widget_info(|| egui::WidgetInfo::slider(f64::from(*self.value), ""));
+
+        if ui.is_rect_visible(rect) {
+            let visuals = ui.style().interact(&response);
+            let painter = ui.painter();
+            let radius = self.diameter / 2.0;
+            let center = Pos2::new(rect.center().x, rect.top() + radius);
+            painter.circle(center, radius - 4.0, visuals.bg_fill, visuals.bg_stroke);
+            let fraction = if max > min {
+                (*self.value - min) / (max - min)
+            } else {
+                0.0
+            };
+            let track = radius - 1.5;
+            let stroke = |color| Stroke::new(3.0, color);
+            painter.add(arc(
+                center,
+                track,
+                0.0,
+                1.0,
+                stroke(ui.visuals().faint_bg_color),
+            ));
+            let accent = ui.visuals().selection.bg_fill;
+            painter.add(arc(center, track, 0.0, fraction, stroke(accent)));
+            let angle = KNOB_START + fraction * KNOB_SWEEP;
+            let direction = Vec2::angled(angle);
+            painter.line_segment(
+                [
+                    center + direction * radius * 0.2,
+                    center + direction * (radius - 7.0),
+                ],
+                Stroke::new(2.0, visuals.fg_stroke.color),
+            );
+            if let Some(label) = label {
+                let pos = Pos2::new(
+                    rect.center().x - label.size().x / 2.0,
+                    rect.bottom() - label.size().y,
+                );
+                painter.galley(pos, label);
+            }
+        }
+        response.on_hover_text(format!("{:.2}", *self.value))
+    }
+}
+
+/// The part from `from` to `to` of a knob's arc, both fractions of its sweep.
+fn arc(center: Pos2, radius: f32, from: f32, to: f32, stroke: Stroke) -> Shape {
+    let points = (0..=ARC_STEPS)
+        .map(|i| {
+            let t = from + (to - from) * i as f32 / ARC_STEPS as f32;
+            center + Vec2::angled(KNOB_START + t * KNOB_SWEEP) * radius
+        })
+        .collect();
+    Shape::line(points, stroke)
+}
+
+/// A disc of hues around and saturations out from the middle, picking both by clicking
+/// or dragging on it. The value is left to something else, like a slider.
+pub struct ColorWheel<'a> {
+    color: &'a mut Hsva,
+    diameter: f32,
+}
+
+impl<'a> ColorWheel<'a> {
+    pub fn new(color: &'a mut Hsva) -> Self {
+        Self {
+            color,
+            diameter: 140.0,
+        }
+    }
+
+    pub fn diameter(mut self, diameter: f32) -> Self {
+        self.diameter = diameter;
+        self
+    }
+}
+
+impl Widget for ColorWheel<'_> {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let (rect, mut response) =
+            ui.allocate_exact_size(Vec2::splat(self.diameter), Sense::click_and_drag());
+        let center = rect.center();
+        let radius = self.diameter / 2.0;
+
+        if let Some(pos) = response.interact_pointer_pos() {
+            let offset = pos - center;
+            let hue = (offset.y.atan2(offset.x) / TAU).rem_euclid(1.0);
+            let saturation = (offset.length() / radius).min(1.0);
+            if (hue, saturation) != (self.color.h, self.color.s) {
+                self.color.h = hue;
+                self.color.s = saturation;
+                response.mark_changed();
+            }
+        }
+
+        if ui.is_rect_visible(rect) {
+            let value = self.color.v;
+            let mut mesh = egui::Mesh::default();
+            for ring in 0..=WHEEL_RINGS {
+                let saturation = ring as f32 / WHEEL_RINGS as f32;
+                for segment in 0..=WHEEL_SEGMENTS {
+                    let hue = segment as f32 / WHEEL_SEGMENTS as f32;
+                    let pos = center + Vec2::angled(hue * TAU) * radius * saturation;
+                    let color = Color32::from(Hsva::new(hue, saturation, value, 1.0));
+                    mesh.colored_vertex(pos, color);
+                }
+            }
+            let row = (WHEEL_SEGMENTS + 1) as u32;
+            for ring in 0..WHEEL_RINGS as u32 {
+                for segment in 0..WHEEL_SEGMENTS as u32 {
+                    let inner = ring * row + segment;
+                    let outer = inner + row;
+                    mesh.add_triangle(inner, outer, outer + 1);
+                    mesh.add_triangle(inner, outer + 1, inner + 1);
+                }
+            }
+            let painter = ui.painter();
+            painter.add(mesh);
+            let marker = center + Vec2::angled(self.color.h * TAU) * radius * self.color.s;
+            // Dark on light colors and light on dark ones.
+            let contrast = if value > 0.5 {
+                Color32::BLACK
+            } else {
+                Color32::WHITE
+            };
+            painter.circle_stroke(marker, 5.0, Stroke::new(2.0, contrast));
+        }
+        response
+    }
+}
+
+/// A vertical meter of `level` from 0 to 1, lit in segments going from green to red,
+/// with a line where the peak is held.
+pub struct LevelMeter {
+    level: f32,
+    peak: Option<f32>,
+    size: Vec2,
+}
+
+impl LevelMeter {
+    pub fn new(level: f32) -> Self {
+        Self {
+            level: level.clamp(0.0, 1.0),
+            peak: None,
+            size: Vec2::new(16.0, 140.0),
+        }
+    }
+
+    pub fn peak(mut self, peak: f32) -> Self {
+        self.peak = Some(peak.clamp(0.0, 1.0));
+        self
+    }
+
+    pub fn size(mut self, size: Vec2) -> Self {
+        self.size = size;
+        self
+    }
+}
+
+impl Widget for LevelMeter {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let (rect, response) = ui.allocate_exact_size(self.size, Sense::hover());
+        if ui.is_rect_visible(rect) {
+            let painter = ui.painter();
+            painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+            let inner = rect.shrink(2.0);
+            let height = inner.height() / METER_SEGMENTS as f32;
+            for i in 0..METER_SEGMENTS {
+                let top = (i + 1) as f32 / METER_SEGMENTS as f32;
+                let color = match top {
+                    top if top > 0.9 => Color32::from_rgb(230, 60, 50),
+                    top if top > 0.7 => Color32::from_rgb(230, 200, 40),
+                    _ => Color32::from_rgb(60, 200, 90),
+                };
+                let lit = self.level >= top - 0.5 / METER_SEGMENTS as f32;
+                let color = if lit {
+                    color
+                } else {
+                    color.gamma_multiply(0.15)
+                };
+                let bottom = inner.bottom() - i as f32 * height;
+                let segment = egui::Rect::from_x_y_ranges(
+                    inner.x_range(),
+                    (bottom - height + 1.0)..=(bottom - 1.0),
+                );
+                painter.rect_filled(segment, 1.0, color);
+            }
+            if let Some(peak) = self.peak {
+                let y = inner.bottom() - peak * inner.height();
+                painter.hline(
+                    inner.x_range(),
+                    y,
+                    Stroke::new(2.0, ui.visuals().strong_text_color()),
+                );
+            }
+        }
+        response.on_hover_text(format!("{:.0}%", self.level * 100.0))
+    }
+}
+
+/// The "Widget gallery" window, with the widgets above driving each other: the knobs
+/// set what the meters show, which move on their own.
+pub struct WidgetGallery {
+    pub open: bool,
+    pub gain: f32,
+    pub pan: f32,
+    pub color: Hsva,
+    peaks: [f32; 2],
+}
+
+impl Default for WidgetGallery {
+    fn default() -> Self {
+        Self {
+            open: false,
+            gain: 0.5,
+            pan: 0.0,
+            color: Hsva::new(0.6, 0.7, 0.9, 1.0),
+            peaks: [0.0; 2],
+        }
+    }
+}
+
+impl WidgetGallery {
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        if !self.open {
+            return;
+        }
+
+        let (time, dt) = ctx.input(|i| (i.time as f32, i.stable_dt.min(0.1)));
+        // Something like music, louder where it's panned to.
+        let signal =
+            0.6 + 0.25 * (time * 7.0).sin() * (time * 1.3).cos() + 0.15 * (time * 23.0).sin();
+        let levels = [
+            signal * self.gain * (1.0 - self.pan).min(1.0),
+            signal * self.gain * (1.0 + self.pan).min(1.0),
+        ];
+        for (peak, level) in self.peaks.iter_mut().zip(levels) {
+            *peak = (*peak - dt * 0.3).max(level);
+        }
+
+        let mut open = self.open;
+        egui::Window::new("Widget gallery")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.vertical(|ui| {
+                        ui.add(Knob::new(&mut self.gain, 0.0..=1.0).label("Gain"));
+                        ui.label(format!("{:.0}%", self.gain * 100.0));
+                    });
+                    ui.vertical(|ui| {
+                        ui.add(Knob::new(&mut self.pan, -1.0..=1.0).label("Pan"));
+                        ui.label(format!("{:+.2}", self.pan));
+                    });
+                    for (level, peak) in levels.into_iter().zip(self.peaks) {
+                        ui.add(
+                            LevelMeter::new(level)
+                                .peak(peak)
+                                .size(Vec2::new(14.0, 96.0)),
+                        );
+                    }
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.add(ColorWheel::new(&mut self.color));
+                    ui.vertical(|ui| {
+                        ui.add(egui::Slider::new(&mut self.color.v, 0.0..=1.0).text("Value"));
+                        let (rect, _) =
+                            ui.allocate_exact_size(Vec2::new(64.0, 32.0), Sense::hover());
+                        ui.painter()
+                            .rect_filled(rect, 4.0, Color32::from(self.color));
+                        let [r, g, b, _] = Color32::from(self.color).to_array();
+                        ui.monospace(format!("#{:02x}{:02x}{:02x}", r, g, b));
+                    });
+                });
+                ui.weak("Drag the knobs, or focus one and use the arrow keys");
+            });
+        // The meters keep moving.
+        ctx.request_repaint();
+        self.open = open;
+    }
+}
@@ -0,0 +1,155 @@
+use crate::config;
+
+use serde::{Deserialize, Serialize};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event_loop::EventLoopWindowTarget;
+use winit::monitor::MonitorHandle;
+use winit::window::{Fullscreen, Window, WindowBuilder};
+
+use std::path::PathBuf;
+
+const GEOMETRY_FILE: &str = "window.json";
+
+/// The main window's size when nothing was saved, shrunk to fit the monitor.
+pub const DEFAULT_SIZE: [u32; 2] = [1280, 720];
+
+/// The least of a window that has to be on a monitor for its saved position to be kept.
+const MIN_VISIBLE: i32 = 64;
+
+/// Where the main window was and how large, saved when it's closed to open it the
+/// same way the next time. Sizes and positions are in physical pixels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct WindowGeometry {
+    /// The inner size it had when it was last neither maximized nor fullscreen.
+    pub size: [u32; 2],
+    /// The outer position that went with `size`, `None` where the platform doesn't
+    /// tell, as on Wayland.
+    pub position: Option<[i32; 2]>,
+    pub maximized: bool,
+    pub fullscreen: bool,
+}
+
+impl Default for WindowGeometry {
+    fn default() -> Self {
+        Self {
+            size: DEFAULT_SIZE,
+            position: None,
+            maximized: false,
+            fullscreen: false,
+        }
+    }
+}
+
+impl WindowGeometry {
+    /// What the last run saved, if anything.
+    pub fn load() -> Option<Self> {
+        let path = geometry_path()?;
+        let text = std::fs::read_to_string(&path).ok()?;
+        serde_json::from_str(&text)
+            .map_err(|e| {
+                tracing::warn!("Ignoring invalid window geometry {}: {}", path.display(), e)
+            })
+            .ok()
+    }
+
+    pub fn save(&self) {
+        let Some(path) = geometry_path() else {
+            return;
+        };
+        let result = serde_json::to_string_pretty(self)
+            .map_err(std::io::Error::from)
+            .and_then(|text| {
+                std::fs::create_dir_all(path.parent().unwrap())?;
+                std::fs::write(&path, text)
+            });
+        if let Err(e) = result {
+            tracing::warn!("Failed to save the window geometry: {}", e);
+        }
+    }
+
+    /// Follows the window as it's resized and moved. Only its restored size and
+    /// position are kept, the states are read when it's closed.
+    pub fn track(&mut self, window: &Window) {
+        self.maximized = window.is_maximized();
+        self.fullscreen = window.fullscreen().is_some();
+        if self.maximized || self.fullscreen {
+            return;
+        }
+        let size = window.inner_size();
+        if size.width > 0 && size.height > 0 {
+            self.size = [size.width, size.height];
+        }
+        if let Ok(position) = window.outer_position() {
+            self.position = Some([position.x, position.y]);
+        }
+    }
+
+    /// Opens the window this way on the monitors there are now. A position that's no
+    /// longer on any of them is left to the OS, and the size shrinks to fit.
+    pub fn apply<T>(
+        self,
+        builder: WindowBuilder,
+        event_loop: &EventLoopWindowTarget<T>,
+    ) -> WindowBuilder {
+        let monitors: Vec<MonitorHandle> = event_loop.available_monitors().collect();
+        let position = self.position.filter(|&position| {
+            monitors
+                .iter()
+                .any(|monitor| overlap(monitor, position, self.size) >= MIN_VISIBLE)
+        });
+        // The monitor it opens on, as far as it can be told.
+        let monitor = position
+            .and_then(|position| {
+                monitors
+                    .iter()
+                    .max_by_key(|monitor| overlap(monitor, position, self.size))
+            })
+            .cloned()
+            .or_else(|| event_loop.primary_monitor())
+            .or_else(|| monitors.first().cloned());
+        let size = match &monitor {
+            Some(monitor) => {
+                let available = monitor.size();
+                [
+                    self.size[0].min(available.width),
+                    self.size[1].min(available.height),
+                ]
+            }
+            None => self.size,
+        };
+
+        let mut builder = builder
+            .with_inner_size(PhysicalSize::new(size[0], size[1]))
+            .with_maximized(self.maximized);
+        if let Some([x, y]) = position {
+            builder = builder.with_position(PhysicalPosition::new(x, y));
+        }
+        if self.fullscreen {
+            builder = builder.with_fullscreen(Some(Fullscreen::Borderless(monitor)));
+        }
+        builder
+    }
+}
+
+/// The smaller side of what a window at `position` of `size` has on `monitor`, 0 when
+/// it's not on it at all.
+fn overlap(monitor: &MonitorHandle, position: [i32; 2], size: [u32; 2]) -> i32 {
+    let origin = monitor.position();
+    let extent = monitor.size();
+    let side = |start: i32, length: u32, monitor_start: i32, monitor_length: u32| {
+        let end = start.saturating_add(length as i32);
+        let monitor_end = monitor_start.saturating_add(monitor_length as i32);
+        (end.min(monitor_end) - start.max(monitor_start)).max(0)
+    };
+    side(position[0], size[0], origin.x, extent.width).min(side(
+        position[1],
+        size[1],
+        origin.y,
+        extent.height,
+    ))
+}
+
+fn geometry_path() -> Option<PathBuf> {
+    config::config_dir().map(|dir| dir.join(GEOMETRY_FILE))
+}
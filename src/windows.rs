@@ -0,0 +1,326 @@
+use crate::gfx::GraphicsContext;
+use crate::gui::Gui;
+use crate::theme::Theme;
+use crate::Application;
+
+use instant::Instant;
+use winit::event::WindowEvent;
+use winit::event_loop::EventLoopWindowTarget;
+use winit::window::{Window, WindowBuilder, WindowId};
+
+use std::collections::HashSet;
+use std::iter;
+
+/// A window the app wants open besides the main one, see `Application::windows`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WindowSpec {
+    /// Tells the app's windows apart, it has to stay the same while the window is open.
+    pub id: String,
+    pub title: String,
+    /// The initial inner size, in logical pixels.
+    pub size: [u32; 2],
+}
+
+impl WindowSpec {
+    pub fn new(id: impl Into<String>, title: impl Into<String>, size: [u32; 2]) -> Self {
+        Self {
+            id: id.into(),
+            title: title.into(),
+            size,
+        }
+    }
+}
+
+/// A window with a surface of its own, and its own egui context, input state and
+/// renderer. Only egui draws in it.
+struct ExtraWindow {
+    spec: WindowSpec,
+    window: Window,
+    /// `None` while suspended.
+    surface: Option<wgpu::Surface>,
+    config: wgpu::SurfaceConfiguration,
+    gui: Gui,
+    /// When egui asked to be drawn again, `None` until there is input.
+    repaint_at: Option<Instant>,
+}
+
+/// The app's other windows, all drawn with the main window's device.
+///
+/// Their egui textures aren't counted in `GpuMemory`, the ids clash with the main
+/// window's.
+#[derive(Default)]
+pub(crate) struct Windows {
+    windows: Vec<ExtraWindow>,
+    /// The ones that couldn't be opened, not to be tried again every frame.
+    failed: HashSet<String>,
+}
+
+impl Windows {
+    /// Opens the windows in `specs` that aren't open yet and closes the ones it no
+    /// longer lists. New windows start with `style`, the main window's.
+    ///
+    /// The browser and Android only have the one window, nothing is opened there.
+    pub fn sync<T>(
+        &mut self,
+        specs: Vec<WindowSpec>,
+        target: &EventLoopWindowTarget<T>,
+        gfx: &GraphicsContext,
+        style: &egui::Style,
+    ) {
+        if cfg!(any(target_arch = "wasm32", target_os = "android")) {
+            return;
+        }
+        self.windows
+            .retain(|open| specs.iter().any(|spec| spec.id == open.spec.id));
+        self.failed
+            .retain(|failed| specs.iter().any(|spec| spec.id == *failed));
+        for spec in specs {
+            if self.failed.contains(&spec.id) {
+                continue;
+            }
+            if let Some(open) = self.windows.iter_mut().find(|open| open.spec.id == spec.id) {
+                if open.spec.title != spec.title {
+                    open.window.set_title(&spec.title);
+                    open.spec.title = spec.title;
+                }
+                continue;
+            }
+            match ExtraWindow::open(&spec, target, gfx, style) {
+                Some(window) => self.windows.push(window),
+                None => {
+                    tracing::error!("Opening the {} window failed", spec.id);
+                    self.failed.insert(spec.id);
+                }
+            }
+        }
+    }
+
+    /// Passes a window event to the window it's for.
+    pub fn on_event(
+        &mut self,
+        app: &mut impl Application,
+        gfx: &GraphicsContext,
+        id: WindowId,
+        event: &WindowEvent<'_>,
+    ) {
+        let Some(open) = self.get_mut(id) else {
+            return;
+        };
+        let response = open.gui.on_event(event);
+        if response.repaint {
+            open.window.request_redraw();
+        }
+        if response.consumed {
+            return;
+        }
+        match *event {
+            WindowEvent::Resized(size)
+            | WindowEvent::ScaleFactorChanged {
+                new_inner_size: &mut size,
+                ..
+            } => {
+                open.config.width = size.width;
+                open.config.height = size.height;
+                open.configure(gfx);
+                open.repaint_at = Some(Instant::now());
+            }
+            WindowEvent::CloseRequested => app.window_closed(&open.spec.id),
+            _ => {}
+        }
+    }
+
+    /// Draws the window with `Application::window_ui`.
+    pub fn redraw(&mut self, app: &mut impl Application, gfx: &GraphicsContext, id: WindowId) {
+        if let Some(open) = self.get_mut(id) {
+            open.redraw(app, gfx);
+        }
+    }
+
+    /// Asks the windows egui wants drawn by now to be redrawn.
+    pub fn request_redraws(&self) {
+        let now = Instant::now();
+        for open in &self.windows {
+            if open.repaint_at.is_some_and(|at| at <= now) {
+                open.window.request_redraw();
+            }
+        }
+    }
+
+    pub fn request_redraw_all(&self) {
+        for open in &self.windows {
+            open.window.request_redraw();
+        }
+    }
+
+    /// When the next of them wants to be drawn.
+    pub fn repaint_at(&self) -> Option<Instant> {
+        self.windows.iter().filter_map(|open| open.repaint_at).min()
+    }
+
+    pub fn suspend(&mut self) {
+        for open in &mut self.windows {
+            open.surface = None;
+        }
+    }
+
+    pub fn resume(&mut self, gfx: &GraphicsContext) {
+        for open in &mut self.windows {
+            open.surface = unsafe { gfx.instance.create_surface(&open.window).ok() };
+            open.configure(gfx);
+            open.repaint_at = Some(Instant::now());
+        }
+    }
+
+    /// Makes the surfaces and renderers again with the new instance and device.
+    pub fn device_recreated(&mut self, gfx: &GraphicsContext) {
+        for open in &mut self.windows {
+            open.surface = unsafe { gfx.instance.create_surface(&open.window).ok() };
+            if let Some(surface) = &open.surface {
+                let size = open.window.inner_size();
+                if let Some(config) = surface_config(surface, gfx, size) {
+                    open.config = config;
+                }
+            }
+            open.gui.recreate(&gfx.device, open.config.format);
+            open.configure(gfx);
+            open.repaint_at = Some(Instant::now());
+        }
+    }
+
+    fn get_mut(&mut self, id: WindowId) -> Option<&mut ExtraWindow> {
+        self.windows.iter_mut().find(|open| open.window.id() == id)
+    }
+}
+
+impl ExtraWindow {
+    fn open<T>(
+        spec: &WindowSpec,
+        target: &EventLoopWindowTarget<T>,
+        gfx: &GraphicsContext,
+        style: &egui::Style,
+    ) -> Option<Self> {
+        let window = WindowBuilder::new()
+            .with_title(&spec.title)
+            .with_inner_size(winit::dpi::LogicalSize::new(spec.size[0], spec.size[1]))
+            .build(target)
+            .ok()?;
+        let surface = unsafe { gfx.instance.create_surface(&window) }.ok()?;
+        let config = surface_config(&surface, gfx, window.inner_size())?;
+        let gui = Gui::new(&window, &gfx.device, config.format, Theme::default());
+        gui.context.set_style(style.clone());
+        let open = Self {
+            spec: spec.clone(),
+            window,
+            surface: Some(surface),
+            config,
+            gui,
+            repaint_at: Some(Instant::now()),
+        };
+        open.configure(gfx);
+        Some(open)
+    }
+
+    fn configure(&self, gfx: &GraphicsContext) {
+        if let Some(surface) = self.surface.as_ref().filter(|_| self.has_area()) {
+            surface.configure(&gfx.device, &self.config);
+        }
+    }
+
+    fn has_area(&self) -> bool {
+        self.config.width > 0 && self.config.height > 0
+    }
+
+    fn redraw(&mut self, app: &mut impl Application, gfx: &GraphicsContext) {
+        let Some(surface) = self.surface.as_ref().filter(|_| self.has_area()) else {
+            return;
+        };
+        self.repaint_at = None;
+        let texture = match surface.get_current_texture() {
+            Ok(texture) => texture,
+            Err(wgpu::SurfaceError::Outdated | wgpu::SurfaceError::Lost) => {
+                surface.configure(&gfx.device, &self.config);
+                self.repaint_at = Some(Instant::now());
+                return;
+            }
+            Err(e) => {
+                tracing::warn!("Skipping a frame of the {} window: {}", self.spec.id, e);
+                self.repaint_at = Some(Instant::now());
+                return;
+            }
+        };
+        let view = texture.texture.create_view(&Default::default());
+
+        let size_in_pixels = [self.config.width, self.config.height];
+        let frame = self
+            .gui
+            .run_window(&self.window, app, &self.spec.id, size_in_pixels);
+        let mut encoder = gfx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("window encoder"),
+            });
+        let callback_commands = self
+            .gui
+            .upload(&gfx.device, &gfx.queue, &mut encoder, &frame);
+        // The surface is sRGB, so the clear color is given linear.
+        let fill = egui::Rgba::from(self.gui.context.style().visuals.panel_fill);
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("window ui"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color {
+                        r: fill.r() as f64,
+                        g: fill.g() as f64,
+                        b: fill.b() as f64,
+                        a: 1.0,
+                    }),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        self.gui.paint(&mut pass, &frame);
+        drop(pass);
+        gfx.queue.submit(
+            callback_commands
+                .into_iter()
+                .chain(iter::once(encoder.finish())),
+        );
+        texture.present();
+        self.gui.free(&frame);
+        self.repaint_at = Instant::now().checked_add(frame.repaint_after);
+    }
+}
+
+/// A configuration presenting to `surface` from the main window's adapter, in an sRGB
+/// format and the main window's present mode when it can. `None` when the adapter
+/// can't present to it at all.
+fn surface_config(
+    surface: &wgpu::Surface,
+    gfx: &GraphicsContext,
+    size: winit::dpi::PhysicalSize<u32>,
+) -> Option<wgpu::SurfaceConfiguration> {
+    let capabilities = surface.get_capabilities(&gfx.adapter);
+    let format = capabilities
+        .formats
+        .iter()
+        .copied()
+        .find(|format| format.is_srgb())
+        .or_else(|| capabilities.formats.first().copied())?;
+    let present_mode = if capabilities.present_modes.contains(&gfx.present_mode()) {
+        gfx.present_mode()
+    } else {
+        wgpu::PresentMode::Fifo
+    };
+    Some(wgpu::SurfaceConfiguration {
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        format,
+        width: size.width,
+        height: size.height,
+        present_mode,
+        alpha_mode: capabilities.alpha_modes[0],
+        view_formats: vec![],
+    })
+}